@@ -0,0 +1,74 @@
+/// Allocator benchmarks: many steps contending for one shared resource, which
+/// stresses the resource timeline's range queries.
+///
+/// Run with: cargo bench --manifest-path crates/engine/Cargo.toml
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use skejj_engine::model::{
+    DependencyType, Resource, ResourceKind, ResourceNeed, ScheduleTemplate, Step, StepDependency,
+};
+use skejj_engine::solver;
+
+/// `chains` independent chains of `length` steps, every step needing one of
+/// `capacity` crew members.
+fn contended_template(chains: usize, length: usize, capacity: u32) -> ScheduleTemplate {
+    let mut steps = Vec::new();
+    for c in 0..chains {
+        for i in 0..length {
+            let dependencies = if i == 0 {
+                vec![]
+            } else {
+                vec![StepDependency {
+                    step_id: format!("c{}-s{}", c, i - 1),
+                    dependency_type: DependencyType::FinishToStart,
+                    trigger_at_percent: None,
+                    condition: None,
+                }]
+            };
+            steps.push(Step {
+                id: format!("c{}-s{}", c, i),
+                title: format!("Chain {} step {}", c, i),
+                duration_mins: 10 + ((c * 7 + i * 13) % 50) as u32,
+                dependencies,
+                resource_needs: vec![ResourceNeed {
+                    resource_id: "crew".to_string(),
+                    quantity: 1,
+                    min_people: None,
+                    max_people: None,
+                    role: None,
+                    cost_per_minute: None,
+                    allow_partial: false,
+                }],
+                ..Default::default()
+            });
+        }
+    }
+    ScheduleTemplate {
+        id: "bench".to_string(),
+        name: "Bench".to_string(),
+        steps,
+        resources: vec![Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }],
+        ..Default::default()
+    }
+}
+
+fn bench_solve(c: &mut Criterion) {
+    for (chains, length) in [(10, 10), (20, 25)] {
+        let template = contended_template(chains, length, 3);
+        c.bench_function(&format!("solve {} contended steps", chains * length), |b| {
+            b.iter(|| solver::solve(black_box(&template), None).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, bench_solve);
+criterion_main!(benches);
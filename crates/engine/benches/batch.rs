@@ -0,0 +1,71 @@
+/// Batch solving benchmarks: a batch of 20 templates solved one after
+/// another and in parallel with rayon.
+///
+/// Run with: cargo bench --manifest-path crates/engine/Cargo.toml
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use skejj_engine::model::{
+    DependencyType, Resource, ResourceKind, ResourceNeed, ScheduleTemplate, Step, StepDependency,
+};
+use skejj_engine::solver;
+
+/// A template of 100 steps in chains of five, all competing for a pair of
+/// ovens, with durations varied by `seed`.
+fn template(seed: usize) -> ScheduleTemplate {
+    ScheduleTemplate {
+        id: format!("batch{}", seed),
+        name: format!("Batch {}", seed),
+        steps: (0..100)
+            .map(|i| Step {
+                id: format!("s{}", i),
+                title: format!("Step {}", i),
+                duration_mins: 5 + ((i + seed) % 25) as u32,
+                dependencies: if i % 5 == 0 {
+                    vec![]
+                } else {
+                    vec![StepDependency {
+                        step_id: format!("s{}", i - 1),
+                        dependency_type: DependencyType::FinishToStart,
+                        trigger_at_percent: None,
+                        condition: None,
+                    }]
+                },
+                resource_needs: vec![ResourceNeed {
+                    resource_id: "oven".to_string(),
+                    quantity: 1,
+                    min_people: None,
+                    max_people: None,
+                    role: None,
+                    cost_per_minute: None,
+                    allow_partial: false,
+                }],
+                ..Default::default()
+            })
+            .collect(),
+        resources: vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 2,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }],
+        ..Default::default()
+    }
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let templates: Vec<_> = (0..20).map(template).collect();
+
+    c.bench_function("solve batch of 20 sequential", |b| {
+        b.iter(|| solver::solve_all_scenarios(black_box(&templates), None))
+    });
+    c.bench_function("solve batch of 20 parallel", |b| {
+        b.iter(|| solver::solve_batched_with_rayon(black_box(&templates), None))
+    });
+}
+
+criterion_group!(benches, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+/// Serialization benchmarks: a 200-step solved schedule written and read as
+/// JSON and as compact MessagePack.
+///
+/// Run with: cargo bench --manifest-path crates/engine/Cargo.toml
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use skejj_engine::io::{deserialize_solved_schedule_compact, serialize_solved_schedule_compact};
+use skejj_engine::model::{
+    DependencyType, ScheduleTemplate, SolvedSchedule, Step, StepDependency, TimeConstraint,
+};
+use skejj_engine::solver;
+
+/// A solved schedule of `steps` steps, each depending on the one before it
+/// and, every tenth step, on the step ten back.
+fn solved_schedule(steps: usize) -> SolvedSchedule {
+    let dep = |i: usize| StepDependency {
+        step_id: format!("s{}", i),
+        dependency_type: DependencyType::FinishToStart,
+        trigger_at_percent: None,
+        condition: None,
+    };
+    let template = ScheduleTemplate {
+        id: "bench".to_string(),
+        name: "Bench".to_string(),
+        steps: (0..steps)
+            .map(|i| Step {
+                id: format!("s{}", i),
+                title: format!("Step {}", i),
+                duration_mins: 5 + (i % 30) as u32,
+                dependencies: match i {
+                    0 => vec![],
+                    i if i % 10 == 0 && i >= 10 => vec![dep(i - 1), dep(i - 10)],
+                    i => vec![dep(i - 1)],
+                },
+                ..Default::default()
+            })
+            .collect(),
+        time_constraint: Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: None,
+            timezone: None,
+        }),
+        ..Default::default()
+    };
+    solver::solve(&template, None).unwrap()
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let solved = solved_schedule(200);
+    let json = serde_json::to_vec(&solved).unwrap();
+    let compact = serialize_solved_schedule_compact(&solved);
+
+    c.bench_function("serialize 200 steps json", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&solved)).unwrap())
+    });
+    c.bench_function("serialize 200 steps compact", |b| {
+        b.iter(|| serialize_solved_schedule_compact(black_box(&solved)))
+    });
+    c.bench_function("deserialize 200 steps json", |b| {
+        b.iter(|| serde_json::from_slice::<SolvedSchedule>(black_box(&json)).unwrap())
+    });
+    c.bench_function("deserialize 200 steps compact", |b| {
+        b.iter(|| deserialize_solved_schedule_compact(black_box(&compact)).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_serialize);
+criterion_main!(benches);
@@ -1,8 +1,32 @@
-use std::collections::HashMap;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
 
 use crate::model::{
-    AssignedResource, ResourceInventory, ResourceKind, ScheduleTemplate, SolvedStep, TimingPolicy,
+    AssignedResource, NoteCode, ResourceGroup, ResourceInventory, ResourceInventoryItem,
+    ResourceKind, ResourceNeed, ScheduleTemplate, SolvedSchedule, SolvedStep, Step, StepNote,
+    StepSegment, TimingPolicy,
 };
+use crate::solver::{CpmResult, SolveOptions};
+use crate::util::{effective_capacity, peak_usage, resource_usages};
+
+/// Orders steps for placement: steps comparing `Less` are placed first.
+pub type StepPriority = dyn Fn(&SolvedStep, &SolvedStep) -> Ordering;
+
+/// Timeline key for the template-wide `max_concurrent_steps` limit.
+const CONCURRENCY_ID: &str = "__max_concurrent_steps__";
+
+/// Prefix of the timeline keys for tracks' `max_concurrent` limits.
+const TRACK_CONCURRENCY_PREFIX: &str = "__track_max_concurrent__:";
+
+/// Whether the template has anything for the allocator to do: resources or
+/// a concurrency limit.
+pub(crate) fn needs_allocation(template: &ScheduleTemplate) -> bool {
+    !template.resources.is_empty()
+        || template.max_concurrent_steps.is_some()
+        || template.tracks.iter().any(|t| t.max_concurrent.is_some())
+}
 
 // ---------------------------------------------------------------------------
 // Timeline tracker for Equipment and People resources
@@ -18,24 +42,67 @@ struct Reservation {
 
 /// Per-resource interval list for Equipment and People.
 /// Tracks time-windowed usage; supports range capacity queries.
+///
+/// Alongside the reservations it keeps their start and end times sorted with
+/// running quantity totals, so `used_at_range` is two binary searches instead
+/// of a scan over every reservation.
 #[derive(Debug, Default)]
 struct ResourceTimeline {
     reservations: Vec<Reservation>,
+    /// `(start, total quantity of reservations starting at or before it)`.
+    starts: Vec<(u32, u32)>,
+    /// `(end, total quantity of reservations ending at or before it)`.
+    ends: Vec<(u32, u32)>,
 }
 
 impl ResourceTimeline {
     /// Sum of quantities whose intervals overlap the half-open range [start, end).
     fn used_at_range(&self, start: u32, end: u32) -> u32 {
-        self.reservations
-            .iter()
-            .filter(|r| r.start < end && r.end > start)
-            .map(|r| r.quantity)
-            .sum()
+        if start >= end {
+            // Empty ranges are only asked about for zero-length steps; the
+            // prefix sums below assume a non-empty range.
+            return self
+                .reservations
+                .iter()
+                .filter(|r| r.start < end && r.end > start)
+                .map(|r| r.quantity)
+                .sum();
+        }
+        // Everything starting before `end` overlaps, except what has already
+        // ended by `start`.
+        let started = total_before(&self.starts, |t| t < end);
+        let ended = total_before(&self.ends, |t| t <= start);
+        started - ended
     }
 
     /// Reserve [start, end) with the given quantity.
     fn reserve(&mut self, start: u32, end: u32, quantity: u32) {
         self.reservations.push(Reservation { start, end, quantity });
+        self.rebuild();
+    }
+
+    /// Recompute the sorted start/end totals from `reservations`.
+    fn rebuild(&mut self) {
+        let running = |mut events: Vec<(u32, u32)>| {
+            events.sort_unstable();
+            let mut total = 0;
+            for event in &mut events {
+                total += event.1;
+                event.1 = total;
+            }
+            events
+        };
+        self.starts = running(self.reservations.iter().map(|r| (r.start, r.quantity)).collect());
+        self.ends = running(self.reservations.iter().map(|r| (r.end, r.quantity)).collect());
+    }
+}
+
+/// Running total at the last event whose time satisfies `before` (a predicate
+/// that holds for a prefix of the sorted events), or 0 if none does.
+fn total_before(events: &[(u32, u32)], before: impl Fn(u32) -> bool) -> u32 {
+    match events.partition_point(|e| before(e.0)) {
+        0 => 0,
+        n => events[n - 1].1,
     }
 }
 
@@ -50,20 +117,58 @@ impl ResourceTimeline {
 /// 2. Then by early start ascending
 /// 3. Tie-break by duration descending
 ///
+/// Steps with a `Fixed` timing policy go before all of these and are never
+/// moved. If a fixed step's resources are already taken at its start (by
+/// another fixed step), it keeps its slot anyway and the conflict is
+/// reported.
+///
 /// When `inventory` is provided, its quantities override the template capacity
 /// for matching resources. A warning is emitted for each override.
 ///
+/// A need naming a resource group is met by one of the group's members: the
+/// one that lets the step start earliest, preferring the member with the most
+/// free capacity on ties. `AssignedResource` records the member chosen.
+///
 /// Returns a list of human-readable warnings for any constraint relaxations.
+/// Each affected step also gets a `StepNote` describing what happened to it.
 pub fn allocate_resources(
     template: &ScheduleTemplate,
-    solved_steps: &mut Vec<SolvedStep>,
+    solved_steps: &mut [SolvedStep],
+    early_starts: &HashMap<String, u32>,
+    late_starts: &HashMap<String, u32>,
+    inventory: Option<&ResourceInventory>,
+) -> Vec<String> {
+    allocate_resources_with_priority(
+        template,
+        solved_steps,
+        early_starts,
+        late_starts,
+        inventory,
+        None,
+        false,
+    )
+}
+
+/// Like `allocate_resources`, but when `priority` is given it decides the
+/// order steps are placed in (steps comparing `Less` go first) instead of the
+/// three default rules. The comparator sees each step's CPM timings; `Fixed`
+/// steps still go first.
+///
+/// With `splitting`, an ASAP step that would have to wait for resources may
+/// instead start earlier with part of the quantity of its `allow_partial`
+/// needs, if that finishes it sooner; its duration grows in proportion.
+pub fn allocate_resources_with_priority(
+    template: &ScheduleTemplate,
+    solved_steps: &mut [SolvedStep],
     early_starts: &HashMap<String, u32>,
     late_starts: &HashMap<String, u32>,
     inventory: Option<&ResourceInventory>,
+    priority: Option<&StepPriority>,
+    splitting: bool,
 ) -> Vec<String> {
     let mut warnings: Vec<String> = Vec::new();
 
-    if template.resources.is_empty() {
+    if !needs_allocation(template) {
         return warnings;
     }
 
@@ -88,6 +193,38 @@ pub fn allocate_resources(
         .filter_map(|s| s.timing_policy.as_ref().map(|p| (s.id.as_str(), p)))
         .collect();
 
+    // Tracks with a concurrency limit get a synthetic resource like the
+    // global limit below: track_id -> (need, display name). A limit of 0 is
+    // ignored with a warning, as it would block the track's steps forever.
+    for track in template.tracks.iter().filter(|t| t.max_concurrent == Some(0)) {
+        warnings.push(format!(
+            "Track '{}' has max_concurrent 0 -- ignored, since none of its steps could run",
+            track.name
+        ));
+    }
+    let track_needs: HashMap<&str, (ResourceNeed, String)> = template
+        .tracks
+        .iter()
+        .filter(|t| t.max_concurrent.is_some_and(|limit| limit > 0))
+        .map(|t| {
+            let need = ResourceNeed {
+                resource_id: format!("{}{}", TRACK_CONCURRENCY_PREFIX, t.id),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            };
+            (t.id.as_str(), (need, format!("{} concurrent step limit", t.name)))
+        })
+        .collect();
+    let step_tracks: HashMap<&str, &str> = template
+        .steps
+        .iter()
+        .filter_map(|s| s.track_id.as_deref().map(|t| (s.id.as_str(), t)))
+        .collect();
+
     // Build resource capacity map: resource_id -> capacity
     // Start from template capacities, then apply inventory overrides.
     let mut resource_capacity: HashMap<&str, u32> = template
@@ -120,12 +257,44 @@ pub fn allocate_resources(
     }
 
     // Build resource name map: resource_id -> name
-    let resource_names: HashMap<&str, &str> = template
+    let mut resource_names: HashMap<&str, &str> = template
         .resources
         .iter()
         .map(|r| (r.id.as_str(), r.name.as_str()))
         .collect();
 
+    // A global concurrency limit is modelled as a synthetic resource that
+    // every step needs one unit of, so it blocks placement exactly like a
+    // resource at capacity. A limit of 0 would block every step forever, so
+    // it is ignored with a warning (`validate` rejects it).
+    if template.max_concurrent_steps == Some(0) {
+        warnings.push(
+            "max_concurrent_steps is 0 -- ignored, since no step could ever run".to_string(),
+        );
+    }
+    let concurrency_need = template.max_concurrent_steps.filter(|&limit| limit > 0).map(|limit| {
+        resource_capacity.insert(CONCURRENCY_ID, limit);
+        resource_names.insert(CONCURRENCY_ID, "concurrent step limit");
+        crate::model::ResourceNeed {
+            resource_id: CONCURRENCY_ID.to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: false,
+        }
+    });
+
+    for track in &template.tracks {
+        if let (Some(limit), Some((need, name))) =
+            (track.max_concurrent, track_needs.get(track.id.as_str()))
+        {
+            resource_capacity.insert(need.resource_id.as_str(), limit);
+            resource_names.insert(need.resource_id.as_str(), name.as_str());
+        }
+    }
+
     // Build resource kind map: resource_id -> kind
     let resource_kinds: HashMap<&str, &ResourceKind> = template
         .resources
@@ -133,6 +302,21 @@ pub fn allocate_resources(
         .map(|r| (r.id.as_str(), &r.kind))
         .collect();
 
+    let resource_groups: HashMap<&str, &ResourceGroup> =
+        template.resource_groups.iter().map(|g| (g.id.as_str(), g)).collect();
+
+    // Steps on the working calendar: interruptible ones pause outside it, so
+    // how long they hold their resources depends on where they are placed;
+    // the rest wait for a working stretch that holds them whole.
+    // step_id -> (interruptible, working minutes).
+    let calendar = template.working_calendar.as_ref().filter(|c| crate::calendar::is_usable(c));
+    let calendar_work: HashMap<&str, (bool, u32)> = template
+        .steps
+        .iter()
+        .filter(|s| !matches!(s.timing_policy, Some(TimingPolicy::Fixed { .. })))
+        .map(|s| (s.id.as_str(), (s.interruptible, s.duration_mins)))
+        .collect();
+
     // -----------------------------------------------------------------------
     // Step 1: Build sort order indices
     // -----------------------------------------------------------------------
@@ -143,6 +327,19 @@ pub fn allocate_resources(
         let sa = &solved_steps[a];
         let sb = &solved_steps[b];
 
+        // 0. Fixed steps can't move, so they claim their slots first
+        let fixed = |s: &SolvedStep| {
+            matches!(step_policies.get(s.step_id.as_str()), Some(TimingPolicy::Fixed { .. }))
+        };
+        let fixed_ord = fixed(sb).cmp(&fixed(sa));
+        if fixed_ord != std::cmp::Ordering::Equal {
+            return fixed_ord;
+        }
+
+        if let Some(priority) = priority {
+            return priority(sa, sb);
+        }
+
         // 1. Critical path first
         let crit_ord = sb.is_critical.cmp(&sa.is_critical);
         if crit_ord != std::cmp::Ordering::Equal {
@@ -170,9 +367,60 @@ pub fn allocate_resources(
     let mut timelines: HashMap<String, ResourceTimeline> = template
         .resources
         .iter()
-        .filter(|r| !matches!(r.kind, ResourceKind::Consumable))
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
         .map(|r| (r.id.clone(), ResourceTimeline::default()))
         .collect();
+    if concurrency_need.is_some() {
+        timelines.insert(CONCURRENCY_ID.to_string(), ResourceTimeline::default());
+    }
+    for (need, _) in track_needs.values() {
+        timelines.insert(need.resource_id.clone(), ResourceTimeline::default());
+    }
+
+    // A resource with a calendar is fully booked in the gaps before and
+    // between its availability windows, so the placement search below skips
+    // them like any other reservation. After its last window it is treated
+    // as free again, and steps that land there are warned about.
+    let mut calendar_ends: HashMap<&str, u32> = HashMap::new();
+    for calendar in &template.resource_calendars {
+        let id = calendar.resource_id.as_str();
+        let Some(timeline) = timelines.get_mut(id) else {
+            continue;
+        };
+        let capacity = resource_capacity.get(id).copied().unwrap_or(0);
+        let mut windows: Vec<(u32, u32)> = calendar
+            .available_windows
+            .iter()
+            .filter(|w| w.start_offset_mins < w.end_offset_mins)
+            .map(|w| (w.start_offset_mins, w.end_offset_mins))
+            .collect();
+        windows.sort_unstable();
+        let mut free_from = 0;
+        for (start, end) in windows {
+            if start > free_from {
+                timeline.reserve(free_from, start, capacity);
+            }
+            free_from = free_from.max(end);
+        }
+        calendar_ends.insert(id, free_from);
+    }
+
+    // People resources with named roles also get one sub-timeline per role,
+    // keyed by (resource_id, role), checked against the resource's capacity
+    // alongside the resource-wide timeline. A role with a smaller headcount
+    // is booked up to that headcount for good, like a calendar gap.
+    let mut role_timelines: HashMap<(String, String), ResourceTimeline> = HashMap::new();
+    for r in template.resources.iter().filter(|r| matches!(r.kind, ResourceKind::People)) {
+        let capacity = resource_capacity.get(r.id.as_str()).copied().unwrap_or(0);
+        for role in &r.roles {
+            let mut timeline = ResourceTimeline::default();
+            let headcount = r.role_headcounts.iter().find(|h| h.role == *role);
+            if let Some(h) = headcount.filter(|h| h.headcount < capacity) {
+                timeline.reserve(0, u32::MAX, capacity - h.headcount);
+            }
+            role_timelines.insert((r.id.clone(), role.clone()), timeline);
+        }
+    }
 
     // Consumable: track remaining quantity.
     // Use the overridden capacity from resource_capacity (which has inventory overrides applied)
@@ -196,18 +444,48 @@ pub fn allocate_resources(
         let step_id = solved_steps[idx].step_id.clone();
         let step_id_str = step_id.as_str();
 
-        let needs = match step_needs.get(step_id_str) {
-            Some(n) if !n.is_empty() => *n,
-            _ => continue, // No resource needs — skip allocation, leave CPM times
-        };
+        let needs: &[crate::model::ResourceNeed] =
+            step_needs.get(step_id_str).map(|n| n.as_slice()).unwrap_or(&[]);
+        let track_need = step_tracks
+            .get(step_id_str)
+            .and_then(|track| track_needs.get(track))
+            .map(|(need, _)| need);
+        if needs.is_empty() && concurrency_need.is_none() && track_need.is_none() {
+            continue; // No resource needs — skip allocation, leave CPM times
+        }
 
         let es = early_starts.get(step_id_str).copied().unwrap_or(0);
         let ls = late_starts.get(step_id_str).copied().unwrap_or(es);
-        let duration = solved_steps[idx].end_offset_mins - solved_steps[idx].start_offset_mins;
+        let mut duration =
+            solved_steps[idx].end_offset_mins - solved_steps[idx].start_offset_mins;
         let is_alap = matches!(
             step_policies.get(step_id_str),
             Some(TimingPolicy::Alap)
         );
+        let is_fixed = matches!(
+            step_policies.get(step_id_str),
+            Some(TimingPolicy::Fixed { .. })
+        );
+
+        // Pick a member resource for every need that names a group
+        let resolved: Vec<ResourceNeed>;
+        let needs = if needs.iter().any(|n| resource_groups.contains_key(n.resource_id.as_str())) {
+            resolved = resolve_group_needs(
+                needs,
+                es,
+                duration,
+                &resource_groups,
+                &resource_kinds,
+                &consumable_remaining,
+                &timelines,
+                &role_timelines,
+                &resource_capacity,
+                &resource_names,
+            );
+            resolved.as_slice()
+        } else {
+            needs
+        };
 
         // Handle consumables: check availability and emit warnings before placement
         for need in needs.iter() {
@@ -222,36 +500,67 @@ pub fn allocate_resources(
                         .get(need.resource_id.as_str())
                         .copied()
                         .unwrap_or(need.resource_id.as_str());
-                    warnings.push(format!(
+                    let message = format!(
                         "Consumable '{}' may run out -- {} needed but only {} available",
                         rname, need.quantity, remaining
-                    ));
+                    );
+                    solved_steps[idx].notes.push(StepNote {
+                        code: NoteCode::ResourceConflict,
+                        message: message.clone(),
+                    });
+                    warnings.push(message);
                 }
             }
         }
 
-        // Separate timed needs (Equipment/People) from consumable needs
-        let timed_needs: Vec<_> = needs
+        // Separate timed needs (Equipment/People) from consumable and cost needs
+        let mut timed_needs: Vec<_> = needs
             .iter()
             .filter(|n| {
                 !matches!(
                     resource_kinds.get(n.resource_id.as_str()),
-                    Some(ResourceKind::Consumable)
+                    Some(ResourceKind::Consumable) | Some(ResourceKind::Cost)
                 )
             })
             .collect();
+        timed_needs.extend(concurrency_need.as_ref());
+        timed_needs.extend(track_need);
 
         // -----------------------------------------------------------------------
         // Find feasible start for timed resources
         // -----------------------------------------------------------------------
 
-        let feasible_start: u32;
+        let mut feasible_start: u32;
         let mut pushed_past_float = false;
         let mut blocking_resource_name = String::new();
 
         if timed_needs.is_empty() {
             // Only consumables — keep CPM-computed start
             feasible_start = solved_steps[idx].start_offset_mins;
+        } else if is_fixed {
+            // Fixed: stay put, even over capacity
+            feasible_start = solved_steps[idx].start_offset_mins;
+            let (ok, bad) = check_all_timed(
+                feasible_start,
+                duration,
+                &timed_needs,
+                &timelines,
+                &role_timelines,
+                &resource_capacity,
+            );
+            if let (false, Some(rid)) = (ok, bad) {
+                let title = step_titles.get(step_id_str).copied().unwrap_or(step_id_str);
+                let rname = resource_names.get(rid.as_str()).copied().unwrap_or(rid.as_str());
+                let message = format!(
+                    "Step '{}' is fixed to start at {} min but '{}' is already in use then",
+                    title, feasible_start, rname
+                );
+                solved_steps[idx].notes.push(StepNote {
+                    code: NoteCode::ResourceConflict,
+                    message: message.clone(),
+                });
+                warnings.push(message);
+            }
         } else if is_alap {
             // ALAP: find LATEST feasible start in [es, ls]
             // Gather candidate times from interval boundaries, then scan backward
@@ -289,8 +598,11 @@ pub fn allocate_resources(
                         .get(need.resource_id.as_str())
                         .copied()
                         .unwrap_or(0);
+                    let role_ok = role_timeline(&role_timelines, need)
+                        .map(|tl| tl.used_at_range(t, t + duration) + need.quantity <= cap)
+                        .unwrap_or(true);
                     if let Some(timeline) = timelines.get(need.resource_id.as_str()) {
-                        timeline.used_at_range(t, t + duration) + need.quantity <= cap
+                        role_ok && timeline.used_at_range(t, t + duration) + need.quantity <= cap
                     } else {
                         false
                     }
@@ -310,6 +622,7 @@ pub fn allocate_resources(
                     duration,
                     &timed_needs,
                     &timelines,
+                    &role_timelines,
                     &resource_capacity,
                     &resource_names,
                 );
@@ -326,6 +639,7 @@ pub fn allocate_resources(
                 duration,
                 &timed_needs,
                 &timelines,
+                &role_timelines,
                 &resource_capacity,
                 &resource_names,
             );
@@ -336,6 +650,81 @@ pub fn allocate_resources(
             }
         }
 
+        // A step moved off its CPM start must begin in working time. An
+        // interruptible one spans the non-working time its work crosses from
+        // there; any other waits for a stretch that holds it. Either may run
+        // into other reservations and move again.
+        let cpm_start = solved_steps[idx].start_offset_mins;
+        let timed = calendar.zip(calendar_work.get(step_id_str).copied());
+        if let Some((cal, (interruptible, work))) = timed.filter(|_| feasible_start != cpm_start) {
+            loop {
+                let (start, span) = if interruptible {
+                    let start = crate::calendar::next_working_minute(cal, feasible_start);
+                    (start, crate::calendar::finish_from(cal, start, work) - start)
+                } else {
+                    let start = crate::calendar::next_fit_start(cal, feasible_start, work);
+                    (start.unwrap_or(feasible_start), duration)
+                };
+                let (found, bad_rname) = if timed_needs.is_empty() {
+                    (start, String::new())
+                } else {
+                    find_earliest_feasible(
+                        start,
+                        span,
+                        &timed_needs,
+                        &timelines,
+                        &role_timelines,
+                        &resource_capacity,
+                        &resource_names,
+                    )
+                };
+                feasible_start = found;
+                duration = span;
+                if found == start {
+                    break;
+                }
+                if blocking_resource_name.is_empty() {
+                    blocking_resource_name = bad_rname;
+                }
+            }
+            pushed_past_float = feasible_start > ls;
+        }
+
+        // Resource splitting: rather than wait for every unit, start sooner
+        // with fewer units of the `allow_partial` needs if that finishes
+        // the step earlier. Steps on a working calendar aren't split.
+        let split: Vec<ResourceNeed>;
+        let mut needs = needs;
+        if splitting && timed.is_none() && !is_alap && feasible_start > es {
+            let best = find_partial_split(
+                es,
+                duration,
+                feasible_start + duration,
+                &timed_needs,
+                &resource_kinds,
+                &timelines,
+                &role_timelines,
+                &resource_capacity,
+                &resource_names,
+            );
+            if let Some((start, scaled_duration, ratio)) = best {
+                let title = step_titles.get(step_id_str).copied().unwrap_or(step_id_str);
+                solved_steps[idx].notes.push(StepNote {
+                    code: NoteCode::PartialResources,
+                    message: format!(
+                        "Step '{}' started at {} min with part of its resources, taking {} min \
+                         instead of {}",
+                        title, start, scaled_duration, duration
+                    ),
+                });
+                split = needs.iter().map(|n| scale_need(n, ratio, &resource_kinds)).collect();
+                needs = split.as_slice();
+                feasible_start = start;
+                duration = scaled_duration;
+                pushed_past_float = feasible_start > ls;
+            }
+        }
+
         // Emit warning if step was pushed past its float
         if pushed_past_float {
             let title = step_titles.get(step_id_str).copied().unwrap_or(step_id_str);
@@ -344,10 +733,54 @@ pub fn allocate_resources(
             } else {
                 blocking_resource_name.clone()
             };
-            warnings.push(format!(
+            let message = format!(
                 "Step '{}' was delayed beyond its available slack due to resource conflict with '{}'",
                 title, rname
-            ));
+            );
+            solved_steps[idx].notes.push(StepNote {
+                code: NoteCode::ResourceConflict,
+                message: message.clone(),
+            });
+            warnings.push(message);
+        } else if is_alap && feasible_start < ls {
+            solved_steps[idx].notes.push(StepNote {
+                code: NoteCode::AlternativeSlotAvailable,
+                message: format!(
+                    "Latest start at {} min was taken; placed earlier at {} min",
+                    ls, feasible_start
+                ),
+            });
+        } else if !is_alap && feasible_start > es {
+            solved_steps[idx].notes.push(StepNote {
+                code: NoteCode::FloatConsumedByAllocation,
+                message: format!(
+                    "Waited {} min for resources, using that much of its {} min float",
+                    feasible_start - es,
+                    ls - es
+                ),
+            });
+        }
+
+        for need in &timed_needs {
+            let Some(&available_until) = calendar_ends.get(need.resource_id.as_str()) else {
+                continue;
+            };
+            if feasible_start + duration > available_until {
+                let title = step_titles.get(step_id_str).copied().unwrap_or(step_id_str);
+                let rname = resource_names
+                    .get(need.resource_id.as_str())
+                    .copied()
+                    .unwrap_or(need.resource_id.as_str());
+                let message = format!(
+                    "Step '{}' uses '{}' after its last availability window",
+                    title, rname
+                );
+                solved_steps[idx].notes.push(StepNote {
+                    code: NoteCode::ResourceConflict,
+                    message: message.clone(),
+                });
+                warnings.push(message);
+            }
         }
 
         // -----------------------------------------------------------------------
@@ -355,6 +788,24 @@ pub fn allocate_resources(
         // -----------------------------------------------------------------------
         solved_steps[idx].start_offset_mins = feasible_start;
         solved_steps[idx].end_offset_mins = feasible_start + duration;
+        let stretched = timed.filter(|&(_, (interruptible, _))| interruptible);
+        if let Some((cal, (_, work))) = stretched.filter(|_| feasible_start != cpm_start) {
+            let segments = crate::calendar::working_segments(cal, feasible_start, work);
+            // Wall-clock bounds are filled in by `solver::finish_schedule`.
+            solved_steps[idx].segments = if segments.len() > 1 {
+                segments
+                    .into_iter()
+                    .map(|(start, end)| StepSegment {
+                        start_offset_mins: start,
+                        end_offset_mins: end,
+                        wall_start: None,
+                        wall_end: None,
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        }
 
         let mut assigned: Vec<AssignedResource> = Vec::new();
 
@@ -369,6 +820,7 @@ pub fn allocate_resources(
                         assigned.push(AssignedResource {
                             resource_id: need.resource_id.clone(),
                             quantity_used: used,
+                            role_used: None,
                         });
                     }
                 }
@@ -376,9 +828,32 @@ pub fn allocate_resources(
                     if let Some(timeline) = timelines.get_mut(need.resource_id.as_str()) {
                         timeline.reserve(feasible_start, feasible_start + duration, need.quantity);
                     }
+                    let role_used = match (kind, &need.role) {
+                        (Some(ResourceKind::People), Some(role)) => {
+                            let key = (need.resource_id.clone(), role.clone());
+                            if let Some(timeline) = role_timelines.get_mut(&key) {
+                                timeline.reserve(
+                                    feasible_start,
+                                    feasible_start + duration,
+                                    need.quantity,
+                                );
+                            }
+                            Some(role.clone())
+                        }
+                        _ => None,
+                    };
                     assigned.push(AssignedResource {
                         resource_id: need.resource_id.clone(),
                         quantity_used: need.quantity,
+                        role_used,
+                    });
+                }
+                Some(ResourceKind::Cost) => {
+                    // Cost resources are never capacity-constrained; record the charge only
+                    assigned.push(AssignedResource {
+                        resource_id: need.resource_id.clone(),
+                        quantity_used: need.quantity,
+                        role_used: None,
                     });
                 }
                 None => {
@@ -387,12 +862,706 @@ pub fn allocate_resources(
             }
         }
 
+        for limit in concurrency_need.iter().chain(track_need) {
+            if let Some(timeline) = timelines.get_mut(limit.resource_id.as_str()) {
+                timeline.reserve(feasible_start, feasible_start + duration, 1);
+            }
+        }
+
         solved_steps[idx].assigned_resources = assigned;
     }
 
     warnings
 }
 
+// ---------------------------------------------------------------------------
+// Deadline-proximity ordering
+// ---------------------------------------------------------------------------
+
+/// An alternative to the default placement order: steps with the smallest
+/// float ratio go first, then earlier starts, then longer steps.
+///
+/// A step's float ratio is its total float as a share of its CPM late
+/// finish, i.e. how much of the time it has until it must be done is
+/// slack. Critical steps have a ratio of 0, and a step whose late finish is
+/// close to the deadline only ranks low when it also has plenty of float.
+/// Meant to be called on unallocated CPM results, which is what the
+/// allocator's comparator sees.
+pub fn deadline_proximity_order(a: &SolvedStep, b: &SolvedStep) -> Ordering {
+    float_ratio(a)
+        .total_cmp(&float_ratio(b))
+        .then(a.start_offset_mins.cmp(&b.start_offset_mins))
+        .then_with(|| {
+            let dur = |s: &SolvedStep| s.end_offset_mins.saturating_sub(s.start_offset_mins);
+            dur(b).cmp(&dur(a))
+        })
+}
+
+fn float_ratio(step: &SolvedStep) -> f64 {
+    let late_finish = step.end_offset_mins + step.total_float_mins;
+    if late_finish == 0 {
+        return 0.0;
+    }
+    step.total_float_mins as f64 / late_finish as f64
+}
+
+// ---------------------------------------------------------------------------
+// Cost accumulation
+// ---------------------------------------------------------------------------
+
+/// Total cost of a single step: for each need on a Cost resource,
+/// `quantity * duration * cost_per_minute` (falling back to the resource's
+/// `unit_cost` when the need sets no rate).
+pub fn step_cost(template: &ScheduleTemplate, step_id: &str, duration_mins: u32) -> f64 {
+    template
+        .steps
+        .iter()
+        .find(|s| s.id == step_id)
+        .map_or(0.0, |step| needs_cost(template, step, duration_mins))
+}
+
+/// `step_cost` for a step already looked up.
+fn needs_cost(template: &ScheduleTemplate, step: &Step, duration_mins: u32) -> f64 {
+    step.resource_needs
+        .iter()
+        .filter_map(|need| {
+            let resource = template
+                .resources
+                .iter()
+                .find(|r| r.id == need.resource_id && matches!(r.kind, ResourceKind::Cost))?;
+            let rate = need.cost_per_minute.unwrap_or(resource.unit_cost);
+            Some(need.quantity as f64 * duration_mins as f64 * rate)
+        })
+        .fold(0.0, |total, cost| total + cost)
+}
+
+/// Sum `step_cost` over every solved step, billing each for the minutes it
+/// actually works (not the time the working calendar pauses it).
+pub fn project_total_cost(template: &ScheduleTemplate, solved_steps: &[SolvedStep]) -> f64 {
+    let steps: HashMap<&str, &Step> =
+        template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    solved_steps
+        .iter()
+        .filter_map(|s| {
+            let step = steps.get(s.step_id.as_str())?;
+            Some(needs_cost(template, step, working_mins(s)))
+        })
+        .fold(0.0, |total, cost| total + cost)
+}
+
+/// Minutes `step` actually works: the total of its segments when the
+/// working calendar pauses it, otherwise its whole span.
+fn working_mins(step: &SolvedStep) -> u32 {
+    if step.segments.is_empty() {
+        step.end_offset_mins - step.start_offset_mins
+    } else {
+        step.segments.iter().map(|s| s.end_offset_mins - s.start_offset_mins).sum()
+    }
+}
+
+/// Fill in `SolvedStep::cost` from the assigned resources' rates and return
+/// the schedule total.
+///
+/// Each assignment costs `quantity_used * cost_per_unit_per_min` for every
+/// minute the step actually works, so a step stretched by resource
+/// splitting is charged for its longer run. Steps with no rated assignment
+/// get `None`; the total is `None` when no resource has a rate.
+pub fn apply_resource_rates(
+    template: &ScheduleTemplate,
+    solved_steps: &mut [SolvedStep],
+) -> Option<f64> {
+    let rates: HashMap<&str, f64> = template
+        .resources
+        .iter()
+        .filter_map(|r| r.cost_per_unit_per_min.map(|rate| (r.id.as_str(), rate)))
+        .collect();
+    if rates.is_empty() {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for step in solved_steps.iter_mut() {
+        let duration = working_mins(step);
+        step.cost = step
+            .assigned_resources
+            .iter()
+            .filter_map(|a| {
+                let rate = rates.get(a.resource_id.as_str())?;
+                Some(a.quantity_used as f64 * duration as f64 * rate)
+            })
+            .reduce(|a, b| a + b);
+        total += step.cost.unwrap_or(0.0);
+    }
+    Some(total)
+}
+
+// ---------------------------------------------------------------------------
+// Utilization targets
+// ---------------------------------------------------------------------------
+
+/// Warn about Equipment/People resources whose peak use in the allocated
+/// schedule, as a share of capacity, falls short of their
+/// `utilization_target`. Capacities honour `inventory` overrides.
+pub fn check_utilization_targets(
+    template: &ScheduleTemplate,
+    solved_steps: &[SolvedStep],
+    inventory: Option<&ResourceInventory>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for resource in &template.resources {
+        let Some(target) = resource.utilization_target else {
+            continue;
+        };
+        let capacity = effective_capacity(resource, inventory);
+        if !matches!(resource.kind, ResourceKind::Equipment | ResourceKind::People)
+            || capacity == 0
+        {
+            continue;
+        }
+        let usages = resource_usages(solved_steps, &resource.id);
+        let (peak, _) = peak_usage(&usages, 0, u32::MAX);
+        let utilization = f64::from(peak) / f64::from(capacity);
+        if utilization < target {
+            warnings.push(format!(
+                "Resource '{}' has peak utilization {:.0}%, below target {:.0}%",
+                resource.name,
+                utilization * 100.0,
+                target * 100.0
+            ));
+        }
+    }
+    warnings
+}
+
+// ---------------------------------------------------------------------------
+// Compression reporting
+// ---------------------------------------------------------------------------
+
+/// A non-critical step whose resource reservation delays another step, and
+/// which could be shortened to hand the resource over sooner.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionCandidate {
+    pub step_id: String,
+    pub current_duration_mins: u32,
+    /// Minutes this step would have to shrink by for every step it blocks to
+    /// start at its early start. Capped so the step keeps at least 1 minute.
+    pub min_compression_mins: u32,
+    /// True when at least one blocked step was pushed past its late start, so
+    /// compressing this step would pull downstream work (and possibly the
+    /// project end) earlier.
+    pub compression_frees_downstream: bool,
+}
+
+/// Identify non-critical steps that hold a resource another step was waiting
+/// for.
+///
+/// A step `S` blocks a step `T` when both were assigned the same Equipment or
+/// People resource, `T` was delayed past its CPM early start, and `S` still
+/// held the resource after `T`'s early start while finishing no later than
+/// `T` started. Critical steps are never reported: they are allocated first
+/// and are better shortened through the critical path itself.
+///
+/// Returns an empty list when the template cannot be solved by CPM.
+pub fn find_compressible_steps(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> Vec<CompressionCandidate> {
+    // Early starts from the template solve actually schedules: recurring
+    // copies expanded and inactive conditional steps dropped.
+    let prepared = crate::solver::prepare_template(template, &SolveOptions::default());
+    let cpm = match crate::solver::cpm(&prepared) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    let timed_resources: HashSet<&str> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| r.id.as_str())
+        .collect();
+
+    let timed_ids = |step: &SolvedStep| -> HashSet<String> {
+        step.assigned_resources
+            .iter()
+            .filter(|a| timed_resources.contains(a.resource_id.as_str()))
+            .map(|a| a.resource_id.clone())
+            .collect()
+    };
+
+    // Steps that allocation pushed past their early start: (step, ES, LS, resources)
+    let delayed: Vec<(&SolvedStep, u32, u32, HashSet<String>)> = solved
+        .solved_steps
+        .iter()
+        .filter_map(|s| {
+            let es = cpm.early_starts.get(&s.step_id).copied()?;
+            let ls = cpm.late_starts.get(&s.step_id).copied().unwrap_or(es);
+            if s.start_offset_mins > es {
+                Some((s, es, ls, timed_ids(s)))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut candidates: Vec<CompressionCandidate> = Vec::new();
+
+    for step in solved.solved_steps.iter().filter(|s| !s.is_critical) {
+        let resources = timed_ids(step);
+        if resources.is_empty() {
+            continue;
+        }
+        let duration = step.end_offset_mins - step.start_offset_mins;
+
+        let mut needed: u32 = 0;
+        let mut frees_downstream = false;
+        for (blocked, es, ls, blocked_resources) in &delayed {
+            if blocked.step_id == step.step_id || resources.is_disjoint(blocked_resources) {
+                continue;
+            }
+            let holds_past_es = step.end_offset_mins > *es;
+            let releases_before_start = step.end_offset_mins <= blocked.start_offset_mins;
+            let started_first = step.start_offset_mins < blocked.start_offset_mins;
+            if holds_past_es && releases_before_start && started_first {
+                needed = needed.max(step.end_offset_mins - es);
+                if blocked.start_offset_mins > *ls {
+                    frees_downstream = true;
+                }
+            }
+        }
+
+        let min_compression_mins = needed.min(duration.saturating_sub(1));
+        if min_compression_mins > 0 {
+            candidates.push(CompressionCandidate {
+                step_id: step.step_id.clone(),
+                current_duration_mins: duration,
+                min_compression_mins,
+                compression_frees_downstream: frees_downstream,
+            });
+        }
+    }
+
+    candidates
+}
+
+// ---------------------------------------------------------------------------
+// Float consumption
+// ---------------------------------------------------------------------------
+
+/// Float a step lost waiting for a resource during allocation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FloatConsumption {
+    pub step_id: String,
+    /// Total float in the CPM-only schedule.
+    pub original_float_mins: u32,
+    /// How far allocation moved the step past its CPM-only start, up to
+    /// `original_float_mins`.
+    pub float_consumed_by_allocation: u32,
+    /// The Equipment or People resource the step waited for.
+    pub consuming_resource_id: String,
+}
+
+/// Find the steps whose float was used up by waiting for resources, by
+/// comparing their allocated starts with their CPM-only placements in
+/// `cpm_result` (the late start for ALAP steps, the offset for Fixed ones).
+/// `template` must be the one that was solved (see
+/// `solver::prepare_template`), with the capacities allocation used.
+///
+/// A resource is only blamed when the other steps holding it at the step's
+/// CPM-only placement left too little of it for the step. Of those, the one
+/// another step released exactly when the delayed step started wins.
+/// Steps that had no float, and steps delayed by something else (e.g. a
+/// concurrency limit), are not reported.
+pub fn find_float_consuming_resources(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    cpm_result: &CpmResult,
+) -> Vec<FloatConsumption> {
+    let timed_capacity: HashMap<&str, u32> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| (r.id.as_str(), r.capacity))
+        .collect();
+    let cpm_steps: HashMap<&str, &SolvedStep> =
+        cpm_result.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+
+    let mut consumptions: Vec<FloatConsumption> = Vec::new();
+    for step in &solved.solved_steps {
+        let Some(planned) = cpm_steps.get(step.step_id.as_str()) else {
+            continue;
+        };
+        let original_float_mins = planned.total_float_mins;
+        let delay = step.start_offset_mins.saturating_sub(planned.start_offset_mins);
+        if delay == 0 || original_float_mins == 0 {
+            continue;
+        }
+
+        // The resources that were short while the step would have run at
+        // its CPM-only placement.
+        let from = planned.start_offset_mins;
+        let to = from + (step.end_offset_mins - step.start_offset_mins);
+        let held: Vec<&str> = step
+            .assigned_resources
+            .iter()
+            .filter(|a| {
+                let Some(&capacity) = timed_capacity.get(a.resource_id.as_str()) else {
+                    return false;
+                };
+                let others: Vec<(u32, u32, u32)> = solved
+                    .solved_steps
+                    .iter()
+                    .filter(|o| o.step_id != step.step_id)
+                    .flat_map(|o| {
+                        o.assigned_resources
+                            .iter()
+                            .filter(|b| b.resource_id == a.resource_id)
+                            .map(move |b| (o.start_offset_mins, o.end_offset_mins, b.quantity_used))
+                    })
+                    .collect();
+                peak_usage(&others, from, to).0 + a.quantity_used > capacity
+            })
+            .map(|a| a.resource_id.as_str())
+            .collect();
+        let released_at_start = held.iter().copied().find(|id| {
+            solved.solved_steps.iter().any(|other| {
+                other.step_id != step.step_id
+                    && other.end_offset_mins == step.start_offset_mins
+                    && other.assigned_resources.iter().any(|a| a.resource_id == *id)
+            })
+        });
+        let Some(resource_id) = released_at_start.or(held.first().copied()) else {
+            continue;
+        };
+
+        consumptions.push(FloatConsumption {
+            step_id: step.step_id.clone(),
+            original_float_mins,
+            float_consumed_by_allocation: delay.min(original_float_mins),
+            consuming_resource_id: resource_id.to_string(),
+        });
+    }
+    consumptions
+}
+
+// ---------------------------------------------------------------------------
+// Bottleneck detection
+// ---------------------------------------------------------------------------
+
+/// The resource whose contention stretches the schedule the most.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BottleneckReport {
+    pub resource_id: String,
+    pub resource_name: String,
+    /// Minutes the schedule would shrink by if this resource had unlimited
+    /// capacity.
+    pub delay_caused_mins: u32,
+    /// Steps using this resource that allocation pushed past their early start.
+    pub affected_step_ids: Vec<String>,
+}
+
+/// Find the Equipment or People resource responsible for most of the gap
+/// between the CPM-only duration and the allocated duration.
+///
+/// Each resource is relaxed in turn (its capacity raised to cover every need
+/// at once) and the template re-solved; the resource whose relaxation
+/// shortens `solved` the most is the bottleneck. Returns `None` when
+/// allocation didn't extend the schedule or no single resource accounts for
+/// any of the delay. `inventory` should be the one `solved` was solved with;
+/// its overrides apply to every resource but the relaxed one.
+pub fn find_bottleneck_resource(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Option<BottleneckReport> {
+    // The template solve actually schedules: recurring copies expanded and
+    // inactive conditional steps dropped.
+    let prepared = crate::solver::prepare_template(template, &SolveOptions::default());
+    let cpm = crate::solver::cpm(&prepared).ok()?;
+    let cpm_duration = cpm.solved_steps.iter().map(|s| s.end_offset_mins).max()?;
+    let allocated_duration = solved.summary.total_duration_mins;
+    if allocated_duration <= cpm_duration {
+        return None;
+    }
+
+    let mut best: Option<(&crate::model::Resource, u32)> = None;
+    for resource in template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+    {
+        let total_need: u32 = prepared
+            .steps
+            .iter()
+            .flat_map(|s| s.resource_needs.iter())
+            .filter(|n| n.resource_id == resource.id)
+            .map(|n| n.quantity)
+            .sum();
+        if total_need <= effective_capacity(resource, inventory) {
+            continue; // Never contended
+        }
+
+        let mut relaxed = inventory.cloned().unwrap_or(ResourceInventory { items: vec![] });
+        relaxed.items.retain(|i| i.resource_id != resource.id);
+        relaxed.items.push(ResourceInventoryItem {
+            resource_id: resource.id.clone(),
+            available_quantity: total_need,
+        });
+        let Ok(relaxed_solved) = crate::solver::solve(template, Some(&relaxed)) else {
+            continue;
+        };
+        let saved = allocated_duration.saturating_sub(relaxed_solved.summary.total_duration_mins);
+        if saved > 0 && best.is_none_or(|(_, b)| saved > b) {
+            best = Some((resource, saved));
+        }
+    }
+
+    let (resource, delay_caused_mins) = best?;
+    let affected_step_ids = solved
+        .solved_steps
+        .iter()
+        .filter(|s| s.assigned_resources.iter().any(|a| a.resource_id == resource.id))
+        .filter(|s| {
+            cpm.early_starts
+                .get(&s.step_id)
+                .is_some_and(|&es| s.start_offset_mins > es)
+        })
+        .map(|s| s.step_id.clone())
+        .collect();
+
+    Some(BottleneckReport {
+        resource_id: resource.id.clone(),
+        resource_name: resource.name.clone(),
+        delay_caused_mins,
+        affected_step_ids,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Feasibility pre-check
+// ---------------------------------------------------------------------------
+
+/// A span of time in which the steps that must be running demand more of a
+/// resource than is available.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfeasibleWindow {
+    pub start_mins: u32,
+    pub end_mins: u32,
+    pub resource_id: String,
+    pub demand: u32,
+    pub capacity: u32,
+}
+
+/// Result of `check_resource_feasibility`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeasibilityReport {
+    /// True when no window is over capacity, i.e. the CPM duration might be
+    /// achievable with the available resources.
+    pub feasible: bool,
+    pub infeasible_windows: Vec<InfeasibleWindow>,
+}
+
+/// Cheaply check, without running the allocator, whether the resources could
+/// possibly support the CPM schedule.
+///
+/// A step with total float shorter than its duration must be running during
+/// `[late start, early finish)` however it is placed within its float window.
+/// For Equipment and People, every span where those compulsory parts together
+/// need more than the available capacity is reported. For Consumables, the
+/// total need across all steps is compared with the supply over the whole
+/// schedule. Capacities honour `inventory` overrides.
+///
+/// An infeasible report means the allocator will have to stretch the schedule
+/// (or fail to supply consumables); a feasible one doesn't guarantee it won't.
+pub fn check_resource_feasibility(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Result<FeasibilityReport, crate::solver::SolveError> {
+    let cpm = crate::solver::cpm_only(template)?;
+
+    let capacity_of = |resource_id: &str, template_capacity: u32| {
+        inventory
+            .and_then(|inv| inv.items.iter().find(|i| i.resource_id == resource_id))
+            .map_or(template_capacity, |i| i.available_quantity)
+    };
+    let needs_by_step: HashMap<&str, &[crate::model::ResourceNeed]> = template
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.resource_needs.as_slice()))
+        .collect();
+
+    // Compulsory part of every step: [late start, early finish).
+    let compulsory: Vec<(u32, u32, &str)> = cpm
+        .solved_steps
+        .iter()
+        .filter_map(|s| {
+            let es = cpm.early_starts[&s.step_id];
+            let ls = cpm.late_starts[&s.step_id];
+            let ef = es + (s.end_offset_mins - s.start_offset_mins);
+            (ls < ef).then_some((ls, ef, s.step_id.as_str()))
+        })
+        .collect();
+    let mut boundaries: Vec<u32> = compulsory.iter().flat_map(|c| [c.0, c.1]).collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut infeasible_windows: Vec<InfeasibleWindow> = Vec::new();
+    for resource in &template.resources {
+        let capacity = capacity_of(&resource.id, resource.capacity);
+        let quantity_for = |step_id: &str| -> u32 {
+            needs_by_step
+                .get(step_id)
+                .map(|needs| {
+                    needs
+                        .iter()
+                        .filter(|n| n.resource_id == resource.id)
+                        .map(|n| n.quantity)
+                        .sum()
+                })
+                .unwrap_or(0)
+        };
+
+        match resource.kind {
+            ResourceKind::Equipment | ResourceKind::People => {
+                for pair in boundaries.windows(2) {
+                    let (start, end) = (pair[0], pair[1]);
+                    let demand: u32 = compulsory
+                        .iter()
+                        .filter(|c| c.0 <= start && end <= c.1)
+                        .map(|c| quantity_for(c.2))
+                        .sum();
+                    if demand <= capacity {
+                        continue;
+                    }
+                    // Merge with the previous window when it continues it.
+                    match infeasible_windows.last_mut() {
+                        Some(last)
+                            if last.resource_id == resource.id
+                                && last.end_mins == start
+                                && last.demand == demand =>
+                        {
+                            last.end_mins = end;
+                        }
+                        _ => infeasible_windows.push(InfeasibleWindow {
+                            start_mins: start,
+                            end_mins: end,
+                            resource_id: resource.id.clone(),
+                            demand,
+                            capacity,
+                        }),
+                    }
+                }
+            }
+            ResourceKind::Consumable => {
+                let demand: u32 = template.steps.iter().map(|s| quantity_for(&s.id)).sum();
+                if demand > capacity {
+                    infeasible_windows.push(InfeasibleWindow {
+                        start_mins: 0,
+                        end_mins: cpm.project_end,
+                        resource_id: resource.id.clone(),
+                        demand,
+                        capacity,
+                    });
+                }
+            }
+            ResourceKind::Cost => {}
+        }
+    }
+
+    Ok(FeasibilityReport {
+        feasible: infeasible_windows.is_empty(),
+        infeasible_windows,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helper: resource splitting
+// ---------------------------------------------------------------------------
+
+/// Search for the best way to run a step with part of its `allow_partial`
+/// needs, beating `finish_by` (when the step would finish with every unit).
+///
+/// Each candidate is a fraction `used / wanted` of one partial need's
+/// quantity, never below its `min_people`. Every partial need is scaled by
+/// it (see `scale_need`) and the duration by its inverse (rounding up).
+/// Returns the start, scaled duration and fraction of the candidate
+/// finishing first, preferring more units on ties.
+#[allow(clippy::too_many_arguments)]
+fn find_partial_split(
+    search_from: u32,
+    duration: u32,
+    finish_by: u32,
+    timed_needs: &[&ResourceNeed],
+    resource_kinds: &HashMap<&str, &ResourceKind>,
+    timelines: &HashMap<String, ResourceTimeline>,
+    role_timelines: &HashMap<(String, String), ResourceTimeline>,
+    resource_capacity: &HashMap<&str, u32>,
+    resource_names: &HashMap<&str, &str>,
+) -> Option<(u32, u32, (u32, u32))> {
+    let mut ratios: Vec<(u32, u32)> = timed_needs
+        .iter()
+        .filter(|n| n.allow_partial && is_splittable(n, resource_kinds))
+        .flat_map(|n| (n.min_people.unwrap_or(1).max(1)..n.quantity).map(|used| (used, n.quantity)))
+        .collect();
+    // Largest fraction first, so ties go to the candidate using more units.
+    ratios.sort_by(|a, b| (b.0 as u64 * a.1 as u64).cmp(&(a.0 as u64 * b.1 as u64)));
+
+    let mut best: Option<(u32, u32, (u32, u32))> = None;
+    let mut best_finish = finish_by;
+    for ratio in ratios {
+        let scaled_duration = (duration as u64 * ratio.1 as u64).div_ceil(ratio.0 as u64) as u32;
+        let scaled: Vec<ResourceNeed> =
+            timed_needs.iter().map(|n| scale_need(n, ratio, resource_kinds)).collect();
+        let scaled_refs: Vec<&ResourceNeed> = scaled.iter().collect();
+        let (start, _) = find_earliest_feasible(
+            search_from,
+            scaled_duration,
+            &scaled_refs,
+            timelines,
+            role_timelines,
+            resource_capacity,
+            resource_names,
+        );
+        if start + scaled_duration < best_finish {
+            best_finish = start + scaled_duration;
+            best = Some((start, scaled_duration, ratio));
+        }
+    }
+    best
+}
+
+/// A need's quantity can only be split on Equipment and People resources.
+fn is_splittable(need: &ResourceNeed, resource_kinds: &HashMap<&str, &ResourceKind>) -> bool {
+    matches!(
+        resource_kinds.get(need.resource_id.as_str()),
+        Some(ResourceKind::Equipment) | Some(ResourceKind::People)
+    )
+}
+
+/// `need` with its quantity scaled by `ratio` (rounding up, and never below
+/// its `min_people` or 1) when it allows partial assignment; otherwise an
+/// unchanged copy.
+fn scale_need(
+    need: &ResourceNeed,
+    (used, wanted): (u32, u32),
+    resource_kinds: &HashMap<&str, &ResourceKind>,
+) -> ResourceNeed {
+    if !need.allow_partial || !is_splittable(need, resource_kinds) {
+        return need.clone();
+    }
+    let quantity = (need.quantity as u64 * used as u64).div_ceil(wanted as u64) as u32;
+    let floor = need.min_people.unwrap_or(1).clamp(1, need.quantity.max(1));
+    ResourceNeed {
+        quantity: quantity.max(floor),
+        ..need.clone()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helper: find earliest feasible start >= search_from using boundary-jump scan
 // ---------------------------------------------------------------------------
@@ -406,6 +1575,7 @@ fn find_earliest_feasible(
     duration: u32,
     timed_needs: &[&crate::model::ResourceNeed],
     timelines: &HashMap<String, ResourceTimeline>,
+    role_timelines: &HashMap<(String, String), ResourceTimeline>,
     resource_capacity: &HashMap<&str, u32>,
     resource_names: &HashMap<&str, &str>,
 ) -> (u32, String) {
@@ -428,7 +1598,14 @@ fn find_earliest_feasible(
     let mut found_start: Option<u32> = None;
 
     for t in candidates {
-        let (ok, bad) = check_all_timed(t, duration, timed_needs, timelines, resource_capacity);
+        let (ok, bad) = check_all_timed(
+            t,
+            duration,
+            timed_needs,
+            timelines,
+            role_timelines,
+            resource_capacity,
+        );
         if ok {
             found_start = Some(t);
             break;
@@ -449,6 +1626,86 @@ fn find_earliest_feasible(
     (found_start.unwrap_or(search_from), first_blocker)
 }
 
+// ---------------------------------------------------------------------------
+// Helper: resolve resource-group needs to member resources
+// ---------------------------------------------------------------------------
+
+/// Copy of `needs` with each group ID replaced by the member that lets the
+/// step start earliest after `es`, ties going to the member with the most free
+/// capacity (for Consumables: the most left) and then to group order. Groups
+/// are resolved in need order, each taking the earlier choices into account.
+/// A group without any known member is left as is.
+#[allow(clippy::too_many_arguments)]
+fn resolve_group_needs(
+    needs: &[ResourceNeed],
+    es: u32,
+    duration: u32,
+    groups: &HashMap<&str, &ResourceGroup>,
+    resource_kinds: &HashMap<&str, &ResourceKind>,
+    consumable_remaining: &HashMap<String, u32>,
+    timelines: &HashMap<String, ResourceTimeline>,
+    role_timelines: &HashMap<(String, String), ResourceTimeline>,
+    resource_capacity: &HashMap<&str, u32>,
+    resource_names: &HashMap<&str, &str>,
+) -> Vec<ResourceNeed> {
+    let mut resolved: Vec<ResourceNeed> = needs.to_vec();
+    for i in 0..resolved.len() {
+        let Some(group) = groups.get(resolved[i].resource_id.as_str()) else {
+            continue;
+        };
+        let best = group
+            .resource_ids
+            .iter()
+            .filter_map(|member| {
+                let kind = resource_kinds.get(member.as_str())?;
+                let trial = ResourceNeed {
+                    resource_id: member.clone(),
+                    ..resolved[i].clone()
+                };
+                let capacity = resource_capacity.get(member.as_str()).copied().unwrap_or(0);
+                let key = match kind {
+                    ResourceKind::Equipment | ResourceKind::People => {
+                        // Check the member alongside the needs already settled.
+                        let mut timed: Vec<&ResourceNeed> = resolved
+                            .iter()
+                            .filter(|n| {
+                                matches!(
+                                    resource_kinds.get(n.resource_id.as_str()),
+                                    Some(ResourceKind::Equipment | ResourceKind::People)
+                                )
+                            })
+                            .collect();
+                        timed.push(&trial);
+                        let (start, _) = find_earliest_feasible(
+                            es,
+                            duration,
+                            &timed,
+                            timelines,
+                            role_timelines,
+                            resource_capacity,
+                            resource_names,
+                        );
+                        let used = timelines
+                            .get(member.as_str())
+                            .map_or(0, |tl| tl.used_at_range(es, es + duration));
+                        (start, Reverse(capacity.saturating_sub(used)))
+                    }
+                    ResourceKind::Consumable => {
+                        let left = consumable_remaining.get(member.as_str()).copied();
+                        (es, Reverse(left.unwrap_or(capacity)))
+                    }
+                    ResourceKind::Cost => (es, Reverse(0)),
+                };
+                Some((key, trial))
+            })
+            .min_by_key(|(key, _)| *key);
+        if let Some((_, need)) = best {
+            resolved[i] = need;
+        }
+    }
+    resolved
+}
+
 // ---------------------------------------------------------------------------
 // Helper: check all timed resource needs at a candidate time
 // Returns (feasible: bool, blocking_resource_id: Option<String>)
@@ -459,6 +1716,7 @@ fn check_all_timed(
     duration: u32,
     timed_needs: &[&crate::model::ResourceNeed],
     timelines: &HashMap<String, ResourceTimeline>,
+    role_timelines: &HashMap<(String, String), ResourceTimeline>,
     resource_capacity: &HashMap<&str, u32>,
 ) -> (bool, Option<String>) {
     for need in timed_needs {
@@ -473,6 +1731,654 @@ fn check_all_timed(
         if used + need.quantity > cap {
             return (false, Some(need.resource_id.clone()));
         }
+        let role_used = role_timeline(role_timelines, need)
+            .map(|tl| tl.used_at_range(t, t + duration))
+            .unwrap_or(0);
+        if role_used + need.quantity > cap {
+            return (false, Some(need.resource_id.clone()));
+        }
     }
     (true, None)
 }
+
+/// Look up the role sub-timeline for a need that requests a specific role.
+fn role_timeline<'a>(
+    role_timelines: &'a HashMap<(String, String), ResourceTimeline>,
+    need: &crate::model::ResourceNeed,
+) -> Option<&'a ResourceTimeline> {
+    let role = need.role.as_ref()?;
+    role_timelines.get(&(need.resource_id.clone(), role.clone()))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Resource, ResourceNeed, Step};
+
+    fn oven_step(id: &str, dur: u32) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            duration_mins: dur,
+            dependencies: vec![],
+            track_id: None,
+            timing_policy: None,
+            resource_needs: vec![ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn oven_template(steps: Vec<Step>) -> ScheduleTemplate {
+        ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            steps,
+            tracks: vec![],
+            resources: vec![Resource {
+                id: "oven".to_string(),
+                name: "Oven".to_string(),
+                kind: ResourceKind::Equipment,
+                capacity: 1,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resource_calendar_moves_steps_into_next_window() {
+        use crate::model::{AvailabilityWindow, ResourceCalendar, TimeConstraint};
+
+        // The oven is free 0-60 and 120-180. a(40) takes the first window;
+        // b(30) no longer fits in what is left of it and waits for the second.
+        let mut template = oven_template(vec![oven_step("a", 40), oven_step("b", 30)]);
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: None,
+            timezone: None,
+        });
+        let window = |start, end| AvailabilityWindow {
+            start_offset_mins: start,
+            end_offset_mins: end,
+        };
+        let calendars = vec![ResourceCalendar {
+            resource_id: "oven".to_string(),
+            available_windows: vec![window(120, 180), window(0, 60)],
+        }];
+
+        let solved = crate::solver::solve_with_resource_calendar(&template, &calendars).unwrap();
+        let b = solved.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!((b.start_offset_mins, b.end_offset_mins), (120, 150));
+        assert_eq!(b.start_time.as_deref(), Some("2026-03-01T11:00:00"));
+        assert_eq!(b.end_time.as_deref(), Some("2026-03-01T11:30:00"));
+
+        // With only the first window, b runs past its end and is warned about.
+        let calendars = vec![ResourceCalendar {
+            resource_id: "oven".to_string(),
+            available_windows: vec![window(0, 60)],
+        }];
+        let solved = crate::solver::solve_with_resource_calendar(&template, &calendars).unwrap();
+        let b = solved.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!(b.start_offset_mins, 40);
+        assert!(solved
+            .warnings
+            .contains(&"Step 'b' uses 'Oven' after its last availability window".to_string()));
+    }
+
+    #[test]
+    fn test_working_calendar_defers_moved_step_to_a_window_that_fits() {
+        use crate::model::{WorkingCalendar, WorkingWindow};
+
+        // b(40) is displaced by a(40) to minute 40, but only 20 working
+        // minutes remain in the 0-60 window, so it waits for the next day.
+        let mut template = oven_template(vec![oven_step("a", 40), oven_step("b", 40)]);
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow { start_offset_mins: 0, end_offset_mins: 60 }],
+        });
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let b = solved.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!((b.start_offset_mins, b.end_offset_mins), (1440, 1480));
+        assert!(b.segments.is_empty());
+    }
+
+    #[test]
+    fn test_compressible_step_blocking_past_float() {
+        // a(30) and b(20) share an oven; c(40) sets the project end so neither
+        // oven step is critical. b waits for a and is pushed past its late start.
+        let mut c = oven_step("c", 40);
+        c.resource_needs.clear();
+        let template = oven_template(vec![oven_step("a", 30), oven_step("b", 20), c]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let candidates = find_compressible_steps(&solved, &template);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].step_id, "a");
+        assert_eq!(candidates[0].current_duration_mins, 30);
+        assert_eq!(candidates[0].min_compression_mins, 29);
+        assert!(candidates[0].compression_frees_downstream);
+    }
+
+    #[test]
+    fn test_delayed_steps_get_notes() {
+        let note_codes = |solved: &SolvedSchedule, id: &str| -> Vec<NoteCode> {
+            let step = solved.solved_steps.iter().find(|s| s.step_id == id).unwrap();
+            step.notes.iter().map(|n| n.code).collect()
+        };
+
+        // b waits for a's oven slot and overruns its 20 min of slack.
+        let mut c = oven_step("c", 40);
+        c.resource_needs.clear();
+        let template = oven_template(vec![oven_step("a", 30), oven_step("b", 20), c]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert_eq!(note_codes(&solved, "b"), vec![NoteCode::ResourceConflict]);
+        assert!(note_codes(&solved, "a").is_empty());
+        assert!(solved.solved_steps[1].notes[0].message.contains("'Oven'"));
+
+        // With 40 min of slack, the same wait only eats into b's float.
+        let mut c = oven_step("c", 60);
+        c.resource_needs.clear();
+        let template = oven_template(vec![oven_step("a", 30), oven_step("b", 20), c]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert_eq!(note_codes(&solved, "b"), vec![NoteCode::FloatConsumedByAllocation]);
+        assert!(solved.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_float_consumption_compares_with_cpm_starts() {
+        // b waits 30 min for a's oven slot: that uses 30 of its 40 min of
+        // float, or all 20 when c is shorter.
+        let consumption = |c_duration: u32| {
+            let mut c = oven_step("c", c_duration);
+            c.resource_needs.clear();
+            let template = oven_template(vec![oven_step("a", 30), oven_step("b", 20), c]);
+            let solved = crate::solver::solve(&template, None).unwrap();
+            let cpm = crate::solver::cpm_only(&template).unwrap();
+            find_float_consuming_resources(&solved, &template, &cpm)
+        };
+
+        assert_eq!(
+            consumption(60),
+            vec![FloatConsumption {
+                step_id: "b".to_string(),
+                original_float_mins: 40,
+                float_consumed_by_allocation: 30,
+                consuming_resource_id: "oven".to_string(),
+            }]
+        );
+        let overrun = consumption(40);
+        assert_eq!(overrun.len(), 1);
+        assert_eq!(overrun[0].original_float_mins, 20);
+        assert_eq!(overrun[0].float_consumed_by_allocation, 20);
+    }
+
+    #[test]
+    fn test_float_consumption_needs_a_blocking_resource() {
+        let consumption = |template: &ScheduleTemplate| {
+            let solved = crate::solver::solve(template, None).unwrap();
+            let cpm = crate::solver::cpm_only(template).unwrap();
+            find_float_consuming_resources(&solved, template, &cpm)
+        };
+        let mut c = oven_step("c", 60);
+        c.resource_needs.clear();
+
+        // An ALAP b sits at its late start, clear of a: nothing consumed.
+        let mut b = oven_step("b", 20);
+        b.timing_policy = Some(TimingPolicy::Alap);
+        let template = oven_template(vec![oven_step("a", 30), b, c.clone()]);
+        assert!(consumption(&template).is_empty());
+
+        // b waits for the concurrency limit, not for the two ovens.
+        let mut template = oven_template(vec![oven_step("a", 30), oven_step("b", 20), c]);
+        template.resources[0].capacity = 2;
+        template.max_concurrent_steps = Some(1);
+        template.steps[2].timing_policy = Some(TimingPolicy::Alap);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(solved.solved_steps.iter().any(|s| s.step_id == "b" && s.start_offset_mins > 0));
+        assert!(consumption(&template).is_empty());
+    }
+
+    #[test]
+    fn test_group_need_picks_free_member() {
+        let any_oven = |id: &str| {
+            let mut step = oven_step(id, 30);
+            step.resource_needs[0].resource_id = "any-oven".to_string();
+            step
+        };
+        let mut template = oven_template(vec![any_oven("a"), any_oven("b"), any_oven("c")]);
+        let mut second = template.resources[0].clone();
+        second.id = "oven-2".to_string();
+        second.name = "Oven 2".to_string();
+        template.resources.push(second);
+        template.resource_groups.push(ResourceGroup {
+            id: "any-oven".to_string(),
+            name: "Any oven".to_string(),
+            resource_ids: vec!["oven".to_string(), "oven-2".to_string()],
+        });
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let placed: Vec<(&str, u32)> = solved
+            .solved_steps
+            .iter()
+            .map(|s| (s.assigned_resources[0].resource_id.as_str(), s.start_offset_mins))
+            .collect();
+        assert_eq!(placed, vec![("oven", 0), ("oven-2", 0), ("oven", 30)]);
+        assert_eq!(solved.summary.total_duration_mins, 60);
+    }
+
+    #[test]
+    fn test_role_need_records_role_used() {
+        let mut step = oven_step("a", 30);
+        step.resource_needs = vec![ResourceNeed {
+            resource_id: "crew".to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: Some("driver".to_string()),
+            cost_per_minute: None,
+            allow_partial: false,
+        }];
+        let mut template = oven_template(vec![step]);
+        template.resources.push(Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity: 2,
+            roles: vec!["driver".to_string(), "navigator".to_string()],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let assigned = &solved.solved_steps[0].assigned_resources[0];
+        assert_eq!(assigned.resource_id, "crew");
+        assert_eq!(assigned.role_used.as_deref(), Some("driver"));
+    }
+
+    #[test]
+    fn test_role_headcount_limits_role_before_capacity() {
+        let crew_step = |id: &str, role: Option<&str>| {
+            let mut step = oven_step(id, 30);
+            step.resource_needs = vec![ResourceNeed {
+                resource_id: "crew".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: role.map(str::to_string),
+                cost_per_minute: None,
+                allow_partial: false,
+            }];
+            step
+        };
+        let mut template = oven_template(vec![
+            crew_step("a", Some("driver")),
+            crew_step("b", Some("driver")),
+            crew_step("c", None),
+        ]);
+        template.resources = vec![Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity: 3,
+            roles: vec!["driver".to_string(), "navigator".to_string()],
+            role_headcounts: vec![crate::model::RoleHeadcount {
+                role: "driver".to_string(),
+                headcount: 1,
+            }],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+
+        // Only one of the three crew can drive: b waits for a although two
+        // people are free, while c needs no role and starts at once.
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let start = |id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+        assert_eq!((start("a"), start("b"), start("c")), (0, 30, 0));
+    }
+
+    #[test]
+    fn test_cost_resource_is_unconstrained_and_accumulates() {
+        let budget_need = |rate: Option<f64>| ResourceNeed {
+            resource_id: "budget".to_string(),
+            quantity: 2,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: rate,
+            allow_partial: false,
+        };
+        let mut a = oven_step("a", 30);
+        a.resource_needs = vec![budget_need(None)];
+        let mut b = oven_step("b", 10);
+        b.resource_needs = vec![budget_need(Some(1.5))];
+        let mut template = oven_template(vec![a, b]);
+        template.resources.push(Resource {
+            id: "budget".to_string(),
+            name: "Budget".to_string(),
+            kind: ResourceKind::Cost,
+            capacity: 0,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.5,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        // Both steps run at once despite a capacity of 0.
+        assert!(solved.solved_steps.iter().all(|s| s.start_offset_mins == 0));
+        // a: 2 * 30 * 0.5 = 30, b: 2 * 10 * 1.5 = 30
+        assert_eq!(solved.summary.project_total_cost, 60.0);
+        assert_eq!(solved.summary.total_cost, None);
+    }
+
+    #[test]
+    fn test_cost_resource_bills_working_minutes_only() {
+        use crate::model::{WorkingCalendar, WorkingWindow};
+
+        let mut cure = oven_step("cure", 120);
+        cure.interruptible = true;
+        cure.resource_needs = vec![ResourceNeed {
+            resource_id: "budget".to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: Some(1.0),
+            allow_partial: false,
+        }];
+        let mut template = oven_template(vec![cure]);
+        template.resources[0] = Resource {
+            id: "budget".to_string(),
+            name: "Budget".to_string(),
+            kind: ResourceKind::Cost,
+            capacity: 0,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        };
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow { start_offset_mins: 0, end_offset_mins: 60 }],
+        });
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        // cure spans 0-1500 but only works 120 of those minutes.
+        assert_eq!(solved.solved_steps[0].end_offset_mins, 1500);
+        assert_eq!(solved.summary.project_total_cost, 120.0);
+    }
+
+    #[test]
+    fn test_utilization_below_target_is_warned() {
+        // Two one-oven steps at once use 2 of 4 ovens at the peak.
+        let mut template = oven_template(vec![oven_step("a", 30), oven_step("b", 10)]);
+        template.resources[0].capacity = 4;
+        template.resources[0].utilization_target = Some(0.8);
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(solved
+            .warnings
+            .contains(&"Resource 'Oven' has peak utilization 50%, below target 80%".to_string()));
+
+        template.resources[0].utilization_target = Some(0.5);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(solved.warnings.iter().all(|w| !w.contains("peak utilization")));
+
+        // With 8 ovens in stock the same peak is only 25%.
+        let inventory = ResourceInventory {
+            items: vec![crate::model::ResourceInventoryItem {
+                resource_id: "oven".to_string(),
+                available_quantity: 8,
+            }],
+        };
+        let solved = crate::solver::solve(&template, Some(&inventory)).unwrap();
+        assert!(solved
+            .warnings
+            .contains(&"Resource 'Oven' has peak utilization 25%, below target 50%".to_string()));
+    }
+
+    #[test]
+    fn test_resource_rates_price_each_assignment() {
+        let mut a = oven_step("a", 30);
+        a.resource_needs[0].quantity = 2;
+        let b = oven_step("b", 10);
+        let mut c = oven_step("c", 20);
+        c.resource_needs.clear();
+        let mut template = oven_template(vec![a, b, c]);
+        template.resources[0].capacity = 2;
+        template.resources[0].cost_per_unit_per_min = Some(0.5);
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let costs: Vec<Option<f64>> = solved.solved_steps.iter().map(|s| s.cost).collect();
+        // a: 2 * 30 * 0.5 = 30, b: 1 * 10 * 0.5 = 5, c uses nothing rated.
+        assert_eq!(costs, vec![Some(30.0), Some(5.0), None]);
+        assert_eq!(solved.summary.total_cost, Some(35.0));
+        assert_eq!(solved.summary.project_total_cost, 0.0);
+        // No Cost resource: a plain zero, not -0.0.
+        assert!(solved.summary.project_total_cost.is_sign_positive());
+        assert!(step_cost(&template, "c", 20).is_sign_positive());
+    }
+
+    #[test]
+    fn test_resource_rates_charge_split_steps_for_their_longer_run() {
+        let crew_need = |quantity| ResourceNeed {
+            resource_id: "crew".to_string(),
+            quantity,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: true,
+        };
+        let mut hold = oven_step("hold", 300);
+        hold.resource_needs = vec![crew_need(2)];
+        let mut a = oven_step("a", 60);
+        a.resource_needs = vec![crew_need(4)];
+        let mut template = oven_template(vec![hold, a]);
+        template.resources = vec![Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity: 4,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: Some(1.0),
+            utilization_target: None,
+        }];
+
+        let solved = crate::solver::solve_with_resource_splitting(&template, None).unwrap();
+        let a = solved.solved_steps.iter().find(|s| s.step_id == "a").unwrap();
+        // Two of the four crew take twice as long: 2 * 120 * 1.0.
+        assert_eq!((a.start_offset_mins, a.end_offset_mins), (0, 120));
+        assert_eq!(a.assigned_resources[0].quantity_used, 2);
+        assert_eq!(a.cost, Some(240.0));
+    }
+
+    #[test]
+    fn test_feasibility_flags_overlapping_critical_steps() {
+        // Two critical 30-minute steps both need the single oven at 0-30.
+        let template = oven_template(vec![oven_step("a", 30), oven_step("b", 30)]);
+        let report = check_resource_feasibility(&template, None).unwrap();
+
+        assert!(!report.feasible);
+        assert_eq!(report.infeasible_windows.len(), 1);
+        let window = &report.infeasible_windows[0];
+        assert_eq!((window.start_mins, window.end_mins), (0, 30));
+        assert_eq!((window.demand, window.capacity), (2, 1));
+
+        let inventory = ResourceInventory {
+            items: vec![crate::model::ResourceInventoryItem {
+                resource_id: "oven".to_string(),
+                available_quantity: 2,
+            }],
+        };
+        assert!(check_resource_feasibility(&template, Some(&inventory)).unwrap().feasible);
+    }
+
+    #[test]
+    fn test_bottleneck_resource_is_contended_oven() {
+        let template = oven_template(vec![oven_step("a", 30), oven_step("b", 20)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let report = find_bottleneck_resource(&solved, &template, None).unwrap();
+        assert_eq!(report.resource_id, "oven");
+        assert_eq!(report.delay_caused_mins, 20);
+        assert_eq!(report.affected_step_ids, vec!["b".to_string()]);
+
+        // With a second oven in the inventory the ovens run side by side and
+        // the single mixer holds things up instead.
+        let mixer_step = |id: &str| {
+            let mut step = oven_step(id, 20);
+            step.resource_needs[0].resource_id = "mixer".to_string();
+            step
+        };
+        let mut template = oven_template(vec![
+            oven_step("a", 30),
+            oven_step("b", 30),
+            mixer_step("m1"),
+            mixer_step("m2"),
+        ]);
+        let mut mixer = template.resources[0].clone();
+        mixer.id = "mixer".to_string();
+        mixer.name = "Mixer".to_string();
+        template.resources.push(mixer);
+        let inventory = ResourceInventory {
+            items: vec![ResourceInventoryItem {
+                resource_id: "oven".to_string(),
+                available_quantity: 2,
+            }],
+        };
+        let solved = crate::solver::solve(&template, Some(&inventory)).unwrap();
+        let report = find_bottleneck_resource(&solved, &template, Some(&inventory)).unwrap();
+        assert_eq!(report.resource_id, "mixer");
+        assert_eq!(report.delay_caused_mins, 10);
+        assert_eq!(report.affected_step_ids, vec!["m2".to_string()]);
+    }
+
+    #[test]
+    fn test_max_concurrent_steps_serializes_unconstrained_steps() {
+        let mut a = oven_step("a", 30);
+        a.resource_needs.clear();
+        let mut b = oven_step("b", 20);
+        b.resource_needs.clear();
+        let mut template = oven_template(vec![a, b]);
+        template.resources.clear();
+        template.max_concurrent_steps = Some(1);
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let a = &solved.solved_steps[0];
+        let b = &solved.solved_steps[1];
+        let no_overlap =
+            b.start_offset_mins >= a.end_offset_mins || a.start_offset_mins >= b.end_offset_mins;
+        assert!(no_overlap);
+        assert_eq!(solved.summary.total_duration_mins, 50);
+        assert!(a.assigned_resources.is_empty());
+
+        // Unvalidated, a limit of 0 is ignored with a warning.
+        template.max_concurrent_steps = Some(0);
+        let options = crate::solver::SolveOptions {
+            skip_validation: true,
+            ..Default::default()
+        };
+        let solved = crate::solver::solve_with_options(&template, None, &options).unwrap();
+        assert_eq!(solved.summary.total_duration_mins, 30);
+        assert_eq!(
+            solved.warnings,
+            vec!["max_concurrent_steps is 0 -- ignored, since no step could ever run"]
+        );
+    }
+
+    #[test]
+    fn test_track_max_concurrent_limits_steps_on_that_track() {
+        use crate::model::Track;
+
+        // Three 30-minute steps in a cold room that fits two at a time; a
+        // step on another track is unaffected.
+        let on = |id: &str, track: &str| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 30,
+            track_id: Some(track.to_string()),
+            ..Default::default()
+        };
+        let track = |id: &str, max_concurrent| Track {
+            id: id.to_string(),
+            name: id.to_string(),
+            steps: vec![],
+            max_concurrent,
+        };
+        let mut template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![on("a", "cold"), on("b", "cold"), on("c", "cold"), on("d", "bench")],
+            tracks: vec![track("cold", Some(2)), track("bench", None)],
+            ..Default::default()
+        };
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let starts: Vec<u32> = solved.solved_steps.iter().map(|s| s.start_offset_mins).collect();
+        assert_eq!(starts, vec![0, 0, 30, 0]);
+        assert!(solved.warnings.iter().any(|w| w.contains("'cold concurrent step limit'")));
+
+        // A limit of 0 is ignored, and the solve says so.
+        template.tracks[0].max_concurrent = Some(0);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(solved.solved_steps.iter().all(|s| s.start_offset_mins == 0));
+        assert_eq!(
+            solved.warnings,
+            vec!["Track 'cold' has max_concurrent 0 -- ignored, since none of its steps could run"]
+        );
+    }
+
+    #[test]
+    fn test_used_at_range_matches_naive_sum() {
+        let mut timeline = ResourceTimeline::default();
+        let intervals = [(0, 30, 1), (10, 20, 2), (20, 50, 1), (25, 25, 4), (40, 90, 3)];
+        for &(start, end, quantity) in &intervals {
+            timeline.reserve(start, end, quantity);
+        }
+        for start in 0..100 {
+            for end in start..100 {
+                let naive: u32 = intervals
+                    .iter()
+                    .filter(|r| r.0 < end && r.1 > start)
+                    .map(|r| r.2)
+                    .sum();
+                assert_eq!(timeline.used_at_range(start, end), naive, "[{}, {})", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_compression_without_contention() {
+        let template = oven_template(vec![oven_step("a", 30)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(find_compressible_steps(&solved, &template).is_empty());
+    }
+}
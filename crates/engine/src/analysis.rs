@@ -0,0 +1,2249 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    AssignedResource, DependencyType, Resource, ResourceInventory, ResourceKind, ScheduleTemplate,
+    SolvedSchedule, SolvedStep, Step, TimingPolicy,
+};
+use crate::solver::{SolveError, SolveOptions};
+use crate::util::{effective_capacity, peak_usage, resource_usages};
+
+// ---------------------------------------------------------------------------
+// Resource histogram types
+// ---------------------------------------------------------------------------
+
+/// Usage of one resource within a single `[start_mins, end_mins)` interval.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    pub start_mins: u32,
+    pub end_mins: u32,
+    /// Peak number of units in use at any moment within the bucket.
+    pub used: u32,
+    /// Capacity left over at that peak (never negative).
+    pub available: u32,
+}
+
+/// A resource's usage over the schedule, bucketed into fixed-width intervals.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceHistogram {
+    pub resource_id: String,
+    pub capacity: u32,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+// ---------------------------------------------------------------------------
+// Resource histogram
+// ---------------------------------------------------------------------------
+
+/// Bucket the usage of `resource_id` across the solved schedule into
+/// intervals of `bucket_mins`, from time 0 to the schedule's total duration.
+///
+/// Each bucket reports the peak concurrent quantity assigned to the resource
+/// within that interval, taken from the steps' `assigned_resources`.
+/// Capacity honours `inventory` overrides; pass the inventory `solved` was
+/// solved with.
+pub fn get_resource_histogram(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    resource_id: &str,
+    bucket_mins: u32,
+) -> Result<ResourceHistogram, String> {
+    if bucket_mins == 0 {
+        return Err("bucket_mins must be greater than 0".to_string());
+    }
+    let resource = template
+        .resources
+        .iter()
+        .find(|r| r.id == resource_id)
+        .ok_or_else(|| format!("Resource '{}' isn't defined", resource_id))?;
+    let capacity = effective_capacity(resource, inventory);
+
+    let usages = resource_usages(&solved.solved_steps, resource_id);
+
+    let total = solved.summary.total_duration_mins;
+    let mut buckets = Vec::new();
+    let mut start = 0;
+    while start < total {
+        let end = (start + bucket_mins).min(total);
+        let (peak, _) = peak_usage(&usages, start, end);
+
+        buckets.push(HistogramBucket {
+            start_mins: start,
+            end_mins: end,
+            used: peak,
+            available: capacity.saturating_sub(peak),
+        });
+        start = end;
+    }
+
+    Ok(ResourceHistogram {
+        resource_id: resource.id.clone(),
+        capacity,
+        buckets,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Float histogram types
+// ---------------------------------------------------------------------------
+
+/// Steps whose total float lies in `[min_float, max_float]`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloatBucket {
+    pub min_float: u32,
+    pub max_float: u32,
+    pub step_count: u32,
+    pub step_ids: Vec<String>,
+}
+
+/// How total float is spread across a schedule's steps.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloatHistogram {
+    pub bucket_size_mins: u32,
+    pub buckets: Vec<FloatBucket>,
+}
+
+// ---------------------------------------------------------------------------
+// Float histogram
+// ---------------------------------------------------------------------------
+
+/// Count steps by total float. The first bucket holds exactly the critical
+/// steps (0 float); after it come buckets of `bucket_size_mins` (1 to
+/// `bucket_size_mins`, then the next `bucket_size_mins`, ...) up to the
+/// largest float, including empty ones. Many steps in the low buckets mean
+/// a fragile schedule: small slips there delay the end.
+pub fn float_histogram(
+    solved: &SolvedSchedule,
+    bucket_size_mins: u32,
+) -> Result<FloatHistogram, String> {
+    if bucket_size_mins == 0 {
+        return Err("bucket_size_mins must be greater than 0".to_string());
+    }
+    let max_float = solved.solved_steps.iter().map(|s| s.total_float_mins).max().unwrap_or(0);
+    let mut buckets = vec![FloatBucket {
+        min_float: 0,
+        max_float: 0,
+        step_count: 0,
+        step_ids: Vec::new(),
+    }];
+    for k in 0..max_float.div_ceil(bucket_size_mins) {
+        buckets.push(FloatBucket {
+            min_float: k * bucket_size_mins + 1,
+            max_float: (k + 1) * bucket_size_mins,
+            step_count: 0,
+            step_ids: Vec::new(),
+        });
+    }
+    for step in &solved.solved_steps {
+        let bucket = &mut buckets[step.total_float_mins.div_ceil(bucket_size_mins) as usize];
+        bucket.step_count += 1;
+        bucket.step_ids.push(step.step_id.clone());
+    }
+
+    Ok(FloatHistogram {
+        bucket_size_mins,
+        buckets,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Resource loading types
+// ---------------------------------------------------------------------------
+
+/// One row of a `ResourceLoadingReport`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLoad {
+    pub resource_id: String,
+    pub name: String,
+    /// Load per period, aligned with `ResourceLoadingReport::periods`.
+    pub loads: Vec<u32>,
+}
+
+/// Every resource's load per period, as a table.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLoadingReport {
+    /// Start of each period, in minutes from schedule start. Period `i` runs
+    /// to `periods[i + 1]`, the last one to the schedule's total duration.
+    pub periods: Vec<u32>,
+    pub resources: Vec<ResourceLoad>,
+}
+
+// ---------------------------------------------------------------------------
+// Resource loading
+// ---------------------------------------------------------------------------
+
+/// Tabulate the load of every template resource across the solved schedule,
+/// in periods of `period_mins` from time 0 to the total duration.
+///
+/// A resource's load in a period is the total quantity of it assigned to
+/// steps running at some point in that period, so two steps using the one
+/// oven back to back within an hour load it 2 for that hour. Consumables
+/// count in the period their step starts. For the peak in use at any one
+/// moment, see `get_resource_histogram`.
+pub fn resource_loading_report(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    period_mins: u32,
+) -> Result<ResourceLoadingReport, String> {
+    if period_mins == 0 {
+        return Err("period_mins must be greater than 0".to_string());
+    }
+    let total = solved.summary.total_duration_mins;
+    let periods: Vec<u32> = (0..total).step_by(period_mins as usize).collect();
+
+    let resources = template
+        .resources
+        .iter()
+        .map(|resource| {
+            let consumable = matches!(resource.kind, ResourceKind::Consumable);
+            let mut loads = vec![0; periods.len()];
+            for step in &solved.solved_steps {
+                let quantity: u32 = step
+                    .assigned_resources
+                    .iter()
+                    .filter(|a| a.resource_id == resource.id)
+                    .map(|a| a.quantity_used)
+                    .sum();
+                if quantity == 0 {
+                    continue;
+                }
+                let first = (step.start_offset_mins / period_mins) as usize;
+                let last = match consumable {
+                    true => first,
+                    false => ((step.end_offset_mins.max(1) - 1) / period_mins) as usize,
+                };
+                for load in loads.iter_mut().take(last + 1).skip(first) {
+                    *load += quantity;
+                }
+            }
+            ResourceLoad {
+                resource_id: resource.id.clone(),
+                name: resource.name.clone(),
+                loads,
+            }
+        })
+        .collect();
+
+    Ok(ResourceLoadingReport { periods, resources })
+}
+
+// ---------------------------------------------------------------------------
+// S-curve types
+// ---------------------------------------------------------------------------
+
+/// Cumulative usage of one resource at the end of a bucket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SCurveBucket {
+    /// End of the bucket, in minutes from schedule start.
+    pub time_mins: u32,
+    /// Usage from time 0 up to `time_mins`, per the solved schedule.
+    pub cumulative_used: f64,
+    /// Straight-line reference: the schedule's total usage spread evenly
+    /// over its duration.
+    pub planned_used: f64,
+}
+
+/// Cumulative usage of a resource over the schedule.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SCurve {
+    pub resource_id: String,
+    pub buckets: Vec<SCurveBucket>,
+}
+
+// ---------------------------------------------------------------------------
+// S-curve
+// ---------------------------------------------------------------------------
+
+/// Cumulative usage of `resource_id` across the solved schedule, sampled at
+/// the end of every `bucket_mins` interval (the last bucket ends at the
+/// schedule's total duration).
+///
+/// Consumables are used up in full when a step starts, so the curve tracks
+/// depletion. Other resources are counted in unit-minutes (person-minutes
+/// for People) as steps run. Comparing `cumulative_used` with the straight
+/// `planned_used` line shows whether usage is front- or back-loaded.
+pub fn compute_resource_s_curve(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    resource_id: &str,
+    bucket_mins: u32,
+) -> Result<SCurve, String> {
+    if bucket_mins == 0 {
+        return Err("bucket_mins must be greater than 0".to_string());
+    }
+    let resource = template
+        .resources
+        .iter()
+        .find(|r| r.id == resource_id)
+        .ok_or_else(|| format!("Resource '{}' isn't defined", resource_id))?;
+    let consumable = matches!(resource.kind, ResourceKind::Consumable);
+
+    let usages = resource_usages(&solved.solved_steps, resource_id);
+    let used_by = |t: u32| -> f64 {
+        usages
+            .iter()
+            .map(|&(start, end, quantity)| match consumable {
+                true if start < t => quantity as f64,
+                true => 0.0,
+                false => quantity as f64 * end.min(t).saturating_sub(start) as f64,
+            })
+            .sum()
+    };
+
+    let total = solved.summary.total_duration_mins;
+    let total_used = used_by(total);
+    let mut buckets = Vec::new();
+    let mut start = 0;
+    while start < total {
+        let end = (start + bucket_mins).min(total);
+        buckets.push(SCurveBucket {
+            time_mins: end,
+            cumulative_used: used_by(end),
+            planned_used: total_used * end as f64 / total as f64,
+        });
+        start = end;
+    }
+
+    Ok(SCurve {
+        resource_id: resource.id.clone(),
+        buckets,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Schedule risk types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+/// A structural weakness in a solved schedule.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRisk {
+    /// Stable identifier: the risk kind plus the step or resource it is about,
+    /// e.g. `"resource-peak:oven"`.
+    pub risk_id: String,
+    pub severity: RiskSeverity,
+    pub message: String,
+    pub affected_step_ids: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Schedule risks
+// ---------------------------------------------------------------------------
+
+/// Flag structural risks in a solved schedule:
+///
+/// 1. Critical steps that need at least half of an Equipment/People
+///    resource's capacity (high severity).
+/// 2. Steps whose only predecessor is critical, so a slip there passes
+///    straight on (low severity).
+/// 3. Equipment/People resources whose peak usage exceeds 90% of capacity
+///    (medium severity).
+/// 4. Consumables with less than 10% left once every step has drawn on them
+///    (high severity).
+/// 5. Unanchored ALAP steps (no dependencies and nothing depending on them)
+///    starting within 10% of their total float of their late start, leaving
+///    almost no room to slip (medium severity).
+/// 6. Critical steps running at a resource's peak when the template has a
+///    deadline (see `check_resource_deadline_conflicts`): high severity when
+///    the peak falls after the deadline, medium otherwise.
+pub fn identify_schedule_risks(
+    template: &ScheduleTemplate,
+    solved: &SolvedSchedule,
+) -> Vec<ScheduleRisk> {
+    // The template solve actually schedules, whose step IDs match `solved`'s.
+    let prepared = crate::solver::prepare_template(template, &SolveOptions::default());
+    let template = prepared.as_ref();
+    let mut risks = Vec::new();
+    let steps: HashMap<&str, &crate::model::Step> =
+        template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let solved_by_id: HashMap<&str, &SolvedStep> =
+        solved.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+    let title_of = |id: &str| steps.get(id).map_or(id, |s| s.title.as_str()).to_string();
+    let timed =
+        |kind: &ResourceKind| matches!(kind, ResourceKind::Equipment | ResourceKind::People);
+
+    // 1. Zero float with high resource demand
+    for solved_step in solved.solved_steps.iter().filter(|s| s.total_float_mins == 0) {
+        let Some(step) = steps.get(solved_step.step_id.as_str()) else {
+            continue;
+        };
+        let heavy = step.resource_needs.iter().find_map(|need| {
+            let resource = template.resources.iter().find(|r| r.id == need.resource_id)?;
+            let high = timed(&resource.kind) && need.quantity * 2 >= resource.capacity;
+            high.then_some(resource)
+        });
+        if let Some(resource) = heavy {
+            risks.push(ScheduleRisk {
+                risk_id: format!("critical-high-demand:{}", step.id),
+                severity: RiskSeverity::High,
+                message: format!(
+                    "Critical step '{}' needs at least half of '{}' -- any shortage delays the finish",
+                    step.title, resource.name
+                ),
+                affected_step_ids: vec![step.id.clone()],
+            });
+        }
+    }
+
+    // 2. Single predecessor with zero float
+    for solved_step in &solved.solved_steps {
+        let Some(step) = steps.get(solved_step.step_id.as_str()) else {
+            continue;
+        };
+        let [dep] = step.dependencies.as_slice() else {
+            continue;
+        };
+        if solved_by_id.get(dep.step_id.as_str()).is_some_and(|p| p.total_float_mins == 0) {
+            risks.push(ScheduleRisk {
+                risk_id: format!("single-critical-predecessor:{}", step.id),
+                severity: RiskSeverity::Low,
+                message: format!(
+                    "Step '{}' depends only on critical step '{}' -- a slip there passes straight on",
+                    step.title,
+                    title_of(&dep.step_id)
+                ),
+                affected_step_ids: vec![dep.step_id.clone(), step.id.clone()],
+            });
+        }
+    }
+
+    // 3. Resources above 90% of capacity at peak
+    for resource in template.resources.iter().filter(|r| timed(&r.kind) && r.capacity > 0) {
+        let usages = resource_usages(&solved.solved_steps, &resource.id);
+        let (peak, _) = peak_usage(&usages, 0, u32::MAX);
+        if peak * 10 > resource.capacity * 9 {
+            risks.push(ScheduleRisk {
+                risk_id: format!("resource-peak:{}", resource.id),
+                severity: RiskSeverity::Medium,
+                message: format!(
+                    "Resource '{}' peaks at {} of {} ({:.0}%)",
+                    resource.name,
+                    peak,
+                    resource.capacity,
+                    peak as f64 / resource.capacity as f64 * 100.0
+                ),
+                affected_step_ids: solved
+                    .solved_steps
+                    .iter()
+                    .filter(|s| s.assigned_resources.iter().any(|a| a.resource_id == resource.id))
+                    .map(|s| s.step_id.clone())
+                    .collect(),
+            });
+        }
+    }
+
+    // 4. Consumables running low
+    for resource in template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Consumable) && r.capacity > 0)
+    {
+        let mut consumed = 0;
+        let mut affected = Vec::new();
+        for solved_step in &solved.solved_steps {
+            let Some(step) = steps.get(solved_step.step_id.as_str()) else {
+                continue;
+            };
+            for need in step.resource_needs.iter().filter(|n| n.resource_id == resource.id) {
+                consumed += need.quantity;
+                affected.push(step.id.clone());
+            }
+        }
+        let remaining = resource.capacity.saturating_sub(consumed);
+        if remaining * 10 < resource.capacity {
+            risks.push(ScheduleRisk {
+                risk_id: format!("consumable-low:{}", resource.id),
+                severity: RiskSeverity::High,
+                message: format!(
+                    "Consumable '{}' drops to {} of {} -- less than 10% left",
+                    resource.name, remaining, resource.capacity
+                ),
+                affected_step_ids: affected,
+            });
+        }
+    }
+
+    // 5. Unanchored ALAP steps with almost no room before their late start
+    let has_successor: HashSet<&str> = template
+        .steps
+        .iter()
+        .flat_map(|s| s.dependencies.iter().map(|d| d.step_id.as_str()))
+        .collect();
+    if let Ok(cpm) = crate::solver::cpm_only(template) {
+        for solved_step in solved.solved_steps.iter().filter(|s| s.total_float_mins > 0) {
+            let Some(step) = steps.get(solved_step.step_id.as_str()) else {
+                continue;
+            };
+            let unanchored =
+                step.dependencies.is_empty() && !has_successor.contains(step.id.as_str());
+            if step.timing_policy != Some(TimingPolicy::Alap) || !unanchored {
+                continue;
+            }
+            let Some(&late_start) = cpm.late_starts.get(&step.id) else {
+                continue;
+            };
+            let room = late_start.saturating_sub(solved_step.start_offset_mins);
+            if room * 10 <= solved_step.total_float_mins {
+                risks.push(ScheduleRisk {
+                    risk_id: format!("alap-near-late-start:{}", step.id),
+                    severity: RiskSeverity::Medium,
+                    message: format!(
+                        "ALAP step '{}' starts {} min before its late start -- it has used up its float",
+                        step.title, room
+                    ),
+                    affected_step_ids: vec![step.id.clone()],
+                });
+            }
+        }
+    }
+
+    // 6. Resource peaks on deadline-critical steps
+    for conflict in check_resource_deadline_conflicts(solved, template) {
+        let resource_name = template
+            .resources
+            .iter()
+            .find(|r| r.id == conflict.resource_id)
+            .map_or(conflict.resource_id.as_str(), |r| r.name.as_str());
+        let timing = match conflict.margin_mins {
+            m if m < 0 => format!("{} min after the deadline", -m),
+            m => format!("{} min before the deadline", m),
+        };
+        risks.push(ScheduleRisk {
+            risk_id: format!("resource-deadline:{}:{}", conflict.step_id, conflict.resource_id),
+            severity: match conflict.margin_mins < 0 {
+                true => RiskSeverity::High,
+                false => RiskSeverity::Medium,
+            },
+            message: format!(
+                "Critical step '{}' runs at the peak of '{}' at {} min, {}",
+                title_of(&conflict.step_id),
+                resource_name,
+                conflict.peak_at_mins,
+                timing
+            ),
+            affected_step_ids: vec![conflict.step_id],
+        });
+    }
+
+    risks
+}
+
+// ---------------------------------------------------------------------------
+// Resource peaks against deadlines
+// ---------------------------------------------------------------------------
+
+/// A critical step using a resource at the moment its usage peaks.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDeadlineConflict {
+    pub step_id: String,
+    pub resource_id: String,
+    /// When the resource peaks during the step, in minutes from schedule start.
+    pub peak_at_mins: u32,
+    /// The template's deadline, in minutes from schedule start.
+    pub deadline_offset_mins: u32,
+    /// `deadline_offset_mins - peak_at_mins`: negative when the peak falls
+    /// after the deadline.
+    pub margin_mins: i32,
+}
+
+/// Find critical steps that run at an Equipment/People resource's peak
+/// usage while the template has a deadline (a time constraint with both
+/// `start_time` and `end_time`). With a deadline, a critical step is one
+/// that can't slip without missing it, so any shortage of a resource at
+/// its busiest moment goes straight into the finish.
+///
+/// Returns one conflict per critical step and peaking resource, at the
+/// first peak moment within the step. Empty when there is no deadline.
+pub fn check_resource_deadline_conflicts(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> Vec<ResourceDeadlineConflict> {
+    let tz = template
+        .time_constraint
+        .as_ref()
+        .and_then(|tc| tc.timezone.as_deref())
+        .and_then(|name| name.parse().ok());
+    let Some(deadline) = crate::solver::deadline_mins(template, tz) else {
+        return Vec::new();
+    };
+
+    let mut conflicts = Vec::new();
+    for resource in template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+    {
+        let usages = resource_usages(&solved.solved_steps, &resource.id);
+        let (peak, _) = peak_usage(&usages, 0, u32::MAX);
+        if peak == 0 {
+            continue;
+        }
+
+        let critical_users = solved.solved_steps.iter().filter(|s| {
+            s.is_critical && s.assigned_resources.iter().any(|a| a.resource_id == resource.id)
+        });
+        for step in critical_users {
+            let (step_peak, peak_at) =
+                peak_usage(&usages, step.start_offset_mins, step.end_offset_mins);
+            if step_peak == peak {
+                conflicts.push(ResourceDeadlineConflict {
+                    step_id: step.step_id.clone(),
+                    resource_id: resource.id.clone(),
+                    peak_at_mins: peak_at,
+                    deadline_offset_mins: deadline as u32,
+                    margin_mins: (deadline - i64::from(peak_at)) as i32,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+// ---------------------------------------------------------------------------
+// Parallel opportunities
+// ---------------------------------------------------------------------------
+
+/// Two steps that run one after the other but could overlap.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParallelOpportunity {
+    /// The step that runs first.
+    pub step_a_id: String,
+    /// The step that runs after it.
+    pub step_b_id: String,
+    /// How much earlier step B could start (and finish) if it ran alongside
+    /// step A.
+    pub potential_savings_mins: u32,
+}
+
+/// Find pairs of steps that the solved schedule runs one after the other
+/// although neither depends on the other, even transitively, and their
+/// Equipment/People needs fit within capacity together.
+///
+/// For such a pair, the later step B could start as soon as both A has
+/// started and B's own dependencies allow (its CPM early start); the savings
+/// are how much earlier that is. Pairs where B's dependencies keep it from
+/// overlapping A at all are left out. Results are sorted by savings, largest
+/// first. Templates that can't be solved yield no opportunities.
+pub fn detect_parallel_opportunities(template: &ScheduleTemplate) -> Vec<ParallelOpportunity> {
+    let expanded;
+    let template = if template.recurring_patterns.is_empty() {
+        template
+    } else {
+        expanded = crate::expander::expand_recurring(template);
+        &expanded
+    };
+    let (Ok(solved), Ok(cpm)) =
+        (crate::solver::solve(template, None), crate::solver::cpm_only(template))
+    else {
+        return Vec::new();
+    };
+    if template.max_concurrent_steps.is_some_and(|limit| limit < 2) {
+        return Vec::new();
+    }
+
+    let ancestors = transitive_predecessors(template);
+    let related = |a: &str, b: &str| ancestors[a].contains(b) || ancestors[b].contains(a);
+
+    // Capacity of every timed resource, with a group counting its members'.
+    let capacity: HashMap<&str, u32> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| (r.id.as_str(), r.capacity))
+        .collect();
+    let group_capacity: HashMap<&str, u32> = template
+        .resource_groups
+        .iter()
+        .map(|g| {
+            let total = g.resource_ids.iter().filter_map(|id| capacity.get(id.as_str())).sum();
+            (g.id.as_str(), total)
+        })
+        .collect();
+    let needs: HashMap<&str, &[crate::model::ResourceNeed]> =
+        template.steps.iter().map(|s| (s.id.as_str(), s.resource_needs.as_slice())).collect();
+    let fits_together = |a: &str, b: &str| {
+        let mut demand: HashMap<&str, u32> = HashMap::new();
+        for need in needs[a].iter().chain(needs[b]) {
+            *demand.entry(need.resource_id.as_str()).or_default() += need.quantity;
+        }
+        demand.iter().all(|(id, &qty)| {
+            match capacity.get(id).or_else(|| group_capacity.get(id)) {
+                Some(&cap) => qty <= cap,
+                None => true, // Consumable, Cost or undefined: no time limit.
+            }
+        })
+    };
+
+    let mut opportunities = Vec::new();
+    for a in &solved.solved_steps {
+        for b in &solved.solved_steps {
+            let (a_id, b_id) = (a.step_id.as_str(), b.step_id.as_str());
+            if a_id == b_id || a.end_offset_mins > b.start_offset_mins || related(a_id, b_id) {
+                continue;
+            }
+            let earliest = cpm.early_starts.get(b_id).copied().unwrap_or(0);
+            let parallel_start = earliest.max(a.start_offset_mins);
+            if parallel_start >= a.end_offset_mins || !fits_together(a_id, b_id) {
+                continue;
+            }
+            opportunities.push(ParallelOpportunity {
+                step_a_id: a.step_id.clone(),
+                step_b_id: b.step_id.clone(),
+                potential_savings_mins: b.start_offset_mins - parallel_start,
+            });
+        }
+    }
+    opportunities.sort_by(|x, y| {
+        y.potential_savings_mins
+            .cmp(&x.potential_savings_mins)
+            .then_with(|| x.step_a_id.cmp(&y.step_a_id))
+            .then_with(|| x.step_b_id.cmp(&y.step_b_id))
+    });
+    opportunities
+}
+
+/// Every step's direct and indirect predecessors.
+fn transitive_predecessors(template: &ScheduleTemplate) -> HashMap<&str, HashSet<&str>> {
+    let deps: HashMap<&str, Vec<&str>> = template
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.dependencies.iter().map(|d| d.step_id.as_str()).collect()))
+        .collect();
+    let mut ancestors: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for step in &template.steps {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&str> = deps[step.id.as_str()].clone();
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                stack.extend(deps.get(id).into_iter().flatten());
+            }
+        }
+        ancestors.insert(step.id.as_str(), seen);
+    }
+    ancestors
+}
+
+// ---------------------------------------------------------------------------
+// Critical path explanation
+// ---------------------------------------------------------------------------
+
+/// One step of an explained critical path.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainLink {
+    pub step_id: String,
+    pub step_title: String,
+    pub duration_mins: u32,
+    /// The dependency on the previous link that fixes this step's timing;
+    /// `None` for the first link and for steps that waited on a resource.
+    pub predecessor_dependency_type: Option<DependencyType>,
+    /// Resources this step waited for, e.g. "Waited for 'Oven' until
+    /// 'Bake' finished".
+    pub resource_conflicts: Vec<String>,
+}
+
+/// The chain of steps that sets a solved schedule's total duration.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalPathExplanation {
+    pub duration_mins: u32,
+    /// From the first step of the chain to the one finishing last.
+    pub chain: Vec<ChainLink>,
+}
+
+/// Explain why the schedule takes as long as it does by tracing back from
+/// the step that finishes last.
+///
+/// Each step is linked to what its timing waited on: a dependency that
+/// binds exactly (a finish-to-start predecessor releasing it at its start,
+/// and so on), or else an Equipment/People resource another step released
+/// at its start. The trace stops at a step that starts at 0 or whose start
+/// neither explains, e.g. one held back by a calendar or fixed time. Like
+/// `driving_steps`, this follows resource waits rather than the
+/// resource-free CPM critical path.
+pub fn explain_critical_path(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> CriticalPathExplanation {
+    // The template solve actually schedules, whose step IDs match `solved`'s.
+    let prepared = crate::solver::prepare_template(template, &SolveOptions::default());
+    let template = prepared.as_ref();
+    let steps: HashMap<&str, _> = template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let solved_steps: HashMap<&str, &SolvedStep> =
+        solved.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+    let timed: HashMap<&str, &str> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| (r.id.as_str(), r.name.as_str()))
+        .collect();
+    let title = |id: &str| steps.get(id).map_or(id.to_string(), |s| s.title.clone());
+
+    let duration_mins = solved.summary.total_duration_mins;
+    let mut chain: Vec<ChainLink> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut current = solved.solved_steps.iter().find(|s| s.end_offset_mins == duration_mins);
+    while let Some(step) = current {
+        if !seen.insert(step.step_id.as_str()) {
+            break;
+        }
+        let dependencies = steps.get(step.step_id.as_str()).map_or(&[][..], |s| &s.dependencies);
+        let binding = dependencies.iter().find_map(|dep| {
+            let pred = solved_steps.get(dep.step_id.as_str())?;
+            let pred_span = i64::from(pred.end_offset_mins) - i64::from(pred.start_offset_mins);
+            let binds = match dep.dependency_type {
+                DependencyType::FinishToStart => {
+                    i64::from(pred.start_offset_mins) + crate::solver::trigger_point(dep, pred_span)
+                        == i64::from(step.start_offset_mins)
+                }
+                DependencyType::StartToStart => pred.start_offset_mins == step.start_offset_mins,
+                DependencyType::FinishToFinish => pred.end_offset_mins == step.end_offset_mins,
+                DependencyType::StartToFinish => pred.start_offset_mins == step.end_offset_mins,
+            };
+            binds.then_some((dep.dependency_type.clone(), *pred))
+        });
+
+        let mut blockers: Vec<&SolvedStep> = Vec::new();
+        let mut resource_conflicts: Vec<String> = Vec::new();
+        if step.start_offset_mins > 0 {
+            for assigned in &step.assigned_resources {
+                let Some(name) = timed.get(assigned.resource_id.as_str()) else {
+                    continue;
+                };
+                for other in &solved.solved_steps {
+                    let held = |a: &AssignedResource| a.resource_id == assigned.resource_id;
+                    let released = other.step_id != step.step_id
+                        && other.end_offset_mins == step.start_offset_mins
+                        && other.assigned_resources.iter().any(held);
+                    if released {
+                        resource_conflicts.push(format!(
+                            "Waited for '{}' until '{}' finished",
+                            name,
+                            title(&other.step_id)
+                        ));
+                        blockers.push(other);
+                    }
+                }
+            }
+        }
+
+        chain.push(ChainLink {
+            step_id: step.step_id.clone(),
+            step_title: title(&step.step_id),
+            duration_mins: steps.get(step.step_id.as_str()).map_or(0, |s| s.duration_mins),
+            predecessor_dependency_type: binding.as_ref().map(|(kind, _)| kind.clone()),
+            resource_conflicts,
+        });
+        current = match binding {
+            Some((_, pred)) => Some(pred),
+            None => blockers.first().copied(),
+        };
+    }
+    chain.reverse();
+
+    CriticalPathExplanation {
+        duration_mins,
+        chain,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// What-if analysis
+// ---------------------------------------------------------------------------
+
+/// How a change to the template moves the solved schedule.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhatIfReport {
+    pub baseline_duration_mins: u32,
+    pub new_duration_mins: u32,
+    /// How much sooner the schedule ends; 0 if it doesn't end sooner.
+    pub savings_mins: u32,
+    /// `savings_mins` as a percentage of the baseline duration.
+    pub savings_percent: f64,
+    /// Steps driving the schedule end after the change but not before (see
+    /// `driving_steps`).
+    pub newly_critical_steps: Vec<String>,
+    /// Steps driving the schedule end before the change but not after.
+    pub relieved_steps: Vec<String>,
+    /// Resources no step uses any more (only for step removal).
+    pub freed_resources: Vec<String>,
+}
+
+/// Steps that the solved schedule's end waits on: those finishing at the
+/// end, and those finishing exactly when a driving step starts if it
+/// depends on them or uses one of their Equipment/People resources.
+///
+/// Unlike `SolvedStep::is_critical`, which comes from the resource-free CPM
+/// pass, this follows resource waits, so it shows which steps a change of
+/// capacity really relieves.
+fn driving_steps(template: &ScheduleTemplate, solved: &SolvedSchedule) -> HashSet<String> {
+    let timed: HashSet<&str> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| r.id.as_str())
+        .collect();
+    let deps: HashMap<&str, HashSet<&str>> = template
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.dependencies.iter().map(|d| d.step_id.as_str()).collect()))
+        .collect();
+    let waits_on = |later: &SolvedStep, earlier: &SolvedStep| {
+        if later.start_offset_mins != earlier.end_offset_mins {
+            return false;
+        }
+        let depends = deps
+            .get(later.step_id.as_str())
+            .is_some_and(|d| d.contains(earlier.step_id.as_str()));
+        let shares_resource = later.assigned_resources.iter().any(|a| {
+            timed.contains(a.resource_id.as_str())
+                && earlier.assigned_resources.iter().any(|b| b.resource_id == a.resource_id)
+        });
+        depends || shares_resource
+    };
+
+    let end = solved.summary.total_duration_mins;
+    let mut driving: HashSet<String> = HashSet::new();
+    let mut frontier: Vec<&SolvedStep> =
+        solved.solved_steps.iter().filter(|s| s.end_offset_mins == end).collect();
+    while let Some(step) = frontier.pop() {
+        if !driving.insert(step.step_id.clone()) {
+            continue;
+        }
+        frontier.extend(solved.solved_steps.iter().filter(|earlier| waits_on(step, earlier)));
+    }
+    driving
+}
+
+/// Compare the schedule before and after a change to the template.
+fn what_if_report(
+    before: (&ScheduleTemplate, &SolvedSchedule),
+    after: (&ScheduleTemplate, &SolvedSchedule),
+) -> WhatIfReport {
+    let (baseline, changed) = (before.1, after.1);
+    let baseline_duration_mins = baseline.summary.total_duration_mins;
+    let new_duration_mins = changed.summary.total_duration_mins;
+    let savings_mins = baseline_duration_mins.saturating_sub(new_duration_mins);
+    let savings_percent = if baseline_duration_mins == 0 {
+        0.0
+    } else {
+        f64::from(savings_mins) * 100.0 / f64::from(baseline_duration_mins)
+    };
+    let (before, after) = (driving_steps(before.0, before.1), driving_steps(after.0, after.1));
+    // In step order, skipping steps that only exist on one side.
+    let in_order = |from: &HashSet<String>, not_in: &HashSet<String>| -> Vec<String> {
+        changed
+            .solved_steps
+            .iter()
+            .map(|s| &s.step_id)
+            .filter(|id| from.contains(*id) && !not_in.contains(*id))
+            .filter(|id| baseline.solved_steps.iter().any(|s| &s.step_id == *id))
+            .cloned()
+            .collect()
+    };
+
+    WhatIfReport {
+        baseline_duration_mins,
+        new_duration_mins,
+        savings_mins,
+        savings_percent,
+        newly_critical_steps: in_order(&after, &before),
+        relieved_steps: in_order(&before, &after),
+        freed_resources: Vec::new(),
+    }
+}
+
+/// How the schedule changes with `extra_quantity` more of `new_resource`.
+/// If the template already has a resource with that ID, its capacity (and
+/// inventory override) grows by `extra_quantity`; otherwise `new_resource`
+/// is added with `extra_quantity` as its capacity.
+///
+/// Both schedules are solved with `inventory`; fails if either can't be.
+pub fn what_if_add_resource(
+    template: &ScheduleTemplate,
+    new_resource: &Resource,
+    extra_quantity: u32,
+    inventory: Option<&ResourceInventory>,
+) -> Result<WhatIfReport, SolveError> {
+    let baseline = crate::solver::solve(template, inventory)?;
+
+    let mut changed = template.clone();
+    let mut changed_inventory = inventory.cloned();
+    crate::solver::add_capacity(
+        &mut changed,
+        changed_inventory.as_mut(),
+        new_resource,
+        extra_quantity,
+    );
+    let solved = crate::solver::solve(&changed, changed_inventory.as_ref())?;
+
+    Ok(what_if_report((template, &baseline), (&changed, &solved)))
+}
+
+/// How the schedule changes without step `step_id`. The step is dropped
+/// along with every reference to it: dependencies on it, track orderings,
+/// hammock group memberships and recurring patterns repeating it.
+/// `freed_resources` lists the resources (or resource groups) it was the
+/// only step to need.
+///
+/// Both schedules are solved with `inventory`; fails if the step doesn't
+/// exist or either schedule can't be solved.
+pub fn what_if_remove_step(
+    template: &ScheduleTemplate,
+    step_id: &str,
+    inventory: Option<&ResourceInventory>,
+) -> Result<WhatIfReport, SolveError> {
+    let Some(removed) = template.steps.iter().find(|s| s.id == step_id) else {
+        return Err(SolveError::ValidationFailed(format!(
+            "Step '{}' isn't in the template",
+            step_id
+        )));
+    };
+    let baseline = crate::solver::solve(template, inventory)?;
+
+    let mut changed = template.clone();
+    changed.steps.retain(|s| s.id != step_id);
+    for step in &mut changed.steps {
+        step.dependencies.retain(|d| d.step_id != step_id);
+    }
+    for track in &mut changed.tracks {
+        track.steps.retain(|id| id != step_id);
+    }
+    for group in &mut changed.hammock_groups {
+        group.member_step_ids.retain(|id| id != step_id);
+    }
+    changed.recurring_patterns.retain(|p| p.template_step_id != step_id);
+    let solved = crate::solver::solve(&changed, inventory)?;
+
+    let still_used: HashSet<&str> = changed
+        .steps
+        .iter()
+        .flat_map(|s| s.resource_needs.iter().map(|n| n.resource_id.as_str()))
+        .collect();
+    let mut freed_resources: Vec<String> = Vec::new();
+    for need in &removed.resource_needs {
+        if !still_used.contains(need.resource_id.as_str())
+            && !freed_resources.contains(&need.resource_id)
+        {
+            freed_resources.push(need.resource_id.clone());
+        }
+    }
+
+    Ok(WhatIfReport {
+        freed_resources,
+        ..what_if_report((template, &baseline), (&changed, &solved))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Staffing estimates
+// ---------------------------------------------------------------------------
+
+/// How many people a template needs to finish within a target duration.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeopleEstimate {
+    /// The smallest headcount meeting the target.
+    pub min_people: u32,
+    /// Total duration with `min_people` people.
+    pub achieved_duration_mins: u32,
+    /// The smallest headcount from which no number of extra people saves
+    /// another 5% of the duration.
+    pub diminishing_returns_at: u32,
+}
+
+/// Find the smallest headcount that gets `template` done within
+/// `target_duration_mins`.
+///
+/// Each headcount tried becomes the template's `default_num_people` and the
+/// capacity of every People resource. The search starts at the largest
+/// number of people any single step needs and solves with one more person at
+/// a time until no step waits for people any more. Fails if the template has
+/// no People resources, if it can't be solved, or if even that headcount
+/// misses the target.
+pub fn estimate_people_required(
+    template: &ScheduleTemplate,
+    target_duration_mins: u32,
+) -> Result<PeopleEstimate, SolveError> {
+    let people: HashSet<&str> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::People))
+        .map(|r| r.id.as_str())
+        .collect();
+    if people.is_empty() {
+        return Err(SolveError::ValidationFailed(format!(
+            "Template '{}' has no People resources to staff",
+            template.name
+        )));
+    }
+
+    // Below the largest single need some step can never be staffed; at the
+    // total of all needs on one resource no step waits for people.
+    let people_needs = || {
+        template
+            .steps
+            .iter()
+            .flat_map(|s| &s.resource_needs)
+            .filter(|n| people.contains(n.resource_id.as_str()))
+    };
+    let smallest = people_needs()
+        .map(|n| n.quantity.max(n.min_people.unwrap_or(0)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut totals: HashMap<&str, u32> = HashMap::new();
+    for need in people_needs() {
+        *totals.entry(need.resource_id.as_str()).or_insert(0) += need.quantity;
+    }
+    let largest = totals.values().copied().max().unwrap_or(0).max(smallest);
+
+    let duration_with = |headcount: u32| -> Result<u32, SolveError> {
+        let mut staffed = template.clone();
+        staffed.default_num_people = Some(headcount);
+        for resource in &mut staffed.resources {
+            if matches!(resource.kind, ResourceKind::People) {
+                resource.capacity = headcount;
+            }
+        }
+        Ok(crate::solver::solve(&staffed, None)?.summary.total_duration_mins)
+    };
+
+    let mut durations: Vec<(u32, u32)> = Vec::new();
+    for headcount in smallest..=largest {
+        durations.push((headcount, duration_with(headcount)?));
+    }
+    let Some(&(min_people, achieved_duration_mins)) =
+        durations.iter().find(|&&(_, duration)| duration <= target_duration_mins)
+    else {
+        let (headcount, duration) = durations[durations.len() - 1];
+        return Err(SolveError::ValidationFailed(format!(
+            "Even {} people take {} min, more than the target of {} min",
+            headcount, duration, target_duration_mins
+        )));
+    };
+
+    let shortest = durations.iter().map(|&(_, duration)| duration).min().unwrap_or(0);
+    let diminishing_returns_at = durations
+        .iter()
+        .find(|&&(_, duration)| {
+            duration == shortest || f64::from(duration - shortest) < f64::from(duration) * 0.05
+        })
+        .map_or(largest, |&(headcount, _)| headcount);
+
+    Ok(PeopleEstimate {
+        min_people,
+        achieved_duration_mins,
+        diminishing_returns_at,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Leveling metrics
+// ---------------------------------------------------------------------------
+
+/// What resource leveling cost and bought, comparing a schedule before
+/// allocation with the same schedule after.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelingMetrics {
+    /// How much longer the leveled schedule takes (0 if it isn't longer).
+    pub duration_extension_mins: u32,
+    /// Steps starting later after leveling.
+    pub steps_delayed: usize,
+    /// Highest concurrent need of any Equipment/People resource, before and
+    /// after leveling.
+    pub max_resource_peak_before: u32,
+    pub max_resource_peak_after: u32,
+    /// How much lower the peak is after leveling, as a percentage of the
+    /// peak before (0 when there was no peak).
+    pub peak_reduction_percent: f64,
+    /// Steps that weren't critical before leveling but are after it, in
+    /// schedule order.
+    pub newly_critical_steps: Vec<String>,
+}
+
+/// Compare `pre` (e.g. a `dry_run` solve) with `post` (the leveled solve of
+/// the same template) step by step.
+///
+/// A step counts as newly critical when it is flagged critical only in
+/// `post`, or when leveling delayed it by at least its float in `pre`. Peaks
+/// are worked out from the steps' `resource_needs` at their scheduled times
+/// rather than from `assigned_resources`, which CPM-only schedules don't
+/// have. Steps missing from either schedule are ignored.
+pub fn leveling_metrics(
+    pre: &SolvedSchedule,
+    post: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> LevelingMetrics {
+    let before: HashMap<&str, &SolvedStep> =
+        pre.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+    let steps_delayed = post
+        .solved_steps
+        .iter()
+        .filter(|s| {
+            before
+                .get(s.step_id.as_str())
+                .is_some_and(|b| s.start_offset_mins > b.start_offset_mins)
+        })
+        .count();
+    let newly_critical_steps = post
+        .solved_steps
+        .iter()
+        .filter(|s| {
+            before.get(s.step_id.as_str()).is_some_and(|b| {
+                let delay = s.start_offset_mins.saturating_sub(b.start_offset_mins);
+                !b.is_critical && (s.is_critical || (delay > 0 && delay >= b.total_float_mins))
+            })
+        })
+        .map(|s| s.step_id.clone())
+        .collect();
+
+    let max_resource_peak_before = max_resource_peak(pre, template);
+    let max_resource_peak_after = max_resource_peak(post, template);
+    let peak_reduction_percent = if max_resource_peak_before == 0 {
+        0.0
+    } else {
+        (f64::from(max_resource_peak_before) - f64::from(max_resource_peak_after))
+            / f64::from(max_resource_peak_before)
+            * 100.0
+    };
+
+    LevelingMetrics {
+        duration_extension_mins: post
+            .summary
+            .total_duration_mins
+            .saturating_sub(pre.summary.total_duration_mins),
+        steps_delayed,
+        max_resource_peak_before,
+        max_resource_peak_after,
+        peak_reduction_percent,
+        newly_critical_steps,
+    }
+}
+
+/// The highest total need of any one Equipment/People resource at any
+/// moment of `solved`.
+fn max_resource_peak(solved: &SolvedSchedule, template: &ScheduleTemplate) -> u32 {
+    let needs: HashMap<&str, &Step> = template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let mut peak = 0;
+    for resource in template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+    {
+        let usages: Vec<(u32, u32, u32)> = solved
+            .solved_steps
+            .iter()
+            .filter_map(|s| {
+                let quantity: u32 = needs
+                    .get(s.step_id.as_str())?
+                    .resource_needs
+                    .iter()
+                    .filter(|n| n.resource_id == resource.id)
+                    .map(|n| n.quantity)
+                    .sum();
+                (quantity > 0).then_some((s.start_offset_mins, s.end_offset_mins, quantity))
+            })
+            .collect();
+        peak = peak.max(peak_usage(&usages, 0, u32::MAX).0);
+    }
+    peak
+}
+
+// ---------------------------------------------------------------------------
+// Schedule confidence
+// ---------------------------------------------------------------------------
+
+/// How long a step took on an earlier run compared with its plan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalExecution {
+    pub step_id: String,
+    pub planned_duration_mins: u32,
+    pub actual_duration_mins: u32,
+}
+
+/// A step's duration as its history suggests.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepDurationEstimate {
+    pub step_id: String,
+    /// Historical executions the estimate is based on.
+    pub samples: usize,
+    pub expected_duration_mins: f64,
+    pub std_dev_mins: f64,
+}
+
+/// How far the schedule's total duration can be trusted, given how long its
+/// steps have taken before.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceReport {
+    /// The solved schedule's total duration.
+    pub planned_duration_mins: u32,
+    pub expected_duration_mins: f64,
+    pub std_dev_mins: f64,
+    /// The central 80% range of the total duration (10th to 90th
+    /// percentile).
+    pub p80_low_mins: u32,
+    pub p80_high_mins: u32,
+    /// Steps with historical executions, in schedule order.
+    pub step_estimates: Vec<StepDurationEstimate>,
+}
+
+/// z-score of the 90th percentile of a normal distribution.
+const Z_90: f64 = 1.2816;
+
+/// Estimate the spread of `solved`'s total duration from `actuals`.
+///
+/// Each step's historical actual/planned ratios scale its duration; their
+/// spread gives its variance. Steps without history keep their planned
+/// duration with no variance. The expected times are then carried forward
+/// through the template's dependencies as in the CPM forward pass, no step
+/// starting before its solved start (so resource waits are kept), and each
+/// step inherits the variance of the predecessor that drives its start.
+/// The total duration is taken to be normally distributed around the latest
+/// expected finish.
+pub fn estimate_schedule_confidence(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    actuals: &[HistoricalExecution],
+) -> ConfidenceReport {
+    let steps: HashMap<&str, &Step> = template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let solved_steps: HashMap<&str, &SolvedStep> =
+        solved.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+
+    // step ID -> (expected duration, variance)
+    let mut durations: HashMap<&str, (f64, f64)> = HashMap::new();
+    let mut step_estimates = Vec::new();
+    for step in &solved.solved_steps {
+        let planned = steps.get(step.step_id.as_str()).map_or(
+            f64::from(step.end_offset_mins - step.start_offset_mins),
+            |s| f64::from(s.duration_mins),
+        );
+        let ratios: Vec<f64> = actuals
+            .iter()
+            .filter(|a| a.step_id == step.step_id && a.planned_duration_mins > 0)
+            .map(|a| f64::from(a.actual_duration_mins) / f64::from(a.planned_duration_mins))
+            .collect();
+        if ratios.is_empty() {
+            durations.insert(step.step_id.as_str(), (planned, 0.0));
+            continue;
+        }
+        let n = ratios.len() as f64;
+        let mean = ratios.iter().sum::<f64>() / n;
+        let variance = ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+        let expected = planned * mean;
+        let std_dev = planned * variance.sqrt();
+        durations.insert(step.step_id.as_str(), (expected, std_dev * std_dev));
+        step_estimates.push(StepDurationEstimate {
+            step_id: step.step_id.clone(),
+            samples: ratios.len(),
+            expected_duration_mins: expected,
+            std_dev_mins: std_dev,
+        });
+    }
+
+    let mut times: HashMap<&str, StepTimes> = HashMap::new();
+    for step in &solved.solved_steps {
+        expected_times(step.step_id.as_str(), &steps, &solved_steps, &durations, &mut times);
+    }
+    let (expected, variance) = times
+        .values()
+        .map(|t| (t.finish, t.finish_variance))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((0.0, 0.0));
+    let std_dev = variance.sqrt();
+
+    ConfidenceReport {
+        planned_duration_mins: solved.summary.total_duration_mins,
+        expected_duration_mins: expected,
+        std_dev_mins: std_dev,
+        p80_low_mins: (expected - Z_90 * std_dev).max(0.0).floor() as u32,
+        p80_high_mins: (expected + Z_90 * std_dev).ceil() as u32,
+        step_estimates,
+    }
+}
+
+/// Expected start and finish of a step, with the variance of each.
+#[derive(Clone, Copy)]
+struct StepTimes {
+    start: f64,
+    start_variance: f64,
+    finish: f64,
+    finish_variance: f64,
+}
+
+fn expected_times<'a>(
+    step_id: &'a str,
+    steps: &HashMap<&str, &'a Step>,
+    solved_steps: &HashMap<&str, &SolvedStep>,
+    durations: &HashMap<&str, (f64, f64)>,
+    times: &mut HashMap<&'a str, StepTimes>,
+) -> StepTimes {
+    if let Some(&t) = times.get(step_id) {
+        return t;
+    }
+    let (duration, variance) = durations.get(step_id).copied().unwrap_or((0.0, 0.0));
+    let mut start = solved_steps.get(step_id).map_or(0.0, |s| f64::from(s.start_offset_mins));
+    let mut start_variance = 0.0;
+    let dependencies = steps.get(step_id).map_or(&[][..], |s| &s.dependencies[..]);
+    for dep in dependencies {
+        if !solved_steps.contains_key(dep.step_id.as_str()) {
+            continue;
+        }
+        let Some(&pred) = steps.get(dep.step_id.as_str()) else {
+            continue;
+        };
+        let p = expected_times(pred.id.as_str(), steps, solved_steps, durations, times);
+        let (release, release_variance) = match dep.dependency_type {
+            DependencyType::FinishToStart => (p.finish, p.finish_variance),
+            DependencyType::StartToStart => (p.start, p.start_variance),
+            DependencyType::FinishToFinish => (p.finish - duration, p.finish_variance),
+            DependencyType::StartToFinish => (p.start - duration, p.start_variance),
+        };
+        if release > start {
+            start = release;
+            start_variance = release_variance;
+        } else if release == start {
+            start_variance = start_variance.max(release_variance);
+        }
+    }
+    let t = StepTimes {
+        start,
+        start_variance,
+        finish: start + duration,
+        finish_variance: start_variance + variance,
+    };
+    times.insert(step_id, t);
+    t
+}
+
+// ---------------------------------------------------------------------------
+// Missing dependencies
+// ---------------------------------------------------------------------------
+
+/// A dependency the template may have forgotten: `to_step_id` should perhaps
+/// wait for `from_step_id`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedDependency {
+    pub from_step_id: String,
+    pub to_step_id: String,
+    /// Why the dependency is suggested; several reasons are joined by "; ".
+    pub reason: String,
+    /// 0.0 to 1.0.
+    pub confidence: f64,
+}
+
+/// Title words that usually mark a step coming before one whose title has
+/// the second word.
+const ORDER_KEYWORDS: &[(&str, &str)] =
+    &[("prepare", "use"), ("preheat", "bake"), ("set up", "run"), ("build", "test")];
+
+/// Suggest dependencies between steps that don't depend on each other, even
+/// transitively, but probably should:
+///
+/// - steps whose Equipment/People needs don't fit within capacity together
+///   will be sequenced by the allocator anyway, so the order may as well be
+///   explicit (confidence 0.3);
+/// - steps on the same track usually happen one after the other
+///   (confidence 0.4);
+/// - titles with words like "prepare" and "use" imply their order
+///   (confidence 0.6, see `ORDER_KEYWORDS`).
+///
+/// A pair with several reasons gets one suggestion, combining the
+/// confidences as independent evidence. Contention and tracks give no order
+/// of their own, so unless the titles say otherwise the step listed first in
+/// the template comes first. Results are sorted by confidence, highest first.
+pub fn suggest_missing_dependencies(template: &ScheduleTemplate) -> Vec<SuggestedDependency> {
+    let ancestors = transitive_predecessors(template);
+    let timed_capacity: HashMap<&str, (&str, u32)> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| (r.id.as_str(), (r.name.as_str(), r.capacity)))
+        .collect();
+    let track_names: HashMap<&str, &str> =
+        template.tracks.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+
+    let mut suggestions = Vec::new();
+    let steps = &template.steps;
+    for (i, a) in steps.iter().enumerate() {
+        for b in &steps[i + 1..] {
+            if ancestors[a.id.as_str()].contains(b.id.as_str())
+                || ancestors[b.id.as_str()].contains(a.id.as_str())
+            {
+                continue;
+            }
+            let mut reasons: Vec<(String, f64)> = Vec::new();
+            let mut b_first = false;
+
+            for need in &a.resource_needs {
+                let Some(&(name, capacity)) = timed_capacity.get(need.resource_id.as_str())
+                else {
+                    continue;
+                };
+                let together: u32 = b
+                    .resource_needs
+                    .iter()
+                    .filter(|n| n.resource_id == need.resource_id)
+                    .map(|n| n.quantity)
+                    .sum();
+                if together > 0 && together + need.quantity > capacity {
+                    let reason = format!("Both need '{}' and it can't serve them at once", name);
+                    reasons.push((reason, 0.3));
+                }
+            }
+
+            if let (Some(track_a), Some(track_b)) = (&a.track_id, &b.track_id) {
+                if track_a == track_b {
+                    let name = track_names.get(track_a.as_str()).copied().unwrap_or(track_a);
+                    reasons.push((format!("Both are on track '{}'", name), 0.4));
+                }
+            }
+
+            let (title_a, title_b) = (a.title.to_lowercase(), b.title.to_lowercase());
+            let precedes = |first: &str, second: &str, before: &str, after: &str| {
+                first.contains(before) && !first.contains(after) && second.contains(after)
+            };
+            for &(before, after) in ORDER_KEYWORDS {
+                let reason = format!("'{}' usually comes before '{}'", before, after);
+                if precedes(&title_a, &title_b, before, after) {
+                    reasons.push((reason, 0.6));
+                } else if precedes(&title_b, &title_a, before, after) {
+                    reasons.push((reason, 0.6));
+                    b_first = true;
+                }
+            }
+
+            if reasons.is_empty() {
+                continue;
+            }
+            let (from, to) = if b_first { (b, a) } else { (a, b) };
+            suggestions.push(SuggestedDependency {
+                from_step_id: from.id.clone(),
+                to_step_id: to.id.clone(),
+                confidence: 1.0 - reasons.iter().map(|(_, c)| 1.0 - c).product::<f64>(),
+                reason: reasons.into_iter().map(|(r, _)| r).collect::<Vec<_>>().join("; "),
+            });
+        }
+    }
+
+    suggestions.sort_by(|x, y| {
+        y.confidence
+            .total_cmp(&x.confidence)
+            .then_with(|| x.from_step_id.cmp(&y.from_step_id))
+            .then_with(|| x.to_step_id.cmp(&y.to_step_id))
+    });
+    suggestions
+}
+
+// ---------------------------------------------------------------------------
+// Timing policy comparison
+// ---------------------------------------------------------------------------
+
+/// How heavily one Equipment/People resource is used under a timing policy.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyResourceUsage {
+    pub resource_id: String,
+    /// Unit-minutes assigned as a percentage of capacity x total duration.
+    pub utilization_percent: f64,
+    /// Most units in use at any one moment.
+    pub peak_usage: u32,
+}
+
+/// The schedule one timing policy produces.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyOutcome {
+    pub total_duration_mins: u32,
+    /// Steps that allocation pushed past their CPM late start.
+    pub steps_delayed_beyond_float: usize,
+    /// One entry per Equipment/People resource, in template order.
+    pub resources: Vec<PolicyResourceUsage>,
+}
+
+/// The template solved with every step ASAP and with every step ALAP.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyComparisonReport {
+    pub asap: PolicyOutcome,
+    pub alap: PolicyOutcome,
+}
+
+/// Solve the template twice, with every step's timing policy forced to ASAP
+/// and then to ALAP, and compare the two schedules. Steps with a `Fixed`
+/// policy keep it. Capacities come from `inventory` where it overrides the
+/// template.
+///
+/// Fails if either schedule can't be solved.
+pub fn compare_timing_policies(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Result<PolicyComparisonReport, SolveError> {
+    Ok(PolicyComparisonReport {
+        asap: policy_outcome(template, inventory, TimingPolicy::Asap)?,
+        alap: policy_outcome(template, inventory, TimingPolicy::Alap)?,
+    })
+}
+
+fn policy_outcome(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    policy: TimingPolicy,
+) -> Result<PolicyOutcome, SolveError> {
+    let mut forced = template.clone();
+    for step in &mut forced.steps {
+        if !matches!(step.timing_policy, Some(TimingPolicy::Fixed { .. })) {
+            step.timing_policy = Some(policy.clone());
+        }
+    }
+    // Late starts from the template solve actually schedules: recurring
+    // copies expanded and inactive conditional steps dropped.
+    let prepared = crate::solver::prepare_template(&forced, &SolveOptions::default());
+    let late_starts = crate::solver::cpm_only(&prepared)?.late_starts;
+    let solved = crate::solver::solve(&forced, inventory)?;
+
+    let steps_delayed_beyond_float = solved
+        .solved_steps
+        .iter()
+        .filter(|s| late_starts.get(&s.step_id).is_some_and(|&ls| s.start_offset_mins > ls))
+        .count();
+
+    let total = solved.summary.total_duration_mins;
+    let resources = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|resource| {
+            let capacity = effective_capacity(resource, inventory);
+            let usages = resource_usages(&solved.solved_steps, &resource.id);
+            let unit_mins: u64 = usages
+                .iter()
+                .map(|&(start, end, quantity)| u64::from(end - start) * u64::from(quantity))
+                .sum();
+            let available = u64::from(capacity) * u64::from(total);
+            let (peak_usage, _) = peak_usage(&usages, 0, u32::MAX);
+            PolicyResourceUsage {
+                resource_id: resource.id.clone(),
+                utilization_percent: if available == 0 {
+                    0.0
+                } else {
+                    unit_mins as f64 / available as f64 * 100.0
+                },
+                peak_usage,
+            }
+        })
+        .collect();
+
+    Ok(PolicyOutcome {
+        total_duration_mins: total,
+        steps_delayed_beyond_float,
+        resources,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, Resource, ResourceNeed, Step, StepDependency};
+
+    fn crew_step(id: &str, dur: u32, quantity: u32) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: dur,
+            resource_needs: vec![ResourceNeed {
+                resource_id: "crew".to_string(),
+                quantity,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn crew_template(steps: Vec<Step>) -> ScheduleTemplate {
+        ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            steps,
+            resources: vec![Resource {
+                id: "crew".to_string(),
+                name: "Crew".to_string(),
+                kind: ResourceKind::People,
+                capacity: 3,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_histogram_buckets_peak_usage() {
+        // a (2 people, 0-60) and b (1 person, 0-30) run together.
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 30, 1)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let histogram = get_resource_histogram(&solved, &template, None, "crew", 30).unwrap();
+        assert_eq!(histogram.capacity, 3);
+        let used: Vec<u32> = histogram.buckets.iter().map(|b| b.used).collect();
+        assert_eq!(used, vec![3, 2]);
+        assert_eq!(histogram.buckets[1].available, 1);
+        assert_eq!(histogram.buckets[1].end_mins, 60);
+
+        // Four people in stock leave two free in the second half hour.
+        let inventory = ResourceInventory {
+            items: vec![crate::model::ResourceInventoryItem {
+                resource_id: "crew".to_string(),
+                available_quantity: 4,
+            }],
+        };
+        let solved = crate::solver::solve(&template, Some(&inventory)).unwrap();
+        let histogram =
+            get_resource_histogram(&solved, &template, Some(&inventory), "crew", 30).unwrap();
+        assert_eq!(histogram.capacity, 4);
+        assert_eq!(histogram.buckets[1].available, 2);
+    }
+
+    #[test]
+    fn test_histogram_rejects_unknown_resource_and_zero_bucket() {
+        let template = crew_template(vec![crew_step("a", 60, 1)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        assert!(get_resource_histogram(&solved, &template, None, "oven", 30).is_err());
+        assert!(get_resource_histogram(&solved, &template, None, "crew", 0).is_err());
+    }
+
+    #[test]
+    fn test_s_curve_accumulates_person_minutes() {
+        // a (2 people, 0-60) and b (1 person, 0-30): 150 person-minutes.
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 30, 1)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let curve = compute_resource_s_curve(&solved, &template, "crew", 20).unwrap();
+        let times: Vec<u32> = curve.buckets.iter().map(|b| b.time_mins).collect();
+        assert_eq!(times, vec![20, 40, 60]);
+        let used: Vec<f64> = curve.buckets.iter().map(|b| b.cumulative_used).collect();
+        assert_eq!(used, vec![60.0, 110.0, 150.0]);
+        let planned: Vec<f64> = curve.buckets.iter().map(|b| b.planned_used).collect();
+        assert_eq!(planned, vec![50.0, 100.0, 150.0]);
+    }
+
+    #[test]
+    fn test_float_histogram_puts_critical_steps_first() {
+        // a(100) is critical; b, c and d have 70, 60 and 99 min of float.
+        let template = crew_template(vec![
+            crew_step("a", 100, 0),
+            crew_step("b", 30, 0),
+            crew_step("c", 40, 0),
+            crew_step("d", 1, 0),
+        ]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let histogram = float_histogram(&solved, 60).unwrap();
+        let summary: Vec<(u32, u32, Vec<&str>)> = histogram
+            .buckets
+            .iter()
+            .map(|b| (b.min_float, b.max_float, b.step_ids.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(0, 0, vec!["a"]), (1, 60, vec!["c"]), (61, 120, vec!["b", "d"])]
+        );
+        assert!(float_histogram(&solved, 0).is_err());
+    }
+
+    #[test]
+    fn test_loading_report_totals_per_period() {
+        // a (2 people, 0-60) and b (1 person, 0-30) over 40-minute periods.
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 30, 1)]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let report = resource_loading_report(&solved, &template, 40).unwrap();
+        assert_eq!(report.periods, vec![0, 40]);
+        assert_eq!(report.resources.len(), 1);
+        assert_eq!(report.resources[0].resource_id, "crew");
+        assert_eq!(report.resources[0].loads, vec![3, 2]);
+        assert!(resource_loading_report(&solved, &template, 0).is_err());
+    }
+
+    #[test]
+    fn test_s_curve_tracks_consumable_depletion() {
+        let mut first = crew_step("a", 10, 1);
+        first.resource_needs[0].resource_id = "flour".to_string();
+        first.resource_needs[0].quantity = 5;
+        let mut second = first.clone();
+        second.id = "b".to_string();
+        second.resource_needs[0].quantity = 3;
+        second.dependencies.push(StepDependency {
+            step_id: "a".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let mut template = crew_template(vec![first, second]);
+        template.resources.push(Resource {
+            id: "flour".to_string(),
+            name: "Flour".to_string(),
+            kind: ResourceKind::Consumable,
+            capacity: 20,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let curve = compute_resource_s_curve(&solved, &template, "flour", 10).unwrap();
+        let used: Vec<f64> = curve.buckets.iter().map(|b| b.cumulative_used).collect();
+        assert_eq!(used, vec![5.0, 8.0]);
+        assert!(compute_resource_s_curve(&solved, &template, "flour", 0).is_err());
+    }
+
+    #[test]
+    fn test_parallel_opportunities_under_concurrency_limit() {
+        // Two at a time: a (0-60) and b (0-30) run first, c waits for b. d
+        // depends on b, so it can't be moved alongside it.
+        let mut d = crew_step("d", 10, 1);
+        d.dependencies.push(StepDependency {
+            step_id: "b".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let mut template = crew_template(vec![
+            crew_step("a", 60, 1),
+            crew_step("b", 30, 1),
+            crew_step("c", 30, 1),
+            d,
+        ]);
+        template.max_concurrent_steps = Some(2);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let start_of = |id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+        assert_eq!((start_of("b"), start_of("c")), (0, 30));
+
+        let opportunities = detect_parallel_opportunities(&template);
+        let pairs: Vec<(&str, &str, u32)> = opportunities
+            .iter()
+            .map(|o| (o.step_a_id.as_str(), o.step_b_id.as_str(), o.potential_savings_mins))
+            .collect();
+        assert!(pairs.contains(&("b", "c", 30)), "{:?}", pairs);
+        assert!(!pairs.iter().any(|p| p.0 == "b" && p.1 == "d"), "{:?}", pairs);
+
+        // One step at a time: nothing can overlap.
+        template.max_concurrent_steps = Some(1);
+        assert!(detect_parallel_opportunities(&template).is_empty());
+    }
+
+    #[test]
+    fn test_what_if_add_resource_reports_savings_and_driving_steps() {
+        // a and b each need 2 of the 3 crew, so b waits for a and the
+        // schedule ends at 120. A fourth crew member lets them run together,
+        // leaving the crew-free c(90) to set the end.
+        let mut c = crew_step("c", 90, 0);
+        c.resource_needs.clear();
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 60, 2), c]);
+
+        let extra = template.resources[0].clone();
+        let report = what_if_add_resource(&template, &extra, 1, None).unwrap();
+        assert_eq!((report.baseline_duration_mins, report.new_duration_mins), (120, 90));
+        assert_eq!(report.savings_mins, 30);
+        assert!((report.savings_percent - 25.0).abs() < 1e-9);
+        assert_eq!(report.newly_critical_steps, vec!["c"]);
+        assert_eq!(report.relieved_steps, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_what_if_remove_step_drops_references_and_frees_resources() {
+        // a(60, oven) -> b(30, crew); c(40, crew) runs alongside. Without a,
+        // b starts at once and c sets the end.
+        let mut a = crew_step("a", 60, 1);
+        a.resource_needs[0].resource_id = "oven".to_string();
+        let mut b = crew_step("b", 30, 1);
+        b.dependencies.push(StepDependency {
+            step_id: "a".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let mut template = crew_template(vec![a, b, crew_step("c", 40, 1)]);
+        template.resources.push(Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+
+        let report = what_if_remove_step(&template, "a", None).unwrap();
+        assert_eq!((report.baseline_duration_mins, report.new_duration_mins), (90, 40));
+        assert_eq!(report.newly_critical_steps, vec!["c"]);
+        assert_eq!(report.relieved_steps, vec!["b"]);
+        assert_eq!(report.freed_resources, vec!["oven"]);
+
+        assert!(what_if_remove_step(&template, "zz", None).is_err());
+    }
+
+    #[test]
+    fn test_risks_flag_critical_demand_peak_chain_and_alap() {
+        // a (2 of 3 crew, 60 min) -> b (no crew), with c running alongside
+        // both as ALAP.
+        let mut b = crew_step("b", 30, 1);
+        b.resource_needs.clear();
+        b.dependencies.push(StepDependency {
+            step_id: "a".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let mut c = crew_step("c", 80, 1);
+        c.timing_policy = Some(TimingPolicy::Alap);
+        let template = crew_template(vec![crew_step("a", 60, 2), b, c]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let risks = identify_schedule_risks(&template, &solved);
+        let ids: Vec<&str> = risks.iter().map(|r| r.risk_id.as_str()).collect();
+        assert!(ids.contains(&"critical-high-demand:a"), "{:?}", ids);
+        assert!(ids.contains(&"single-critical-predecessor:b"), "{:?}", ids);
+        assert!(ids.contains(&"resource-peak:crew"), "{:?}", ids);
+        assert!(ids.contains(&"alap-near-late-start:c"), "{:?}", ids);
+
+        // An ALAP step with a successor is anchored by it and isn't flagged.
+        let mut d = crew_step("d", 10, 1);
+        d.resource_needs.clear();
+        d.timing_policy = Some(TimingPolicy::Alap);
+        let mut e = crew_step("e", 5, 1);
+        e.resource_needs.clear();
+        e.dependencies = vec![StepDependency {
+            step_id: "d".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        }];
+        let mut template = template;
+        template.steps.extend([d, e]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let risks = identify_schedule_risks(&template, &solved);
+        let ids: Vec<&str> = risks.iter().map(|r| r.risk_id.as_str()).collect();
+        assert!(ids.contains(&"alap-near-late-start:c"), "{:?}", ids);
+        assert!(!ids.contains(&"alap-near-late-start:d"), "{:?}", ids);
+    }
+
+    #[test]
+    fn test_resource_deadline_conflicts_need_a_deadline() {
+        use crate::model::TimeConstraint;
+
+        // b (1 crew, 30 min) -> c (1 crew, 60 min) exactly fills the
+        // 90-minute deadline; a (2 crew, 60 min) has float.
+        let mut c = crew_step("c", 60, 1);
+        c.dependencies.push(StepDependency {
+            step_id: "b".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let mut template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 30, 1), c]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        assert!(check_resource_deadline_conflicts(&solved, &template).is_empty());
+
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: Some("2026-03-01T10:30:00".to_string()),
+            timezone: None,
+        });
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let conflicts = check_resource_deadline_conflicts(&solved, &template);
+        let found: Vec<(&str, u32, i32)> = conflicts
+            .iter()
+            .map(|c| (c.step_id.as_str(), c.peak_at_mins, c.margin_mins))
+            .collect();
+        // The crew peaks at 3 while a runs alongside critical c.
+        assert_eq!(found, vec![("c", 30, 60)]);
+        assert_eq!(conflicts[0].deadline_offset_mins, 90);
+
+        let risks = identify_schedule_risks(&template, &solved);
+        assert!(risks.iter().any(|r| r.risk_id == "resource-deadline:c:crew"));
+    }
+
+    #[test]
+    fn test_risks_flag_low_consumable() {
+        let mut step = crew_step("a", 10, 1);
+        step.resource_needs[0].resource_id = "flour".to_string();
+        step.resource_needs[0].quantity = 19;
+        let mut template = crew_template(vec![step]);
+        template.resources.push(Resource {
+            id: "flour".to_string(),
+            name: "Flour".to_string(),
+            kind: ResourceKind::Consumable,
+            capacity: 20,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let risks = identify_schedule_risks(&template, &solved);
+        let low = risks.iter().find(|r| r.risk_id == "consumable-low:flour").unwrap();
+        assert_eq!(low.severity, RiskSeverity::High);
+        assert_eq!(low.affected_step_ids, vec!["a"]);
+    }
+
+    #[test]
+    fn test_explain_critical_path_follows_dependencies_and_resource_waits() {
+        // a(30) -> c(10), b(20) and d(10) after b: a, b and d share one
+        // oven, so b waits for a and the schedule ends with d at 60.
+        let after = |id: &str, dur: u32, pred: &str| {
+            let mut step = crew_step(id, dur, 1);
+            step.resource_needs.clear();
+            step.dependencies.push(StepDependency {
+                step_id: pred.to_string(),
+                dependency_type: DependencyType::FinishToStart,
+                trigger_at_percent: None,
+                condition: None,
+            });
+            step
+        };
+        let mut a = crew_step("a", 30, 1);
+        let mut b = crew_step("b", 20, 1);
+        let c = after("c", 10, "a");
+        let mut d = after("d", 10, "b");
+        a.resource_needs[0].resource_id = "oven".to_string();
+        b.resource_needs[0].resource_id = "oven".to_string();
+        d.resource_needs = b.resource_needs.clone();
+        let mut template = crew_template(vec![a, b, c, d]);
+        template.resources[0].id = "oven".to_string();
+        template.resources[0].name = "Oven".to_string();
+        template.resources[0].kind = ResourceKind::Equipment;
+        template.resources[0].capacity = 1;
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let explanation = explain_critical_path(&solved, &template);
+        assert_eq!(explanation.duration_mins, 60);
+        let ids: Vec<&str> = explanation.chain.iter().map(|l| l.step_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "d"]);
+        assert_eq!(explanation.chain[0].predecessor_dependency_type, None);
+        assert_eq!(explanation.chain[1].predecessor_dependency_type, None);
+        assert_eq!(
+            explanation.chain[1].resource_conflicts,
+            vec!["Waited for 'Oven' until 'a' finished".to_string()]
+        );
+        assert_eq!(
+            explanation.chain[2].predecessor_dependency_type,
+            Some(DependencyType::FinishToStart)
+        );
+        assert_eq!(explanation.chain[2].duration_mins, 10);
+    }
+
+    #[test]
+    fn test_estimate_people_required_searches_headcounts() {
+        // Four independent hour-long steps for one person each: 1 person
+        // takes 240 min, 2 or 3 take 120 and 4 take 60.
+        let template = crew_template(vec![
+            crew_step("a", 60, 1),
+            crew_step("b", 60, 1),
+            crew_step("c", 60, 1),
+            crew_step("d", 60, 1),
+        ]);
+
+        let estimate = estimate_people_required(&template, 130).unwrap();
+        assert_eq!(estimate.min_people, 2);
+        assert_eq!(estimate.achieved_duration_mins, 120);
+        assert_eq!(estimate.diminishing_returns_at, 4);
+
+        assert!(estimate_people_required(&template, 50).is_err());
+        let mut unstaffed = template.clone();
+        unstaffed.resources[0].kind = ResourceKind::Equipment;
+        assert!(matches!(
+            estimate_people_required(&unstaffed, 130),
+            Err(SolveError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_leveling_metrics_compare_cpm_and_leveled_schedules() {
+        // a and b need 2 of the 3 crew each, so leveling runs b after a.
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 30, 2)]);
+        let options = crate::solver::SolveOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let pre = crate::solver::solve_with_options(&template, None, &options).unwrap();
+        let post = crate::solver::solve(&template, None).unwrap();
+
+        let metrics = leveling_metrics(&pre, &post, &template);
+        assert_eq!(metrics.duration_extension_mins, 30);
+        assert_eq!(metrics.steps_delayed, 1);
+        assert_eq!(metrics.max_resource_peak_before, 4);
+        assert_eq!(metrics.max_resource_peak_after, 2);
+        assert_eq!(metrics.peak_reduction_percent, 50.0);
+        assert_eq!(metrics.newly_critical_steps, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_compare_timing_policies_spreads_peak_with_alap() {
+        // a (60) holds 2 crew throughout. ASAP runs b (2 crew) and e (1 crew)
+        // alongside it from the start; ALAP moves e before f and b to the end.
+        let a = crew_step("a", 60, 2);
+        let b = crew_step("b", 10, 2);
+        let e = crew_step("e", 20, 1);
+        let f = Step {
+            id: "f".to_string(),
+            title: "f".to_string(),
+            duration_mins: 20,
+            dependencies: vec![StepDependency {
+                step_id: "e".to_string(),
+                dependency_type: DependencyType::FinishToStart,
+                trigger_at_percent: None,
+                condition: None,
+            }],
+            ..Default::default()
+        };
+        let mut template = crew_template(vec![a, b, e, f]);
+        template.resources[0].capacity = 5;
+
+        let report = compare_timing_policies(&template, None).unwrap();
+        assert_eq!(report.asap.resources[0].peak_usage, 5);
+        assert_eq!(report.alap.resources[0].peak_usage, 4);
+        for outcome in [&report.asap, &report.alap] {
+            assert_eq!(outcome.total_duration_mins, 60);
+            assert_eq!(outcome.steps_delayed_beyond_float, 0);
+            // (120 + 20 + 20) crew-minutes of 5 x 60.
+            assert!((outcome.resources[0].utilization_percent - 160.0 / 3.0).abs() < 1e-9);
+        }
+
+        // With only 3 crew, b can't run alongside a under either policy.
+        let inventory = ResourceInventory {
+            items: vec![crate::model::ResourceInventoryItem {
+                resource_id: "crew".to_string(),
+                available_quantity: 3,
+            }],
+        };
+        let report = compare_timing_policies(&template, Some(&inventory)).unwrap();
+        assert_eq!(report.asap.steps_delayed_beyond_float, 1);
+        assert_eq!(report.alap.steps_delayed_beyond_float, 1);
+    }
+
+    #[test]
+    fn test_compare_timing_policies_ignores_inactive_steps_for_float() {
+        // The 300-minute step is inactive, so solve finishes at 70 and b has
+        // no float left once it waits for a's crew.
+        let idle = Step {
+            id: "idle".to_string(),
+            title: "idle".to_string(),
+            duration_mins: 300,
+            condition: Some("overnight".to_string()),
+            ..Default::default()
+        };
+        let template = crew_template(vec![crew_step("a", 60, 2), crew_step("b", 10, 2), idle]);
+
+        let report = compare_timing_policies(&template, None).unwrap();
+        assert_eq!(report.asap.total_duration_mins, 70);
+        assert_eq!(report.asap.steps_delayed_beyond_float, 1);
+    }
+
+    #[test]
+    fn test_schedule_confidence_propagates_step_variance() {
+        // a (60) then b (30). a has overrun by 10% and 30% before.
+        let mut b = crew_step("b", 30, 1);
+        b.dependencies.push(StepDependency {
+            step_id: "a".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let template = crew_template(vec![crew_step("a", 60, 1), b]);
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let history = |actual| HistoricalExecution {
+            step_id: "a".to_string(),
+            planned_duration_mins: 60,
+            actual_duration_mins: actual,
+        };
+
+        let report = estimate_schedule_confidence(&solved, &template, &[history(66), history(78)]);
+        assert_eq!(report.planned_duration_mins, 90);
+        // a: 60 * 1.2 = 72 +- 60 * 0.1 = 6, so the total is 102 +- 6.
+        assert!((report.expected_duration_mins - 102.0).abs() < 1e-9);
+        assert!((report.std_dev_mins - 6.0).abs() < 1e-9);
+        assert_eq!((report.p80_low_mins, report.p80_high_mins), (94, 110));
+        assert_eq!(report.step_estimates.len(), 1);
+        assert_eq!(report.step_estimates[0].samples, 2);
+
+        let unknown = estimate_schedule_confidence(&solved, &template, &[]);
+        assert_eq!((unknown.p80_low_mins, unknown.p80_high_mins), (90, 90));
+    }
+
+    #[test]
+    fn test_suggest_missing_dependencies_combines_reasons() {
+        // ice and glaze contend for the crew, share a track and have titles
+        // implying glaze comes first; mix only fits alongside ice, and glaze
+        // already depends on it.
+        let mut ice = crew_step("ice", 10, 2);
+        ice.title = "Use glaze on cake".to_string();
+        ice.track_id = Some("kitchen".to_string());
+        let mut glaze = crew_step("glaze", 10, 2);
+        glaze.title = "Prepare glaze".to_string();
+        glaze.track_id = Some("kitchen".to_string());
+        glaze.dependencies = vec![StepDependency {
+            step_id: "mix".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        }];
+        let template = crew_template(vec![ice, crew_step("mix", 10, 1), glaze]);
+
+        let suggestions = suggest_missing_dependencies(&template);
+        assert_eq!(suggestions.len(), 1);
+        let s = &suggestions[0];
+        assert_eq!((s.from_step_id.as_str(), s.to_step_id.as_str()), ("glaze", "ice"));
+        assert_eq!(
+            s.reason,
+            "Both need 'Crew' and it can't serve them at once; Both are on track 'kitchen'; \
+             'prepare' usually comes before 'use'"
+        );
+        assert!((s.confidence - 0.832).abs() < 1e-9);
+    }
+}
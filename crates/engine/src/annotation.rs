@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::allocator::FloatConsumption;
+use crate::gantt::GanttRow;
+use crate::model::{
+    ResourceNeed, ScheduleSummary, ScheduleTemplate, SolvedHammock, SolvedSchedule, SolvedStep,
+};
+use crate::solver::{NearCriticalPath, SolveOptions};
+
+// ---------------------------------------------------------------------------
+// Annotated schedule types
+// ---------------------------------------------------------------------------
+
+/// A solved step together with the template data describing it, so it can
+/// be displayed without looking the step up in the template.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedSolvedStep {
+    /// The solved step's own fields, serialized inline.
+    #[serde(flatten)]
+    pub solved: SolvedStep,
+    pub title: String,
+    pub description: Option<String>,
+    pub track_id: Option<String>,
+    /// `None` when the step has no track or names one the template doesn't
+    /// define.
+    pub track_name: Option<String>,
+    pub resource_needs: Vec<ResourceNeed>,
+}
+
+/// A `SolvedSchedule` whose steps carry their template data.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedSolvedSchedule {
+    pub template_id: String,
+    pub template_name: String,
+    pub solved_steps: Vec<AnnotatedSolvedStep>,
+    pub summary: ScheduleSummary,
+    pub warnings: Vec<String>,
+    pub gantt_rows: Option<Vec<GanttRow>>,
+    pub near_critical_paths: Option<Vec<NearCriticalPath>>,
+    pub float_consumption: Option<Vec<FloatConsumption>>,
+    pub hammocks: Vec<SolvedHammock>,
+}
+
+// ---------------------------------------------------------------------------
+// Annotation
+// ---------------------------------------------------------------------------
+
+/// Copy each solved step's title, description, track and resource needs
+/// from the template it was solved from, prepared with the `options` it was
+/// solved with so every recurring copy and active conditional step finds
+/// its own step. A solved step the template still doesn't know (e.g. one
+/// solved from a different template) is titled with its ID.
+pub fn annotate(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    options: &SolveOptions,
+) -> AnnotatedSolvedSchedule {
+    let prepared = crate::solver::prepare_template(template, options);
+    let steps: HashMap<&str, _> = prepared.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let track_names: HashMap<&str, &str> =
+        template.tracks.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+
+    let solved_steps = solved
+        .solved_steps
+        .iter()
+        .map(|solved_step| {
+            let step = steps.get(solved_step.step_id.as_str());
+            let track_id = step.and_then(|s| s.track_id.clone());
+            AnnotatedSolvedStep {
+                solved: solved_step.clone(),
+                title: step.map_or_else(|| solved_step.step_id.clone(), |s| s.title.clone()),
+                description: step.and_then(|s| s.description.clone()),
+                track_name: track_id
+                    .as_deref()
+                    .and_then(|id| track_names.get(id))
+                    .map(|name| name.to_string()),
+                track_id,
+                resource_needs: step.map(|s| s.resource_needs.clone()).unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    AnnotatedSolvedSchedule {
+        template_id: solved.template_id.clone(),
+        template_name: template.name.clone(),
+        solved_steps,
+        summary: solved.summary.clone(),
+        warnings: solved.warnings.clone(),
+        gantt_rows: solved.gantt_rows.clone(),
+        near_critical_paths: solved.near_critical_paths.clone(),
+        float_consumption: solved.float_consumption.clone(),
+        hammocks: solved.hammocks.clone(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{RecurringStepPattern, Step, Track};
+
+    #[test]
+    fn test_annotate_embeds_template_data() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Bread".to_string(),
+            steps: vec![
+                Step {
+                    id: "mix".to_string(),
+                    title: "Mix".to_string(),
+                    description: Some("By hand".to_string()),
+                    duration_mins: 20,
+                    track_id: Some("bench".to_string()),
+                    ..Default::default()
+                },
+                Step {
+                    id: "bake".to_string(),
+                    title: "Bake".to_string(),
+                    duration_mins: 45,
+                    track_id: Some("nowhere".to_string()),
+                    ..Default::default()
+                },
+            ],
+            tracks: vec![Track {
+                id: "bench".to_string(),
+                name: "Bench".to_string(),
+                steps: vec![],
+                max_concurrent: None,
+            }],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let annotated = annotate(&solved, &template, &SolveOptions::default());
+        assert_eq!(annotated.template_name, "Bread");
+        let mix = annotated.solved_steps.iter().find(|s| s.solved.step_id == "mix").unwrap();
+        assert_eq!(mix.title, "Mix");
+        assert_eq!(mix.description.as_deref(), Some("By hand"));
+        assert_eq!(mix.track_name.as_deref(), Some("Bench"));
+        let bake = annotated.solved_steps.iter().find(|s| s.solved.step_id == "bake").unwrap();
+        assert_eq!(bake.track_id.as_deref(), Some("nowhere"));
+        assert_eq!(bake.track_name, None);
+
+        // Solved step fields sit alongside the annotations.
+        let json = serde_json::to_value(mix).unwrap();
+        assert_eq!(json["stepId"], "mix");
+        assert_eq!(json["title"], "Mix");
+        assert_eq!(json["endOffsetMins"], 20);
+    }
+
+    #[test]
+    fn test_annotate_covers_recurring_copies() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Proofing".to_string(),
+            steps: vec![Step {
+                id: "knead".to_string(),
+                title: "Knead".to_string(),
+                duration_mins: 10,
+                ..Default::default()
+            }],
+            recurring_patterns: vec![RecurringStepPattern {
+                template_step_id: "knead".to_string(),
+                interval_mins: 30,
+                count: 2,
+                prefix: "knead".to_string(),
+            }],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let annotated = annotate(&solved, &template, &SolveOptions::default());
+        let knead = annotated.solved_steps.iter().find(|s| s.solved.step_id == "knead-2").unwrap();
+        assert_eq!(knead.title, "Knead #2");
+        assert_eq!(knead.solved.start_offset_mins, 30);
+    }
+
+    #[test]
+    fn test_annotate_covers_active_conditional_steps() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Bread".to_string(),
+            steps: vec![Step {
+                id: "glaze".to_string(),
+                title: "Glaze".to_string(),
+                duration_mins: 5,
+                condition: Some("shiny".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let options = SolveOptions {
+            active_conditions: vec!["shiny".to_string()],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve_with_options(&template, None, &options).unwrap();
+
+        let annotated = annotate(&solved, &template, &options);
+        assert_eq!(annotated.solved_steps[0].title, "Glaze");
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::model::SolvedSchedule;
+
+// ---------------------------------------------------------------------------
+// Baseline snapshot types
+// ---------------------------------------------------------------------------
+
+/// Frozen timing of one step at the moment the baseline was taken.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineStep {
+    #[serde(alias = "step_id")]
+    pub step_id: String,
+    #[serde(alias = "start_offset_mins")]
+    pub start_offset_mins: u32,
+    #[serde(alias = "end_offset_mins")]
+    pub end_offset_mins: u32,
+    #[serde(alias = "total_float_mins")]
+    pub total_float_mins: u32,
+}
+
+/// A frozen copy of a solved schedule's step offsets, used as the reference
+/// plan when tracking variance. Can be embedded in a `ScheduleTemplate` for
+/// persistence.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleBaseline {
+    #[serde(alias = "template_id")]
+    pub template_id: String,
+    #[serde(alias = "total_duration_mins")]
+    pub total_duration_mins: u32,
+    pub steps: Vec<BaselineStep>,
+}
+
+/// How far one step has moved relative to the baseline. Positive values mean
+/// later (or more float) than planned.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepVariance {
+    pub step_id: String,
+    pub start_variance_mins: i32,
+    pub finish_variance_mins: i32,
+    pub float_delta_mins: i32,
+}
+
+/// Step-by-step comparison of a current schedule against a baseline.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineVarianceReport {
+    /// Current total duration minus baseline total duration.
+    pub duration_variance_mins: i32,
+    /// Variances for steps present in both schedules, in current-schedule order.
+    pub steps: Vec<StepVariance>,
+    /// Steps in the current schedule that the baseline doesn't know about.
+    pub added_step_ids: Vec<String>,
+    /// Baseline steps missing from the current schedule.
+    pub removed_step_ids: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Snapshot and variance
+// ---------------------------------------------------------------------------
+
+/// Freeze the step offsets of a solved schedule.
+pub fn snapshot_baseline(solved: &SolvedSchedule) -> ScheduleBaseline {
+    ScheduleBaseline {
+        template_id: solved.template_id.clone(),
+        total_duration_mins: solved.summary.total_duration_mins,
+        steps: solved
+            .solved_steps
+            .iter()
+            .map(|s| BaselineStep {
+                step_id: s.step_id.clone(),
+                start_offset_mins: s.start_offset_mins,
+                end_offset_mins: s.end_offset_mins,
+                total_float_mins: s.total_float_mins,
+            })
+            .collect(),
+    }
+}
+
+/// Compare a current solved schedule against a baseline.
+pub fn variance_report(
+    baseline: &ScheduleBaseline,
+    current: &SolvedSchedule,
+) -> BaselineVarianceReport {
+    let planned: HashMap<&str, &BaselineStep> = baseline
+        .steps
+        .iter()
+        .map(|s| (s.step_id.as_str(), s))
+        .collect();
+
+    let mut steps = Vec::new();
+    let mut added_step_ids = Vec::new();
+    for step in &current.solved_steps {
+        match planned.get(step.step_id.as_str()) {
+            Some(plan) => steps.push(StepVariance {
+                step_id: step.step_id.clone(),
+                start_variance_mins: diff(step.start_offset_mins, plan.start_offset_mins),
+                finish_variance_mins: diff(step.end_offset_mins, plan.end_offset_mins),
+                float_delta_mins: diff(step.total_float_mins, plan.total_float_mins),
+            }),
+            None => added_step_ids.push(step.step_id.clone()),
+        }
+    }
+
+    let removed_step_ids = baseline
+        .steps
+        .iter()
+        .filter(|b| !current.solved_steps.iter().any(|s| s.step_id == b.step_id))
+        .map(|b| b.step_id.clone())
+        .collect();
+
+    BaselineVarianceReport {
+        duration_variance_mins: diff(
+            current.summary.total_duration_mins,
+            baseline.total_duration_mins,
+        ),
+        steps,
+        added_step_ids,
+        removed_step_ids,
+    }
+}
+
+fn diff(current: u32, planned: u32) -> i32 {
+    current as i32 - planned as i32
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ScheduleTemplate, Step};
+
+    fn make_step(id: &str, dur: u32) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: dur,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_variance_against_baseline() {
+        let mut template = ScheduleTemplate {
+            steps: vec![make_step("a", 30), make_step("b", 10)],
+            ..Default::default()
+        };
+        let baseline = snapshot_baseline(&crate::solver::solve(&template, None).unwrap());
+
+        template.steps[0].duration_mins = 45;
+        template.steps[1].id = "c".to_string();
+        let current = crate::solver::solve(&template, None).unwrap();
+
+        let report = variance_report(&baseline, &current);
+        assert_eq!(report.duration_variance_mins, 15);
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(report.steps[0].finish_variance_mins, 15);
+        assert_eq!(report.added_step_ids, vec!["c".to_string()]);
+        assert_eq!(report.removed_step_ids, vec!["b".to_string()]);
+    }
+}
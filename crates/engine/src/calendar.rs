@@ -0,0 +1,288 @@
+use crate::model::WorkingCalendar;
+
+// ---------------------------------------------------------------------------
+// Working-time arithmetic
+// ---------------------------------------------------------------------------
+//
+// All times are minutes from schedule start. A calendar's working windows
+// repeat every `cycle_mins`, so window `[s, e)` also covers
+// `[s + k * cycle, e + k * cycle)` for every k >= 0.
+
+/// True when the calendar can be used for scheduling: a positive cycle and at
+/// least one window that is still non-empty once clamped to it. Other
+/// calendars are ignored by the solver (and reported by the validator).
+pub fn is_usable(calendar: &WorkingCalendar) -> bool {
+    calendar.cycle_mins > 0 && !windows(calendar).is_empty()
+}
+
+/// Windows clamped to the cycle, sorted by start, empty ones dropped.
+fn windows(calendar: &WorkingCalendar) -> Vec<(u32, u32)> {
+    let mut windows: Vec<(u32, u32)> = calendar
+        .working_windows
+        .iter()
+        .map(|w| (w.start_offset_mins, w.end_offset_mins.min(calendar.cycle_mins)))
+        .filter(|(s, e)| s < e)
+        .collect();
+    windows.sort();
+    windows
+}
+
+/// The `[start, end)` working periods that `work_mins` of work occupies when
+/// started at `start`, skipping non-working time. Adjacent periods are merged,
+/// so a result with more than one segment means the work was interrupted.
+///
+/// The calendar must be usable (see `is_usable`).
+pub fn working_segments(calendar: &WorkingCalendar, start: u32, work_mins: u32) -> Vec<(u32, u32)> {
+    let windows = windows(calendar);
+    let cycle = calendar.cycle_mins;
+    let mut segments: Vec<(u32, u32)> = Vec::new();
+    let mut t = start;
+    let mut remaining = work_mins;
+
+    while remaining > 0 {
+        let base = t / cycle * cycle;
+        let next = windows.iter().find(|(_, e)| base + e > t);
+        let (seg_start, window_end) = match next {
+            Some(&(s, e)) => ((base + s).max(t), base + e),
+            None => {
+                // Nothing left in this cycle: jump to the first window of the next.
+                let (s, e) = windows[0];
+                (base + cycle + s, base + cycle + e)
+            }
+        };
+        let seg_end = (seg_start + remaining).min(window_end);
+        remaining -= seg_end - seg_start;
+        t = seg_end;
+
+        match segments.last_mut() {
+            Some(last) if last.1 == seg_start => last.1 = seg_end,
+            _ => segments.push((seg_start, seg_end)),
+        }
+    }
+    segments
+}
+
+/// First working minute at or after `t`.
+pub fn next_working_minute(calendar: &WorkingCalendar, t: u32) -> u32 {
+    working_segments(calendar, t, 1).first().map_or(t, |s| s.0)
+}
+
+/// When work of `work_mins` started at `start` finishes.
+pub fn finish_from(calendar: &WorkingCalendar, start: u32, work_mins: u32) -> u32 {
+    working_segments(calendar, start, work_mins).last().map_or(start, |s| s.1)
+}
+
+/// The latest start from which `work_mins` of work finishes by `finish`.
+/// Returns `None` if there isn't enough working time before `finish`.
+pub fn start_before(calendar: &WorkingCalendar, finish: u32, work_mins: u32) -> Option<u32> {
+    let windows = windows(calendar);
+    let cycle = calendar.cycle_mins;
+    let mut t = finish;
+    let mut remaining = work_mins;
+
+    while remaining > 0 {
+        let base = t.saturating_sub(1) / cycle * cycle;
+        let prev = windows.iter().rev().find(|(s, _)| base + s < t);
+        let (window_start, seg_end) = match prev {
+            Some(&(s, e)) => (base + s, (base + e).min(t)),
+            None if base >= cycle => {
+                let (s, e) = windows[windows.len() - 1];
+                (base - cycle + s, base - cycle + e)
+            }
+            None => return None,
+        };
+        let seg_start = seg_end.saturating_sub(remaining).max(window_start);
+        remaining -= seg_end - seg_start;
+        t = seg_start;
+    }
+    Some(t)
+}
+
+/// Earliest start at or after `t` from which `work_mins` of work runs without
+/// a break. `None` if no stretch of working time is that long (see
+/// `longest_working_stretch`).
+pub fn next_fit_start(calendar: &WorkingCalendar, t: u32, work_mins: u32) -> Option<u32> {
+    if work_mins == 0 {
+        return Some(t);
+    }
+    if longest_working_stretch(calendar).is_some_and(|longest| longest < work_mins) {
+        return None;
+    }
+    let mut start = t;
+    loop {
+        let segments = working_segments(calendar, start, work_mins);
+        match segments.get(1) {
+            // Interrupted: no start before the next working period fits either.
+            Some(next) => start = next.0,
+            None => return Some(segments[0].0),
+        }
+    }
+}
+
+/// The latest start from which `work_mins` of work runs without a break and
+/// finishes by `finish`. `None` if there is no such start.
+pub fn latest_fit_start(calendar: &WorkingCalendar, finish: u32, work_mins: u32) -> Option<u32> {
+    if work_mins == 0 {
+        return Some(finish);
+    }
+    if longest_working_stretch(calendar).is_some_and(|longest| longest < work_mins) {
+        return None;
+    }
+    let mut by = finish;
+    loop {
+        let start = start_before(calendar, by, work_mins)?;
+        let segments = working_segments(calendar, start, work_mins);
+        if segments.len() == 1 {
+            return Some(start);
+        }
+        // Interrupted: the work has to be done before the last break.
+        by = segments[segments.len() - 2].1;
+    }
+}
+
+/// The longest stretch of uninterrupted working time, counting windows that
+/// touch or overlap as one and a window running to the end of the cycle as
+/// continuing into one starting at 0. `None` if working time never stops,
+/// or if there is none at all (an unusable calendar, see `is_usable`).
+pub fn longest_working_stretch(calendar: &WorkingCalendar) -> Option<u32> {
+    let mut stretches: Vec<(u32, u32)> = Vec::new();
+    for (s, e) in windows(calendar) {
+        match stretches.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ => stretches.push((s, e)),
+        }
+    }
+    let (&first, &last) = (stretches.first()?, stretches.last()?);
+    if first == (0, calendar.cycle_mins) {
+        return None;
+    }
+    let wrapped = if first.0 == 0 && last.1 == calendar.cycle_mins {
+        (last.1 - last.0) + (first.1 - first.0)
+    } else {
+        0
+    };
+    stretches.iter().map(|(s, e)| e - s).chain([wrapped]).max()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WorkingWindow;
+
+    /// 08:00-17:00 every day, with the schedule starting at midnight.
+    fn office_hours() -> WorkingCalendar {
+        WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow {
+                start_offset_mins: 480,
+                end_offset_mins: 1020,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_segments_skip_non_working_time() {
+        let cal = office_hours();
+        // 10 hours of work starting at 08:00 spills into the next morning.
+        assert_eq!(
+            working_segments(&cal, 480, 600),
+            vec![(480, 1020), (1440 + 480, 1440 + 540)]
+        );
+        // Starting at night waits for the next window.
+        assert_eq!(next_working_minute(&cal, 1100), 1440 + 480);
+        assert_eq!(finish_from(&cal, 0, 60), 540);
+    }
+
+    #[test]
+    fn test_start_before_walks_backwards() {
+        let cal = office_hours();
+        assert_eq!(start_before(&cal, 1440 + 540, 600), Some(480));
+        assert_eq!(start_before(&cal, 1020, 60), Some(960));
+        // Not enough working time before 09:00 on day one.
+        assert_eq!(start_before(&cal, 540, 120), None);
+    }
+
+    #[test]
+    fn test_fit_starts_skip_stretches_too_short() {
+        // 08:00-10:00 and 11:00-17:00: three hours only fit after 11:00.
+        let cal = WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![
+                WorkingWindow {
+                    start_offset_mins: 480,
+                    end_offset_mins: 600,
+                },
+                WorkingWindow {
+                    start_offset_mins: 660,
+                    end_offset_mins: 1020,
+                },
+            ],
+        };
+        assert_eq!(next_fit_start(&cal, 0, 180), Some(660));
+        assert_eq!(next_fit_start(&cal, 500, 60), Some(500));
+        assert_eq!(next_fit_start(&cal, 900, 180), Some(1440 + 660));
+        assert_eq!(next_fit_start(&cal, 0, 400), None);
+
+        assert_eq!(latest_fit_start(&cal, 1440 + 700, 180), Some(840));
+        assert_eq!(latest_fit_start(&cal, 620, 60), Some(540));
+        assert_eq!(latest_fit_start(&cal, 600, 180), None);
+    }
+
+    #[test]
+    fn test_longest_working_stretch_joins_touching_windows() {
+        let window = |start_offset_mins, end_offset_mins| WorkingWindow {
+            start_offset_mins,
+            end_offset_mins,
+        };
+        assert_eq!(longest_working_stretch(&office_hours()), Some(540));
+
+        // 08:00-12:00 and 12:00-14:00 make one six-hour stretch; the night
+        // shift 22:00-06:00 wraps round midnight.
+        let cal = WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![
+                window(480, 720),
+                window(720, 840),
+                window(1320, 1440),
+                window(0, 360),
+            ],
+        };
+        assert_eq!(longest_working_stretch(&cal), Some(480));
+
+        let always = WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![window(0, 1440)],
+        };
+        assert_eq!(longest_working_stretch(&always), None);
+    }
+
+    #[test]
+    fn test_window_beyond_cycle_is_unusable() {
+        let cal = WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow {
+                start_offset_mins: 1500,
+                end_offset_mins: 1600,
+            }],
+        };
+        assert!(!is_usable(&cal));
+        assert_eq!(longest_working_stretch(&cal), None);
+
+        let template = crate::model::ScheduleTemplate {
+            steps: vec![crate::model::Step {
+                id: "a".to_string(),
+                title: "A".to_string(),
+                duration_mins: 30,
+                ..Default::default()
+            }],
+            working_calendar: Some(cal),
+            ..Default::default()
+        };
+        let result = crate::validator::validate(&template);
+        assert!(result.error_summary().contains("fit within the 1440-minute cycle"));
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{SolvedSchedule, SolvedStep};
+use crate::solver::parse_datetime;
+
+// ---------------------------------------------------------------------------
+// Progress update types
+// ---------------------------------------------------------------------------
+
+/// Actual times reported for one step while the schedule is being carried
+/// out. Either time may be left out, e.g. for a step that has started but
+/// not finished.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressUpdate {
+    pub step_id: String,
+    /// ISO 8601 datetime the step actually started.
+    pub actual_start_time: Option<String>,
+    /// ISO 8601 datetime the step actually finished.
+    pub actual_end_time: Option<String>,
+}
+
+/// Actual times further than this from the planned ones are reported by
+/// `record_actuals`.
+pub const DEVIATION_THRESHOLD_MINS: i64 = 15;
+
+// ---------------------------------------------------------------------------
+// Recording actuals
+// ---------------------------------------------------------------------------
+
+/// Write reported actual times onto the matching solved steps.
+///
+/// Returns warnings for updates naming unknown steps, for times that don't
+/// parse (those are not recorded), and for actual times more than
+/// `DEVIATION_THRESHOLD_MINS` away from the planned wall-clock times. Steps
+/// of a schedule without wall-clock times can't be compared, so their
+/// actuals are recorded without checks.
+pub fn record_actuals(solved: &mut SolvedSchedule, updates: &[ProgressUpdate]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for update in updates {
+        let Some(step) = solved.solved_steps.iter_mut().find(|s| s.step_id == update.step_id)
+        else {
+            warnings.push(format!("Step '{}' isn't in the schedule", update.step_id));
+            continue;
+        };
+
+        let fields = [
+            ("start", &update.actual_start_time, &step.start_time, &mut step.actual_start_time),
+            ("end", &update.actual_end_time, &step.end_time, &mut step.actual_end_time),
+        ];
+        for (which, actual, planned, recorded) in fields {
+            let Some(actual) = actual else {
+                continue;
+            };
+            let Some(actual_dt) = parse_datetime(actual) else {
+                warnings.push(format!(
+                    "Actual {} time '{}' of step '{}' isn't an ISO 8601 datetime",
+                    which, actual, update.step_id
+                ));
+                continue;
+            };
+            *recorded = Some(actual.clone());
+
+            let Some(planned_dt) = planned.as_deref().and_then(parse_datetime) else {
+                continue;
+            };
+            let deviation = (actual_dt - planned_dt).num_minutes();
+            if deviation.abs() > DEVIATION_THRESHOLD_MINS {
+                warnings.push(format!(
+                    "Step '{}' {} {} min {} plan",
+                    update.step_id,
+                    if which == "start" { "started" } else { "finished" },
+                    deviation.abs(),
+                    if deviation > 0 { "behind" } else { "ahead of" }
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+// ---------------------------------------------------------------------------
+// Progress interpolation
+// ---------------------------------------------------------------------------
+
+/// Where a step stands at a given point in the schedule.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StepStatus {
+    NotStarted,
+    InProgress,
+    Completed,
+    /// Past its planned end without an actual end time recorded.
+    Overdue,
+}
+
+/// A step's progress estimated from elapsed time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StepProgress {
+    pub step_id: String,
+    /// Share of the planned duration elapsed; above 100 for overdue steps,
+    /// e.g. 150 for a step half its duration past its planned end.
+    pub percent_complete: f64,
+    pub status: StepStatus,
+}
+
+/// Estimate how far along `step` is at `current_time_mins` (minutes from
+/// schedule start), assuming work progresses evenly over its planned span.
+///
+/// A step with an `actual_end_time` is `Completed` at 100%. Otherwise it is
+/// `NotStarted` before its planned start, `InProgress` up to and including
+/// its planned end, and `Overdue` after that.
+pub fn interpolate_step_progress(step: &SolvedStep, current_time_mins: u32) -> StepProgress {
+    let span = step.end_offset_mins.saturating_sub(step.start_offset_mins);
+    let elapsed = current_time_mins.saturating_sub(step.start_offset_mins);
+    let (percent_complete, status) = if step.actual_end_time.is_some() {
+        (100.0, StepStatus::Completed)
+    } else if current_time_mins < step.start_offset_mins {
+        (0.0, StepStatus::NotStarted)
+    } else if span == 0 {
+        (100.0, StepStatus::InProgress)
+    } else {
+        let percent = f64::from(elapsed) * 100.0 / f64::from(span);
+        if current_time_mins <= step.end_offset_mins {
+            (percent, StepStatus::InProgress)
+        } else {
+            (percent, StepStatus::Overdue)
+        }
+    };
+    StepProgress {
+        step_id: step.step_id.clone(),
+        percent_complete,
+        status,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ScheduleTemplate, Step, TimeConstraint};
+
+    fn solved() -> SolvedSchedule {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![Step {
+                id: "bake".to_string(),
+                title: "Bake".to_string(),
+                duration_mins: 60,
+                ..Default::default()
+            }],
+            time_constraint: Some(TimeConstraint {
+                start_time: Some("2026-03-01T09:00:00".to_string()),
+                end_time: None,
+                timezone: None,
+            }),
+            ..Default::default()
+        };
+        crate::solver::solve(&template, None).unwrap()
+    }
+
+    #[test]
+    fn test_record_actuals_writes_times_and_flags_deviations() {
+        let mut solved = solved();
+        let warnings = record_actuals(
+            &mut solved,
+            &[
+                ProgressUpdate {
+                    step_id: "bake".to_string(),
+                    actual_start_time: Some("2026-03-01T09:10:00".to_string()),
+                    actual_end_time: Some("2026-03-01T10:30:00".to_string()),
+                },
+                ProgressUpdate {
+                    step_id: "ice".to_string(),
+                    actual_start_time: None,
+                    actual_end_time: None,
+                },
+            ],
+        );
+
+        let step = &solved.solved_steps[0];
+        assert_eq!(step.actual_start_time.as_deref(), Some("2026-03-01T09:10:00"));
+        assert_eq!(step.actual_end_time.as_deref(), Some("2026-03-01T10:30:00"));
+        // Ten minutes late to start is within the threshold; thirty to finish isn't.
+        assert_eq!(
+            warnings,
+            vec![
+                "Step 'bake' finished 30 min behind plan".to_string(),
+                "Step 'ice' isn't in the schedule".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolate_step_progress() {
+        let mut solved = solved();
+        let progress = |solved: &SolvedSchedule, mins: u32| {
+            let p = interpolate_step_progress(&solved.solved_steps[0], mins);
+            (p.percent_complete, p.status)
+        };
+
+        assert_eq!(progress(&solved, 0), (0.0, StepStatus::InProgress));
+        assert_eq!(progress(&solved, 15), (25.0, StepStatus::InProgress));
+        assert_eq!(progress(&solved, 60), (100.0, StepStatus::InProgress));
+        assert_eq!(progress(&solved, 90), (150.0, StepStatus::Overdue));
+
+        solved.solved_steps[0].start_offset_mins = 30;
+        solved.solved_steps[0].end_offset_mins = 90;
+        assert_eq!(progress(&solved, 10), (0.0, StepStatus::NotStarted));
+        solved.solved_steps[0].actual_end_time = Some("2026-03-01T10:00:00".to_string());
+        assert_eq!(progress(&solved, 120), (100.0, StepStatus::Completed));
+    }
+
+    #[test]
+    fn test_record_actuals_skips_unparseable_times() {
+        let mut solved = solved();
+        let warnings = record_actuals(
+            &mut solved,
+            &[ProgressUpdate {
+                step_id: "bake".to_string(),
+                actual_start_time: Some("soon".to_string()),
+                actual_end_time: None,
+            }],
+        );
+        assert_eq!(solved.solved_steps[0].actual_start_time, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'soon'"));
+    }
+}
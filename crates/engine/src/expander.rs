@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::model::{DependencyType, RecurringStepPattern, ScheduleTemplate, Step, StepDependency};
+
+/// Materialize every `RecurringStepPattern` in the template.
+///
+/// Each referenced step is replaced by `count` copies with IDs `{prefix}-1`,
+/// `{prefix}-2`, ... The first copy inherits the original's dependencies and
+/// every later copy has a `FinishToStart` dependency on the copy before it.
+/// When `interval_mins` is longer than the step, a wait step
+/// (`{prefix}-wait-{n}`) covering the difference is chained in between so
+/// consecutive copies start `interval_mins` apart. Steps that depended on the
+/// original now depend on its last copy.
+///
+/// Patterns with a `count` of zero or an unknown step ID are ignored. The
+/// returned template has no recurring patterns left.
+pub fn expand_recurring(template: &ScheduleTemplate) -> ScheduleTemplate {
+    let mut expanded = template.clone();
+    expanded.recurring_patterns.clear();
+
+    // Original step ID -> ID of its last copy, for rewiring successors.
+    let mut last_copy: HashMap<String, String> = HashMap::new();
+    let mut steps: Vec<Step> = Vec::with_capacity(template.steps.len());
+
+    for step in &template.steps {
+        let Some(pattern) = pattern_for(template, step) else {
+            steps.push(step.clone());
+            continue;
+        };
+
+        let mut prev: Option<String> = None;
+        for n in 1..=pattern.count {
+            let mut copy = step.clone();
+            copy.id = format!("{}-{}", pattern.prefix, n);
+            copy.title = format!("{} #{}", step.title, n);
+
+            if let Some(prev_id) = &prev {
+                copy.dependencies = vec![finish_to_start(prev_id)];
+                if pattern.interval_mins > step.duration_mins {
+                    let wait_id = format!("{}-wait-{}", pattern.prefix, n - 1);
+                    steps.push(Step {
+                        id: wait_id.clone(),
+                        title: format!("{} (wait)", step.title),
+                        duration_mins: pattern.interval_mins - step.duration_mins,
+                        dependencies: vec![finish_to_start(prev_id)],
+                        track_id: step.track_id.clone(),
+                        ..Default::default()
+                    });
+                    copy.dependencies.push(finish_to_start(&wait_id));
+                }
+            }
+
+            prev = Some(copy.id.clone());
+            steps.push(copy);
+        }
+
+        if let Some(last) = prev {
+            last_copy.insert(step.id.clone(), last);
+        }
+    }
+
+    for step in &mut steps {
+        for dep in &mut step.dependencies {
+            if let Some(last) = last_copy.get(&dep.step_id) {
+                dep.step_id = last.clone();
+            }
+        }
+    }
+
+    expanded.steps = steps;
+    expanded
+}
+
+/// How many steps `expand_recurring` would return, without expanding.
+pub fn expanded_step_count(template: &ScheduleTemplate) -> usize {
+    template
+        .steps
+        .iter()
+        .map(|step| match pattern_for(template, step) {
+            Some(pattern) => {
+                let copies = pattern.count as usize;
+                let waits = if pattern.interval_mins > step.duration_mins { copies - 1 } else { 0 };
+                copies + waits
+            }
+            None => 1,
+        })
+        .sum()
+}
+
+/// The pattern `step` is expanded by, if any.
+fn pattern_for<'a>(
+    template: &'a ScheduleTemplate,
+    step: &Step,
+) -> Option<&'a RecurringStepPattern> {
+    template
+        .recurring_patterns
+        .iter()
+        .find(|p| p.template_step_id == step.id && p.count > 0)
+}
+
+fn finish_to_start(step_id: &str) -> StepDependency {
+    StepDependency {
+        step_id: step_id.to_string(),
+        dependency_type: DependencyType::FinishToStart,
+        trigger_at_percent: None,
+        condition: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::RecurringStepPattern;
+
+    fn make_step(id: &str, dur: u32, deps: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: dur,
+            dependencies: deps.iter().map(|d| finish_to_start(d)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_expand_chains_copies_and_rewires_successors() {
+        let template = ScheduleTemplate {
+            steps: vec![
+                make_step("standup", 15, &[]),
+                make_step("wrap-up", 30, &["standup"]),
+            ],
+            recurring_patterns: vec![RecurringStepPattern {
+                template_step_id: "standup".to_string(),
+                interval_mins: 60,
+                count: 3,
+                prefix: "standup".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let expanded = expand_recurring(&template);
+        assert!(expanded.recurring_patterns.is_empty());
+        let ids: Vec<&str> = expanded.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["standup-1", "standup-wait-1", "standup-2", "standup-wait-2", "standup-3", "wrap-up"]
+        );
+
+        let wrap_up = expanded.steps.iter().find(|s| s.id == "wrap-up").unwrap();
+        assert_eq!(wrap_up.dependencies[0].step_id, "standup-3");
+
+        // Copies start an hour apart.
+        let solved = crate::solver::solve(&expanded, None).unwrap();
+        let start = |id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+        assert_eq!(start("standup-2"), 60);
+        assert_eq!(start("standup-3"), 120);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::model::{ScheduleTemplate, SolvedSchedule};
+
+// ---------------------------------------------------------------------------
+// Dependency graph
+// ---------------------------------------------------------------------------
+
+/// Render the template's dependency network as a Graphviz DOT digraph: one
+/// node per step, labelled with its title and duration, and one edge per
+/// dependency, labelled with its type.
+///
+/// With a solved schedule, steps on its critical path are coloured red, and
+/// so are the edges between two of them (dashed).
+pub fn to_dot(template: &ScheduleTemplate, solved: Option<&SolvedSchedule>) -> String {
+    let critical: HashSet<&str> = solved
+        .map(|s| s.summary.critical_path_step_ids.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+    let ids: HashSet<&str> = template.steps.iter().map(|s| s.id.as_str()).collect();
+
+    let mut dot = format!("digraph \"{}\" {{\n", escape(&template.name));
+    dot.push_str("  rankdir=LR;\n  node [shape=box];\n");
+    for step in &template.steps {
+        let _ = write!(
+            dot,
+            "  \"{}\" [label=\"{}\\n{} min\"",
+            escape(&step.id),
+            escape(&step.title),
+            step.duration_mins
+        );
+        if critical.contains(step.id.as_str()) {
+            dot.push_str(", color=red, fontcolor=red");
+        }
+        dot.push_str("];\n");
+    }
+    for step in &template.steps {
+        for dep in &step.dependencies {
+            // Dangling references are the validator's business.
+            if !ids.contains(dep.step_id.as_str()) {
+                continue;
+            }
+            let _ = write!(
+                dot,
+                "  \"{}\" -> \"{}\" [label=\"{:?}\"",
+                escape(&dep.step_id),
+                escape(&step.id),
+                dep.dependency_type
+            );
+            if critical.contains(dep.step_id.as_str()) && critical.contains(step.id.as_str()) {
+                dot.push_str(", color=red, style=dashed");
+            }
+            dot.push_str("];\n");
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Make text safe inside a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace(['\r', '\n'], " ")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, Step, StepDependency};
+
+    #[test]
+    fn test_dot_lists_steps_and_dependencies() {
+        let dependency = |step_id: &str, dependency_type| StepDependency {
+            step_id: step_id.to_string(),
+            dependency_type,
+            trigger_at_percent: None,
+            condition: None,
+        };
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Bread".to_string(),
+            steps: vec![
+                Step {
+                    id: "mix".to_string(),
+                    title: "Mix \"dough\"".to_string(),
+                    duration_mins: 20,
+                    ..Default::default()
+                },
+                Step {
+                    id: "proof".to_string(),
+                    title: "Proof".to_string(),
+                    duration_mins: 5,
+                    ..Default::default()
+                },
+                Step {
+                    id: "bake".to_string(),
+                    title: "Bake".to_string(),
+                    duration_mins: 45,
+                    dependencies: vec![
+                        dependency("mix", DependencyType::FinishToStart),
+                        dependency("proof", DependencyType::StartToStart),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let plain = to_dot(&template, None);
+        assert!(plain.starts_with("digraph \"Bread\" {\n"));
+        assert!(plain.ends_with("}\n"));
+        assert!(plain.contains("  \"mix\" [label=\"Mix \\\"dough\\\"\\n20 min\"];\n"));
+        assert!(plain.contains("  \"mix\" -> \"bake\" [label=\"FinishToStart\"];\n"));
+        assert!(plain.contains("  \"proof\" -> \"bake\" [label=\"StartToStart\"];\n"));
+        assert!(!plain.contains("red"));
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let coloured = to_dot(&template, Some(&solved));
+        assert!(coloured.contains("20 min\", color=red, fontcolor=red];\n"));
+        assert!(coloured.contains("[label=\"FinishToStart\", color=red, style=dashed];\n"));
+        assert!(coloured.contains("[label=\"StartToStart\"];\n"));
+    }
+}
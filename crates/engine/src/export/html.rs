@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::model::{ResourceKind, ScheduleTemplate, SolvedSchedule};
+
+/// Inline stylesheet so the report is a single self-contained file.
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 1px solid #ccc; padding-bottom: 0.2em; margin-top: 1.5em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 4px 8px; border-bottom: 1px solid #eee; text-align: left; }
+th { background: #f5f5f5; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+td.bar { width: 45%; }
+.track { position: relative; height: 14px; background: #f0f0f0; }
+.fill { position: absolute; top: 0; height: 14px; background: #4a7bd0; }
+.fill.critical { background: #d04a4a; }
+ul.warnings li { color: #8a5a00; }
+";
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+/// Render a self-contained HTML report of a solved schedule: summary,
+/// Gantt-style table, critical path, resource utilization chart and warnings.
+///
+/// All styling is inlined and the page needs no JavaScript.
+pub fn generate_schedule_report_html(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> String {
+    let titles: HashMap<&str, &str> = template
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.title.as_str()))
+        .collect();
+    let title_of = |id: &str| titles.get(id).copied().unwrap_or(id).to_string();
+    let total = solved.summary.total_duration_mins;
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(&template.name));
+    let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&template.name));
+    if let Some(description) = &template.description {
+        let _ = writeln!(html, "<p>{}</p>", escape(description));
+    }
+
+    // Summary
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    let _ = writeln!(html, "<tr><th>Total duration</th><td>{}</td></tr>", format_mins(total));
+    let _ = writeln!(html, "<tr><th>Steps</th><td>{}</td></tr>", solved.solved_steps.len());
+    let _ = writeln!(
+        html,
+        "<tr><th>Critical steps</th><td>{}</td></tr>",
+        solved.summary.critical_path_step_ids.len()
+    );
+    if solved.summary.project_total_cost > 0.0 {
+        let _ = writeln!(
+            html,
+            "<tr><th>Total cost</th><td>{:.2}</td></tr>",
+            solved.summary.project_total_cost
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Gantt-style table
+    html.push_str("<h2>Schedule</h2>\n<table>\n");
+    html.push_str(
+        "<tr><th>Step</th><th>Start</th><th>End</th><th>Float</th><th>Timeline</th></tr>\n",
+    );
+    for step in &solved.solved_steps {
+        let (left, width) = if total == 0 {
+            (0.0, 0.0)
+        } else {
+            let left = step.start_offset_mins as f64 / total as f64 * 100.0;
+            let span = step.end_offset_mins - step.start_offset_mins;
+            let width = span as f64 / total as f64 * 100.0;
+            (left, width)
+        };
+        let class = if step.is_critical { "fill critical" } else { "fill" };
+        let start = step.start_time.clone().unwrap_or_else(|| format_mins(step.start_offset_mins));
+        let end = step.end_time.clone().unwrap_or_else(|| format_mins(step.end_offset_mins));
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td>\
+             <td class=\"bar\"><div class=\"track\"><div class=\"{}\" \
+             style=\"left: {:.2}%; width: {:.2}%\"></div></div></td></tr>",
+            escape(&title_of(&step.step_id)),
+            escape(&start),
+            escape(&end),
+            format_mins(step.total_float_mins),
+            class,
+            left,
+            width
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Critical path
+    html.push_str("<h2>Critical path</h2>\n");
+    if solved.summary.critical_path_step_ids.is_empty() {
+        html.push_str("<p>No critical steps.</p>\n");
+    } else {
+        html.push_str("<ol>\n");
+        for id in &solved.summary.critical_path_step_ids {
+            let _ = writeln!(html, "<li>{}</li>", escape(&title_of(id)));
+        }
+        html.push_str("</ol>\n");
+    }
+
+    // Resource utilization
+    html.push_str("<h2>Resource utilization</h2>\n");
+    let utilization = resource_utilization(solved, template);
+    if utilization.is_empty() {
+        html.push_str("<p>No capacity-limited resources.</p>\n");
+    } else {
+        html.push_str(&utilization_chart(&utilization));
+    }
+
+    // Warnings
+    html.push_str("<h2>Warnings</h2>\n");
+    if solved.warnings.is_empty() {
+        html.push_str("<p>None.</p>\n");
+    } else {
+        html.push_str("<ul class=\"warnings\">\n");
+        for warning in &solved.warnings {
+            let _ = writeln!(html, "<li>{}</li>", escape(warning));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Share of each Equipment/People resource's capacity in use over the whole
+/// schedule, as `(name, fraction)` pairs in template order.
+fn resource_utilization<'a>(
+    solved: &SolvedSchedule,
+    template: &'a ScheduleTemplate,
+) -> Vec<(&'a str, f64)> {
+    let total = solved.summary.total_duration_mins as u64;
+    template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .filter(|r| r.capacity > 0)
+        .map(|r| {
+            let used: u64 = solved
+                .solved_steps
+                .iter()
+                .flat_map(|s| {
+                    let dur = (s.end_offset_mins - s.start_offset_mins) as u64;
+                    s.assigned_resources
+                        .iter()
+                        .filter(|a| a.resource_id == r.id)
+                        .map(move |a| a.quantity_used as u64 * dur)
+                })
+                .sum();
+            let available = r.capacity as u64 * total;
+            let share = if available == 0 { 0.0 } else { used as f64 / available as f64 };
+            (r.name.as_str(), share.min(1.0))
+        })
+        .collect()
+}
+
+/// Horizontal bar chart as inline SVG, one bar per resource.
+fn utilization_chart(utilization: &[(&str, f64)]) -> String {
+    const LABEL_WIDTH: usize = 160;
+    const BAR_WIDTH: usize = 400;
+    const ROW_HEIGHT: usize = 24;
+
+    let height = utilization.len() * ROW_HEIGHT;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-family=\"sans-serif\" font-size=\"12\">",
+        LABEL_WIDTH + BAR_WIDTH + 60,
+        height
+    );
+    for (i, (name, share)) in utilization.iter().enumerate() {
+        let y = i * ROW_HEIGHT;
+        let width = (share * BAR_WIDTH as f64).round() as usize;
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\">{}</text>\
+             <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"16\" fill=\"#f0f0f0\"/>\
+             <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"16\" fill=\"#4a7bd0\"/>\
+             <text x=\"{}\" y=\"{}\">{:.0}%</text>",
+            y + 13,
+            escape(name),
+            LABEL_WIDTH,
+            y + 2,
+            BAR_WIDTH,
+            LABEL_WIDTH,
+            y + 2,
+            width,
+            LABEL_WIDTH + BAR_WIDTH + 6,
+            y + 13,
+            share * 100.0
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Minutes as `1h 05m` (or `45m` under an hour).
+fn format_mins(mins: u32) -> String {
+    if mins < 60 {
+        format!("{}m", mins)
+    } else {
+        format!("{}h {:02}m", mins / 60, mins % 60)
+    }
+}
+
+/// Escape text for use in HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Step;
+
+    #[test]
+    fn test_report_contains_sections_and_escapes_titles() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Dinner <party>".to_string(),
+            steps: vec![Step {
+                id: "a".to_string(),
+                title: "Fish & chips".to_string(),
+                duration_mins: 90,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let html = generate_schedule_report_html(&solved, &template);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Dinner &lt;party&gt;</title>"));
+        assert!(html.contains("Fish &amp; chips"));
+        assert!(html.contains("1h 30m"));
+        let sections = ["Summary", "Schedule", "Critical path", "Resource utilization", "Warnings"];
+        for section in sections {
+            assert!(html.contains(&format!("<h2>{}</h2>", section)), "missing {}", section);
+        }
+        assert!(!html.contains("<script"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::model::{ScheduleTemplate, SolvedSchedule};
+
+// ---------------------------------------------------------------------------
+// Dependency diagram
+// ---------------------------------------------------------------------------
+
+/// Render the template's dependency network as a PlantUML diagram: one box
+/// per step, labelled with its title and duration, and one arrow per
+/// dependency, labelled with its type.
+///
+/// With a solved schedule, steps on its critical path are coloured red.
+/// Boxes use generated aliases (`s0`, `s1`, ...) so step IDs never need
+/// escaping.
+pub fn to_plantuml(template: &ScheduleTemplate, solved: Option<&SolvedSchedule>) -> String {
+    let critical: HashSet<&str> = solved
+        .map(|s| s.summary.critical_path_step_ids.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+    let alias: HashMap<&str, String> = template
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), format!("s{}", i)))
+        .collect();
+
+    let mut uml = String::from("@startuml\n");
+    let _ = writeln!(uml, "title {}", escape(&template.name));
+    for step in &template.steps {
+        let _ = write!(
+            uml,
+            "rectangle \"{}\\n{} min\" as {}",
+            escape(&step.title),
+            step.duration_mins,
+            alias[step.id.as_str()]
+        );
+        if critical.contains(step.id.as_str()) {
+            uml.push_str(" #red");
+        }
+        uml.push('\n');
+    }
+    for step in &template.steps {
+        for dep in &step.dependencies {
+            // Dangling references are the validator's business.
+            let Some(from) = alias.get(dep.step_id.as_str()) else {
+                continue;
+            };
+            let _ = writeln!(
+                uml,
+                "{} --> {} : {:?}",
+                from,
+                alias[step.id.as_str()],
+                dep.dependency_type
+            );
+        }
+    }
+    uml.push_str("@enduml\n");
+    uml
+}
+
+/// Make text safe inside a quoted PlantUML label: double quotes would end
+/// the label and line breaks would end the statement.
+fn escape(text: &str) -> String {
+    text.replace('"', "'").replace(['\r', '\n'], " ")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, Step, StepDependency};
+
+    #[test]
+    fn test_plantuml_lists_steps_and_dependencies() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "Bread".to_string(),
+            steps: vec![
+                Step {
+                    id: "mix".to_string(),
+                    title: "Mix \"dough\"".to_string(),
+                    duration_mins: 20,
+                    ..Default::default()
+                },
+                Step {
+                    id: "proof".to_string(),
+                    title: "Proof".to_string(),
+                    duration_mins: 5,
+                    ..Default::default()
+                },
+                Step {
+                    id: "bake".to_string(),
+                    title: "Bake".to_string(),
+                    duration_mins: 45,
+                    dependencies: vec![
+                        StepDependency {
+                            step_id: "mix".to_string(),
+                            dependency_type: DependencyType::FinishToStart,
+                            trigger_at_percent: None,
+                            condition: None,
+                        },
+                        StepDependency {
+                            step_id: "proof".to_string(),
+                            dependency_type: DependencyType::StartToStart,
+                            trigger_at_percent: None,
+                            condition: None,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let plain = to_plantuml(&template, None);
+        assert!(plain.starts_with("@startuml\ntitle Bread\n"));
+        assert!(plain.ends_with("@enduml\n"));
+        assert!(plain.contains("rectangle \"Mix 'dough'\\n20 min\" as s0\n"));
+        assert!(plain.contains("s0 --> s2 : FinishToStart\n"));
+        assert!(plain.contains("s1 --> s2 : StartToStart\n"));
+        assert!(!plain.contains("#red"));
+
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let coloured = to_plantuml(&template, Some(&solved));
+        assert!(coloured.contains("as s0 #red\n"));
+        assert!(coloured.contains("as s1\n"));
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use petgraph::algo::toposort;
+use petgraph::graph::DiGraph;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+use crate::model::{DependencyType, ScheduleTemplate};
+use crate::solver::{trigger_point, SolveError};
+
+// ---------------------------------------------------------------------------
+// Path float
+// ---------------------------------------------------------------------------
+
+/// Free network float ("path float") of every step: how much the longest
+/// root-to-sink chain of steps passing through it could grow before it
+/// became the longest chain in the network.
+///
+/// Chains are measured in step durations, linked the way CPM links them
+/// (dependency type and partial-completion trigger), ignoring external
+/// dependencies, calendars and resources. It is a property of the network's
+/// logic alone, so unlike `SolvedStep::total_float_mins` it doesn't move
+/// when resources are allocated — useful for deciding which chains of work
+/// to keep an eye on.
+///
+/// Pass the template as solved (see `solver::prepare_template`) so recurring
+/// copies are covered and inactive conditional steps don't count.
+///
+/// Fails on dependencies on unknown steps and on dependency cycles.
+pub fn compute_free_network_float_per_step(
+    template: &ScheduleTemplate,
+) -> Result<HashMap<String, u32>, SolveError> {
+    let index: HashMap<&str, usize> =
+        template.steps.iter().enumerate().map(|(i, s)| (s.id.as_str(), i)).collect();
+    let duration = |i: usize| i64::from(template.steps[i].duration_mins);
+
+    // Each edge carries the lag from the predecessor's start to the earliest
+    // start it allows the successor.
+    let mut graph: DiGraph<usize, i64> = DiGraph::new();
+    let nodes: Vec<_> = (0..template.steps.len()).map(|i| graph.add_node(i)).collect();
+    for (i, step) in template.steps.iter().enumerate() {
+        for dep in &step.dependencies {
+            let Some(&pred) = index.get(dep.step_id.as_str()) else {
+                return Err(SolveError::UnknownDependency(step.id.clone(), dep.step_id.clone()));
+            };
+            let lag = match dep.dependency_type {
+                DependencyType::FinishToStart => trigger_point(dep, duration(pred)),
+                DependencyType::StartToStart => 0,
+                DependencyType::FinishToFinish => duration(pred) - duration(i),
+                DependencyType::StartToFinish => -duration(i),
+            };
+            graph.add_edge(nodes[pred], nodes[i], lag);
+        }
+    }
+    let order = toposort(&graph, None).map_err(|cycle| {
+        SolveError::CyclicDependency(template.steps[graph[cycle.node_id()]].id.clone())
+    })?;
+
+    // Earliest start of each step, and the longest chain from its start to
+    // the end of the network.
+    let mut head = vec![0i64; nodes.len()];
+    let mut tail = vec![0i64; nodes.len()];
+    for &node in &order {
+        head[graph[node]] = graph
+            .edges_directed(node, Direction::Incoming)
+            .map(|e| head[graph[e.source()]] + e.weight())
+            .fold(0, i64::max);
+    }
+    for &node in order.iter().rev() {
+        tail[graph[node]] = graph
+            .edges_directed(node, Direction::Outgoing)
+            .map(|e| e.weight() + tail[graph[e.target()]])
+            .fold(duration(graph[node]), i64::max);
+    }
+
+    let longest = (0..nodes.len()).map(|i| head[i] + duration(i)).max().unwrap_or(0);
+    Ok(order
+        .iter()
+        .map(|&node| {
+            let i = graph[node];
+            let float = (longest - head[i] - tail[i]).max(0);
+            (template.steps[i].id.clone(), float as u32)
+        })
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, Step, StepDependency};
+
+    fn step(id: &str, duration_mins: u32, deps: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins,
+            dependencies: deps
+                .iter()
+                .map(|d| StepDependency {
+                    step_id: d.to_string(),
+                    dependency_type: DependencyType::FinishToStart,
+                    trigger_at_percent: None,
+                    condition: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_path_float_measures_slack_of_longest_chain_through_step() {
+        // a(30) -> c(5) is the longest chain (35); b(10) -> c is 20 short of
+        // it and the lone d(10) is 25 short.
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("a", 30, &[]),
+                step("b", 10, &[]),
+                step("c", 5, &["a", "b"]),
+                step("d", 10, &[]),
+            ],
+            ..Default::default()
+        };
+
+        let floats = compute_free_network_float_per_step(&template).unwrap();
+        assert_eq!(floats["a"], 0);
+        assert_eq!(floats["b"], 20);
+        assert_eq!(floats["c"], 0);
+        assert_eq!(floats["d"], 25);
+
+        let mut cyclic = template.clone();
+        cyclic.steps[0].dependencies = step("a", 30, &["c"]).dependencies;
+        assert!(matches!(
+            compute_free_network_float_per_step(&cyclic),
+            Err(SolveError::CyclicDependency(_))
+        ));
+    }
+
+    #[test]
+    fn test_path_float_follows_dependency_types() {
+        // b(10) starts with a(30) and c(40) finishes with it, so c sets the
+        // finish at 40 and a can slip 10 before c has to finish later.
+        let mut b = step("b", 10, &["a"]);
+        b.dependencies[0].dependency_type = DependencyType::StartToStart;
+        let mut c = step("c", 40, &["a"]);
+        c.dependencies[0].dependency_type = DependencyType::FinishToFinish;
+        // Inactive, so it drops out of the template solve schedules.
+        let mut d = step("d", 100, &["c"]);
+        d.condition = Some("overtime".to_string());
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("a", 30, &[]), b, c, d],
+            ..Default::default()
+        };
+        let prepared = crate::solver::prepare_template(&template, &Default::default());
+
+        let floats = compute_free_network_float_per_step(&prepared).unwrap();
+        assert_eq!(floats.len(), 3);
+        assert_eq!(floats["a"], 10);
+        assert_eq!(floats["b"], 30);
+        assert_eq!(floats["c"], 0);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ScheduleTemplate, SolvedSchedule, SolvedStep};
+
+/// Track name used for the row(s) holding steps that have no `track_id`.
+pub const DEFAULT_TRACK_NAME: &str = "Default";
+
+// ---------------------------------------------------------------------------
+// Gantt layout types
+// ---------------------------------------------------------------------------
+
+/// A single bar on a Gantt row, covering `[start, end)` in minutes from
+/// schedule start.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GanttBar {
+    pub step_id: String,
+    pub start: u32,
+    pub end: u32,
+    pub is_critical: bool,
+    /// Suggested colour for frontends; set for critical-path bars.
+    pub color_hint: Option<String>,
+}
+
+/// One horizontal lane of a Gantt chart. A track whose steps overlap in time
+/// is spread over several consecutive rows sharing the same `track_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GanttRow {
+    /// `None` for the default row of untracked steps.
+    pub track_id: Option<String>,
+    pub track_name: String,
+    /// Non-overlapping bars ordered by start time.
+    pub bars: Vec<GanttBar>,
+}
+
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+/// Lay out the solved steps as Gantt rows.
+///
+/// Rows follow the template's track order; steps with no track (or a track ID
+/// the template doesn't define) come last. Within a track, a sweep over the
+/// bars sorted by start time places each bar on the first lane that is free,
+/// opening a new lane when none is.
+///
+/// `template` should be the template as solved (see
+/// `solver::prepare_template`), so recurring copies keep their step's track.
+pub fn compute_gantt_rows(solved: &SolvedSchedule, template: &ScheduleTemplate) -> Vec<GanttRow> {
+    let step_tracks: HashMap<&str, Option<&str>> = template
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.track_id.as_deref()))
+        .collect();
+
+    // Group steps by track ID, preserving first-seen order of unknown tracks.
+    let mut group_order: Vec<Option<&str>> =
+        template.tracks.iter().map(|t| Some(t.id.as_str())).collect();
+    let mut groups: HashMap<Option<&str>, Vec<&SolvedStep>> = HashMap::new();
+    for step in &solved.solved_steps {
+        let track = step_tracks.get(step.step_id.as_str()).copied().flatten();
+        if !group_order.contains(&track) {
+            group_order.push(track);
+        }
+        groups.entry(track).or_default().push(step);
+    }
+
+    // Untracked steps always go last.
+    group_order.retain(|t| t.is_some());
+    group_order.push(None);
+
+    let track_names: HashMap<&str, &str> = template
+        .tracks
+        .iter()
+        .map(|t| (t.id.as_str(), t.name.as_str()))
+        .collect();
+
+    let mut rows: Vec<GanttRow> = Vec::new();
+    for track in group_order {
+        let Some(steps) = groups.get_mut(&track) else {
+            continue;
+        };
+        steps.sort_by_key(|s| (s.start_offset_mins, s.end_offset_mins));
+
+        // Sweep line: each lane remembers where its last bar ends.
+        let mut lanes: Vec<(u32, Vec<GanttBar>)> = Vec::new();
+        for step in steps.iter() {
+            let bar = GanttBar {
+                step_id: step.step_id.clone(),
+                start: step.start_offset_mins,
+                end: step.end_offset_mins,
+                is_critical: step.is_critical,
+                color_hint: step.is_critical.then(|| "red".to_string()),
+            };
+            match lanes.iter_mut().find(|(end, _)| *end <= bar.start) {
+                Some(lane) => {
+                    lane.0 = bar.end;
+                    lane.1.push(bar);
+                }
+                None => lanes.push((bar.end, vec![bar])),
+            }
+        }
+
+        let track_name = match track {
+            Some(id) => track_names.get(id).copied().unwrap_or(id).to_string(),
+            None => DEFAULT_TRACK_NAME.to_string(),
+        };
+        for (_, bars) in lanes {
+            rows.push(GanttRow {
+                track_id: track.map(str::to_string),
+                track_name: track_name.clone(),
+                bars,
+            });
+        }
+    }
+
+    rows
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{RecurringStepPattern, Step, Track};
+    use crate::solver::SolveOptions;
+
+    fn make_step(id: &str, dur: u32, track_id: Option<&str>) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            duration_mins: dur,
+            dependencies: vec![],
+            track_id: track_id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_overlapping_steps_split_into_lanes() {
+        let template = ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            steps: vec![
+                make_step("a", 30, Some("kitchen")),
+                make_step("b", 10, Some("kitchen")),
+                make_step("c", 5, None),
+            ],
+            tracks: vec![Track {
+                id: "kitchen".to_string(),
+                name: "Kitchen".to_string(),
+                steps: vec![],
+                max_concurrent: None,
+            }],
+            resources: vec![],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let rows = compute_gantt_rows(&solved, &template);
+
+        // a and b both start at 0, so the kitchen track needs two lanes.
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].track_name, "Kitchen");
+        assert_eq!(rows[1].track_id.as_deref(), Some("kitchen"));
+        assert_eq!(rows[2].track_id, None);
+        assert_eq!(rows[2].track_name, DEFAULT_TRACK_NAME);
+        assert_eq!(rows[0].bars[0].step_id, "b");
+        assert_eq!(rows[0].bars[0].color_hint, None);
+        assert_eq!(rows[1].bars[0].step_id, "a");
+        assert_eq!(rows[1].bars[0].color_hint.as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn test_recurring_copies_stay_on_their_track() {
+        let template = ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            steps: vec![make_step("knead", 10, Some("bench"))],
+            tracks: vec![Track {
+                id: "bench".to_string(),
+                name: "Bench".to_string(),
+                steps: vec![],
+                max_concurrent: None,
+            }],
+            recurring_patterns: vec![RecurringStepPattern {
+                template_step_id: "knead".to_string(),
+                interval_mins: 30,
+                count: 2,
+                prefix: "knead".to_string(),
+            }],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let prepared = crate::solver::prepare_template(&template, &SolveOptions::default());
+        let rows = compute_gantt_rows(&solved, &prepared);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].track_name, "Bench");
+        assert_eq!(rows[0].bars.len(), solved.solved_steps.len());
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::model::{
+    DependencyType, Resource, ResourceInventory, ResourceKind, ResourceNeed, ScheduleTemplate,
+    SolvedSchedule, Step, StepDependency, Track,
+};
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("Failed to read template file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON template: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid TOML template: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Can't tell the format of '{0}' -- expected a .json or .toml file")]
+    UnknownFormat(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeserError {
+    #[error("Invalid compact schedule: {0}")]
+    Msgpack(#[from] rmp_serde::decode::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("No markdown table found")]
+    NoTable,
+    #[error("Markdown table has no '{0}' column")]
+    MissingColumn(&'static str),
+    #[error("Row {row}: step has no title")]
+    MissingTitle { row: usize },
+    #[error("Row {row}: step '{title}' appears more than once")]
+    DuplicateStep { row: usize, title: String },
+    #[error("Row {row}: invalid duration '{value}' -- expected a whole number of minutes")]
+    InvalidDuration { row: usize, value: String },
+    #[error("Row {row}: depends on unknown step '{title}'")]
+    UnknownDependency { row: usize, title: String },
+}
+
+// ---------------------------------------------------------------------------
+// TOML
+// ---------------------------------------------------------------------------
+//
+// TOML uses the same field names as the JSON protocol (camelCase). Unset
+// options are left out rather than written as null, since TOML has no null.
+
+/// Serialize a template to TOML, which is easier to write by hand than JSON.
+pub fn to_toml(template: &ScheduleTemplate) -> Result<String, toml::ser::Error> {
+    toml::to_string(template)
+}
+
+/// Parse a template from TOML.
+pub fn from_toml(s: &str) -> Result<ScheduleTemplate, toml::de::Error> {
+    toml::from_str(s)
+}
+
+// ---------------------------------------------------------------------------
+// Compact binary
+// ---------------------------------------------------------------------------
+//
+// MessagePack with struct fields written in declaration order rather than by
+// name, so a large schedule is a fraction of its JSON size. Field order is
+// part of the format: bytes are only readable by a build with the same model.
+
+/// Serialize a solved schedule to compact MessagePack.
+pub fn serialize_solved_schedule_compact(solved: &SolvedSchedule) -> Vec<u8> {
+    rmp_serde::to_vec(solved).expect("a solved schedule always serializes")
+}
+
+/// Read a solved schedule written by `serialize_solved_schedule_compact`.
+pub fn deserialize_solved_schedule_compact(bytes: &[u8]) -> Result<SolvedSchedule, DeserError> {
+    Ok(rmp_serde::from_slice(bytes)?)
+}
+
+// ---------------------------------------------------------------------------
+// JSON Lines
+// ---------------------------------------------------------------------------
+//
+// One JSON document per line, for tools like `jq` that process a stream of
+// results one at a time.
+
+/// Write each schedule to `writer` as a single line of JSON.
+pub fn to_jsonl(schedules: &[SolvedSchedule], writer: &mut impl Write) -> Result<(), io::Error> {
+    for solved in schedules {
+        serde_json::to_writer(&mut *writer, solved)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Solve each template as it arrives and write its schedule to `writer` as a
+/// line of JSON straight away, so results are never all held in memory. A
+/// template that can't be solved gets a `{ "templateId", "error" }` line
+/// instead, and the stream carries on.
+pub fn solve_stream(
+    templates: impl Iterator<Item = ScheduleTemplate>,
+    writer: &mut impl Write,
+) -> Result<(), io::Error> {
+    solve_stream_with_inventory(templates, None, writer)
+}
+
+/// `solve_stream`, solving every template against `inventory`.
+pub fn solve_stream_with_inventory(
+    templates: impl Iterator<Item = ScheduleTemplate>,
+    inventory: Option<&ResourceInventory>,
+    writer: &mut impl Write,
+) -> Result<(), io::Error> {
+    for template in templates {
+        let result = crate::solver::solve(&template, inventory);
+        write_stream_line(writer, &template.id, result.map_err(|e| e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Write one `solve_stream` line for the template `template_id`: its
+/// schedule, or `{ "templateId", "error" }`. Flushes, so the line is seen
+/// straight away.
+pub fn write_stream_line(
+    writer: &mut impl Write,
+    template_id: &str,
+    result: Result<SolvedSchedule, String>,
+) -> Result<(), io::Error> {
+    match result {
+        Ok(solved) => serde_json::to_writer(&mut *writer, &solved)?,
+        Err(error) => serde_json::to_writer(
+            &mut *writer,
+            &serde_json::json!({ "templateId": template_id, "error": error }),
+        )?,
+    }
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+// ---------------------------------------------------------------------------
+// Markdown tables
+// ---------------------------------------------------------------------------
+//
+// For templates written in Obsidian or GitHub markdown:
+//
+//   # Dinner
+//
+//   | Step | Duration (mins) | Depends On  | Resources | Track   |
+//   |------|-----------------|-------------|-----------|---------|
+//   | Prep | 20              |             | Board     | Kitchen |
+//   | Cook | 40              | Prep        | Oven      | Kitchen |
+//
+// `Step` and `Duration (mins)` are required; the other columns are optional
+// and columns the parser doesn't know are ignored.
+
+/// Parse a template from the first markdown table in `markdown`, one step
+/// per row. The first `#` heading, if any, names the template.
+///
+/// "Depends On" lists the titles of finish-to-start predecessors, separated
+/// by commas. "Resources" lists resource names the same way: each distinct
+/// name becomes an Equipment resource with capacity 1, and each step needs
+/// one of it. "Track" names the step's track. IDs are derived from titles
+/// and names.
+pub fn parse_schedule_from_markdown_table(
+    markdown: &str,
+) -> Result<ScheduleTemplate, ParseError> {
+    let name = markdown
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let mut lines = markdown
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with('|'))
+        .take_while(|line| line.starts_with('|'));
+    let header = table_cells(lines.next().ok_or(ParseError::NoTable)?);
+    let column = |wanted: &[&str]| {
+        header.iter().position(|cell| wanted.iter().any(|w| cell.eq_ignore_ascii_case(w)))
+    };
+    let step_col = column(&["Step"]).ok_or(ParseError::MissingColumn("Step"))?;
+    let duration_col = column(&["Duration (mins)", "Duration"])
+        .ok_or(ParseError::MissingColumn("Duration (mins)"))?;
+    let depends_col = column(&["Depends On"]);
+    let resources_col = column(&["Resources"]);
+    let track_col = column(&["Track"]);
+    // The |---|---| line under the header.
+    lines.next();
+
+    let rows: Vec<Vec<String>> = lines.map(table_cells).collect();
+    let cell = |row: &[String], col: Option<usize>| -> String {
+        col.and_then(|c| row.get(c)).cloned().unwrap_or_default()
+    };
+
+    // Titles first, so dependencies can point at later rows.
+    let mut ids: HashMap<String, String> = HashMap::new();
+    let mut used_ids: Vec<String> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let title = cell(row, Some(step_col));
+        if title.is_empty() {
+            return Err(ParseError::MissingTitle { row: i + 1 });
+        }
+        if ids.contains_key(&title) {
+            return Err(ParseError::DuplicateStep { row: i + 1, title });
+        }
+        let id = unique_slug(&title, &used_ids);
+        used_ids.push(id.clone());
+        ids.insert(title, id);
+    }
+
+    let mut template = ScheduleTemplate {
+        id: slug(&name),
+        name,
+        ..Default::default()
+    };
+    for (i, row) in rows.iter().enumerate() {
+        let title = cell(row, Some(step_col));
+        let value = cell(row, Some(duration_col));
+        let duration_mins = value
+            .parse::<u32>()
+            .map_err(|_| ParseError::InvalidDuration { row: i + 1, value })?;
+
+        let mut dependencies = Vec::new();
+        for predecessor in list(&cell(row, depends_col)) {
+            let Some(step_id) = ids.get(&predecessor) else {
+                return Err(ParseError::UnknownDependency {
+                    row: i + 1,
+                    title: predecessor,
+                });
+            };
+            dependencies.push(StepDependency {
+                step_id: step_id.clone(),
+                dependency_type: DependencyType::FinishToStart,
+                trigger_at_percent: None,
+                condition: None,
+            });
+        }
+
+        let mut resource_needs = Vec::new();
+        for resource_name in list(&cell(row, resources_col)) {
+            let resource_id = slug(&resource_name);
+            if !template.resources.iter().any(|r| r.id == resource_id) {
+                template.resources.push(Resource {
+                    id: resource_id.clone(),
+                    name: resource_name,
+                    kind: ResourceKind::Equipment,
+                    capacity: 1,
+                    roles: vec![],
+                    role_headcounts: vec![],
+                    unit_cost: 0.0,
+                    cost_per_unit_per_min: None,
+                    utilization_target: None,
+                });
+            }
+            resource_needs.push(ResourceNeed {
+                resource_id,
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+        }
+
+        let track_name = cell(row, track_col);
+        let track_id = (!track_name.is_empty()).then(|| slug(&track_name));
+        if let Some(track_id) = &track_id {
+            if !template.tracks.iter().any(|t| &t.id == track_id) {
+                template.tracks.push(Track {
+                    id: track_id.clone(),
+                    name: track_name,
+                    steps: vec![],
+                    max_concurrent: None,
+                });
+            }
+        }
+
+        template.steps.push(Step {
+            id: ids[&title].clone(),
+            title,
+            duration_mins,
+            dependencies,
+            resource_needs,
+            track_id,
+            ..Default::default()
+        });
+    }
+    Ok(template)
+}
+
+/// The trimmed cells of a `| a | b |` table line.
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim().trim_start_matches('|');
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+/// The non-empty entries of a comma-separated cell.
+fn list(cell: &str) -> Vec<String> {
+    cell.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+/// Lowercase `text` with runs of anything but letters and digits turned
+/// into single dashes, e.g. "Preheat Oven!" -> "preheat-oven".
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "step".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// `slug(title)`, with a number appended if another step already has it.
+fn unique_slug(title: &str, used: &[String]) -> String {
+    let base = slug(title);
+    let mut id = base.clone();
+    let mut n = 2;
+    while used.contains(&id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+// ---------------------------------------------------------------------------
+// Files
+// ---------------------------------------------------------------------------
+
+/// Read a template from a `.json` or `.toml` file, picking the format from
+/// the extension (case-insensitively). Any other extension is rejected
+/// before the file is read.
+pub fn load_template_from_file(path: &Path) -> Result<ScheduleTemplate, LoadError> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if extension.eq_ignore_ascii_case("json") {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    } else if extension.eq_ignore_ascii_case("toml") {
+        Ok(from_toml(&std::fs::read_to_string(path)?)?)
+    } else {
+        Err(LoadError::UnknownFormat(path.display().to_string()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, Step, StepDependency};
+
+    #[test]
+    fn test_from_toml_reads_hand_written_template() {
+        let template = from_toml(
+            r#"
+            id = "dinner"
+            name = "Dinner"
+            tracks = []
+            resources = []
+
+            [[steps]]
+            id = "prep"
+            title = "Prep"
+            durationMins = 20
+            dependencies = []
+            resourceNeeds = []
+
+            [[steps]]
+            id = "cook"
+            title = "Cook"
+            durationMins = 40
+            resourceNeeds = []
+            dependencies = [{ stepId = "prep", dependencyType = "FinishToStart" }]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(template.steps.len(), 2);
+        assert_eq!(template.steps[1].dependencies[0].step_id, "prep");
+        assert_eq!(template.description, None);
+    }
+
+    #[test]
+    fn test_to_toml_round_trips() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                Step {
+                    id: "a".to_string(),
+                    title: "A".to_string(),
+                    duration_mins: 10,
+                    ..Default::default()
+                },
+                Step {
+                    id: "b".to_string(),
+                    title: "B".to_string(),
+                    duration_mins: 5,
+                    dependencies: vec![StepDependency {
+                        step_id: "a".to_string(),
+                        dependency_type: DependencyType::StartToStart,
+                        trigger_at_percent: None,
+                        condition: None,
+                    }],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let text = to_toml(&template).unwrap();
+        assert!(text.contains("durationMins = 10"));
+        assert_eq!(from_toml(&text).unwrap(), template);
+    }
+
+    #[test]
+    fn test_compact_schedule_round_trips_smaller_than_json() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: (0..20)
+                .map(|i| Step {
+                    id: format!("step-{}", i),
+                    title: format!("Step {}", i),
+                    duration_mins: 10 + i,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+
+        let bytes = serialize_solved_schedule_compact(&solved);
+        assert!(bytes.len() < serde_json::to_vec(&solved).unwrap().len() / 2);
+        assert_eq!(deserialize_solved_schedule_compact(&bytes).unwrap(), solved);
+        assert!(deserialize_solved_schedule_compact(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_jsonl_writes_one_schedule_per_line() {
+        let template = |id: &str, duration_mins: u32| ScheduleTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            steps: vec![Step {
+                id: "a".to_string(),
+                title: "A".to_string(),
+                duration_mins,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let templates = vec![template("short", 10), template("broken", 0), template("long", 60)];
+
+        let mut streamed = Vec::new();
+        solve_stream(templates.clone().into_iter(), &mut streamed).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(streamed)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["summary"]["totalDurationMins"], 10);
+        assert_eq!(lines[1]["templateId"], "broken");
+        assert!(lines[1]["error"].as_str().unwrap().contains("no duration"));
+        assert_eq!(lines[2]["summary"]["totalDurationMins"], 60);
+
+        let solved: Vec<SolvedSchedule> = [&templates[0], &templates[2]]
+            .iter()
+            .map(|t| crate::solver::solve(t, None).unwrap())
+            .collect();
+        let mut written = Vec::new();
+        to_jsonl(&solved, &mut written).unwrap();
+        let text = String::from_utf8(written).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        let first: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!(first, lines[0]);
+    }
+
+    #[test]
+    fn test_markdown_table_becomes_template() {
+        let markdown = "\
+# Sunday Dinner
+
+Notes before the table are skipped.
+
+| Step         | Duration (mins) | Depends On        | Resources   | Track   | Notes |
+|--------------|-----------------|-------------------|-------------|---------|-------|
+| Prep veg     | 20              |                   | Board       | Kitchen | wash  |
+| Roast        | 60              | Prep veg          | Oven, Board | Kitchen |       |
+| Make gravy   | 10              | Roast, Set table  | Hob         |         |       |
+| Set table    | 5               |                   |             |         |       |
+";
+        let template = parse_schedule_from_markdown_table(markdown).unwrap();
+        assert_eq!(template.id, "sunday-dinner");
+        assert_eq!(template.name, "Sunday Dinner");
+        let ids: Vec<&str> = template.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["prep-veg", "roast", "make-gravy", "set-table"]);
+
+        let gravy = &template.steps[2];
+        assert_eq!(gravy.duration_mins, 10);
+        let predecessors: Vec<&str> =
+            gravy.dependencies.iter().map(|d| d.step_id.as_str()).collect();
+        assert_eq!(predecessors, ["roast", "set-table"]);
+        assert_eq!(gravy.track_id, None);
+
+        let resources: Vec<&str> = template.resources.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(resources, ["board", "oven", "hob"]);
+        assert_eq!(template.steps[1].resource_needs.len(), 2);
+        assert_eq!(template.tracks.len(), 1);
+        assert_eq!(template.steps[0].track_id.as_deref(), Some("kitchen"));
+        assert!(crate::validator::validate(&template).is_ok());
+
+        let err =
+            parse_schedule_from_markdown_table("| Step | Duration |\n|---|---|\n| A | soon |")
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Row 1: invalid duration 'soon' -- expected a whole number of minutes"
+        );
+        let err =
+            parse_schedule_from_markdown_table("| Step | Depends On |\n|---|---|").unwrap_err();
+        assert!(matches!(err, ParseError::MissingColumn("Duration (mins)")));
+    }
+}
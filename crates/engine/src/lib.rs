@@ -1,4 +1,19 @@
 pub mod allocator;
+pub mod analysis;
+pub mod annotation;
+pub mod baseline;
+pub mod calendar;
+pub mod execution;
+pub mod expander;
+pub mod export;
+pub mod float;
+pub mod gantt;
+pub mod io;
 pub mod model;
+pub mod multi;
+pub mod preprocess;
+pub mod schema;
+pub mod scoring;
 pub mod solver;
+pub mod util;
 pub mod validator;
@@ -1,10 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 
-use skejj_engine::model::{ResourceInventory, ResourceInventoryItem, ScheduleTemplate};
-use skejj_engine::{solver, validator};
+use skejj_engine::execution::ProgressUpdate;
+use skejj_engine::model::{
+    Resource, ResourceInventory, ResourceInventoryItem, ScheduleTemplate, SolvedSchedule,
+};
+use skejj_engine::scoring::ScoreWeights;
+use skejj_engine::solver::SolveOptions;
+use skejj_engine::validator::{ComplexityLimits, ValidationError};
+use skejj_engine::{
+    allocator, analysis, annotation, execution, export, float, gantt, multi, schema, scoring,
+    solver, util, validator,
+};
 
 // ---------------------------------------------------------------------------
 // Request / Response types
@@ -18,12 +29,225 @@ enum Request {
         /// Simple name->count map provided by the caller. Converted to
         /// ResourceInventory by matching resource names to IDs from the template.
         inventory: Option<HashMap<String, u32>>,
+        /// Skip resource allocation and return CPM timings only. Same as
+        /// passing `--dry-run`.
+        #[serde(default, rename = "dryRun")]
+        dry_run: bool,
+        /// Conditions that switch on conditional steps.
+        #[serde(default, rename = "activeConditions")]
+        active_conditions: Vec<String>,
+        /// When set, include chains of steps whose float is within this many
+        /// minutes as `nearCriticalPaths`.
+        #[serde(rename = "nearCriticalThreshold")]
+        near_critical_threshold: Option<u32>,
+        /// `[from, to]` pairs: needs for `from` use `to` instead (see
+        /// `solver::substitute_resources`).
+        #[serde(default)]
+        substitutions: Vec<(String, String)>,
+        /// Fail instead of returning a schedule longer than this (see
+        /// `solver::solve_with_max_duration`).
+        #[serde(rename = "maxDurationMins")]
+        max_duration_mins: Option<u32>,
+        /// Let `allowPartial` needs start with fewer units (see
+        /// `solver::solve_with_resource_splitting`).
+        #[serde(default, rename = "resourceSplitting")]
+        resource_splitting: bool,
+    },
+    /// Solve several scenario templates in one call. Each gets its own
+    /// `{ ok, data }` or `{ ok: false, error }` entry in the response array.
+    SolveBatch {
+        templates: Vec<ScheduleTemplate>,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve several projects that share one resource pool. Each gets its
+    /// own entry in the response array, as for `solveBatch`.
+    SolveMulti {
+        templates: Vec<ScheduleTemplate>,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve backward so the schedule finishes at `deadline` (see
+    /// `solver::solve_backward_from_deadline`).
+    SolveBackward {
+        template: ScheduleTemplate,
+        deadline: String,
+        inventory: Option<HashMap<String, u32>>,
     },
     Validate {
         template: ScheduleTemplate,
+        /// Report errors and warnings as `{ code, message, affectedIds }`
+        /// objects instead of plain messages.
+        #[serde(default)]
+        structured: bool,
+    },
+    /// Solve, then report non-critical steps that could be shortened to free
+    /// resources for the steps they delay.
+    CompressReport {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve with and without resource allocation and report what leveling
+    /// changed (see `analysis::leveling_metrics`).
+    LevelingMetrics {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve with every step ASAP and with every step ALAP and compare the
+    /// results (see `analysis::compare_timing_policies`).
+    ComparePolicies {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Run CPM only and return the raw early/late start windows (debugging aid
+    /// for allocator work).
+    Cpm {
+        template: ScheduleTemplate,
+    },
+    /// Solve, then split the result into one sub-schedule per track.
+    Split {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve, then flag structural risks in the result.
+    Risk {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+    },
+    /// Solve, then report the cumulative usage of one resource over time.
+    SCurve {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+        #[serde(rename = "resourceId")]
+        resource_id: String,
+        #[serde(rename = "bucketMins")]
+        bucket_mins: u32,
+    },
+    /// Steps counted by total float (see `analysis::float_histogram`).
+    FloatHistogram {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+        #[serde(rename = "bucketSizeMins")]
+        bucket_size_mins: u32,
+    },
+    /// Every resource's load per period (see `analysis::resource_loading_report`).
+    LoadingReport {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+        #[serde(rename = "periodMins")]
+        period_mins: u32,
+    },
+    /// Record actual step times on a solved schedule (see
+    /// `execution::record_actuals`). Deviation warnings are appended to the
+    /// schedule's `warnings`.
+    Record {
+        schedule: Box<SolvedSchedule>,
+        updates: Vec<ProgressUpdate>,
+    },
+    /// Estimate one step's progress at a point in time (see
+    /// `execution::interpolate_step_progress`).
+    StepProgress {
+        schedule: Box<SolvedSchedule>,
+        #[serde(rename = "stepId")]
+        step_id: String,
+        #[serde(rename = "currentTimeMins")]
+        current_time_mins: u32,
+    },
+    /// Combine two template modules into one (see `util::merge_templates`).
+    Merge {
+        base: ScheduleTemplate,
+        overlay: Box<ScheduleTemplate>,
+    },
+    /// List pairs of independent steps that could run side by side (see
+    /// `analysis::detect_parallel_opportunities`).
+    ParallelizeReport {
+        template: ScheduleTemplate,
+    },
+    /// How the schedule changes with more of a resource (see
+    /// `analysis::what_if_add_resource`).
+    WhatIfAdd {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+        resource: Resource,
+        #[serde(rename = "extraQuantity")]
+        extra_quantity: u32,
+    },
+    /// How the schedule changes without a step (see
+    /// `analysis::what_if_remove_step`).
+    WhatIfRemove {
+        template: ScheduleTemplate,
+        inventory: Option<HashMap<String, u32>>,
+        #[serde(rename = "stepId")]
+        step_id: String,
+    },
+    /// Dependencies the template may have forgotten (see
+    /// `analysis::suggest_missing_dependencies`).
+    SuggestDeps {
+        template: ScheduleTemplate,
+    },
+    /// JSON Schema for templates (see `schema::export_json_schema`).
+    Schema,
+    /// The dependency graph as a matrix (see `util::to_adjacency_matrix`).
+    AdjacencyMatrix {
+        template: ScheduleTemplate,
+    },
+    /// Each step's level in the dependency graph (see
+    /// `util::topological_levels`).
+    TopologicalLevels {
+        template: ScheduleTemplate,
+    },
+    /// Derive a unique step ID from a title (see `util::generate_step_id`).
+    GenerateStepId {
+        title: String,
+        #[serde(default, rename = "existingIds")]
+        existing_ids: Vec<String>,
+    },
+    /// Score two already-solved schedules of the same template side by side,
+    /// each with the name->count inventory it was solved with, if any.
+    Score {
+        template: ScheduleTemplate,
+        baseline: Box<SolvedSchedule>,
+        candidate: Box<SolvedSchedule>,
+        weights: Option<ScoreWeights>,
+        #[serde(rename = "baselineInventory")]
+        baseline_inventory: Option<HashMap<String, u32>>,
+        #[serde(rename = "candidateInventory")]
+        candidate_inventory: Option<HashMap<String, u32>>,
     },
 }
 
+impl Request {
+    fn template(&self) -> Option<&ScheduleTemplate> {
+        let template = match self {
+            Request::Solve { template, .. }
+            | Request::SolveBackward { template, .. }
+            | Request::Validate { template, .. }
+            | Request::CompressReport { template, .. }
+            | Request::LevelingMetrics { template, .. }
+            | Request::ComparePolicies { template, .. }
+            | Request::Cpm { template }
+            | Request::Split { template, .. }
+            | Request::Risk { template, .. }
+            | Request::SCurve { template, .. }
+            | Request::FloatHistogram { template, .. }
+            | Request::LoadingReport { template, .. }
+            | Request::ParallelizeReport { template }
+            | Request::SuggestDeps { template }
+            | Request::WhatIfAdd { template, .. }
+            | Request::WhatIfRemove { template, .. }
+            | Request::AdjacencyMatrix { template }
+            | Request::TopologicalLevels { template }
+            | Request::Score { template, .. } => template,
+            Request::GenerateStepId { .. }
+            | Request::SolveBatch { .. }
+            | Request::SolveMulti { .. }
+            | Request::Merge { .. }
+            | Request::Record { .. }
+            | Request::StepProgress { .. }
+            | Request::Schema => return None,
+        };
+        Some(template)
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct OkResponse<T: Serialize> {
     ok: bool,
@@ -36,6 +260,230 @@ struct ErrResponse {
     error: String,
 }
 
+/// One template's outcome within a `solveBatch` response.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum BatchEntry {
+    Ok(OkResponse<SolvedSchedule>),
+    Err(ErrResponse),
+}
+
+/// A `validate` response with codes and affected IDs (`structured: true`).
+#[derive(Debug, Serialize)]
+struct StructuredValidation {
+    errors: Vec<ValidationError>,
+    warnings: Vec<ValidationError>,
+}
+
+// ---------------------------------------------------------------------------
+// Command-line flags
+// ---------------------------------------------------------------------------
+
+/// Output format for responses (`--output-format`, or `--format`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// The standard `{ ok, data }` JSON envelope.
+    #[default]
+    Json,
+    /// A self-contained HTML report (errors are still reported as JSON).
+    Html,
+    /// Raw MessagePack with named fields (same shape as the JSON protocol,
+    /// less parsing overhead).
+    Msgpack,
+    /// The `{ ok, data }` envelope as a TOML document.
+    Toml,
+    /// A PlantUML dependency diagram (`solve` results only; errors are still
+    /// reported as JSON).
+    Plantuml,
+    /// A Graphviz DOT dependency graph (`solve` and `validate` results only;
+    /// errors are still reported as JSON).
+    Dot,
+    /// Newline-delimited JSON: `solveBatch` and `solveMulti` write one line
+    /// per template as `io::solve_stream` does (the schedule, or
+    /// `{ templateId, error }`), a sequential `solveBatch` as soon as each
+    /// template is solved. Other responses are written as for json.
+    Jsonl,
+}
+
+/// Wire format for the request on stdin (`--input-format`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    #[default]
+    Json,
+    Msgpack,
+    Toml,
+    /// A markdown table of steps (see `io::parse_schedule_from_markdown_table`),
+    /// solved as a `solve` request.
+    Markdown,
+}
+
+/// The `--format` chosen on the command line, consulted by `write_ok` and
+/// `write_err`. Unset until arguments are parsed, which means JSON.
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// Flags accepted on the command line. The request arrives on stdin unless
+/// `--file` is given; other flags only tweak how it is processed or how the
+/// response is shaped.
+#[derive(Debug, Default)]
+struct CliArgs {
+    /// `--gantt`: include pre-computed Gantt rows in `solve` responses.
+    gantt: bool,
+    /// `--dry-run`: `solve` skips resource allocation (CPM timings only).
+    dry_run: bool,
+    /// `--detailed-float`: fill in each step's `pathFloatMins` in `solve`
+    /// responses.
+    detailed_float: bool,
+    /// `--min-version` / `--max-version`: accepted range (inclusive) for the
+    /// template's `version`. Templates without a version are not checked.
+    min_version: Option<String>,
+    max_version: Option<String>,
+    /// `--output-format json|jsonl|html|msgpack|toml|plantuml|dot` (or
+    /// `--format`): response format (html and plantuml apply to `solve`
+    /// results only, dot to `solve` and `validate`).
+    format: OutputFormat,
+    /// `--input-format json|msgpack|toml|markdown`: request format. Defaults to
+    /// JSON, or MessagePack under `--format msgpack`.
+    input_format: InputFormat,
+    /// `--file path`: solve the template in this `.json` or `.toml` file
+    /// instead of reading a request from stdin.
+    file: Option<PathBuf>,
+    /// `--parallel N`: `solveBatch` solves its templates on N threads.
+    parallel: Option<usize>,
+    /// `--float-consumption`: fill in `floatConsumption` in `solve` responses.
+    float_consumption: bool,
+    /// `--annotated`: `solve` responses carry each step's title, description,
+    /// track and resource needs (see `annotation::annotate`).
+    annotated: bool,
+    /// `--max-steps`, `--max-resources`, `--max-dependencies-per-step` and
+    /// `--max-total-dependencies`: size limits every template must keep to.
+    /// Unset limits keep `ComplexityLimits::default()`.
+    limits: ComplexityLimits,
+}
+
+fn parse_args() -> CliArgs {
+    let mut cli = CliArgs::default();
+    let mut input_format = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--gantt" => cli.gantt = true,
+            "--dry-run" => cli.dry_run = true,
+            "--detailed-float" => cli.detailed_float = true,
+            "--annotated" => cli.annotated = true,
+            "--float-consumption" => cli.float_consumption = true,
+            "--format" | "--output-format" => {
+                cli.format = match args.next().as_deref() {
+                    Some("json") => OutputFormat::Json,
+                    Some("jsonl") => OutputFormat::Jsonl,
+                    Some("html") => OutputFormat::Html,
+                    Some("msgpack") => OutputFormat::Msgpack,
+                    Some("toml") => OutputFormat::Toml,
+                    Some("plantuml") => OutputFormat::Plantuml,
+                    Some("dot") => OutputFormat::Dot,
+                    Some(other) => write_err(format!("Unknown format: {}", other)),
+                    None => write_err(format!(
+                        "{} requires a value (json, jsonl, html, msgpack, toml, plantuml or dot)",
+                        arg
+                    )),
+                }
+            }
+            "--input-format" => {
+                input_format = match args.next().as_deref() {
+                    Some("json") => Some(InputFormat::Json),
+                    Some("msgpack") => Some(InputFormat::Msgpack),
+                    Some("toml") => Some(InputFormat::Toml),
+                    Some("markdown") => Some(InputFormat::Markdown),
+                    Some(other) => write_err(format!("Unknown input format: {}", other)),
+                    None => write_err(
+                        "--input-format requires a value (json, msgpack, toml or markdown)",
+                    ),
+                }
+            }
+            "--min-version" | "--max-version" => {
+                let Some(value) = args.next() else {
+                    write_err(format!("{} requires a version", arg));
+                };
+                if util::parse_version(&value).is_none() {
+                    write_err(format!("{} '{}' isn't a semantic version", arg, value));
+                }
+                if arg == "--min-version" {
+                    cli.min_version = Some(value);
+                } else {
+                    cli.max_version = Some(value);
+                }
+            }
+            "--file" => match args.next() {
+                Some(path) => cli.file = Some(PathBuf::from(path)),
+                None => write_err("--file requires a path"),
+            },
+            "--parallel" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(threads)) if threads > 0 => cli.parallel = Some(threads),
+                _ => write_err("--parallel requires a number of threads (at least 1)"),
+            },
+            "--max-steps" => cli.limits.max_steps = parse_limit(&arg, args.next()),
+            "--max-resources" => cli.limits.max_resources = parse_limit(&arg, args.next()),
+            "--max-dependencies-per-step" => {
+                cli.limits.max_dependencies_per_step = parse_limit(&arg, args.next())
+            }
+            "--max-total-dependencies" => {
+                cli.limits.max_total_dependencies = parse_limit(&arg, args.next())
+            }
+            other => write_err(format!("Unknown argument: {}", other)),
+        }
+    }
+    cli.input_format = input_format.unwrap_or(match cli.format {
+        OutputFormat::Msgpack => InputFormat::Msgpack,
+        _ => InputFormat::Json,
+    });
+    cli
+}
+
+fn parse_limit(flag: &str, value: Option<String>) -> usize {
+    match value.map(|n| n.parse::<usize>()) {
+        Some(Ok(limit)) => limit,
+        _ => write_err(format!("{} requires a number", flag)),
+    }
+}
+
+/// Reject templates larger than the `--max-*` limits allow, or whose version
+/// lies outside `--min-version`..=`--max-version`.
+fn check_template(cli: &CliArgs, template: &ScheduleTemplate) -> Result<(), String> {
+    check_complexity(cli, template)?;
+    check_version(cli, template)
+}
+
+fn check_complexity(cli: &CliArgs, template: &ScheduleTemplate) -> Result<(), String> {
+    let result = validator::validate_complexity(template, &cli.limits);
+    if !result.is_ok() {
+        return Err(result.error_summary());
+    }
+    Ok(())
+}
+
+fn check_version(cli: &CliArgs, template: &ScheduleTemplate) -> Result<(), String> {
+    let Some(version) = template.version.as_deref() else {
+        return Ok(());
+    };
+    let Some(parsed) = util::parse_version(version) else {
+        return Err(format!("Template version '{}' isn't a semantic version", version));
+    };
+    if let Some(min) = cli.min_version.as_deref() {
+        if Some(parsed) < util::parse_version(min) {
+            return Err(format!("Template version {} is older than the minimum {}", version, min));
+        }
+    }
+    if let Some(max) = cli.max_version.as_deref() {
+        if Some(parsed) > util::parse_version(max) {
+            return Err(format!("Template version {} is newer than the maximum {}", version, max));
+        }
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -69,8 +517,105 @@ fn build_inventory(
     ResourceInventory { items }
 }
 
+/// Resolve a name->count map against the resources of several templates.
+fn build_shared_inventory(
+    templates: &[ScheduleTemplate],
+    inventory: &HashMap<String, u32>,
+) -> ResourceInventory {
+    let mut items: Vec<ResourceInventoryItem> =
+        templates.iter().flat_map(|t| build_inventory(t, inventory).items).collect();
+    items.sort_by(|a, b| a.resource_id.cmp(&b.resource_id));
+    items.dedup_by(|a, b| a.resource_id == b.resource_id);
+    ResourceInventory { items }
+}
+
+/// Split a batch into the outcome of `check_template` for every template and
+/// the templates that passed it, in order.
+fn admit_templates(
+    cli: &CliArgs,
+    templates: Vec<ScheduleTemplate>,
+) -> (Vec<Result<(), String>>, Vec<ScheduleTemplate>) {
+    let checks: Vec<Result<(), String>> =
+        templates.iter().map(|template| check_template(cli, template)).collect();
+    let admitted = templates.into_iter().zip(&checks).filter(|(_, c)| c.is_ok());
+    let admitted = admitted.map(|(template, _)| template).collect();
+    (checks, admitted)
+}
+
+/// The result for every template of a batch: its rejection from `checks`,
+/// or the next of `results` (the solves of the templates that passed, in
+/// order).
+fn batch_results(
+    checks: Vec<Result<(), String>>,
+    results: Vec<Result<SolvedSchedule, solver::SolveError>>,
+) -> Vec<Result<SolvedSchedule, String>> {
+    let mut results = results.into_iter();
+    checks
+        .into_iter()
+        .filter_map(|check| match check {
+            Ok(()) => results.next().map(|result| result.map_err(|e| e.to_string())),
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+/// Write a batch's results in the requested format: one `io::solve_stream`
+/// line per template for jsonl, a single response of `{ ok, data }` or
+/// `{ ok: false, error }` entries otherwise.
+fn write_batch(template_ids: Vec<String>, results: Vec<Result<SolvedSchedule, String>>) {
+    if format() == OutputFormat::Jsonl {
+        for (template_id, result) in template_ids.iter().zip(results) {
+            let _ = skejj_engine::io::write_stream_line(&mut io::stdout(), template_id, result);
+        }
+    } else {
+        let entries: Vec<BatchEntry> = results
+            .into_iter()
+            .map(|result| match result {
+                Ok(data) => BatchEntry::Ok(OkResponse { ok: true, data }),
+                Err(error) => BatchEntry::Err(ErrResponse { ok: false, error }),
+            })
+            .collect();
+        write_ok(entries);
+    }
+}
+
+/// Solve the template with an optional name->count inventory, exiting with an
+/// error response if solving fails.
+fn solve_or_exit(
+    template: &ScheduleTemplate,
+    inventory: Option<&HashMap<String, u32>>,
+) -> SolvedSchedule {
+    let inventory_struct: Option<ResourceInventory> =
+        inventory.map(|map| build_inventory(template, map));
+    match solver::solve(template, inventory_struct.as_ref()) {
+        Ok(solved) => solved,
+        Err(e) => write_err(e),
+    }
+}
+
 fn write_ok<T: Serialize>(data: T) {
     let resp = OkResponse { ok: true, data };
+    match format() {
+        OutputFormat::Msgpack => {
+            match rmp_serde::to_vec_named(&resp) {
+                Ok(bytes) => write_bytes(&bytes),
+                Err(e) => write_err(format!("serialization error: {}", e)),
+            }
+            return;
+        }
+        OutputFormat::Toml => {
+            match toml::to_string(&resp) {
+                Ok(text) => write_raw(&text),
+                Err(e) => write_err(format!("serialization error: {}", e)),
+            }
+            return;
+        }
+        OutputFormat::Json
+        | OutputFormat::Jsonl
+        | OutputFormat::Html
+        | OutputFormat::Plantuml
+        | OutputFormat::Dot => {}
+    }
     let json = serde_json::to_string(&resp).unwrap_or_else(|e| {
         format!("{{\"ok\":false,\"error\":\"serialization error: {}\"}}", e)
     });
@@ -78,11 +623,42 @@ fn write_ok<T: Serialize>(data: T) {
     let _ = io::stdout().flush();
 }
 
+fn write_bytes(bytes: &[u8]) {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(bytes);
+    let _ = stdout.flush();
+}
+
+/// Write a non-JSON document (e.g. an HTML report) to stdout as-is.
+fn write_raw(document: &str) {
+    print!("{}", document);
+    let _ = io::stdout().flush();
+}
+
 fn write_err(msg: impl std::fmt::Display) -> ! {
     let resp = ErrResponse {
         ok: false,
         error: msg.to_string(),
     };
+    match format() {
+        OutputFormat::Msgpack => {
+            if let Ok(bytes) = rmp_serde::to_vec_named(&resp) {
+                write_bytes(&bytes);
+            }
+            std::process::exit(1);
+        }
+        OutputFormat::Toml => {
+            if let Ok(text) = toml::to_string(&resp) {
+                write_raw(&text);
+            }
+            std::process::exit(1);
+        }
+        OutputFormat::Json
+        | OutputFormat::Jsonl
+        | OutputFormat::Html
+        | OutputFormat::Plantuml
+        | OutputFormat::Dot => {}
+    }
     let json = serde_json::to_string(&resp).unwrap_or_else(|_| {
         "{\"ok\":false,\"error\":\"double serialization error\"}".to_string()
     });
@@ -91,37 +667,438 @@ fn write_err(msg: impl std::fmt::Display) -> ! {
     std::process::exit(1);
 }
 
+/// Read the request from stdin in `--input-format`.
+fn read_request(cli: &CliArgs) -> Request {
+    let mut input = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut input) {
+        write_err(format!("Failed to read stdin: {}", e));
+    }
+
+    match cli.input_format {
+        InputFormat::Json => match serde_json::from_slice(&input) {
+            Ok(r) => r,
+            Err(e) => write_err(format!("Invalid JSON input: {}", e)),
+        },
+        InputFormat::Msgpack => match rmp_serde::from_slice(&input) {
+            Ok(r) => r,
+            Err(e) => write_err(format!("Invalid MessagePack input: {}", e)),
+        },
+        InputFormat::Toml => {
+            let parsed = std::str::from_utf8(&input)
+                .map_err(|e| e.to_string())
+                .and_then(|text| toml::from_str(text).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(r) => r,
+                Err(e) => write_err(format!("Invalid TOML input: {}", e)),
+            }
+        }
+        InputFormat::Markdown => {
+            let parsed = std::str::from_utf8(&input).map_err(|e| e.to_string()).and_then(|text| {
+                skejj_engine::io::parse_schedule_from_markdown_table(text)
+                    .map_err(|e| e.to_string())
+            });
+            match parsed {
+                Ok(template) => solve_request(template),
+                Err(e) => write_err(format!("Invalid markdown input: {}", e)),
+            }
+        }
+    }
+}
+
+/// A plain `solve` of `template`, for templates that don't arrive as a
+/// request (`--file`, markdown input).
+fn solve_request(template: ScheduleTemplate) -> Request {
+    Request::Solve {
+        template,
+        inventory: None,
+        dry_run: false,
+        active_conditions: Vec::new(),
+        near_critical_threshold: None,
+        substitutions: Vec::new(),
+        max_duration_mins: None,
+        resource_splitting: false,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Entry point
 // ---------------------------------------------------------------------------
 
 fn main() {
-    // Read all of stdin
-    let mut input = String::new();
-    if let Err(e) = io::stdin().read_to_string(&mut input) {
-        write_err(format!("Failed to read stdin: {}", e));
-    }
+    let cli = parse_args();
+    let _ = FORMAT.set(cli.format);
 
-    // Parse request
-    let request: Request = match serde_json::from_str(&input) {
-        Ok(r) => r,
-        Err(e) => write_err(format!("Invalid JSON input: {}", e)),
+    let request = match &cli.file {
+        Some(path) => match skejj_engine::io::load_template_from_file(path) {
+            Ok(template) => solve_request(template),
+            Err(e) => write_err(e),
+        },
+        None => read_request(&cli),
     };
 
+    if let Some(Err(e)) = request.template().map(|template| check_template(&cli, template)) {
+        write_err(e);
+    }
+
     match request {
-        Request::Solve { template, inventory } => {
+        Request::Solve {
+            template,
+            inventory,
+            dry_run,
+            active_conditions,
+            near_critical_threshold,
+            substitutions,
+            max_duration_mins,
+            resource_splitting,
+        } => {
+            let template = if substitutions.is_empty() {
+                template
+            } else {
+                match solver::substitute_resources(&template, &substitutions) {
+                    Ok(substituted) => substituted,
+                    Err(e) => write_err(e),
+                }
+            };
             // Convert simple HashMap inventory to ResourceInventory if provided
             let inventory_struct: Option<ResourceInventory> =
                 inventory.as_ref().map(|map| build_inventory(&template, map));
+            let options = SolveOptions {
+                dry_run: dry_run || cli.dry_run,
+                // The caller validates separately via the `validate` command.
+                skip_validation: true,
+                active_conditions,
+                resource_splitting,
+            };
 
-            match solver::solve(&template, inventory_struct.as_ref()) {
+            match solver::solve_with_options(&template, inventory_struct.as_ref(), &options) {
+                Ok(mut solved) => {
+                    if let Some(limit) = max_duration_mins {
+                        if let Err(e) = solver::check_max_duration(&solved, limit) {
+                            write_err(e);
+                        }
+                    }
+                    if cli.gantt {
+                        let prepared = solver::prepare_template(&template, &options);
+                        solved.gantt_rows = Some(gantt::compute_gantt_rows(&solved, &prepared));
+                    }
+                    if cli.detailed_float {
+                        let prepared = solver::prepare_template(&template, &options);
+                        match float::compute_free_network_float_per_step(&prepared) {
+                            Ok(floats) => {
+                                for step in &mut solved.solved_steps {
+                                    step.path_float_mins = floats.get(&step.step_id).copied();
+                                }
+                            }
+                            Err(e) => write_err(e),
+                        }
+                    }
+                    if cli.float_consumption {
+                        // The template as allocated: prepared, with inventory capacities.
+                        let mut prepared =
+                            solver::prepare_template(&template, &options).into_owned();
+                        for item in inventory_struct.iter().flat_map(|inv| &inv.items) {
+                            if let Some(r) =
+                                prepared.resources.iter_mut().find(|r| r.id == item.resource_id)
+                            {
+                                r.capacity = item.available_quantity;
+                            }
+                        }
+                        match solver::cpm_only(&prepared) {
+                            Ok(cpm) => {
+                                solved.float_consumption =
+                                    Some(allocator::find_float_consuming_resources(
+                                        &solved, &prepared, &cpm,
+                                    ));
+                            }
+                            Err(e) => write_err(e),
+                        }
+                    }
+                    if let Some(threshold) = near_critical_threshold {
+                        solved.near_critical_paths = Some(solver::detect_near_critical_paths(
+                            &solved, &template, threshold,
+                        ));
+                    }
+                    match cli.format {
+                        OutputFormat::Json
+                        | OutputFormat::Jsonl
+                        | OutputFormat::Msgpack
+                        | OutputFormat::Toml => {
+                            if cli.annotated {
+                                write_ok(annotation::annotate(&solved, &template, &options))
+                            } else {
+                                write_ok(solved)
+                            }
+                        }
+                        OutputFormat::Html => write_raw(
+                            &export::html::generate_schedule_report_html(&solved, &template),
+                        ),
+                        OutputFormat::Plantuml => {
+                            write_raw(&export::plantuml::to_plantuml(&template, Some(&solved)))
+                        }
+                        OutputFormat::Dot => {
+                            write_raw(&export::dot::to_dot(&template, Some(&solved)))
+                        }
+                    }
+                }
+                Err(e) => write_err(e),
+            }
+        }
+        Request::SolveBatch { templates, inventory } => {
+            // Each solve ignores entries for resources it doesn't define.
+            let inventory_struct =
+                inventory.as_ref().map(|map| build_shared_inventory(&templates, map));
+            if cli.format == OutputFormat::Jsonl && cli.parallel.is_none() {
+                // Rejected templates get their error line as the stream
+                // reaches them.
+                let admitted = templates.into_iter().filter(|template| {
+                    let Err(e) = check_template(&cli, template) else {
+                        return true;
+                    };
+                    let _ = skejj_engine::io::write_stream_line(
+                        &mut io::stdout(),
+                        &template.id,
+                        Err(e),
+                    );
+                    false
+                });
+                let _ = skejj_engine::io::solve_stream_with_inventory(
+                    admitted,
+                    inventory_struct.as_ref(),
+                    &mut io::stdout(),
+                );
+                return;
+            }
+            let template_ids = templates.iter().map(|t| t.id.clone()).collect();
+            let (checks, templates) = admit_templates(&cli, templates);
+            let results = match cli.parallel {
+                Some(threads) => {
+                    let pool = rayon::ThreadPoolBuilder::new()
+                        .num_threads(threads)
+                        .build()
+                        .unwrap_or_else(|e| write_err(format!("Can't start threads: {}", e)));
+                    pool.install(|| {
+                        solver::solve_batched_with_rayon(&templates, inventory_struct.as_ref())
+                    })
+                }
+                None => solver::solve_all_scenarios(&templates, inventory_struct.as_ref()),
+            };
+            write_batch(template_ids, batch_results(checks, results));
+        }
+        Request::SolveMulti { templates, inventory } => {
+            let shared_inventory = match inventory {
+                Some(map) => build_shared_inventory(&templates, &map),
+                None => ResourceInventory { items: Vec::new() },
+            };
+            let template_ids = templates.iter().map(|t| t.id.clone()).collect();
+            let (checks, templates) = admit_templates(&cli, templates);
+            write_batch(
+                template_ids,
+                batch_results(checks, multi::solve_multi_project(&templates, &shared_inventory)),
+            );
+        }
+        Request::SolveBackward {
+            template,
+            deadline,
+            inventory,
+        } => {
+            let deadline = match solver::parse_datetime(&deadline) {
+                Some(deadline) => deadline,
+                None => write_err(format!("Invalid deadline '{}'", deadline)),
+            };
+            let inventory_struct: Option<ResourceInventory> =
+                inventory.as_ref().map(|map| build_inventory(&template, map));
+            match solver::solve_backward_from_deadline(
+                &template,
+                deadline,
+                inventory_struct.as_ref(),
+            ) {
                 Ok(solved) => write_ok(solved),
                 Err(e) => write_err(e),
             }
         }
-        Request::Validate { template } => {
+        Request::Validate {
+            template,
+            structured,
+        } => {
             let result = validator::validate(&template);
-            write_ok(result);
+            if cli.format == OutputFormat::Dot {
+                if !result.is_ok() {
+                    write_err(result.error_summary());
+                }
+                write_raw(&export::dot::to_dot(&template, None));
+            } else if structured {
+                write_ok(StructuredValidation {
+                    errors: result.errors,
+                    warnings: result.warnings,
+                });
+            } else {
+                write_ok(result);
+            }
+        }
+        Request::CompressReport { template, inventory } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            let prepared = solver::prepare_template(&template, &SolveOptions::default());
+            write_ok(allocator::find_compressible_steps(&solved, &prepared));
+        }
+        Request::LevelingMetrics { template, inventory } => {
+            let dry_run = SolveOptions {
+                dry_run: true,
+                ..Default::default()
+            };
+            let pre = match solver::solve_with_options(&template, None, &dry_run) {
+                Ok(solved) => solved,
+                Err(e) => write_err(e),
+            };
+            let post = solve_or_exit(&template, inventory.as_ref());
+            write_ok(analysis::leveling_metrics(&pre, &post, &template));
+        }
+        Request::ComparePolicies { template, inventory } => {
+            let inventory = inventory.map(|map| build_inventory(&template, &map));
+            match analysis::compare_timing_policies(&template, inventory.as_ref()) {
+                Ok(report) => write_ok(report),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::Cpm { template } => match solver::cpm_only(&template) {
+            Ok(result) => write_ok(result),
+            Err(e) => write_err(e),
+        },
+        Request::Split { template, inventory } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            let prepared = solver::prepare_template(&template, &SolveOptions::default());
+            write_ok(util::split_schedule_by_track(&solved, &prepared));
+        }
+        Request::Risk { template, inventory } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            write_ok(analysis::identify_schedule_risks(&template, &solved));
+        }
+        Request::SCurve {
+            template,
+            inventory,
+            resource_id,
+            bucket_mins,
+        } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            let curve =
+                analysis::compute_resource_s_curve(&solved, &template, &resource_id, bucket_mins);
+            match curve {
+                Ok(curve) => write_ok(curve),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::FloatHistogram {
+            template,
+            inventory,
+            bucket_size_mins,
+        } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            match analysis::float_histogram(&solved, bucket_size_mins) {
+                Ok(histogram) => write_ok(histogram),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::LoadingReport {
+            template,
+            inventory,
+            period_mins,
+        } => {
+            let solved = solve_or_exit(&template, inventory.as_ref());
+            match analysis::resource_loading_report(&solved, &template, period_mins) {
+                Ok(report) => write_ok(report),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::Schema => {
+            let schema: serde_json::Value =
+                serde_json::from_str(schema::export_json_schema()).expect("schema is valid JSON");
+            write_ok(schema);
+        }
+        Request::ParallelizeReport { template } => {
+            write_ok(analysis::detect_parallel_opportunities(&template));
+        }
+        Request::WhatIfAdd {
+            template,
+            inventory,
+            resource,
+            extra_quantity,
+        } => {
+            let inventory = inventory.map(|map| build_inventory(&template, &map));
+            match analysis::what_if_add_resource(
+                &template,
+                &resource,
+                extra_quantity,
+                inventory.as_ref(),
+            ) {
+                Ok(report) => write_ok(report),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::WhatIfRemove {
+            template,
+            inventory,
+            step_id,
+        } => {
+            let inventory = inventory.map(|map| build_inventory(&template, &map));
+            match analysis::what_if_remove_step(&template, &step_id, inventory.as_ref()) {
+                Ok(report) => write_ok(report),
+                Err(e) => write_err(e),
+            }
+        }
+        Request::SuggestDeps { template } => {
+            write_ok(analysis::suggest_missing_dependencies(&template));
+        }
+        Request::AdjacencyMatrix { template } => match util::to_adjacency_matrix(&template) {
+            Ok(adjacency) => write_ok(adjacency),
+            Err(e) => write_err(e),
+        },
+        Request::TopologicalLevels { template } => match util::topological_levels(&template) {
+            Ok(levels) => write_ok(levels),
+            Err(e) => write_err(e),
+        },
+        Request::Record {
+            mut schedule,
+            updates,
+        } => {
+            let warnings = execution::record_actuals(&mut schedule, &updates);
+            schedule.warnings.extend(warnings);
+            write_ok(schedule);
+        }
+        Request::StepProgress {
+            schedule,
+            step_id,
+            current_time_mins,
+        } => match schedule.solved_steps.iter().find(|s| s.step_id == step_id) {
+            Some(step) => write_ok(execution::interpolate_step_progress(step, current_time_mins)),
+            None => write_err(format!("Step '{}' isn't in the schedule", step_id)),
+        },
+        Request::Merge { base, overlay } => match util::merge_templates(&base, &overlay) {
+            Ok(merged) => write_ok(merged),
+            Err(e) => write_err(e),
+        },
+        Request::GenerateStepId { title, existing_ids } => {
+            let existing: HashSet<&str> = existing_ids.iter().map(String::as_str).collect();
+            write_ok(util::generate_step_id(&title, &existing));
+        }
+        Request::Score {
+            template,
+            baseline,
+            candidate,
+            weights,
+            baseline_inventory,
+            candidate_inventory,
+        } => {
+            let weights = weights.unwrap_or_default();
+            let baseline_inventory = baseline_inventory.map(|map| build_inventory(&template, &map));
+            let candidate_inventory =
+                candidate_inventory.map(|map| build_inventory(&template, &map));
+            write_ok(scoring::compare_schedules(
+                &baseline,
+                baseline_inventory.as_ref(),
+                &candidate,
+                candidate_inventory.as_ref(),
+                &template,
+                &weights,
+            ));
         }
     }
 }
@@ -1,19 +1,33 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::allocator::FloatConsumption;
+use crate::baseline::ScheduleBaseline;
+use crate::gantt::GanttRow;
+use crate::solver::NearCriticalPath;
+
 // ---------------------------------------------------------------------------
 // Enums
 // ---------------------------------------------------------------------------
 
-/// Per-step scheduling policy: schedule as soon as possible or as late as
-/// possible within the available window.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Per-step scheduling policy: schedule as soon as possible, as late as
+/// possible within the available window, or at a fixed time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum TimingPolicy {
     Asap,
     Alap,
+    /// Start exactly `offset_mins` after the project start (e.g. a briefing
+    /// at T+0:30). The step has no float; solving fails if its dependencies
+    /// can't be met by then.
+    #[serde(rename_all = "camelCase")]
+    Fixed {
+        #[serde(alias = "offset_mins")]
+        offset_mins: u32,
+    },
 }
 
 /// The four standard dependency relationship types used in project scheduling.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum DependencyType {
     /// Successor starts after predecessor finishes (most common).
     FinishToStart,
@@ -25,9 +39,20 @@ pub enum DependencyType {
     StartToFinish,
 }
 
+/// How a step combines its dependencies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub enum DependencyMode {
+    /// The step waits for every dependency to be satisfied.
+    #[default]
+    AllOf,
+    /// The step may start once any one dependency is satisfied (e.g. it
+    /// needs the parts from whichever supplier delivers first).
+    AnyOf,
+}
+
 /// Category of a resource, which determines how capacity and quantity are
 /// interpreted.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum ResourceKind {
     /// Physical asset with integer slot capacity (e.g. an oven with 3 spaces).
     Equipment,
@@ -35,34 +60,83 @@ pub enum ResourceKind {
     People,
     /// Items consumed during execution (quantity decreases as steps run).
     Consumable,
+    /// Budget line charged per minute of use. Never capacity-constrained.
+    Cost,
 }
 
 // ---------------------------------------------------------------------------
 // Building blocks
 // ---------------------------------------------------------------------------
+//
+// Template and inventory fields are camelCase on the wire. On input their
+// multi-word fields are also accepted in snake_case (`duration_mins`), which
+// is handier in hand-written files; output is always camelCase.
 
 /// A directed dependency from one step to another.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StepDependency {
     /// The ID of the predecessor step.
+    #[serde(alias = "step_id")]
     pub step_id: String,
+    #[serde(alias = "dependency_type")]
     pub dependency_type: DependencyType,
+    /// FinishToStart only: release the successor once the predecessor is this
+    /// percent complete (1-100) instead of waiting for it to finish.
+    #[serde(alias = "trigger_at_percent")]
+    pub trigger_at_percent: Option<u8>,
+    /// Only enforce this dependency when the condition is listed in
+    /// `SolveOptions::active_conditions`, so one template can describe
+    /// alternative execution paths.
+    pub condition: Option<String>,
+}
+
+/// A dependency on a milestone in another project. The external step isn't
+/// part of this template, so its finish is assumed to happen
+/// `expected_offset_mins` after this schedule starts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalDependency {
+    #[serde(alias = "external_project_id")]
+    pub external_project_id: String,
+    #[serde(alias = "external_step_id")]
+    pub external_step_id: String,
+    /// When the external step is expected to finish, in minutes from the
+    /// start of this schedule. The dependent step can't start earlier.
+    #[serde(alias = "expected_offset_mins")]
+    pub expected_offset_mins: u32,
 }
 
 /// What a single step requires from a resource.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceNeed {
-    /// References a `Resource` by its ID.
+    /// References a `Resource`, or a `ResourceGroup` whose members can be
+    /// used interchangeably, by its ID.
+    #[serde(alias = "resource_id")]
     pub resource_id: String,
     /// How many units/slots/people are needed.
     pub quantity: u32,
     /// For People resources: optional lower bound (overrides `quantity` as the
     /// minimum when set).
+    #[serde(alias = "min_people")]
     pub min_people: Option<u32>,
     /// For People resources: optional upper bound on how many may be assigned.
+    #[serde(alias = "max_people")]
     pub max_people: Option<u32>,
+    /// For People resources: the named role required (must appear in the
+    /// resource's `roles`). `None` means any member of the resource will do.
+    pub role: Option<String>,
+    /// For Cost resources: cost per unit per minute for this step, overriding
+    /// the resource's `unit_cost`.
+    #[serde(alias = "cost_per_minute")]
+    pub cost_per_minute: Option<f64>,
+    /// For Equipment and People resources: when resource splitting is on
+    /// (`SolveOptions::resource_splitting`), the step may start with fewer
+    /// than `quantity` units rather than wait for all of them, taking
+    /// proportionally longer (half the units, twice the duration).
+    #[serde(default, alias = "allow_partial")]
+    pub allow_partial: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -70,36 +144,80 @@ pub struct ResourceNeed {
 // ---------------------------------------------------------------------------
 
 /// A single work unit in a schedule template.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Step {
+    #[schemars(length(min = 1))]
     pub id: String,
     pub title: String,
     pub description: Option<String>,
     /// Duration of this step in minutes. Must be > 0.
+    #[schemars(range(min = 1))]
+    #[serde(alias = "duration_mins")]
     pub duration_mins: u32,
     /// Predecessor dependencies with their relationship type.
     pub dependencies: Vec<StepDependency>,
     /// Optional membership in a `Track`.
+    #[serde(alias = "track_id")]
     pub track_id: Option<String>,
     /// Scheduling policy for this step. Defaults to ASAP when `None`.
+    #[serde(alias = "timing_policy")]
     pub timing_policy: Option<TimingPolicy>,
     /// Resource requirements for this step.
+    #[serde(alias = "resource_needs")]
     pub resource_needs: Vec<ResourceNeed>,
+    /// Work Breakdown Structure code, e.g. "1.2.3". Must be unique.
+    #[serde(alias = "wbs_code")]
+    pub wbs_code: Option<String>,
+    /// Finish-to-start dependencies on steps in other projects.
+    #[serde(default, alias = "external_dependencies")]
+    pub external_dependencies: Vec<ExternalDependency>,
+    /// When set, the step is only scheduled if this flag is listed in
+    /// `SolveOptions::active_conditions` (e.g. "gluten_free").
+    pub condition: Option<String>,
+    /// Whether the step may pause over non-working time in the template's
+    /// `working_calendar` and resume later (e.g. overnight curing).
+    #[serde(default)]
+    pub interruptible: bool,
+    /// Whether the step waits for all of its `dependencies` or just one.
+    #[serde(default, alias = "dependency_mode")]
+    pub dependency_mode: DependencyMode,
+    /// Domain keywords marking optional modules (e.g. "vegan"); see
+    /// `util::filter_steps_by_tags`.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Organizational grouping of steps (e.g. "Kitchen", "Prep Station").
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Track {
+    #[schemars(length(min = 1))]
     pub id: String,
     pub name: String,
+    /// Step IDs that must run one after another in this order. See
+    /// `preprocess::infer_track_dependencies`. Empty by default.
+    #[serde(default)]
+    pub steps: Vec<String>,
+    /// How many of the track's steps (those whose `track_id` names it) may
+    /// run at the same time, e.g. for a cold room that fits two people.
+    #[serde(alias = "max_concurrent")]
+    pub max_concurrent: Option<u32>,
+}
+
+/// How many people of a People resource can fill one of its roles.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleHeadcount {
+    pub role: String,
+    pub headcount: u32,
 }
 
 /// A resource defined by a schedule template.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Resource {
+    #[schemars(length(min = 1))]
     pub id: String,
     pub name: String,
     pub kind: ResourceKind,
@@ -111,31 +229,178 @@ pub struct Resource {
     /// Named roles within a People resource (e.g. ["driver", "navigator"]).
     /// Empty by default.
     pub roles: Vec<String>,
+    /// How many of the resource's people can fill each role. A role without
+    /// an entry can be filled by the whole `capacity`.
+    #[serde(default, alias = "role_headcounts")]
+    pub role_headcounts: Vec<RoleHeadcount>,
+    /// For Cost resources: default cost per unit per minute, used when a
+    /// `ResourceNeed` doesn't set `cost_per_minute`.
+    #[serde(default, alias = "unit_cost")]
+    pub unit_cost: f64,
+    /// Cost per unit per minute of use, for any kind of resource. Charged on
+    /// every assignment as `quantity_used * duration_mins * rate` and
+    /// reported in `SolvedStep::cost` and `ScheduleSummary::total_cost`,
+    /// separately from the Cost-resource charges above.
+    #[serde(alias = "cost_per_unit_per_min")]
+    pub cost_per_unit_per_min: Option<f64>,
+    /// Equipment/People: the share of capacity (0.0 to 1.0) the schedule is
+    /// expected to use at its peak. A lower peak gets a warning after
+    /// allocation, as the idle capacity is reserved all the same.
+    #[schemars(range(min = 0.0, max = 1.0))]
+    #[serde(alias = "utilization_target")]
+    pub utilization_target: Option<f64>,
+}
+
+/// Repeats a template step `count` times, chaining the copies so each one
+/// starts at least `interval_mins` after the previous copy started.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringStepPattern {
+    /// The step to repeat. It is replaced by its copies.
+    #[serde(alias = "template_step_id")]
+    pub template_step_id: String,
+    /// Minimum start-to-start spacing between consecutive copies.
+    #[serde(alias = "interval_mins")]
+    pub interval_mins: u32,
+    pub count: u32,
+    /// Copies get IDs `{prefix}-1`, `{prefix}-2`, ...
+    pub prefix: String,
+}
+
+/// A working period within a `WorkingCalendar` cycle, `[start, end)` in
+/// minutes from the start of the cycle.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingWindow {
+    #[serde(alias = "start_offset_mins")]
+    pub start_offset_mins: u32,
+    #[serde(alias = "end_offset_mins")]
+    pub end_offset_mins: u32,
+}
+
+/// Repeating working hours. The windows describe one cycle (e.g. a day of
+/// 1440 minutes) that starts at the schedule start and repeats indefinitely;
+/// everything outside them is non-working time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingCalendar {
+    #[serde(alias = "cycle_mins")]
+    pub cycle_mins: u32,
+    #[serde(alias = "working_windows")]
+    pub working_windows: Vec<WorkingWindow>,
+}
+
+/// A period when a resource can be used, `[start, end)` in minutes from the
+/// schedule start.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityWindow {
+    #[serde(alias = "start_offset_mins")]
+    pub start_offset_mins: u32,
+    #[serde(alias = "end_offset_mins")]
+    pub end_offset_mins: u32,
+}
+
+/// When an Equipment or People resource is available (e.g. an oven that is
+/// only free in the mornings). Outside its windows the resource can't be
+/// used; steps needing it are moved to the next window they fit in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceCalendar {
+    #[serde(alias = "resource_id")]
+    pub resource_id: String,
+    #[serde(alias = "available_windows")]
+    pub available_windows: Vec<AvailabilityWindow>,
+}
+
+/// A hammock: a summary bar whose span is derived from its member steps,
+/// running from the earliest member start to the latest member finish.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HammockGroup {
+    #[schemars(length(min = 1))]
+    pub id: String,
+    pub name: String,
+    #[serde(alias = "member_step_ids")]
+    pub member_step_ids: Vec<String>,
+}
+
+/// Interchangeable resources. A `ResourceNeed` naming the group's ID is
+/// satisfied by any one member, chosen by the allocator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceGroup {
+    #[schemars(length(min = 1))]
+    pub id: String,
+    pub name: String,
+    #[serde(alias = "resource_ids")]
+    pub resource_ids: Vec<String>,
 }
 
 /// Schedule-level time constraint that drives forward or backward scheduling.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeConstraint {
     /// ISO 8601 datetime string: drives forward scheduling from this point.
+    #[serde(alias = "start_time")]
     pub start_time: Option<String>,
     /// ISO 8601 datetime string: drives backward scheduling from this point.
+    #[serde(alias = "end_time")]
     pub end_time: Option<String>,
+    /// IANA timezone name (e.g. "America/New_York"). When set, `start_time`
+    /// and `end_time` are read as local times in this zone and solved wall
+    /// clock times carry the zone's UTC offset.
+    pub timezone: Option<String>,
 }
 
 /// The user-defined schedule template. Contains no concrete wall-clock times.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleTemplate {
+    #[schemars(length(min = 1))]
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    /// Semantic version of the template (e.g. "1.2.0").
+    pub version: Option<String>,
     pub steps: Vec<Step>,
     pub tracks: Vec<Track>,
     pub resources: Vec<Resource>,
+    #[serde(alias = "time_constraint")]
     pub time_constraint: Option<TimeConstraint>,
     /// Fallback headcount for steps that declare no explicit people need.
+    #[serde(alias = "default_num_people")]
     pub default_num_people: Option<u32>,
+    /// Global cap on how many steps may run at the same time, regardless of
+    /// resources (e.g. a CI system with 4 parallel lanes).
+    #[serde(alias = "max_concurrent_steps")]
+    pub max_concurrent_steps: Option<u32>,
+    /// Steps to repeat; materialized by `expander::expand_recurring` before
+    /// solving.
+    #[serde(default, alias = "recurring_patterns")]
+    pub recurring_patterns: Vec<RecurringStepPattern>,
+    /// Frozen reference plan persisted alongside the template (see
+    /// `baseline::snapshot_baseline`).
+    #[serde(alias = "embedded_baseline")]
+    pub embedded_baseline: Option<ScheduleBaseline>,
+    /// Working hours used to stretch interruptible steps over non-working
+    /// time (see `Step::interruptible`).
+    #[serde(alias = "working_calendar")]
+    pub working_calendar: Option<WorkingCalendar>,
+    /// Summary groups whose spans are computed after solving.
+    #[serde(default, alias = "hammock_groups")]
+    pub hammock_groups: Vec<HammockGroup>,
+    /// Groups of substitutable resources, referenced from `ResourceNeed`s by
+    /// group ID.
+    #[serde(default, alias = "resource_groups")]
+    pub resource_groups: Vec<ResourceGroup>,
+    /// Availability windows of individual resources; resources without a
+    /// calendar are always available.
+    #[serde(default, alias = "resource_calendars")]
+    pub resource_calendars: Vec<ResourceCalendar>,
+    /// Keywords describing the whole template (e.g. "kosher").
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -144,16 +409,18 @@ pub struct ScheduleTemplate {
 
 /// Declares how many of a particular resource the user actually has available
 /// at solve time. This may differ from the template's theoretical `capacity`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceInventoryItem {
     /// References a `Resource` by its ID.
+    #[serde(alias = "resource_id")]
     pub resource_id: String,
+    #[serde(alias = "available_quantity")]
     pub available_quantity: u32,
 }
 
 /// The complete set of real-world resource availability provided at solve time.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceInventory {
     pub items: Vec<ResourceInventoryItem>,
@@ -164,15 +431,52 @@ pub struct ResourceInventory {
 // ---------------------------------------------------------------------------
 
 /// Records which resource was assigned to a solved step, and how much.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AssignedResource {
     pub resource_id: String,
     pub quantity_used: u32,
+    /// The role the assignment was made for, echoed from `ResourceNeed.role`.
+    pub role_used: Option<String>,
+}
+
+/// One uninterrupted stretch of work of a solved interruptible step.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StepSegment {
+    pub start_offset_mins: u32,
+    pub end_offset_mins: u32,
+    /// Wall-clock bounds, populated alongside `SolvedStep::start_time`.
+    pub wall_start: Option<String>,
+    pub wall_end: Option<String>,
+}
+
+/// What a `StepNote` is about.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NoteCode {
+    /// The step was delayed past its slack, or a consumable it needs ran short.
+    ResourceConflict,
+    /// An ALAP step couldn't get its latest slot and was placed earlier.
+    AlternativeSlotAvailable,
+    /// The step waited for resources, using up some of its float.
+    FloatConsumedByAllocation,
+    /// The step finishes after the template's deadline.
+    DeadlineWarning,
+    /// The step started with fewer resource units than it needs, taking
+    /// longer than its duration.
+    PartialResources,
+}
+
+/// A solver diagnostic attached to a single solved step.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StepNote {
+    pub code: NoteCode,
+    pub message: String,
 }
 
 /// A step in a solved schedule with concrete timing information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SolvedStep {
     /// References a `Step` from the template.
@@ -185,24 +489,61 @@ pub struct SolvedStep {
     pub start_time: Option<String>,
     /// Wall-clock end time (ISO 8601) — populated alongside `start_time`.
     pub end_time: Option<String>,
+    /// When the step actually started (ISO 8601), recorded during execution
+    /// (see `execution::record_actuals`).
+    pub actual_start_time: Option<String>,
+    /// When the step actually finished (ISO 8601), recorded during execution.
+    pub actual_end_time: Option<String>,
     pub assigned_resources: Vec<AssignedResource>,
     /// Total float (slack) in minutes. Zero means this step is on the critical path.
     pub total_float_mins: u32,
+    /// Free network float (see `float::compute_free_network_float_per_step`).
+    /// Only filled in on request, e.g. by the CLI's `--detailed-float`.
+    pub path_float_mins: Option<u32>,
     /// True when `total_float_mins == 0` (step is on the critical path).
     pub is_critical: bool,
+    /// Echoed from the template step's `wbs_code`.
+    pub wbs_code: Option<String>,
+    /// Interruptible steps split across non-working time: the working
+    /// stretches between `start_offset_mins` and `end_offset_mins`. Empty
+    /// when the step runs as one contiguous block.
+    #[serde(default)]
+    pub segments: Vec<StepSegment>,
+    /// Diagnostics about how the solver placed this step.
+    #[serde(default)]
+    pub notes: Vec<StepNote>,
+    /// What this step's assigned resources cost at their
+    /// `cost_per_unit_per_min` rates. `None` when none of them has a rate.
+    pub cost: Option<f64>,
+}
+
+/// The solved span of a `HammockGroup`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SolvedHammock {
+    pub group_id: String,
+    pub start_offset_mins: u32,
+    pub end_offset_mins: u32,
+    pub duration_mins: u32,
 }
 
 /// Schedule-level metadata produced alongside the solved steps.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleSummary {
     pub total_duration_mins: u32,
     pub critical_path_step_ids: Vec<String>,
+    /// Sum of all Cost resource charges across the schedule.
+    #[serde(default)]
+    pub project_total_cost: f64,
+    /// Sum of `SolvedStep::cost` over all steps. `None` when no resource in
+    /// the template has a `cost_per_unit_per_min` rate.
+    pub total_cost: Option<f64>,
 }
 
 /// The complete solver output: every step has concrete timing plus summary
 /// metadata and any warnings generated during solving.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SolvedSchedule {
     /// References the originating `ScheduleTemplate` by ID.
@@ -212,4 +553,16 @@ pub struct SolvedSchedule {
     /// Human-readable warnings (e.g. consumable shortfalls, constraint
     /// violations that were relaxed).
     pub warnings: Vec<String>,
+    /// Pre-computed Gantt layout — only populated on request (see
+    /// `gantt::compute_gantt_rows`).
+    pub gantt_rows: Option<Vec<GanttRow>>,
+    /// Chains of steps with little float — only populated on request (see
+    /// `solver::detect_near_critical_paths`).
+    pub near_critical_paths: Option<Vec<NearCriticalPath>>,
+    /// Float used up by resource waits — only populated on request (see
+    /// `allocator::find_float_consuming_resources`).
+    pub float_consumption: Option<Vec<FloatConsumption>>,
+    /// One entry per hammock group with at least one scheduled member.
+    #[serde(default)]
+    pub hammocks: Vec<SolvedHammock>,
 }
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+
+use crate::model::{
+    Resource, ResourceInventory, ResourceKind, ResourceNeed, ScheduleTemplate, SolvedSchedule,
+};
+use crate::solver::{CpmResult, SolveError, SolveOptions};
+
+/// Prefix of the synthetic resources standing in for each project's
+/// `max_concurrent_steps` limit.
+const PROJECT_CONCURRENCY_PREFIX: &str = "__project_concurrent_steps__:";
+
+// ---------------------------------------------------------------------------
+// Multi-project solving
+// ---------------------------------------------------------------------------
+
+/// Solve several independent projects that draw on one shared resource pool
+/// (the same workshop, the same staff).
+///
+/// Each project is prepared as `solve` would (recurring steps expanded,
+/// conditional steps dropped) and gets its own CPM pass and post-CPM
+/// checks, so its critical path, floats and headcount warnings are its own.
+/// The greedy allocator then places the steps of all projects
+/// together against one timeline per resource, in its usual priority order
+/// (critical steps first, then earliest start, then longest duration), so
+/// projects compete for capacity step by step rather than one after another.
+///
+/// Resources are matched across projects by ID; the first project defining
+/// a resource (or a calendar for it) sets its capacity (or availability),
+/// which `shared_inventory` can override. Tracks and `max_concurrent_steps`
+/// limits stay within their project. Allocation warnings concern the shared
+/// pool and are reported on every schedule.
+///
+/// Steps moved by allocation are re-timed through the working calendar as
+/// in `solve`, so all projects must share one: a project whose calendar
+/// differs from the first successful project's fails with
+/// `SolveError::ValidationFailed`.
+///
+/// Returns one result per project, in input order. A project that fails CPM
+/// (e.g. a cycle) or has a different calendar gets its error and takes no
+/// part in allocation.
+pub fn solve_multi_project(
+    projects: &[ScheduleTemplate],
+    shared_inventory: &ResourceInventory,
+) -> Vec<Result<SolvedSchedule, SolveError>> {
+    // Prepare each project as `solve` does and run CPM on it.
+    let options = SolveOptions::default();
+    let mut prepared: Vec<Result<(ScheduleTemplate, CpmResult), SolveError>> = projects
+        .iter()
+        .map(|project| {
+            let template = crate::solver::prepare_template(project, &options).into_owned();
+            let result = crate::solver::cpm(&template)?;
+            Ok((template, result))
+        })
+        .collect();
+
+    // The allocator re-times moved steps through a single working calendar.
+    let calendar = prepared.iter().flatten().next().and_then(|(t, _)| t.working_calendar.clone());
+    for project in &mut prepared {
+        if let Ok((template, _)) = project {
+            if template.working_calendar != calendar {
+                *project = Err(SolveError::ValidationFailed(format!(
+                    "Project '{}' has a different working calendar from the other projects",
+                    template.name
+                )));
+            }
+        }
+    }
+
+    // One combined template and step list, step IDs keyed by project index.
+    let key = |index: usize, step_id: &str| format!("{}:{}", index, step_id);
+    let mut combined = ScheduleTemplate {
+        id: "multi-project".to_string(),
+        name: "Multi-project".to_string(),
+        working_calendar: calendar,
+        ..Default::default()
+    };
+    let mut solved_steps = Vec::new();
+    let mut early_starts: HashMap<String, u32> = HashMap::new();
+    let mut late_starts: HashMap<String, u32> = HashMap::new();
+    let mut owners: Vec<usize> = Vec::new();
+    let mut project_warnings: Vec<Vec<String>> = vec![Vec::new(); projects.len()];
+    for (index, (template, result)) in prepared
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| p.as_ref().ok().map(|p| (i, p)))
+    {
+        let headcounts =
+            crate::validator::validate_after_cpm(template, result, Some(shared_inventory));
+        project_warnings[index] = headcounts.warnings.into_iter().map(|w| w.message).collect();
+
+        // A project's concurrency limit is a resource only its steps need.
+        let concurrency = template.max_concurrent_steps.map(|limit| {
+            let id = format!("{}{}", PROJECT_CONCURRENCY_PREFIX, index);
+            combined.resources.push(Resource {
+                id: id.clone(),
+                name: format!("{} concurrent step limit", template.name),
+                kind: ResourceKind::Equipment,
+                capacity: limit,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            });
+            ResourceNeed {
+                resource_id: id,
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            }
+        });
+        for step in &template.steps {
+            let mut step = step.clone();
+            step.id = key(index, &step.id);
+            for dep in &mut step.dependencies {
+                dep.step_id = key(index, &dep.step_id);
+            }
+            step.track_id = step.track_id.map(|t| key(index, &t));
+            step.resource_needs.extend(concurrency.clone());
+            combined.steps.push(step);
+        }
+        for track in &template.tracks {
+            let mut track = track.clone();
+            track.id = key(index, &track.id);
+            for step_id in &mut track.steps {
+                *step_id = key(index, step_id);
+            }
+            combined.tracks.push(track);
+        }
+        for resource in &template.resources {
+            if !combined.resources.iter().any(|r| r.id == resource.id) {
+                combined.resources.push(resource.clone());
+            }
+        }
+        for calendar in &template.resource_calendars {
+            if !combined.resource_calendars.iter().any(|c| c.resource_id == calendar.resource_id) {
+                combined.resource_calendars.push(calendar.clone());
+            }
+        }
+        for group in &template.resource_groups {
+            if !combined.resource_groups.iter().any(|g| g.id == group.id) {
+                combined.resource_groups.push(group.clone());
+            }
+        }
+        for step in &result.solved_steps {
+            let id = key(index, &step.step_id);
+            early_starts.insert(id.clone(), result.early_starts[&step.step_id]);
+            late_starts.insert(id.clone(), result.late_starts[&step.step_id]);
+            let mut step = step.clone();
+            step.step_id = id;
+            solved_steps.push(step);
+            owners.push(index);
+        }
+    }
+
+    let mut warnings = crate::allocator::allocate_resources(
+        &combined,
+        &mut solved_steps,
+        &early_starts,
+        &late_starts,
+        Some(shared_inventory),
+    );
+    warnings.extend(crate::allocator::check_utilization_targets(
+        &combined,
+        &solved_steps,
+        Some(shared_inventory),
+    ));
+
+    // Hand the placed steps back to their projects.
+    let mut per_project: Vec<Vec<_>> = vec![Vec::new(); projects.len()];
+    for (mut step, index) in solved_steps.into_iter().zip(owners) {
+        step.step_id = step.step_id[step.step_id.find(':').unwrap() + 1..].to_string();
+        step.assigned_resources.retain(|a| !a.resource_id.starts_with(PROJECT_CONCURRENCY_PREFIX));
+        per_project[index].push(step);
+    }
+
+    prepared
+        .into_iter()
+        .zip(per_project)
+        .zip(project_warnings)
+        .map(|((prepared, steps), mut project_warnings)| {
+            let (template, result) = prepared?;
+            let result = CpmResult {
+                solved_steps: steps,
+                ..result
+            };
+            project_warnings.extend(warnings.iter().cloned());
+            crate::solver::finish_schedule(&template, result, project_warnings)
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        DependencyType, Resource, ResourceInventoryItem, ResourceKind, ResourceNeed, Step,
+        StepDependency,
+    };
+
+    fn project(id: &str, bake_mins: u32) -> ScheduleTemplate {
+        ScheduleTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            steps: vec![Step {
+                id: "bake".to_string(),
+                title: "Bake".to_string(),
+                duration_mins: bake_mins,
+                resource_needs: vec![ResourceNeed {
+                    resource_id: "oven".to_string(),
+                    quantity: 1,
+                    min_people: None,
+                    max_people: None,
+                    role: None,
+                    cost_per_minute: None,
+                    allow_partial: false,
+                }],
+                ..Default::default()
+            }],
+            resources: vec![Resource {
+                id: "oven".to_string(),
+                name: "Oven".to_string(),
+                kind: ResourceKind::Equipment,
+                capacity: 1,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_projects_share_resource_pool() {
+        let inventory = ResourceInventory { items: vec![] };
+        let results = solve_multi_project(&[project("bread", 60), project("pie", 30)], &inventory);
+
+        let bread = results[0].as_ref().unwrap();
+        let pie = results[1].as_ref().unwrap();
+        assert_eq!(bread.template_id, "bread");
+        assert_eq!(bread.solved_steps[0].step_id, "bake");
+        // One oven: the pie waits for the bread.
+        assert_eq!(bread.solved_steps[0].start_offset_mins, 0);
+        assert_eq!(pie.solved_steps[0].start_offset_mins, 60);
+        assert_eq!(pie.summary.total_duration_mins, 90);
+
+        // A second oven lets both bake at once.
+        let inventory = ResourceInventory {
+            items: vec![ResourceInventoryItem {
+                resource_id: "oven".to_string(),
+                available_quantity: 2,
+            }],
+        };
+        let results = solve_multi_project(&[project("bread", 60), project("pie", 30)], &inventory);
+        assert_eq!(results[1].as_ref().unwrap().solved_steps[0].start_offset_mins, 0);
+    }
+
+    #[test]
+    fn test_failing_project_does_not_block_others() {
+        let mut broken = project("broken", 10);
+        broken.steps[0].dependencies.push(StepDependency {
+            step_id: "bake".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let inventory = ResourceInventory { items: vec![] };
+        let results = solve_multi_project(&[broken, project("pie", 30)], &inventory);
+
+        assert!(matches!(results[0], Err(SolveError::CyclicDependency(_))));
+        assert_eq!(results[1].as_ref().unwrap().solved_steps[0].start_offset_mins, 0);
+    }
+
+    #[test]
+    fn test_project_with_other_working_calendar_is_rejected() {
+        use crate::model::{WorkingCalendar, WorkingWindow};
+
+        let mut night_shift = project("night", 30);
+        night_shift.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow { start_offset_mins: 0, end_offset_mins: 480 }],
+        });
+        let inventory = ResourceInventory { items: vec![] };
+        let results = solve_multi_project(&[project("bread", 60), night_shift], &inventory);
+
+        assert_eq!(results[0].as_ref().unwrap().solved_steps[0].start_offset_mins, 0);
+        assert!(matches!(&results[1], Err(SolveError::ValidationFailed(e)) if e.contains("night")));
+    }
+
+    #[test]
+    fn test_projects_are_prepared_like_solve() {
+        let start_of = |solved: &SolvedSchedule, id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+        let inventory = ResourceInventory { items: vec![] };
+
+        // An inactive conditional step takes no part, so it can't hold the oven.
+        let mut bread = project("bread", 60);
+        let mut extra = bread.steps[0].clone();
+        extra.id = "extra".to_string();
+        extra.duration_mins = 120;
+        extra.condition = Some("second_batch".to_string());
+        bread.steps.push(extra);
+        let results = solve_multi_project(&[bread, project("pie", 30)], &inventory);
+        let bread = results[0].as_ref().unwrap();
+        assert_eq!(bread.solved_steps.len(), 1);
+        assert_eq!(start_of(bread, "bake"), 0);
+        assert_eq!(start_of(results[1].as_ref().unwrap(), "bake"), 60);
+
+        // With two ovens, a project allowed one step at a time still runs
+        // its own steps one after another, without holding up the other.
+        let mut bread = project("bread", 60);
+        let mut second = bread.steps[0].clone();
+        second.id = "rolls".to_string();
+        second.duration_mins = 20;
+        bread.steps.push(second);
+        bread.max_concurrent_steps = Some(1);
+        bread.resources[0].capacity = 2;
+        let results = solve_multi_project(&[bread, project("pie", 30)], &inventory);
+        let bread = results[0].as_ref().unwrap();
+        assert_eq!((start_of(bread, "bake"), start_of(bread, "rolls")), (0, 60));
+        assert_eq!(bread.solved_steps[1].assigned_resources.len(), 1);
+        assert_eq!(start_of(results[1].as_ref().unwrap(), "bake"), 0);
+
+        // A track limit works the same way.
+        let mut bread = bread_on_one_track();
+        bread.resources[0].capacity = 2;
+        let results = solve_multi_project(&[bread, project("pie", 30)], &inventory);
+        let bread = results[0].as_ref().unwrap();
+        assert_eq!((start_of(bread, "bake"), start_of(bread, "rolls")), (0, 60));
+    }
+
+    /// Bread with two oven steps on a one-at-a-time "counter" track.
+    fn bread_on_one_track() -> ScheduleTemplate {
+        let mut bread = project("bread", 60);
+        let mut rolls = bread.steps[0].clone();
+        rolls.id = "rolls".to_string();
+        rolls.duration_mins = 20;
+        bread.steps.push(rolls);
+        for step in &mut bread.steps {
+            step.track_id = Some("counter".to_string());
+        }
+        bread.tracks.push(crate::model::Track {
+            id: "counter".to_string(),
+            name: "Counter".to_string(),
+            steps: vec![],
+            max_concurrent: Some(1),
+        });
+        bread
+    }
+}
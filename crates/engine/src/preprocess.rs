@@ -0,0 +1,438 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{DependencyType, ResourceKind, ScheduleTemplate, Step, StepDependency, Track};
+use crate::util::generate_step_id;
+
+// ---------------------------------------------------------------------------
+// Track inference
+// ---------------------------------------------------------------------------
+
+/// Assign tracks to untracked steps based on the resources they use.
+///
+/// Every resource used by at least one untracked step becomes a track with
+/// the resource's ID and name (an existing track with that ID is reused), and
+/// the steps using it are placed on it. A step that uses several resources
+/// goes on the alphabetically first of their tracks, with a warning. Steps
+/// that already have a `track_id`, or use no resources, are left alone. Cost
+/// resources are budget lines rather than workstreams and are ignored.
+///
+/// The warnings are listed by `track_inference_warnings`.
+pub fn infer_track_ids_from_resources(template: &ScheduleTemplate) -> ScheduleTemplate {
+    let mut result = template.clone();
+    for (index, candidates) in track_candidates(template) {
+        let (resource_id, resource_name) = candidates[0];
+        if !result.tracks.iter().any(|t| t.id == resource_id) {
+            result.tracks.push(Track {
+                id: resource_id.to_string(),
+                name: resource_name.to_string(),
+                steps: vec![],
+                max_concurrent: None,
+            });
+        }
+        result.steps[index].track_id = Some(resource_id.to_string());
+    }
+    result
+}
+
+/// The warnings `infer_track_ids_from_resources` raises for `template`: one
+/// per step that uses resources from several tracks.
+pub fn track_inference_warnings(template: &ScheduleTemplate) -> Vec<String> {
+    track_candidates(template)
+        .into_iter()
+        .filter(|(_, candidates)| candidates.len() > 1)
+        .map(|(index, candidates)| {
+            let names: Vec<&str> = candidates.iter().map(|c| c.1).collect();
+            format!(
+                "Step '{}' uses resources from several tracks ({}) -- assigned to '{}'",
+                template.steps[index].title,
+                names.join(", "),
+                candidates[0].1
+            )
+        })
+        .collect()
+}
+
+/// For each untracked step using at least one non-cost resource, its index
+/// and the `(id, name)` of those resources, alphabetically by name.
+fn track_candidates(template: &ScheduleTemplate) -> Vec<(usize, Vec<(&str, &str)>)> {
+    let resource_names: HashMap<&str, &str> = template
+        .resources
+        .iter()
+        .filter(|r| !matches!(r.kind, ResourceKind::Cost))
+        .map(|r| (r.id.as_str(), r.name.as_str()))
+        .collect();
+
+    let mut result = Vec::new();
+    for (index, step) in template.steps.iter().enumerate().filter(|(_, s)| s.track_id.is_none()) {
+        let mut candidates: Vec<(&str, &str)> = step
+            .resource_needs
+            .iter()
+            .filter_map(|n| {
+                resource_names
+                    .get_key_value(n.resource_id.as_str())
+                    .map(|(id, name)| (*id, *name))
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
+        candidates.dedup();
+        if !candidates.is_empty() {
+            result.push((index, candidates));
+        }
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Track order
+// ---------------------------------------------------------------------------
+
+/// Turn each track's ordered `steps` list into dependencies: every listed
+/// step gets a `FinishToStart` dependency on the one listed before it.
+///
+/// Pairs already linked by a dependency (in either direction) are left as
+/// they are. Listed IDs that don't match a step are skipped, and the steps
+/// either side of them are chained directly; `validate` warns about them.
+pub fn infer_track_dependencies(template: &ScheduleTemplate) -> ScheduleTemplate {
+    let mut result = template.clone();
+
+    for track in &template.tracks {
+        let mut previous: Option<&str> = None;
+        for step_id in &track.steps {
+            if !template.steps.iter().any(|s| &s.id == step_id) {
+                continue;
+            }
+            if let Some(prev) = previous {
+                let linked = |from: &str, to: &str| {
+                    result
+                        .steps
+                        .iter()
+                        .any(|s| s.id == from && s.dependencies.iter().any(|d| d.step_id == to))
+                };
+                if !linked(step_id, prev) && !linked(prev, step_id) {
+                    let step = result.steps.iter_mut().find(|s| &s.id == step_id).unwrap();
+                    step.dependencies.push(StepDependency {
+                        step_id: prev.to_string(),
+                        dependency_type: DependencyType::FinishToStart,
+                        trigger_at_percent: None,
+                        condition: None,
+                    });
+                }
+            }
+            previous = Some(step_id);
+        }
+    }
+
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Schedule buffers
+// ---------------------------------------------------------------------------
+
+/// Tag carried by the steps `auto_insert_buffers` adds. The solver lists
+/// steps with this tag in the schedule's `warnings`.
+pub const BUFFER_TAG: &str = "ccpm-buffer";
+
+/// Add critical chain buffers to a template, CCPM-style.
+///
+/// A "Project Buffer" step of `buffer_fraction` times the critical path's
+/// duration follows the last critical steps. Wherever a non-critical step
+/// feeds a critical one through a finish-to-start dependency, a "Feeding
+/// Buffer" step of `buffer_fraction` times the length of the non-critical
+/// chain ending there is put between them. Conditional steps and
+/// dependencies are left alone, as are buffers that would round to zero
+/// minutes.
+///
+/// Buffer steps are tagged `BUFFER_TAG`. The template is returned unchanged
+/// when `buffer_fraction` isn't positive or CPM fails (solving it reports the
+/// error).
+pub fn auto_insert_buffers(template: &ScheduleTemplate, buffer_fraction: f64) -> ScheduleTemplate {
+    let mut result = template.clone();
+    if buffer_fraction.is_nan() || buffer_fraction <= 0.0 {
+        return result;
+    }
+    let Ok(cpm) = crate::solver::cpm_only(template) else {
+        return result;
+    };
+    let buffer_mins = |mins: u32| (mins as f64 * buffer_fraction).round() as u32;
+
+    let steps_by_id: HashMap<&str, &Step> =
+        template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let critical: HashSet<&str> = cpm
+        .solved_steps
+        .iter()
+        .filter(|s| s.is_critical && steps_by_id.contains_key(s.step_id.as_str()))
+        .map(|s| s.step_id.as_str())
+        .collect();
+    let mut ids: HashSet<String> = template.steps.iter().map(|s| s.id.clone()).collect();
+    let mut new_id = |slug: &str| {
+        let existing: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        let id = generate_step_id(slug, &existing);
+        ids.insert(id.clone());
+        id
+    };
+    let feeds = |d: &StepDependency| {
+        d.dependency_type == DependencyType::FinishToStart
+            && d.condition.is_none()
+            && !critical.contains(d.step_id.as_str())
+            && steps_by_id.get(d.step_id.as_str()).is_some_and(|s| s.condition.is_none())
+    };
+
+    // Non-critical steps feeding the critical path, in template order.
+    let mut feeding: Vec<&str> = Vec::new();
+    for step in template.steps.iter().filter(|s| critical.contains(s.id.as_str())) {
+        for dep in step.dependencies.iter().filter(|d| feeds(d)) {
+            if !feeding.contains(&dep.step_id.as_str()) {
+                feeding.push(dep.step_id.as_str());
+            }
+        }
+    }
+
+    let mut chain_lengths: HashMap<&str, u32> = HashMap::new();
+    let mut buffers: Vec<Step> = Vec::new();
+    for feeder in feeding {
+        let mins = buffer_mins(feeding_chain_mins(
+            feeder,
+            &steps_by_id,
+            &critical,
+            &mut chain_lengths,
+        ));
+        if mins == 0 {
+            continue;
+        }
+        let id = new_id(&format!("feeding-buffer-{}", feeder));
+        for step in result.steps.iter_mut().filter(|s| critical.contains(s.id.as_str())) {
+            for dep in step.dependencies.iter_mut() {
+                if dep.step_id == feeder && feeds(dep) {
+                    dep.step_id = id.clone();
+                }
+            }
+        }
+        buffers.push(buffer_step(
+            id,
+            format!("Feeding Buffer ({})", steps_by_id[feeder].title),
+            mins,
+            &[feeder],
+        ));
+    }
+
+    let mut chain_ends: Vec<&str> = cpm
+        .solved_steps
+        .iter()
+        .filter(|s| critical.contains(s.step_id.as_str()) && s.end_offset_mins == cpm.project_end)
+        .map(|s| s.step_id.as_str())
+        .collect();
+    chain_ends.sort_unstable();
+    let mins = buffer_mins(cpm.project_end);
+    if !chain_ends.is_empty() && mins > 0 {
+        let id = new_id("project-buffer");
+        buffers.push(buffer_step(id, "Project Buffer".to_string(), mins, &chain_ends));
+    }
+
+    result.steps.extend(buffers);
+    result
+}
+
+/// Longest run of non-critical steps ending with `step_id`, in minutes.
+fn feeding_chain_mins<'a>(
+    step_id: &'a str,
+    steps_by_id: &HashMap<&'a str, &'a Step>,
+    critical: &HashSet<&str>,
+    memo: &mut HashMap<&'a str, u32>,
+) -> u32 {
+    if let Some(&mins) = memo.get(step_id) {
+        return mins;
+    }
+    let step = steps_by_id[step_id];
+    let mut longest_before = 0;
+    for dep in &step.dependencies {
+        if let Some((&pred_id, _)) = steps_by_id.get_key_value(dep.step_id.as_str()) {
+            if !critical.contains(pred_id) {
+                longest_before = longest_before
+                    .max(feeding_chain_mins(pred_id, steps_by_id, critical, memo));
+            }
+        }
+    }
+    let mins = longest_before + step.duration_mins;
+    memo.insert(step_id, mins);
+    mins
+}
+
+fn buffer_step(id: String, title: String, duration_mins: u32, after: &[&str]) -> Step {
+    Step {
+        id,
+        title,
+        duration_mins,
+        dependencies: after
+            .iter()
+            .map(|pred| StepDependency {
+                step_id: pred.to_string(),
+                dependency_type: DependencyType::FinishToStart,
+                trigger_at_percent: None,
+                condition: None,
+            })
+            .collect(),
+        tags: vec![BUFFER_TAG.to_string()],
+        ..Default::default()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Resource, ResourceNeed, Step};
+
+    fn need(resource_id: &str) -> ResourceNeed {
+        ResourceNeed {
+            resource_id: resource_id.to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: false,
+        }
+    }
+
+    fn step(id: &str, needs: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            resource_needs: needs.iter().map(|r| need(r)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn resource(id: &str, name: &str) -> Resource {
+        Resource {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }
+    }
+
+    #[test]
+    fn test_buffers_protect_the_critical_chain() {
+        let mut mix = step("mix", &[]);
+        mix.duration_mins = 60;
+        let mut glaze = step("glaze", &[]);
+        glaze.duration_mins = 20;
+        let mut bake = step("bake", &[]);
+        bake.duration_mins = 30;
+        for pred in ["mix", "glaze"] {
+            bake.dependencies.push(StepDependency {
+                step_id: pred.to_string(),
+                dependency_type: DependencyType::FinishToStart,
+                trigger_at_percent: None,
+                condition: None,
+            });
+        }
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![mix, glaze, bake],
+            ..Default::default()
+        };
+        let buffered = auto_insert_buffers(&template, 0.5);
+
+        let find = |id: &str| buffered.steps.iter().find(|s| s.id == id).unwrap();
+        let feeding = find("feeding-buffer-glaze");
+        assert_eq!(feeding.title, "Feeding Buffer (glaze)");
+        assert_eq!(feeding.duration_mins, 10);
+        assert_eq!(feeding.dependencies[0].step_id, "glaze");
+        let bake_preds: Vec<&str> =
+            find("bake").dependencies.iter().map(|d| d.step_id.as_str()).collect();
+        assert_eq!(bake_preds, vec!["mix", "feeding-buffer-glaze"]);
+        let project = find("project-buffer");
+        assert_eq!(project.duration_mins, 45);
+        assert_eq!(project.dependencies[0].step_id, "bake");
+
+        let solved = crate::solver::solve(&buffered, None).unwrap();
+        assert_eq!(solved.summary.total_duration_mins, 135);
+        assert!(solved.warnings.iter().any(|w| w.contains("'project-buffer'")));
+        assert_eq!(auto_insert_buffers(&template, 0.0), template);
+    }
+
+    #[test]
+    fn test_track_order_becomes_dependencies() {
+        let mut bake = step("bake", &[]);
+        bake.dependencies.push(StepDependency {
+            step_id: "preheat".to_string(),
+            dependency_type: DependencyType::StartToStart,
+            trigger_at_percent: None,
+            condition: None,
+        });
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("preheat", &[]), bake, step("cool", &[])],
+            tracks: vec![Track {
+                id: "oven".to_string(),
+                name: "Oven".to_string(),
+                steps: vec![
+                    "preheat".to_string(),
+                    "bake".to_string(),
+                    "glaze".to_string(),
+                    "cool".to_string(),
+                ],
+                max_concurrent: None,
+            }],
+            ..Default::default()
+        };
+        let result = infer_track_dependencies(&template);
+
+        let deps_of = |id: &str| -> Vec<(String, DependencyType)> {
+            let step = result.steps.iter().find(|s| s.id == id).unwrap();
+            step.dependencies
+                .iter()
+                .map(|d| (d.step_id.clone(), d.dependency_type.clone()))
+                .collect()
+        };
+        assert!(deps_of("preheat").is_empty());
+        // The existing dependency is kept as declared.
+        assert_eq!(deps_of("bake"), vec![("preheat".to_string(), DependencyType::StartToStart)]);
+        // "glaze" doesn't exist, so "cool" follows "bake" directly.
+        assert_eq!(deps_of("cool"), vec![("bake".to_string(), DependencyType::FinishToStart)]);
+    }
+
+    #[test]
+    fn test_infers_one_track_per_resource() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("bake", &["oven"]),
+                step("chop", &["board"]),
+                step("both", &["oven", "board"]),
+                step("free", &[]),
+            ],
+            resources: vec![resource("oven", "Oven"), resource("board", "Board")],
+            ..Default::default()
+        };
+        let result = infer_track_ids_from_resources(&template);
+        let warnings = track_inference_warnings(&template);
+
+        let track_of = |id: &str| {
+            result.steps.iter().find(|s| s.id == id).unwrap().track_id.clone()
+        };
+        assert_eq!(track_of("bake").as_deref(), Some("oven"));
+        assert_eq!(track_of("chop").as_deref(), Some("board"));
+        // "Board" sorts before "Oven".
+        assert_eq!(track_of("both").as_deref(), Some("board"));
+        assert_eq!(track_of("free"), None);
+        assert_eq!(result.tracks.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'both'"));
+    }
+}
@@ -0,0 +1,21 @@
+use std::sync::OnceLock;
+
+use crate::model::ScheduleTemplate;
+
+// ---------------------------------------------------------------------------
+// JSON Schema
+// ---------------------------------------------------------------------------
+
+/// JSON Schema (draft 2020-12) for `ScheduleTemplate` as the engine reads
+/// it, for editors and other tools that validate or auto-complete templates.
+///
+/// Generated from the model types, so it always matches what the engine
+/// accepts: required fields, enum variants, non-empty IDs and positive step
+/// durations. Built once and cached.
+pub fn export_json_schema() -> &'static str {
+    static SCHEMA: OnceLock<String> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        let schema = schemars::schema_for!(ScheduleTemplate);
+        serde_json::to_string_pretty(&schema).expect("schema serializes to JSON")
+    })
+}
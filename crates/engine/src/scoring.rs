@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ResourceInventory, ResourceKind, ScheduleTemplate, SolvedSchedule};
+use crate::solver::SolveOptions;
+use crate::util::effective_capacity;
+
+// ---------------------------------------------------------------------------
+// Score types
+// ---------------------------------------------------------------------------
+
+/// Relative weight of each objective in `ScheduleScore::total`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreWeights {
+    pub duration: f64,
+    pub resource_utilization: f64,
+    pub critical_path_length: f64,
+    pub total_float: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            duration: 1.0,
+            resource_utilization: 1.0,
+            critical_path_length: 1.0,
+            total_float: 1.0,
+        }
+    }
+}
+
+/// Per-objective scores, each in `[0, 1]` where higher is better, plus their
+/// weighted sum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleScore {
+    pub total: f64,
+    /// CPM lower-bound duration / actual duration. 1.0 means resource
+    /// allocation didn't stretch the schedule at all.
+    pub duration_score: f64,
+    /// Average share of Equipment/People capacity (after inventory
+    /// overrides) in use over the schedule.
+    pub utilization_score: f64,
+    /// Average total float as a share of the schedule duration.
+    pub float_score: f64,
+}
+
+/// Side-by-side scores of two solved schedules of the same template.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreComparison {
+    pub baseline: ScheduleScore,
+    pub candidate: ScheduleScore,
+    /// `candidate.total - baseline.total`; positive means the candidate is better.
+    pub total_delta: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Scoring
+// ---------------------------------------------------------------------------
+
+/// Score a solved schedule against several objectives and combine them with
+/// `weights`. `inventory` should be the one `solved` was solved with.
+///
+/// The critical-path objective rewards schedules where fewer steps are
+/// critical (`1 - critical / total`); it contributes to `total` but has no
+/// separate field.
+pub fn schedule_score(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    weights: &ScoreWeights,
+) -> ScheduleScore {
+    let duration = solved.summary.total_duration_mins;
+    let step_count = solved.solved_steps.len();
+
+    let duration_score = if duration == 0 {
+        1.0
+    } else {
+        // CPM on the template solve actually schedules: recurring copies
+        // expanded and inactive conditional steps dropped.
+        let prepared = crate::solver::prepare_template(template, &SolveOptions::default());
+        let lower_bound = crate::solver::cpm(&prepared)
+            .ok()
+            .and_then(|r| r.solved_steps.iter().map(|s| s.end_offset_mins).max())
+            .unwrap_or(duration);
+        (lower_bound as f64 / duration as f64).min(1.0)
+    };
+
+    let timed: Vec<_> = template
+        .resources
+        .iter()
+        .filter(|r| matches!(r.kind, ResourceKind::Equipment | ResourceKind::People))
+        .map(|r| (r, effective_capacity(r, inventory)))
+        .filter(|&(_, capacity)| capacity > 0)
+        .collect();
+    let utilization_score = if timed.is_empty() || duration == 0 {
+        0.0
+    } else {
+        let sum: f64 = timed
+            .iter()
+            .map(|&(r, capacity)| {
+                let used_minutes: u64 = solved
+                    .solved_steps
+                    .iter()
+                    .flat_map(|s| {
+                        let dur = (s.end_offset_mins - s.start_offset_mins) as u64;
+                        s.assigned_resources
+                            .iter()
+                            .filter(|a| a.resource_id == r.id)
+                            .map(move |a| a.quantity_used as u64 * dur)
+                    })
+                    .sum();
+                let available = capacity as u64 * duration as u64;
+                (used_minutes as f64 / available as f64).min(1.0)
+            })
+            .sum();
+        sum / timed.len() as f64
+    };
+
+    let critical_path_score = if step_count == 0 {
+        1.0
+    } else {
+        let critical = solved.solved_steps.iter().filter(|s| s.is_critical).count();
+        1.0 - critical as f64 / step_count as f64
+    };
+
+    let float_score = if step_count == 0 || duration == 0 {
+        0.0
+    } else {
+        let total_float: u64 = solved
+            .solved_steps
+            .iter()
+            .map(|s| s.total_float_mins as u64)
+            .sum();
+        (total_float as f64 / step_count as f64 / duration as f64).min(1.0)
+    };
+
+    let total = weights.duration * duration_score
+        + weights.resource_utilization * utilization_score
+        + weights.critical_path_length * critical_path_score
+        + weights.total_float * float_score;
+
+    ScheduleScore {
+        total,
+        duration_score,
+        utilization_score,
+        float_score,
+    }
+}
+
+/// Score two solved schedules of the same template with the same weights,
+/// each against the inventory it was solved with.
+pub fn compare_schedules(
+    baseline: &SolvedSchedule,
+    baseline_inventory: Option<&ResourceInventory>,
+    candidate: &SolvedSchedule,
+    candidate_inventory: Option<&ResourceInventory>,
+    template: &ScheduleTemplate,
+    weights: &ScoreWeights,
+) -> ScoreComparison {
+    let baseline = schedule_score(baseline, template, baseline_inventory, weights);
+    let candidate = schedule_score(candidate, template, candidate_inventory, weights);
+    let total_delta = candidate.total - baseline.total;
+    ScoreComparison {
+        baseline,
+        candidate,
+        total_delta,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Resource, ResourceInventory, ResourceInventoryItem, ResourceNeed, Step,
+    };
+
+    fn oven_template() -> ScheduleTemplate {
+        let step = |id: &str| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            duration_mins: 30,
+            dependencies: vec![],
+            track_id: None,
+            timing_policy: None,
+            resource_needs: vec![ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            }],
+            ..Default::default()
+        };
+        ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            steps: vec![step("a"), step("b")],
+            tracks: vec![],
+            resources: vec![Resource {
+                id: "oven".to_string(),
+                name: "Oven".to_string(),
+                kind: ResourceKind::Equipment,
+                capacity: 2,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_schedule_scores_full_duration() {
+        let template = oven_template();
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let score = schedule_score(&solved, &template, None, &ScoreWeights::default());
+        assert_eq!(score.duration_score, 1.0);
+        assert_eq!(score.utilization_score, 1.0);
+    }
+
+    #[test]
+    fn test_inventory_override_lowers_duration_score() {
+        let template = oven_template();
+        let full = crate::solver::solve(&template, None).unwrap();
+        let inventory = ResourceInventory {
+            items: vec![ResourceInventoryItem {
+                resource_id: "oven".to_string(),
+                available_quantity: 1,
+            }],
+        };
+        let limited = crate::solver::solve(&template, Some(&inventory)).unwrap();
+
+        let weights = ScoreWeights::default();
+        let cmp = compare_schedules(&full, None, &limited, Some(&inventory), &template, &weights);
+        assert_eq!(cmp.candidate.duration_score, 0.5);
+        // The one oven in stock is busy throughout.
+        assert_eq!(cmp.candidate.utilization_score, 1.0);
+        assert!(cmp.total_delta < 0.0);
+    }
+}
@@ -1,14 +1,26 @@
-use std::collections::HashMap;
-
-use chrono::NaiveDateTime;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
 use petgraph::algo::{is_cyclic_directed, toposort};
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use petgraph::Direction;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::allocator::StepPriority;
+use crate::calendar;
 use crate::model::{
-    AssignedResource, DependencyType, ResourceInventory, ScheduleSummary, ScheduleTemplate,
-    SolvedSchedule, SolvedStep, TimingPolicy,
+    AssignedResource, DependencyMode, DependencyType, NoteCode, Resource, ResourceCalendar,
+    ResourceInventory, ScheduleSummary, ScheduleTemplate, SolvedHammock, SolvedSchedule,
+    SolvedStep, Step, StepDependency, StepNote, StepSegment, TimeConstraint, TimingPolicy,
 };
 
 // ---------------------------------------------------------------------------
@@ -25,15 +37,57 @@ pub enum SolveError {
     MissingDuration(String),
     #[error("{0}")]
     ValidationFailed(String),
+    #[error("Solve timed out after {0} ms")]
+    Timeout(u64),
+}
+
+/// Why `solve_with_max_duration` rejected a template.
+#[derive(Debug, thiserror::Error)]
+pub enum ConstraintViolation {
+    #[error("Schedule takes {computed} min, more than the maximum of {limit} min")]
+    MaxDurationExceeded { computed: u32, limit: u32 },
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+}
+
+// ---------------------------------------------------------------------------
+// Solve options
+// ---------------------------------------------------------------------------
+
+/// Knobs for `solve_with_options`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveOptions {
+    /// Return CPM timings only: skip resource allocation, leaving every step's
+    /// `assigned_resources` empty.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Don't run `validator::validate` before solving. When validation runs,
+    /// any error aborts the solve with `SolveError::ValidationFailed`.
+    #[serde(default)]
+    pub skip_validation: bool,
+    /// Flags enabling conditional steps and dependencies. A step whose
+    /// `condition` isn't listed here is removed before solving, along with
+    /// dependencies on it; a dependency whose `condition` isn't listed is
+    /// dropped.
+    #[serde(default)]
+    pub active_conditions: Vec<String>,
+    /// Let resource needs marked `allow_partial` start with fewer units when
+    /// that finishes the step sooner than waiting for all of them.
+    #[serde(default)]
+    pub resource_splitting: bool,
 }
 
 // ---------------------------------------------------------------------------
-// Internal CPM result
+// CPM result
 // ---------------------------------------------------------------------------
 
-/// Internal CPM result used to pass intermediate data to the allocator.
-/// Kept as a pub(crate) struct so allocator.rs can access it.
-pub(crate) struct CpmResult {
+/// Raw CPM output, before any resource allocation. The early/late start
+/// windows are what an allocator may move steps within; custom allocators
+/// can obtain them via `cpm_only`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpmResult {
     pub solved_steps: Vec<SolvedStep>,
     /// Early start time (minutes) keyed by step_id — used by resource allocator.
     pub early_starts: HashMap<String, u32>,
@@ -54,25 +108,10 @@ const DATETIME_FORMATS: &[&str] = &[
     "%Y-%m-%d",
 ];
 
-fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
-    // Strip trailing Z or timezone offset for NaiveDateTime parsing.
-    let s = s.trim_end_matches('Z');
-    let s = if let Some(pos) = s.rfind('+') {
-        if pos > 10 {
-            &s[..pos]
-        } else {
-            s
-        }
-    } else {
-        s
-    };
-    // Also strip -HH:MM timezone offsets
-    let s = if s.len() > 19 && s.chars().nth(19) == Some('-') {
-        &s[..19]
-    } else {
-        s
-    };
-
+/// Parse an ISO 8601 date or datetime as a naive local time, ignoring any
+/// UTC offset. Returns `None` for anything else.
+pub fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
+    let s = strip_utc_offset(s.trim());
     for fmt in DATETIME_FORMATS {
         if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
             return Some(dt);
@@ -85,10 +124,102 @@ fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
     None
 }
 
+/// Drop a trailing ISO 8601 UTC designator (`Z`, `+HH:MM`, `-HH:MM`,
+/// `+HHMM` or `+HH`) so the rest parses as a naive local time. Only the time
+/// part is searched, so the dashes of a bare date are never mistaken for an
+/// offset.
+fn strip_utc_offset(s: &str) -> &str {
+    if let Some(rest) = s.strip_suffix(['Z', 'z']) {
+        return rest;
+    }
+    let Some(time_start) = s.find(['T', ' ']) else {
+        return s;
+    };
+    let Some(sign) = s[time_start..].rfind(['+', '-']).map(|i| time_start + i) else {
+        return s;
+    };
+    let offset = &s[sign + 1..];
+    let digits: String = offset.chars().filter(|&c| c != ':').collect();
+    let well_formed = matches!(digits.len(), 2 | 4)
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && (offset.len() == digits.len() || offset.find(':') == Some(2));
+    if well_formed {
+        &s[..sign]
+    } else {
+        s
+    }
+}
+
 fn format_datetime(dt: NaiveDateTime) -> String {
     dt.format("%Y-%m-%dT%H:%M:%S").to_string()
 }
 
+/// Parse the optional IANA timezone name of a template's time constraint.
+fn parse_timezone(template: &ScheduleTemplate) -> Result<Option<Tz>, SolveError> {
+    match template.time_constraint.as_ref().and_then(|tc| tc.timezone.as_deref()) {
+        Some(name) => name
+            .parse::<Tz>()
+            .map(Some)
+            .map_err(|_| SolveError::ValidationFailed(format!("Unknown timezone '{}'", name))),
+        None => Ok(None),
+    }
+}
+
+/// Minutes from the time constraint's start to its end when both are set:
+/// the deadline of a forward-scheduled template.
+pub(crate) fn deadline_mins(template: &ScheduleTemplate, tz: Option<Tz>) -> Option<i64> {
+    let tc = template.time_constraint.as_ref()?;
+    let start = parse_datetime(tc.start_time.as_deref()?)?;
+    let end = parse_datetime(tc.end_time.as_deref()?)?;
+    Some(minutes_between(start, end, tz).max(0))
+}
+
+/// Resolve a naive local time in `tz`. Ambiguous times (DST fall-back) take
+/// the earlier instant; times that don't exist (DST spring-forward gap) are
+/// moved forward past the gap.
+fn resolve_local(tz: Tz, dt: NaiveDateTime) -> DateTime<Tz> {
+    match tz.from_local_datetime(&dt) {
+        LocalResult::Single(t) => t,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => tz
+            .from_local_datetime(&(dt + chrono::Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(|| tz.from_utc_datetime(&dt)),
+    }
+}
+
+/// Format `anchor + offset_mins` as ISO 8601. With a timezone the arithmetic
+/// happens in absolute time — so a step spanning a DST change covers the
+/// right number of real minutes — and the output carries the zone's UTC
+/// offset at that instant.
+fn wall_clock(anchor: NaiveDateTime, tz: Option<Tz>, offset_mins: i64) -> String {
+    let delta = chrono::Duration::minutes(offset_mins);
+    match tz {
+        Some(tz) => (resolve_local(tz, anchor) + delta)
+            .format("%Y-%m-%dT%H:%M:%S%:z")
+            .to_string(),
+        None => format_datetime(anchor + delta),
+    }
+}
+
+/// Real minutes elapsed between two local times, honouring DST when a
+/// timezone is known.
+fn minutes_between(start: NaiveDateTime, end: NaiveDateTime, tz: Option<Tz>) -> i64 {
+    match tz {
+        Some(tz) => (resolve_local(tz, end) - resolve_local(tz, start)).num_minutes(),
+        None => (end - start).num_minutes(),
+    }
+}
+
+/// Minutes into the predecessor at which a FinishToStart dependency releases
+/// its successor: the full duration unless `trigger_at_percent` is set.
+pub(crate) fn trigger_point(dep: &StepDependency, pred_dur: i64) -> i64 {
+    match dep.trigger_at_percent {
+        Some(pct) => pred_dur * i64::from(pct.min(100)) / 100,
+        None => pred_dur,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Core CPM implementation
 // ---------------------------------------------------------------------------
@@ -118,14 +249,14 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
     //    Edge weight = DependencyType
     // -----------------------------------------------------------------------
     let n = template.steps.len();
-    let mut graph: DiGraph<usize, DependencyType> = DiGraph::with_capacity(n, n);
+    let mut graph: DiGraph<usize, &StepDependency> = DiGraph::with_capacity(n, n);
 
     // Add nodes first; node index == step index
     let node_indices: Vec<NodeIndex> = (0..n).map(|i| graph.add_node(i)).collect();
 
-    // Add edges (predecessor → successor)
+    // Add edges (predecessor → successor), one per distinct dependency.
     for (succ_idx, step) in template.steps.iter().enumerate() {
-        for dep in &step.dependencies {
+        for dep in unique_dependencies(step) {
             let pred_idx = match id_to_idx.get(dep.step_id.as_str()) {
                 Some(&idx) => idx,
                 None => {
@@ -135,11 +266,7 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
                     ))
                 }
             };
-            graph.add_edge(
-                node_indices[pred_idx],
-                node_indices[succ_idx],
-                dep.dependency_type.clone(),
-            );
+            graph.add_edge(node_indices[pred_idx], node_indices[succ_idx], dep);
         }
     }
 
@@ -169,49 +296,133 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
     // 5. Forward pass — compute Early Start (ES) and Early Finish (EF)
     //    All values in minutes (i64 to avoid underflow).
     // -----------------------------------------------------------------------
-    let mut es: Vec<i64> = vec![0; n]; // Early Start
+    // External dependencies pin the earliest start to their expected offset.
+    let mut es: Vec<i64> = template
+        .steps
+        .iter()
+        .map(|s| {
+            s.external_dependencies
+                .iter()
+                .map(|d| d.expected_offset_mins as i64)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect(); // Early Start
     let mut ef: Vec<i64> = vec![0; n]; // Early Finish
 
+    // Interruptible steps stretch over non-working time in the calendar, so
+    // their finish depends on when they start. Other steps wait for a
+    // working stretch long enough to hold them, unless they are fixed.
+    let calendar = template.working_calendar.as_ref().filter(|c| calendar::is_usable(c));
+    let fixed_offset = |i: usize| match template.steps[i].timing_policy {
+        Some(TimingPolicy::Fixed { offset_mins }) => Some(offset_mins as i64),
+        _ => None,
+    };
+    let stretches = |i: usize| calendar.filter(|_| template.steps[i].interruptible);
+    let fits = |i: usize| {
+        calendar.filter(|_| !template.steps[i].interruptible && fixed_offset(i).is_none())
+    };
+    // When `work` minutes of step `i`'s work are done, if it starts at `start`.
+    let progress_at = |i: usize, start: i64, work: u32| -> i64 {
+        match stretches(i) {
+            Some(cal) if work > 0 => calendar::finish_from(cal, start.max(0) as u32, work) as i64,
+            _ => start + work as i64,
+        }
+    };
+    let finish_at = |i: usize, start: i64| progress_at(i, start, template.steps[i].duration_mins);
+    // The earliest start at or after `t` the calendar allows.
+    let earliest_start = |i: usize, t: i64| -> i64 {
+        let dur = template.steps[i].duration_mins;
+        let t_mins = t.max(0) as u32;
+        let start = match (stretches(i), fits(i)) {
+            (Some(cal), _) => Some(calendar::next_working_minute(cal, t_mins)),
+            (None, Some(cal)) => calendar::next_fit_start(cal, t_mins, dur),
+            (None, None) => None,
+        };
+        start.map_or(t, |s| s as i64)
+    };
+    // The latest start from which `work` minutes of step `i`'s work are done
+    // by `by`.
+    let latest_start_reaching = |i: usize, by: i64, work: u32| -> i64 {
+        let dur = template.steps[i].duration_mins;
+        let start = match (stretches(i), fits(i)) {
+            _ if by <= 0 => None,
+            (Some(cal), _) if work > 0 => calendar::start_before(cal, by as u32, work),
+            (None, Some(cal)) if work == dur => calendar::latest_fit_start(cal, by as u32, dur),
+            _ => None,
+        };
+        start.map_or(by - work as i64, |s| s as i64)
+    };
+    let start_for =
+        |i: usize, finish: i64| latest_start_reaching(i, finish, template.steps[i].duration_mins);
+
+    // Earliest start released by any one predecessor of each AnyOf step.
+    let any_of = |i: usize| template.steps[i].dependency_mode == DependencyMode::AnyOf;
+    let mut any_of_release: Vec<Option<i64>> = vec![None; n];
+    let released_at = |pred: usize, succ: usize, dep: &StepDependency, es: &[i64], ef: &[i64]| {
+        let pred_dur = template.steps[pred].duration_mins as i64;
+        let succ_dur = template.steps[succ].duration_mins as i64;
+        let start = match dep.dependency_type {
+            DependencyType::FinishToStart => match trigger_point(dep, pred_dur) {
+                trigger if trigger == pred_dur => ef[pred],
+                trigger => progress_at(pred, es[pred], trigger as u32),
+            },
+            DependencyType::StartToStart => es[pred],
+            DependencyType::FinishToFinish => ef[pred] - succ_dur,
+            DependencyType::StartToFinish => es[pred] - succ_dur,
+        };
+        start.max(0)
+    };
+
     for node in &topo_order {
         let step_idx = *graph.node_weight(*node).unwrap();
-        let dur = template.steps[step_idx].duration_mins as i64;
-        ef[step_idx] = es[step_idx] + dur;
+        if let Some(release) = any_of_release[step_idx] {
+            es[step_idx] = es[step_idx].max(release);
+        }
+        if let Some(offset) = fixed_offset(step_idx) {
+            if es[step_idx] > offset {
+                return Err(SolveError::ValidationFailed(format!(
+                    "Step '{}' is fixed to start at {} minutes but its dependencies allow no earlier than {}",
+                    template.steps[step_idx].id, offset, es[step_idx]
+                )));
+            }
+            es[step_idx] = offset;
+        }
+        es[step_idx] = earliest_start(step_idx, es[step_idx]);
+        ef[step_idx] = finish_at(step_idx, es[step_idx]);
 
         // Propagate to successors
         for edge in graph.edges(*node) {
             let succ_node = edge.target();
             let succ_idx = *graph.node_weight(succ_node).unwrap();
-            let succ_dur = template.steps[succ_idx].duration_mins as i64;
-            let dep_type = edge.weight();
-
-            let candidate_es = match dep_type {
-                DependencyType::FinishToStart => ef[step_idx],
-                DependencyType::StartToStart => es[step_idx],
-                DependencyType::FinishToFinish => ef[step_idx] - succ_dur,
-                DependencyType::StartToFinish => es[step_idx] - succ_dur,
-            };
-
-            let new_es = candidate_es.max(0);
-            if new_es > es[succ_idx] {
+            let dep = *edge.weight();
+            let new_es = released_at(step_idx, succ_idx, dep, &es, &ef);
+
+            if any_of(succ_idx) {
+                // Only the earliest release counts.
+                let release = &mut any_of_release[succ_idx];
+                *release = Some(release.map_or(new_es, |r: i64| r.min(new_es)));
+            } else if new_es > es[succ_idx] {
                 es[succ_idx] = new_es;
                 // EF will be recomputed when we visit this node in topo order
             }
         }
 
         // Recompute EF after ES has been finalized for this node
-        ef[step_idx] = es[step_idx] + dur;
+        ef[step_idx] = finish_at(step_idx, es[step_idx]);
     }
 
     // Recompute EF for all nodes after forward pass (ES could have been updated
     // by earlier propagation before the node was visited)
     for i in 0..n {
-        ef[i] = es[i] + template.steps[i].duration_mins as i64;
+        ef[i] = finish_at(i, es[i]);
     }
 
     // -----------------------------------------------------------------------
     // 6. Determine project_end
     // -----------------------------------------------------------------------
     let max_ef: i64 = ef.iter().copied().max().unwrap_or(0);
+    let tz = parse_timezone(template)?;
 
     // Check for time constraints
     let (project_end, backward_only) = if let Some(tc) = &template.time_constraint {
@@ -221,8 +432,7 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
                 if let (Some(start_dt), Some(end_dt)) =
                     (parse_datetime(start_str), parse_datetime(end_str))
                 {
-                    let deadline_mins =
-                        (end_dt - start_dt).num_minutes().max(0) as i64;
+                    let deadline_mins = minutes_between(start_dt, end_dt, tz).max(0);
                     (deadline_mins.max(max_ef), false)
                 } else {
                     (max_ef, false)
@@ -250,32 +460,50 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
 
     // Initialize LS from LF
     for i in 0..n {
-        ls[i] = lf[i] - template.steps[i].duration_mins as i64;
+        ls[i] = start_for(i, lf[i]);
     }
 
     // Process in reverse topological order
     for node in topo_order.iter().rev() {
         let step_idx = *graph.node_weight(*node).unwrap();
-        let dur = template.steps[step_idx].duration_mins as i64;
-        ls[step_idx] = lf[step_idx] - dur;
+        ls[step_idx] = start_for(step_idx, lf[step_idx]);
+        // A fixed step can't move, so its predecessors must fit before it.
+        if fixed_offset(step_idx).is_some() {
+            ls[step_idx] = es[step_idx];
+            lf[step_idx] = ef[step_idx];
+        }
 
         // Propagate to predecessors using incoming edges
         for edge in graph.edges_directed(*node, Direction::Incoming) {
             let pred_node = edge.source();
             let pred_idx = *graph.node_weight(pred_node).unwrap();
             let pred_dur = template.steps[pred_idx].duration_mins as i64;
-            let dep_type = edge.weight();
+            let dep = *edge.weight();
+
+            // An AnyOf step only waits on the predecessor that releases it
+            // first; the others don't constrain its timing.
+            if any_of(step_idx)
+                && Some(released_at(pred_idx, step_idx, dep, &es, &ef)) > any_of_release[step_idx]
+            {
+                continue;
+            }
 
-            let candidate_lf = match dep_type {
-                DependencyType::FinishToStart => ls[step_idx],
-                DependencyType::StartToStart => ls[step_idx] + pred_dur,
+            // The latest finish that keeps the predecessor's release (its
+            // start, trigger point or finish) in time for this step.
+            let candidate_lf = match dep.dependency_type {
+                DependencyType::FinishToStart => {
+                    let trigger = trigger_point(dep, pred_dur) as u32;
+                    let start = latest_start_reaching(pred_idx, ls[step_idx], trigger);
+                    finish_at(pred_idx, start)
+                }
+                DependencyType::StartToStart => finish_at(pred_idx, ls[step_idx]),
                 DependencyType::FinishToFinish => lf[step_idx],
-                DependencyType::StartToFinish => lf[step_idx] + pred_dur,
+                DependencyType::StartToFinish => finish_at(pred_idx, lf[step_idx]),
             };
 
             if candidate_lf < lf[pred_idx] {
                 lf[pred_idx] = candidate_lf;
-                ls[pred_idx] = lf[pred_idx] - pred_dur;
+                ls[pred_idx] = start_for(pred_idx, lf[pred_idx]);
             }
         }
     }
@@ -292,7 +520,7 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
 
         let policy = step.timing_policy.as_ref().unwrap_or(&TimingPolicy::Asap);
         actual_starts[i] = match policy {
-            TimingPolicy::Asap => es[i],
+            TimingPolicy::Asap | TimingPolicy::Fixed { .. } => es[i],
             TimingPolicy::Alap => ls[i],
         };
     }
@@ -317,35 +545,41 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
     let mut early_starts_map: HashMap<String, u32> = HashMap::new();
     let mut late_starts_map: HashMap<String, u32> = HashMap::new();
 
+    // Wall-clock time of a schedule offset, if the template is anchored
+    let wall_at = |offset: u32| -> Option<String> {
+        if is_backward {
+            // Backward scheduling: map relative offsets backward from end_time
+            end_dt_opt.map(|end_dt| wall_clock(end_dt, tz, -(max_ef - offset as i64)))
+        } else {
+            // Forward scheduling
+            start_dt_opt.map(|start_dt| wall_clock(start_dt, tz, offset as i64))
+        }
+    };
+
     for (i, step) in template.steps.iter().enumerate() {
         let actual_start = actual_starts[i].max(0) as u32;
-        let dur = step.duration_mins;
+        let actual_end = finish_at(i, actual_start as i64) as u32;
         let tf = total_floats[i].max(0) as u32;
 
-        // Wall-clock computation
-        let (wc_start, wc_end) = if is_backward {
-            // Backward scheduling: map relative offsets backward from end_time
-            if let Some(end_dt) = end_dt_opt {
-                let project_end_mins = max_ef as i64;
-                let offset_from_end_start = project_end_mins - actual_start as i64;
-                let offset_from_end_end = project_end_mins - (actual_start as i64 + dur as i64);
-                let wall_start = end_dt
-                    - chrono::Duration::minutes(offset_from_end_start);
-                let wall_end = end_dt
-                    - chrono::Duration::minutes(offset_from_end_end);
-                (Some(format_datetime(wall_start)), Some(format_datetime(wall_end)))
-            } else {
-                (None, None)
-            }
-        } else if let Some(start_dt) = start_dt_opt {
-            // Forward scheduling
-            let wall_start =
-                start_dt + chrono::Duration::minutes(actual_start as i64);
-            let wall_end =
-                start_dt + chrono::Duration::minutes((actual_start + dur) as i64);
-            (Some(format_datetime(wall_start)), Some(format_datetime(wall_end)))
+        let wc_start = wall_at(actual_start);
+        let wc_end = wall_at(actual_end);
+
+        let segments = match stretches(i) {
+            Some(cal) => calendar::working_segments(cal, actual_start, step.duration_mins),
+            None => Vec::new(),
+        };
+        let segments: Vec<StepSegment> = if segments.len() > 1 {
+            segments
+                .into_iter()
+                .map(|(start, end)| StepSegment {
+                    start_offset_mins: start,
+                    end_offset_mins: end,
+                    wall_start: wall_at(start),
+                    wall_end: wall_at(end),
+                })
+                .collect()
         } else {
-            (None, None)
+            Vec::new()
         };
 
         early_starts_map.insert(step.id.clone(), es[i].max(0) as u32);
@@ -354,12 +588,19 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
         solved_steps.push(SolvedStep {
             step_id: step.id.clone(),
             start_offset_mins: actual_start,
-            end_offset_mins: actual_start + dur,
+            end_offset_mins: actual_end,
             start_time: wc_start,
             end_time: wc_end,
+            actual_start_time: None,
+            actual_end_time: None,
             assigned_resources: Vec::<AssignedResource>::new(),
             total_float_mins: tf,
+            path_float_mins: None,
             is_critical: tf == 0,
+            wbs_code: step.wbs_code.clone(),
+            segments,
+            notes: Vec::new(),
+            cost: None,
         });
     }
 
@@ -373,6 +614,19 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
     })
 }
 
+/// `step`'s dependencies with exact repeats left out, keeping the first of
+/// each. Entries that differ in anything (type, trigger point, condition)
+/// are all kept, since each is a constraint of its own.
+fn unique_dependencies(step: &Step) -> Vec<&StepDependency> {
+    let mut unique: Vec<&StepDependency> = Vec::new();
+    for dep in &step.dependencies {
+        if !unique.contains(&dep) {
+            unique.push(dep);
+        }
+    }
+    unique
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -384,22 +638,238 @@ pub(crate) fn cpm(template: &ScheduleTemplate) -> Result<CpmResult, SolveError>
 /// 2. If the template defines resources, runs the greedy resource allocator to
 ///    stagger conflicting steps within their float windows.
 /// 3. Recalculates total duration after allocation (steps may be pushed out).
+///
+/// Recurring step patterns are expanded first, so solved step IDs refer to
+/// the materialized copies.
+///
+/// The template is not validated; callers are expected to have run
+/// `validator::validate` already. See `solve_with_options` for more control.
 pub fn solve(
     template: &ScheduleTemplate,
     inventory: Option<&ResourceInventory>,
 ) -> Result<SolvedSchedule, SolveError> {
+    let options = SolveOptions {
+        skip_validation: true,
+        ..Default::default()
+    };
+    solve_with_options(template, inventory, &options)
+}
+
+/// Like `solve`, but optionally validates the template first and can skip
+/// resource allocation entirely (`dry_run`) for fast CPM-only feedback.
+pub fn solve_with_options(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    options: &SolveOptions,
+) -> Result<SolvedSchedule, SolveError> {
+    solve_prioritized(template, inventory, options, None)
+}
+
+/// A caller-supplied allocation order for `solve_with_custom_priority`.
+pub type PriorityFn = Box<dyn Fn(&SolvedStep, &SolvedStep) -> std::cmp::Ordering + Send>;
+
+/// Like `solve`, but `priority_fn` decides the order in which the allocator
+/// places steps, replacing its default rules (critical steps first, then
+/// earliest start, then longest duration). Steps comparing `Less` are placed
+/// first and so get first pick of contended resources.
+pub fn solve_with_custom_priority(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    priority_fn: PriorityFn,
+) -> Result<SolvedSchedule, SolveError> {
+    let options = SolveOptions {
+        skip_validation: true,
+        ..Default::default()
+    };
+    solve_prioritized(template, inventory, &options, Some(&*priority_fn))
+}
+
+/// Like `solve`, but ignoring step priorities in favour of deadline
+/// proximity: the allocator places steps with the least float relative to
+/// their late finish first (see `allocator::deadline_proximity_order`).
+pub fn solve_with_step_priorities_from_deadline_proximity(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Result<SolvedSchedule, SolveError> {
+    let options = SolveOptions {
+        skip_validation: true,
+        ..Default::default()
+    };
+    solve_prioritized(
+        template,
+        inventory,
+        &options,
+        Some(&crate::allocator::deadline_proximity_order),
+    )
+}
+
+/// Like `solve`, but with resource splitting: a step whose need is marked
+/// `allow_partial` may start with part of the quantity it asks for instead
+/// of waiting for all of it, its duration scaled up to match (2 of 4
+/// workers doubles it). The allocator only splits a need when that gets
+/// the step finished sooner, and records the units actually used in
+/// `AssignedResource::quantity_used`.
+pub fn solve_with_resource_splitting(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Result<SolvedSchedule, SolveError> {
+    let options = SolveOptions {
+        skip_validation: true,
+        resource_splitting: true,
+        ..Default::default()
+    };
+    solve_with_options(template, inventory, &options)
+}
+
+/// Like `solve`, but a schedule taking longer than `max_duration_mins` is an
+/// error rather than a result. Unlike `TimeConstraint::end_time`, which the
+/// solver works towards and only warns about when missed, the limit is
+/// strict.
+pub fn solve_with_max_duration(
+    template: &ScheduleTemplate,
+    max_duration_mins: u32,
+    inventory: Option<&ResourceInventory>,
+) -> Result<SolvedSchedule, ConstraintViolation> {
+    let solved = solve(template, inventory)?;
+    check_max_duration(&solved, max_duration_mins)?;
+    Ok(solved)
+}
+
+/// Fail with `ConstraintViolation::MaxDurationExceeded` when `solved` takes
+/// longer than `max_duration_mins`.
+pub fn check_max_duration(
+    solved: &SolvedSchedule,
+    max_duration_mins: u32,
+) -> Result<(), ConstraintViolation> {
+    let computed = solved.summary.total_duration_mins;
+    if computed > max_duration_mins {
+        return Err(ConstraintViolation::MaxDurationExceeded {
+            computed,
+            limit: max_duration_mins,
+        });
+    }
+    Ok(())
+}
+
+/// Like `solve`, but scheduled backward from `deadline`: the last step ends
+/// at the deadline and the first step's `start_time` is the latest the
+/// work can begin. The template's own start and end times are ignored for
+/// this call (its timezone is kept); the template itself is not changed.
+pub fn solve_backward_from_deadline(
+    template: &ScheduleTemplate,
+    deadline: NaiveDateTime,
+    inventory: Option<&ResourceInventory>,
+) -> Result<SolvedSchedule, SolveError> {
+    let timezone = template.time_constraint.as_ref().and_then(|tc| tc.timezone.clone());
+    let template = ScheduleTemplate {
+        time_constraint: Some(TimeConstraint {
+            start_time: None,
+            end_time: Some(format_datetime(deadline)),
+            timezone,
+        }),
+        ..template.clone()
+    };
+    solve(&template, inventory)
+}
+
+/// Like `solve`, but with `resource_calendars` in place of the template's
+/// own, so resources are only used within their availability windows.
+pub fn solve_with_resource_calendar(
+    template: &ScheduleTemplate,
+    resource_calendars: &[ResourceCalendar],
+) -> Result<SolvedSchedule, SolveError> {
+    let template = ScheduleTemplate {
+        resource_calendars: resource_calendars.to_vec(),
+        ..template.clone()
+    };
+    solve(&template, None)
+}
+
+fn solve_prioritized(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    options: &SolveOptions,
+    priority: Option<&StepPriority>,
+) -> Result<SolvedSchedule, SolveError> {
+    if !options.skip_validation {
+        let validation = crate::validator::validate(template);
+        if !validation.is_ok() {
+            return Err(SolveError::ValidationFailed(validation.error_summary()));
+        }
+    }
+
+    let prepared = prepare_template(template, options);
+    let template = prepared.as_ref();
+
     let mut result = cpm(template)?;
 
     // Resource allocation (greedy with float-window shifting)
     let mut alloc_warnings: Vec<String> = Vec::new();
-    if !template.resources.is_empty() {
-        alloc_warnings = crate::allocator::allocate_resources(
+    if crate::allocator::needs_allocation(template) && !options.dry_run {
+        let headcounts = crate::validator::validate_after_cpm(template, &result, inventory);
+        if !options.skip_validation && !headcounts.is_ok() {
+            return Err(SolveError::ValidationFailed(headcounts.error_summary()));
+        }
+        alloc_warnings = headcounts.warnings.into_iter().map(|w| w.message).collect();
+        alloc_warnings.extend(crate::allocator::allocate_resources_with_priority(
             template,
             &mut result.solved_steps,
             &result.early_starts,
             &result.late_starts,
             inventory,
-        );
+            priority,
+            options.resource_splitting,
+        ));
+    }
+    if !options.dry_run {
+        alloc_warnings.extend(crate::allocator::check_utilization_targets(
+            template,
+            &result.solved_steps,
+            inventory,
+        ));
+    }
+
+    finish_schedule(template, result, alloc_warnings)
+}
+
+/// Turn allocated CPM results into a `SolvedSchedule`: note steps finishing
+/// after the deadline and any buffer steps, then total up the summary and
+/// hammock spans.
+pub(crate) fn finish_schedule(
+    template: &ScheduleTemplate,
+    mut result: CpmResult,
+    mut warnings: Vec<String>,
+) -> Result<SolvedSchedule, SolveError> {
+    let tz = parse_timezone(template)?;
+    if let Some(deadline) = deadline_mins(template, tz) {
+        add_deadline_notes(&mut result.solved_steps, deadline);
+    }
+    let buffers: Vec<String> = template
+        .steps
+        .iter()
+        .filter(|s| s.tags.iter().any(|t| t == crate::preprocess::BUFFER_TAG))
+        .map(|s| format!("'{}'", s.id))
+        .collect();
+    if !buffers.is_empty() {
+        warnings.push(format!(
+            "Steps {} are schedule buffers rather than work",
+            buffers.join(", ")
+        ));
+    }
+
+    // Allocation may have moved steps (e.g. into a resource's next
+    // availability window); keep forward-scheduled wall-clock times in step
+    // with the offsets.
+    let anchor = template.time_constraint.as_ref().and_then(|tc| tc.start_time.as_deref());
+    if let Some(anchor) = anchor.and_then(parse_datetime) {
+        for step in &mut result.solved_steps {
+            step.start_time = Some(wall_clock(anchor, tz, step.start_offset_mins.into()));
+            step.end_time = Some(wall_clock(anchor, tz, step.end_offset_mins.into()));
+            for segment in &mut step.segments {
+                segment.wall_start = Some(wall_clock(anchor, tz, segment.start_offset_mins.into()));
+                segment.wall_end = Some(wall_clock(anchor, tz, segment.end_offset_mins.into()));
+            }
+        }
     }
 
     // Recalculate total duration after allocation (steps may be pushed beyond CPM project_end)
@@ -417,92 +887,920 @@ pub fn solve(
         .map(|s| s.step_id.clone())
         .collect();
 
+    let project_total_cost =
+        crate::allocator::project_total_cost(template, &result.solved_steps);
+    let total_cost = crate::allocator::apply_resource_rates(template, &mut result.solved_steps);
+
     let summary = ScheduleSummary {
         total_duration_mins,
         critical_path_step_ids,
+        project_total_cost,
+        total_cost,
     };
+    let hammocks = solve_hammocks(template, &result.solved_steps);
 
     Ok(SolvedSchedule {
         template_id: template.id.clone(),
         solved_steps: result.solved_steps,
         summary,
-        warnings: alloc_warnings,
+        warnings,
+        gantt_rows: None,
+        near_critical_paths: None,
+        float_consumption: None,
+        hammocks,
     })
 }
 
-// ---------------------------------------------------------------------------
-// Tests
-// ---------------------------------------------------------------------------
+/// Note every step that finishes after `deadline`.
+fn add_deadline_notes(solved_steps: &mut [SolvedStep], deadline: i64) {
+    for step in solved_steps {
+        let late = step.end_offset_mins as i64 - deadline;
+        if late > 0 {
+            step.notes.push(StepNote {
+                code: NoteCode::DeadlineWarning,
+                message: format!("Finishes {} min after the deadline", late),
+            });
+        }
+    }
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::model::{DependencyType, ScheduleTemplate, Step, StepDependency};
+/// Solve several templates (e.g. optimistic, base and pessimistic scenarios)
+/// with the same inventory. Every template is solved even if an earlier one
+/// fails; results are in input order.
+pub fn solve_all_scenarios(
+    templates: &[ScheduleTemplate],
+    inventory: Option<&ResourceInventory>,
+) -> Vec<Result<SolvedSchedule, SolveError>> {
+    templates.iter().map(|t| solve(t, inventory)).collect()
+}
 
-    fn make_template(steps: Vec<Step>) -> ScheduleTemplate {
-        ScheduleTemplate {
-            id: "test".to_string(),
-            name: "Test".to_string(),
-            description: None,
-            steps,
-            tracks: vec![],
-            resources: vec![],
-            time_constraint: None,
-            default_num_people: None,
+/// Like `solve_all_scenarios`, but solves the templates in parallel on the
+/// current rayon thread pool (the global one unless called inside
+/// `ThreadPool::install`). Results are still in input order.
+pub fn solve_batched_with_rayon(
+    templates: &[ScheduleTemplate],
+    inventory: Option<&ResourceInventory>,
+) -> Vec<Result<SolvedSchedule, SolveError>> {
+    templates.par_iter().map(|t| solve(t, inventory)).collect()
+}
+
+/// Like `solve`, but gives up waiting after `timeout`.
+///
+/// Solving runs on a separate thread, which first computes CPM timings and
+/// then the full solve with resource allocation. If the full solve isn't
+/// finished in time, the CPM timings are returned instead (as from a
+/// `dry_run`, so no resources are assigned) with a warning saying so. Only
+/// when even CPM hasn't finished does this fail with `SolveError::Timeout`.
+///
+/// A timed-out thread can't be cancelled; it runs to completion in the
+/// background and its result is discarded.
+pub fn solve_with_timeout(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    timeout: Duration,
+) -> Result<SolvedSchedule, SolveError> {
+    solve_with_timeout_using(template, inventory, timeout, |template, inventory| {
+        solve(template, inventory)
+    })
+}
+
+/// `solve_with_timeout` with the full solve supplied by the caller, so tests
+/// can stand in a deliberately slow one.
+fn solve_with_timeout_using<F>(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    timeout: Duration,
+    full_solve: F,
+) -> Result<SolvedSchedule, SolveError>
+where
+    F: FnOnce(&ScheduleTemplate, Option<&ResourceInventory>) -> Result<SolvedSchedule, SolveError>
+        + Send
+        + 'static,
+{
+    let partial: Arc<Mutex<Option<SolvedSchedule>>> = Arc::new(Mutex::new(None));
+    let (tx, rx) = mpsc::channel();
+    let worker = {
+        let template = template.clone();
+        let inventory = inventory.cloned();
+        let partial = Arc::clone(&partial);
+        thread::spawn(move || {
+            let cpm_options = SolveOptions {
+                dry_run: true,
+                skip_validation: true,
+                ..Default::default()
+            };
+            match solve_with_options(&template, inventory.as_ref(), &cpm_options) {
+                Ok(cpm_only) => *partial.lock().unwrap() = Some(cpm_only),
+                Err(e) => {
+                    // The full solve would fail the same way.
+                    let _ = tx.send(Err(e));
+                    return;
+                }
+            }
+            let _ = tx.send(full_solve(&template, inventory.as_ref()));
+        })
+    };
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            let timeout_ms = timeout.as_millis() as u64;
+            let mut schedule =
+                partial.lock().unwrap().take().ok_or(SolveError::Timeout(timeout_ms))?;
+            schedule.warnings.push(format!(
+                "Solve timed out after {} ms -- showing CPM timings without resource allocation",
+                timeout_ms
+            ));
+            Ok(schedule)
         }
+        // The worker panicked before sending; re-raise its panic here.
+        Err(RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("solver thread exited without sending a result"),
+        },
     }
+}
 
-    fn make_step(id: &str, dur: u32, deps: Vec<(&str, DependencyType)>) -> Step {
-        Step {
-            id: id.to_string(),
-            title: id.to_string(),
-            description: None,
-            duration_mins: dur,
-            dependencies: deps
-                .into_iter()
-                .map(|(dep_id, dt)| StepDependency {
-                    step_id: dep_id.to_string(),
-                    dependency_type: dt,
-                })
-                .collect(),
-            track_id: None,
-            timing_policy: None,
-            resource_needs: vec![],
+/// Solve with some resources standing in for others, e.g. `("oven",
+/// "portable-oven")` when the main oven is unavailable. See
+/// `substitute_resources` for how the list is applied.
+pub fn solve_with_resource_substitution(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    substitutions: &[(String, String)],
+) -> Result<SolvedSchedule, SolveError> {
+    solve(&substitute_resources(template, substitutions)?, inventory)
+}
+
+/// Copy of `template` with every resource need pointing at its substitute.
+///
+/// Each `(from, to)` pair makes needs for `from` use `to` instead.
+/// Substitutions cascade, so with A->B and B->C, needs for A end up on C.
+/// Fails with `SolveError::ValidationFailed` if the substitutions form a
+/// cycle, or if a need would end up on a resource (or resource group) the
+/// template doesn't define.
+pub fn substitute_resources(
+    template: &ScheduleTemplate,
+    substitutions: &[(String, String)],
+) -> Result<ScheduleTemplate, SolveError> {
+    let replacements: HashMap<&str, &str> =
+        substitutions.iter().map(|(from, to)| (from.as_str(), to.as_str())).collect();
+    let defined: HashSet<&str> = template
+        .resources
+        .iter()
+        .map(|r| r.id.as_str())
+        .chain(template.resource_groups.iter().map(|g| g.id.as_str()))
+        .collect();
+
+    let resolve = |id: &str| -> Result<String, SolveError> {
+        let mut chain = vec![id];
+        let mut current = id;
+        while let Some(&next) = replacements.get(current) {
+            if chain.contains(&next) {
+                chain.push(next);
+                return Err(SolveError::ValidationFailed(format!(
+                    "Circular resource substitution: {}",
+                    chain.join(" -> ")
+                )));
+            }
+            chain.push(next);
+            current = next;
         }
-    }
+        if current != id && !defined.contains(current) {
+            return Err(SolveError::ValidationFailed(format!(
+                "Resource '{}' is substituted by '{}', which isn't defined",
+                id, current
+            )));
+        }
+        Ok(current.to_string())
+    };
 
-    #[test]
-    fn test_single_step() {
-        let template = make_template(vec![make_step("a", 30, vec![])]);
-        let result = solve(&template, None).unwrap();
-        assert_eq!(result.solved_steps.len(), 1);
-        let s = &result.solved_steps[0];
-        assert_eq!(s.step_id, "a");
-        assert_eq!(s.start_offset_mins, 0);
-        assert_eq!(s.end_offset_mins, 30);
-        assert_eq!(s.total_float_mins, 0);
-        assert!(s.is_critical);
+    let mut result = template.clone();
+    for need in result.steps.iter_mut().flat_map(|s| s.resource_needs.iter_mut()) {
+        need.resource_id = resolve(&need.resource_id)?;
     }
+    Ok(result)
+}
 
-    #[test]
-    fn test_linear_chain_critical_path() {
-        // a(30) -> b(20): both should be critical
-        let template = make_template(vec![
-            make_step("a", 30, vec![]),
-            make_step("b", 20, vec![("a", DependencyType::FinishToStart)]),
-        ]);
-        let result = solve(&template, None).unwrap();
-        assert_eq!(result.summary.total_duration_mins, 50);
+/// Span each hammock group over its members' solved times. Members that
+/// weren't scheduled (e.g. inactive conditional steps) are skipped, and so
+/// are groups with no scheduled members.
+fn solve_hammocks(template: &ScheduleTemplate, solved_steps: &[SolvedStep]) -> Vec<SolvedHammock> {
+    let by_id: HashMap<&str, &SolvedStep> =
+        solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+    template
+        .hammock_groups
+        .iter()
+        .filter_map(|group| {
+            let members: Vec<&SolvedStep> = group
+                .member_step_ids
+                .iter()
+                .filter_map(|id| by_id.get(id.as_str()).copied())
+                .collect();
+            let start = members.iter().map(|s| s.start_offset_mins).min()?;
+            let end = members.iter().map(|s| s.end_offset_mins).max()?;
+            Some(SolvedHammock {
+                group_id: group.id.clone(),
+                start_offset_mins: start,
+                end_offset_mins: end,
+                duration_mins: end - start,
+            })
+        })
+        .collect()
+}
 
-        let a = result.solved_steps.iter().find(|s| s.step_id == "a").unwrap();
-        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+fn condition_met(condition: &Option<String>, options: &SolveOptions) -> bool {
+    match condition {
+        Some(condition) => options.active_conditions.contains(condition),
+        None => true,
+    }
+}
 
-        assert_eq!(a.start_offset_mins, 0);
-        assert_eq!(a.end_offset_mins, 30);
-        assert_eq!(a.total_float_mins, 0);
-        assert!(a.is_critical);
+/// True when `options` switches off no step or dependency of the template.
+fn all_active(template: &ScheduleTemplate, options: &SolveOptions) -> bool {
+    template.steps.iter().all(|s| {
+        condition_met(&s.condition, options)
+            && s.dependencies.iter().all(|d| condition_met(&d.condition, options))
+    })
+}
 
-        assert_eq!(b.start_offset_mins, 30);
+/// Copy of `template` without the conditional steps and dependencies that
+/// aren't active. Dependencies on removed steps are dropped too, so their
+/// successors are no longer constrained by them.
+fn without_inactive_steps(
+    template: &ScheduleTemplate,
+    options: &SolveOptions,
+) -> ScheduleTemplate {
+    let removed: HashSet<&str> = template
+        .steps
+        .iter()
+        .filter(|s| !condition_met(&s.condition, options))
+        .map(|s| s.id.as_str())
+        .collect();
+    let steps = template
+        .steps
+        .iter()
+        .filter(|s| !removed.contains(s.id.as_str()))
+        .map(|s| {
+            let mut step = s.clone();
+            step.dependencies.retain(|d| {
+                !removed.contains(d.step_id.as_str()) && condition_met(&d.condition, options)
+            });
+            step
+        })
+        .collect();
+    ScheduleTemplate {
+        steps,
+        ..template.clone()
+    }
+}
+
+/// The template `solve_with_options` actually schedules: recurring patterns
+/// expanded, then the conditional steps and dependencies `options` leaves
+/// inactive removed. Borrowed unchanged when there is nothing to do.
+///
+/// Anything reporting on a solved schedule alongside its template should
+/// use this one, whose step IDs and dependencies match the schedule's.
+pub fn prepare_template<'a>(
+    template: &'a ScheduleTemplate,
+    options: &SolveOptions,
+) -> Cow<'a, ScheduleTemplate> {
+    let mut prepared = Cow::Borrowed(template);
+    if !prepared.recurring_patterns.is_empty() {
+        prepared = Cow::Owned(crate::expander::expand_recurring(&prepared));
+    }
+    if !all_active(&prepared, options) {
+        prepared = Cow::Owned(without_inactive_steps(&prepared, options));
+    }
+    prepared
+}
+
+/// Re-solve a template after a few steps changed, reusing `baseline` (a
+/// previous solve of the same template) for everything they can't affect.
+///
+/// `changed_steps` must list every step whose duration, dependencies or
+/// timing policy changed, plus the former neighbours of any removed step;
+/// steps missing from `baseline` count as changed. CPM is re-run only on the
+/// steps reachable from the changed ones by following dependencies forwards
+/// (successors) and backwards (predecessors), repeatedly, so that every
+/// step whose early or late times could move is included. Other steps keep
+/// their previous offsets; only their float (and, for ALAP steps, their
+/// placement) follows the project end if it moves.
+///
+/// Falls back to a full `solve` when every step is affected, and for
+/// templates where timings can't be patched locally: ones that need resource
+/// allocation (which couples every step), use a working calendar, or are
+/// scheduled backward from an end time.
+pub fn solve_incremental(
+    baseline: &SolvedSchedule,
+    changed_steps: &[String],
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+) -> Result<SolvedSchedule, SolveError> {
+    let full_solve = || solve(template, inventory);
+    let backward = template
+        .time_constraint
+        .as_ref()
+        .is_some_and(|tc| tc.start_time.is_none() && tc.end_time.is_some());
+    if crate::allocator::needs_allocation(template)
+        || template.working_calendar.is_some()
+        || backward
+    {
+        return full_solve();
+    }
+
+    // Same preprocessing as `solve`.
+    let expanded;
+    let template = if template.recurring_patterns.is_empty() {
+        template
+    } else {
+        expanded = crate::expander::expand_recurring(template);
+        &expanded
+    };
+    let options = SolveOptions::default();
+    let filtered;
+    let template = if all_active(template, &options) {
+        template
+    } else {
+        filtered = without_inactive_steps(template, &options);
+        &filtered
+    };
+
+    let previous: HashMap<&str, &SolvedStep> =
+        baseline.solved_steps.iter().map(|s| (s.step_id.as_str(), s)).collect();
+
+    // Affected steps: everything connected to a changed step.
+    let mut neighbours: HashMap<&str, Vec<&str>> = HashMap::new();
+    for step in &template.steps {
+        for dep in &step.dependencies {
+            neighbours.entry(dep.step_id.as_str()).or_default().push(step.id.as_str());
+            neighbours.entry(step.id.as_str()).or_default().push(dep.step_id.as_str());
+        }
+    }
+    let mut stack: Vec<&str> = template
+        .steps
+        .iter()
+        .map(|s| s.id.as_str())
+        .filter(|id| !previous.contains_key(id) || changed_steps.iter().any(|c| c == id))
+        .collect();
+    let mut affected: HashSet<&str> = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if affected.insert(id) {
+            stack.extend(neighbours.get(id).into_iter().flatten().copied());
+        }
+    }
+    if affected.len() == template.steps.len() {
+        return full_solve();
+    }
+
+    let sub_template = ScheduleTemplate {
+        steps: template
+            .steps
+            .iter()
+            .filter(|s| affected.contains(s.id.as_str()))
+            .cloned()
+            .collect(),
+        ..template.clone()
+    };
+    let result = cpm(&sub_template)?;
+
+    // Late times are measured back from the project end, which is the later
+    // of the deadline and every step's early finish.
+    let tz = parse_timezone(template)?;
+    let anchor = template
+        .time_constraint
+        .as_ref()
+        .and_then(|tc| tc.start_time.as_deref())
+        .and_then(parse_datetime);
+    let deadline = deadline_mins(template, tz);
+    let is_alap = |id: &str| {
+        template
+            .steps
+            .iter()
+            .any(|s| s.id == id && s.timing_policy == Some(TimingPolicy::Alap))
+    };
+    let early_finish = |s: &SolvedStep| {
+        let slack = if is_alap(&s.step_id) { s.total_float_mins } else { 0 };
+        s.end_offset_mins.saturating_sub(slack) as i64
+    };
+    let untouched: Vec<&SolvedStep> = template
+        .steps
+        .iter()
+        .filter(|s| !affected.contains(s.id.as_str()))
+        .filter_map(|s| previous.get(s.id.as_str()).copied())
+        .collect();
+    let old_end = baseline
+        .solved_steps
+        .iter()
+        .map(early_finish)
+        .max()
+        .unwrap_or(0)
+        .max(deadline.unwrap_or(0));
+    let untouched_end = untouched.iter().map(|s| early_finish(s)).max().unwrap_or(0);
+    let new_end = (result.project_end as i64).max(untouched_end);
+
+    // Move a step's late times by `delta`: float grows or shrinks, and ALAP
+    // steps (which sit at their late start) move with it.
+    let shift_late = |step: &mut SolvedStep, delta: i64| {
+        if delta == 0 {
+            return;
+        }
+        step.total_float_mins = (step.total_float_mins as i64 + delta).max(0) as u32;
+        step.is_critical = step.total_float_mins == 0;
+        if is_alap(&step.step_id) {
+            step.start_offset_mins = (step.start_offset_mins as i64 + delta).max(0) as u32;
+            step.end_offset_mins = (step.end_offset_mins as i64 + delta).max(0) as u32;
+            if let Some(anchor) = anchor {
+                step.start_time = Some(wall_clock(anchor, tz, step.start_offset_mins.into()));
+                step.end_time = Some(wall_clock(anchor, tz, step.end_offset_mins.into()));
+            }
+        }
+    };
+
+    let mut recomputed = result.solved_steps;
+    for step in &mut recomputed {
+        shift_late(step, new_end - result.project_end as i64);
+    }
+    if let Some(deadline) = deadline {
+        add_deadline_notes(&mut recomputed, deadline);
+    }
+    let mut fresh: HashMap<String, SolvedStep> =
+        recomputed.into_iter().map(|s| (s.step_id.clone(), s)).collect();
+    for step in untouched {
+        let mut step = step.clone();
+        shift_late(&mut step, new_end - old_end);
+        fresh.insert(step.step_id.clone(), step);
+    }
+    let mut solved_steps: Vec<SolvedStep> =
+        template.steps.iter().filter_map(|s| fresh.remove(&s.id)).collect();
+    let total_cost = crate::allocator::apply_resource_rates(template, &mut solved_steps);
+
+    let summary = ScheduleSummary {
+        total_duration_mins: solved_steps.iter().map(|s| s.end_offset_mins).max().unwrap_or(0),
+        critical_path_step_ids: solved_steps
+            .iter()
+            .filter(|s| s.is_critical)
+            .map(|s| s.step_id.clone())
+            .collect(),
+        project_total_cost: crate::allocator::project_total_cost(template, &solved_steps),
+        total_cost,
+    };
+    let hammocks = solve_hammocks(template, &solved_steps);
+
+    Ok(SolvedSchedule {
+        template_id: template.id.clone(),
+        solved_steps,
+        summary,
+        warnings: Vec::new(),
+        gantt_rows: None,
+        near_critical_paths: None,
+        float_consumption: None,
+        hammocks,
+    })
+}
+
+/// Run CPM only and return the raw early/late start windows, for callers
+/// implementing their own allocation strategy. Recurring step patterns are
+/// expanded first, as in `solve`.
+pub fn cpm_only(template: &ScheduleTemplate) -> Result<CpmResult, SolveError> {
+    if template.recurring_patterns.is_empty() {
+        cpm(template)
+    } else {
+        cpm(&crate::expander::expand_recurring(template))
+    }
+}
+
+/// Compute the shortest total duration achievable with the given resources.
+///
+/// Unlike `solve`, every step that isn't fixed is forced to ASAP and the
+/// template's time constraint is dropped, so no deadline stretches the
+/// backward pass and no wall-clock times are computed. `additional_resources`
+/// models hypothetical extra capacity: a resource whose ID already exists
+/// adds its capacity to the existing one (including any inventory override),
+/// any other resource is appended to the template.
+pub fn earliest_possible_end(
+    template: &ScheduleTemplate,
+    inventory: Option<&ResourceInventory>,
+    additional_resources: &[Resource],
+) -> Result<u32, SolveError> {
+    // Recurring copies expanded and inactive conditional steps dropped, as
+    // `solve` would.
+    let mut template = prepare_template(template, &SolveOptions::default()).into_owned();
+    template.time_constraint = None;
+    for step in &mut template.steps {
+        if !matches!(step.timing_policy, Some(TimingPolicy::Fixed { .. })) {
+            step.timing_policy = Some(TimingPolicy::Asap);
+        }
+    }
+
+    let mut inventory = inventory.cloned();
+    for extra in additional_resources {
+        add_capacity(&mut template, inventory.as_mut(), extra, extra.capacity);
+    }
+
+    let mut result = cpm(&template)?;
+    if crate::allocator::needs_allocation(&template) {
+        crate::allocator::allocate_resources(
+            &template,
+            &mut result.solved_steps,
+            &result.early_starts,
+            &result.late_starts,
+            inventory.as_ref(),
+        );
+    }
+
+    Ok(result
+        .solved_steps
+        .iter()
+        .map(|s| s.end_offset_mins)
+        .max()
+        .unwrap_or(result.project_end))
+}
+
+/// Give the template `quantity` more of `resource`: a resource whose ID
+/// already exists gains that much capacity (and so does its inventory
+/// override, if any); any other resource is appended with `quantity` as its
+/// capacity.
+pub(crate) fn add_capacity(
+    template: &mut ScheduleTemplate,
+    inventory: Option<&mut ResourceInventory>,
+    resource: &Resource,
+    quantity: u32,
+) {
+    match template.resources.iter_mut().find(|r| r.id == resource.id) {
+        Some(existing) => {
+            existing.capacity += quantity;
+            if let Some(inv) = inventory {
+                for item in inv.items.iter_mut().filter(|i| i.resource_id == resource.id) {
+                    item.available_quantity += quantity;
+                }
+            }
+        }
+        None => template.resources.push(Resource {
+            capacity: quantity,
+            ..resource.clone()
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WBS ordering
+// ---------------------------------------------------------------------------
+
+/// Sort solved steps by WBS code lexicographically, so "1.10" < "1.2". Steps
+/// without a code keep their relative order after all coded steps.
+pub fn sort_by_wbs(steps: &mut [SolvedStep]) {
+    steps.sort_by(|a, b| match (&a.wbs_code, &b.wbs_code) {
+        (Some(ka), Some(kb)) => ka.cmp(kb),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Sensitivity analysis
+// ---------------------------------------------------------------------------
+
+/// How a single step's duration affects the critical path.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepSensitivity {
+    pub step_id: String,
+    /// Critical steps: minutes the project end moves per minute of extra
+    /// duration (always 1.0). Non-critical steps: minutes of extra duration
+    /// the step can absorb before joining the critical path (its total float).
+    pub sensitivity_mins: f64,
+    /// Non-critical steps only: the duration at which the step becomes critical.
+    pub becomes_critical_at_mins: Option<u32>,
+}
+
+/// Per-step duration sensitivity, in template step order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityReport {
+    pub steps: Vec<StepSensitivity>,
+}
+
+/// Compute how much each step's duration can grow before it affects the
+/// project end, using CPM total float (resource allocation is ignored).
+/// Recurring copies are reported individually, as `solve` schedules them.
+pub fn critical_path_sensitivity(
+    template: &ScheduleTemplate,
+) -> Result<SensitivityReport, SolveError> {
+    let prepared = prepare_template(template, &SolveOptions::default());
+    let result = cpm(&prepared)?;
+    let steps = prepared
+        .steps
+        .iter()
+        .zip(result.solved_steps.iter())
+        .map(|(step, solved)| {
+            if solved.is_critical {
+                StepSensitivity {
+                    step_id: step.id.clone(),
+                    sensitivity_mins: 1.0,
+                    becomes_critical_at_mins: None,
+                }
+            } else {
+                StepSensitivity {
+                    step_id: step.id.clone(),
+                    sensitivity_mins: solved.total_float_mins as f64,
+                    becomes_critical_at_mins: Some(step.duration_mins + solved.total_float_mins),
+                }
+            }
+        })
+        .collect();
+    Ok(SensitivityReport { steps })
+}
+
+// ---------------------------------------------------------------------------
+// Near-critical paths
+// ---------------------------------------------------------------------------
+
+/// A dependency chain whose every step has total float within a threshold.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NearCriticalPath {
+    /// Steps from the start of the chain to its end.
+    pub step_ids: Vec<String>,
+    /// The largest total float of any step on the chain.
+    pub max_float_mins: u32,
+}
+
+/// Most chains `detect_near_critical_paths` returns. Densely connected
+/// templates can have exponentially many, so only the ones with the least
+/// float are kept.
+pub const MAX_NEAR_CRITICAL_PATHS: usize = 100;
+
+/// Find the maximal dependency chains made only of steps whose total float
+/// is at most `threshold_mins`. The critical path itself is included (with
+/// `max_float_mins == 0`). At most `MAX_NEAR_CRITICAL_PATHS` chains are
+/// returned, those with the lowest `max_float_mins` first, ordered by
+/// `max_float_mins` and then by their step IDs.
+///
+/// Chains are traced backwards: starting from each near-critical step with no
+/// near-critical successor, predecessors are followed until a step with no
+/// near-critical predecessor is reached. Partial chains are extended
+/// best-first, keyed by the lowest `max_float_mins` any completion of them can
+/// reach, so chains come out in order and the search stops once enough are
+/// found.
+pub fn detect_near_critical_paths(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+    threshold_mins: u32,
+) -> Vec<NearCriticalPath> {
+    let float: HashMap<&str, u32> = solved
+        .solved_steps
+        .iter()
+        .filter(|s| s.total_float_mins <= threshold_mins)
+        .map(|s| (s.step_id.as_str(), s.total_float_mins))
+        .collect();
+
+    // Dependency edges restricted to near-critical steps.
+    let mut preds: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut has_succ: HashSet<&str> = HashSet::new();
+    for step in &template.steps {
+        if !float.contains_key(step.id.as_str()) {
+            continue;
+        }
+        for dep in &step.dependencies {
+            if float.contains_key(dep.step_id.as_str()) {
+                preds.entry(step.id.as_str()).or_default().push(dep.step_id.as_str());
+                has_succ.insert(dep.step_id.as_str());
+            }
+        }
+    }
+
+    /// Lowest max float of any chain from `id` back to a step with no
+    /// near-critical predecessor.
+    fn best_back<'a>(
+        id: &'a str,
+        float: &HashMap<&str, u32>,
+        preds: &HashMap<&'a str, Vec<&'a str>>,
+        memo: &mut HashMap<&'a str, u32>,
+    ) -> u32 {
+        if let Some(&best) = memo.get(id) {
+            return best;
+        }
+        let before = preds
+            .get(id)
+            .and_then(|ps| ps.iter().map(|p| best_back(p, float, preds, memo)).min());
+        let best = before.unwrap_or(0).max(float[id]);
+        memo.insert(id, best);
+        best
+    }
+
+    // Partial chains, sink first, keyed by the best max float they can reach.
+    let mut memo: HashMap<&str, u32> = HashMap::new();
+    let mut queue: BinaryHeap<Reverse<(u32, Vec<&str>)>> = BinaryHeap::new();
+    for step in &template.steps {
+        let id = step.id.as_str();
+        if float.contains_key(id) && !has_succ.contains(id) {
+            queue.push(Reverse((best_back(id, &float, &preds, &mut memo), vec![id])));
+        }
+    }
+
+    let mut paths: Vec<NearCriticalPath> = Vec::new();
+    while let Some(Reverse((bound, chain))) = queue.pop() {
+        if paths.len() >= MAX_NEAR_CRITICAL_PATHS {
+            break;
+        }
+        let head = chain[chain.len() - 1];
+        match preds.get(head) {
+            Some(ps) if !ps.is_empty() => {
+                let so_far = chain.iter().map(|id| float[id]).max().unwrap_or(0);
+                for &pred in ps {
+                    let mut longer = chain.clone();
+                    longer.push(pred);
+                    let key = so_far.max(best_back(pred, &float, &preds, &mut memo));
+                    queue.push(Reverse((key, longer)));
+                }
+            }
+            _ => paths.push(NearCriticalPath {
+                max_float_mins: bound,
+                step_ids: chain.iter().rev().map(|id| id.to_string()).collect(),
+            }),
+        }
+    }
+
+    paths.sort_by(|a, b| {
+        a.max_float_mins
+            .cmp(&b.max_float_mins)
+            .then_with(|| a.step_ids.cmp(&b.step_ids))
+    });
+    paths
+}
+
+// ---------------------------------------------------------------------------
+// Critical path enumeration
+// ---------------------------------------------------------------------------
+
+/// Enumerate the critical paths of a template separately, for schedules
+/// where several chains tie for the longest duration. Resources are ignored
+/// (CPM timings only).
+///
+/// Each path lists step IDs from a step starting at time 0 to a step
+/// finishing at the project end, where every step is critical and each one
+/// is held up by the step before it (its dependency on it is what sets its
+/// early start). Paths are traced backwards from each finishing step, in
+/// template and dependency order, and at most `max_paths` are returned,
+/// since the number of paths can grow exponentially.
+pub fn all_critical_paths(
+    template: &ScheduleTemplate,
+    max_paths: usize,
+) -> Result<Vec<Vec<String>>, SolveError> {
+    let expanded;
+    let template = if template.recurring_patterns.is_empty() {
+        template
+    } else {
+        expanded = crate::expander::expand_recurring(template);
+        &expanded
+    };
+    let result = cpm(template)?;
+    let critical: HashMap<&str, &SolvedStep> = result
+        .solved_steps
+        .iter()
+        .filter(|s| s.is_critical)
+        .map(|s| (s.step_id.as_str(), s))
+        .collect();
+    let steps: HashMap<&str, &Step> =
+        template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let end = result.solved_steps.iter().map(|s| s.end_offset_mins).max().unwrap_or(0);
+
+    // Critical predecessors whose dependency sets each step's start.
+    let driving = |id: &str| -> Vec<&str> {
+        let succ = critical[id];
+        let succ_dur = (succ.end_offset_mins - succ.start_offset_mins) as i64;
+        steps[id]
+            .dependencies
+            .iter()
+            .filter(|dep| {
+                let Some(pred) = critical.get(dep.step_id.as_str()) else {
+                    return false;
+                };
+                let pred_dur = steps[dep.step_id.as_str()].duration_mins as i64;
+                let pred_es = pred.start_offset_mins as i64;
+                let pred_ef = pred.end_offset_mins as i64;
+                let bound = match dep.dependency_type {
+                    DependencyType::FinishToStart => pred_es + trigger_point(dep, pred_dur),
+                    DependencyType::StartToStart => pred_es,
+                    DependencyType::FinishToFinish => pred_ef - succ_dur,
+                    DependencyType::StartToFinish => pred_es - succ_dur,
+                };
+                bound == succ.start_offset_mins as i64
+            })
+            .map(|dep| dep.step_id.as_str())
+            .collect()
+    };
+
+    fn walk<'a>(
+        id: &'a str,
+        driving: &dyn Fn(&str) -> Vec<&'a str>,
+        start_of: &dyn Fn(&str) -> u32,
+        chain: &mut Vec<&'a str>,
+        paths: &mut Vec<Vec<String>>,
+        max_paths: usize,
+    ) {
+        if paths.len() >= max_paths {
+            return;
+        }
+        chain.push(id);
+        let preds = driving(id);
+        if preds.is_empty() {
+            if start_of(id) == 0 {
+                paths.push(chain.iter().rev().map(|s| s.to_string()).collect());
+            }
+        } else {
+            for pred in preds {
+                walk(pred, driving, start_of, chain, paths, max_paths);
+            }
+        }
+        chain.pop();
+    }
+
+    let start_of = |id: &str| critical[id].start_offset_mins;
+    let mut paths = Vec::new();
+    for step in &template.steps {
+        let id = step.id.as_str();
+        if critical.get(id).is_some_and(|s| s.end_offset_mins == end) {
+            walk(id, &driving, &start_of, &mut Vec::new(), &mut paths, max_paths);
+        }
+    }
+    Ok(paths)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DependencyType, ScheduleTemplate, Step, StepDependency};
+
+    fn make_template(steps: Vec<Step>) -> ScheduleTemplate {
+        ScheduleTemplate {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            steps,
+            tracks: vec![],
+            resources: vec![],
+            ..Default::default()
+        }
+    }
+
+    fn make_step(id: &str, dur: u32, deps: Vec<(&str, DependencyType)>) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            duration_mins: dur,
+            dependencies: deps
+                .into_iter()
+                .map(|(dep_id, dt)| StepDependency {
+                    step_id: dep_id.to_string(),
+                    dependency_type: dt,
+                    trigger_at_percent: None,
+                    condition: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_single_step() {
+        let template = make_template(vec![make_step("a", 30, vec![])]);
+        let result = solve(&template, None).unwrap();
+        assert_eq!(result.solved_steps.len(), 1);
+        let s = &result.solved_steps[0];
+        assert_eq!(s.step_id, "a");
+        assert_eq!(s.start_offset_mins, 0);
+        assert_eq!(s.end_offset_mins, 30);
+        assert_eq!(s.total_float_mins, 0);
+        assert!(s.is_critical);
+    }
+
+    #[test]
+    fn test_linear_chain_critical_path() {
+        // a(30) -> b(20): both should be critical
+        let template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 20, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+        let result = solve(&template, None).unwrap();
+        assert_eq!(result.summary.total_duration_mins, 50);
+
+        let a = result.solved_steps.iter().find(|s| s.step_id == "a").unwrap();
+        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+
+        assert_eq!(a.start_offset_mins, 0);
+        assert_eq!(a.end_offset_mins, 30);
+        assert_eq!(a.total_float_mins, 0);
+        assert!(a.is_critical);
+
+        assert_eq!(b.start_offset_mins, 30);
         assert_eq!(b.end_offset_mins, 50);
         assert_eq!(b.total_float_mins, 0);
         assert!(b.is_critical);
@@ -539,6 +1837,1132 @@ mod tests {
         assert!(c.is_critical);
     }
 
+    #[test]
+    fn test_earliest_possible_end_with_extra_capacity() {
+        use crate::model::{ResourceKind, ResourceNeed};
+
+        let oven = |id: &str| {
+            let mut step = make_step(id, 30, vec![]);
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+            step.timing_policy = Some(TimingPolicy::Alap);
+            step
+        };
+        let mut template = make_template(vec![oven("a"), oven("b")]);
+        let resource = Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        };
+        template.resources.push(resource.clone());
+
+        // One oven: the two roasts run back to back.
+        assert_eq!(earliest_possible_end(&template, None, &[]).unwrap(), 60);
+        // A second oven lets them run side by side.
+        assert_eq!(earliest_possible_end(&template, None, &[resource]).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_timezone_offsets_and_dst() {
+        use crate::model::TimeConstraint;
+
+        // 2024-03-10 is the US spring-forward: 02:00 EST jumps to 03:00 EDT.
+        let mut template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 120, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2024-03-10T00:30:00".to_string()),
+            end_time: None,
+            timezone: Some("America/New_York".to_string()),
+        });
+        let result = solve(&template, None).unwrap();
+        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!(b.start_time.as_deref(), Some("2024-03-10T01:00:00-05:00"));
+        // Two real hours later the clock reads 04:00 EDT.
+        assert_eq!(b.end_time.as_deref(), Some("2024-03-10T04:00:00-04:00"));
+    }
+
+    #[test]
+    fn test_parse_datetime_strips_utc_offsets() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let expected = date.and_hms_opt(8, 30, 0);
+        for s in [
+            "2024-01-15T08:30:00",
+            "2024-01-15T08:30:00Z",
+            "2024-01-15T08:30:00z",
+            "2024-01-15T08:30:00+05:30",
+            "2024-01-15T08:30:00-05:00",
+            "2024-01-15T08:30:00-0500",
+            "2024-01-15T08:30:00-05",
+            "2024-01-15T08:30-05:00",
+            "2024-01-15 08:30:00-08:00",
+            " 2024-01-15T08:30:00+00:00 ",
+        ] {
+            assert_eq!(parse_datetime(s), expected, "{}", s);
+        }
+        // A bare date keeps its dashes.
+        assert_eq!(parse_datetime("2024-01-15"), date.and_hms_opt(0, 0, 0));
+        assert_eq!(parse_datetime("2024-01-15T08:30:00-5:00"), None);
+        assert_eq!(parse_datetime("not a date"), None);
+    }
+
+    #[test]
+    fn test_unknown_timezone_error() {
+        use crate::model::TimeConstraint;
+
+        let mut template = make_template(vec![make_step("a", 30, vec![])]);
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2024-03-10T00:30:00".to_string()),
+            end_time: None,
+            timezone: Some("Mars/Olympus_Mons".to_string()),
+        });
+        let err = solve(&template, None).unwrap_err();
+        assert!(matches!(err, SolveError::ValidationFailed(_)));
+    }
+
+    #[test]
+    fn test_critical_path_sensitivity() {
+        // a(30) and b(10) both feed c(5): b can grow by 20 minutes
+        let template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 10, vec![]),
+            make_step(
+                "c",
+                5,
+                vec![
+                    ("a", DependencyType::FinishToStart),
+                    ("b", DependencyType::FinishToStart),
+                ],
+            ),
+        ]);
+        let report = critical_path_sensitivity(&template).unwrap();
+        let a = &report.steps[0];
+        let b = &report.steps[1];
+        assert_eq!(a.sensitivity_mins, 1.0);
+        assert_eq!(a.becomes_critical_at_mins, None);
+        assert_eq!(b.sensitivity_mins, 20.0);
+        assert_eq!(b.becomes_critical_at_mins, Some(30));
+    }
+
+    #[test]
+    fn test_sort_by_wbs_lexicographic() {
+        let mut steps = vec![
+            make_step("c", 10, vec![]),
+            make_step("b", 10, vec![]),
+            make_step("a", 10, vec![]),
+            make_step("none", 10, vec![]),
+        ];
+        steps[0].wbs_code = Some("1.10".to_string());
+        steps[1].wbs_code = Some("1.2".to_string());
+        steps[2].wbs_code = Some("1".to_string());
+        let result = solve(&make_template(steps), None).unwrap();
+
+        let mut solved = result.solved_steps;
+        sort_by_wbs(&mut solved);
+        let ids: Vec<&str> = solved.iter().map(|s| s.step_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c", "b", "none"]);
+    }
+
+    #[test]
+    fn test_trigger_at_percent_starts_successor_early() {
+        let mut review = make_step("review", 30, vec![("code", DependencyType::FinishToStart)]);
+        review.dependencies[0].trigger_at_percent = Some(80);
+        let template = make_template(vec![make_step("code", 100, vec![]), review]);
+        let result = solve(&template, None).unwrap();
+
+        let review = result.solved_steps.iter().find(|s| s.step_id == "review").unwrap();
+        assert_eq!(review.start_offset_mins, 80);
+        assert_eq!(result.summary.total_duration_mins, 110);
+        // Review finishes last, so both steps remain critical.
+        assert!(result.solved_steps.iter().all(|s| s.is_critical));
+    }
+
+    #[test]
+    fn test_dry_run_skips_allocation() {
+        use crate::model::{ResourceKind, ResourceNeed};
+
+        let oven_need = || ResourceNeed {
+            resource_id: "oven".to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: false,
+        };
+        let mut a = make_step("a", 30, vec![]);
+        a.resource_needs.push(oven_need());
+        let mut b = make_step("b", 30, vec![]);
+        b.resource_needs.push(oven_need());
+        let mut template = make_template(vec![a, b]);
+        template.resources.push(Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        });
+
+        let full = solve(&template, None).unwrap();
+        assert_eq!(full.summary.total_duration_mins, 60);
+
+        let options = SolveOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let dry = solve_with_options(&template, None, &options).unwrap();
+        assert_eq!(dry.summary.total_duration_mins, 30);
+        assert!(dry.solved_steps.iter().all(|s| s.assigned_resources.is_empty()));
+    }
+
+    #[test]
+    fn test_solve_with_options_validates_by_default() {
+        let mut a = make_step("a", 30, vec![]);
+        a.wbs_code = Some("1".to_string());
+        let mut b = make_step("b", 30, vec![]);
+        b.wbs_code = Some("1".to_string());
+        let template = make_template(vec![a, b]);
+
+        assert!(solve(&template, None).is_ok());
+        let err = solve_with_options(&template, None, &SolveOptions::default()).unwrap_err();
+        assert!(matches!(err, SolveError::ValidationFailed(_)));
+    }
+
+    #[test]
+    fn test_minimum_headcounts_are_checked_before_allocation() {
+        use crate::model::{ResourceKind, ResourceNeed};
+
+        let crew_need = |min_people: u32| ResourceNeed {
+            resource_id: "crew".to_string(),
+            quantity: 1,
+            min_people: Some(min_people),
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: false,
+        };
+        let mut a = make_step("a", 30, vec![]);
+        a.resource_needs = vec![crew_need(2)];
+        let mut b = make_step("b", 30, vec![]);
+        b.resource_needs = vec![crew_need(2)];
+        let mut template = make_template(vec![a, b]);
+        template.resources = vec![Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity: 3,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+
+        // Together a and b need 4 of 3 people: a warning.
+        let solved = solve_with_options(&template, None, &SolveOptions::default()).unwrap();
+        assert_eq!(
+            solved.warnings[0],
+            "Steps 'a', 'b' need at least 4 people from 'Crew' at minute 0 but only 3 are defined -- some will be delayed"
+        );
+
+        // An inventory of 4 covers them both; one of 1 can't staff either.
+        let inventory = |available_quantity: u32| ResourceInventory {
+            items: vec![crate::model::ResourceInventoryItem {
+                resource_id: "crew".to_string(),
+                available_quantity,
+            }],
+        };
+        let solved =
+            solve_with_options(&template, Some(&inventory(4)), &SolveOptions::default()).unwrap();
+        assert!(!solved.warnings.iter().any(|w| w.contains("need at least")));
+        let err = solve_with_options(&template, Some(&inventory(1)), &SolveOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("Step 'a' needs at least 2 people from 'Crew'"));
+
+        // b alone needing 4 can never be staffed.
+        template.steps[1].resource_needs = vec![crew_need(4)];
+        let err = solve_with_options(&template, None, &SolveOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("Step 'b' needs at least 4 people from 'Crew'"));
+        let check = crate::validator::validate_resource_capacity_for_minimum_headcount(&template);
+        assert_eq!(check.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_cpm_only_exposes_float_windows() {
+        let template = make_template(vec![
+            make_step("long", 60, vec![]),
+            make_step("short", 20, vec![]),
+        ]);
+        let result = cpm_only(&template).unwrap();
+
+        assert_eq!(result.project_end, 60);
+        assert_eq!(result.early_starts["short"], 0);
+        assert_eq!(result.late_starts["short"], 40);
+        assert_eq!(result.late_starts["long"], 0);
+    }
+
+    #[test]
+    fn test_detect_near_critical_paths() {
+        // a(60) -> d(10) is critical; b(50) -> d has 10 float; c(20) -> d has 40.
+        let template = make_template(vec![
+            make_step("a", 60, vec![]),
+            make_step("b", 50, vec![]),
+            make_step("c", 20, vec![]),
+            make_step(
+                "d",
+                10,
+                vec![
+                    ("a", DependencyType::FinishToStart),
+                    ("b", DependencyType::FinishToStart),
+                    ("c", DependencyType::FinishToStart),
+                ],
+            ),
+        ]);
+        let solved = solve(&template, None).unwrap();
+        let paths = detect_near_critical_paths(&solved, &template, 15);
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].step_ids, vec!["a", "d"]);
+        assert_eq!(paths[0].max_float_mins, 0);
+        assert_eq!(paths[1].step_ids, vec!["b", "d"]);
+        assert_eq!(paths[1].max_float_mins, 10);
+    }
+
+    #[test]
+    fn test_near_critical_paths_are_capped_in_dense_networks() {
+        // 40 layers of two equal steps, each depending on both steps of the
+        // layer before: 2^40 critical chains.
+        let mut steps = Vec::new();
+        for layer in 0..40 {
+            for side in ["x", "y"] {
+                let deps = match layer {
+                    0 => vec![],
+                    _ => vec![
+                        (format!("x{}", layer - 1), DependencyType::FinishToStart),
+                        (format!("y{}", layer - 1), DependencyType::FinishToStart),
+                    ],
+                };
+                let deps = deps.iter().map(|(id, t)| (id.as_str(), t.clone())).collect();
+                steps.push(make_step(&format!("{}{}", side, layer), 10, deps));
+            }
+        }
+        // A short branch off x0 has plenty of float, so its chain is dropped.
+        steps.push(make_step("branch", 5, vec![("x0", DependencyType::FinishToStart)]));
+        let template = make_template(steps);
+        let solved = solve(&template, None).unwrap();
+
+        let paths = detect_near_critical_paths(&solved, &template, 1000);
+        assert_eq!(paths.len(), MAX_NEAR_CRITICAL_PATHS);
+        assert!(paths.iter().all(|p| p.max_float_mins == 0 && p.step_ids.len() == 40));
+        assert!(paths.windows(2).all(|w| w[0].step_ids < w[1].step_ids));
+    }
+
+    #[test]
+    fn test_fixed_step_pins_start_and_has_no_float() {
+        let mut b = make_step("b", 10, vec![("a", DependencyType::FinishToStart)]);
+        b.timing_policy = Some(TimingPolicy::Fixed { offset_mins: 40 });
+        let mut template = make_template(vec![
+            make_step("a", 30, vec![]),
+            b,
+            make_step("c", 5, vec![("b", DependencyType::FinishToStart)]),
+        ]);
+        let result = solve(&template, None).unwrap();
+
+        let get = |id: &str| result.solved_steps.iter().find(|s| s.step_id == id).unwrap();
+        assert_eq!((get("b").start_offset_mins, get("b").total_float_mins), (40, 0));
+        // a only has to finish by the fixed start.
+        assert_eq!(get("a").total_float_mins, 10);
+        assert_eq!(get("c").start_offset_mins, 50);
+        assert_eq!(result.summary.total_duration_mins, 55);
+
+        template.steps[1].timing_policy = Some(TimingPolicy::Fixed { offset_mins: 20 });
+        let err = solve(&template, None).unwrap_err();
+        let SolveError::ValidationFailed(msg) = err else {
+            panic!("expected a validation failure, got {:?}", err);
+        };
+        assert!(msg.contains("no earlier than 30"));
+    }
+
+    #[test]
+    fn test_any_of_step_starts_after_first_predecessor() {
+        let mut c = make_step(
+            "c",
+            40,
+            vec![("a", DependencyType::FinishToStart), ("b", DependencyType::FinishToStart)],
+        );
+        c.dependency_mode = DependencyMode::AnyOf;
+        let template =
+            make_template(vec![make_step("a", 30, vec![]), make_step("b", 10, vec![]), c]);
+        let result = solve(&template, None).unwrap();
+
+        let get = |id: &str| result.solved_steps.iter().find(|s| s.step_id == id).unwrap();
+        assert_eq!(get("c").start_offset_mins, 10);
+        assert_eq!(result.summary.total_duration_mins, 50);
+        // b releases c and drives the finish; a doesn't hold anything up.
+        assert_eq!(get("b").total_float_mins, 0);
+        assert_eq!(get("a").total_float_mins, 20);
+    }
+
+    #[test]
+    fn test_external_dependency_shifts_early_start() {
+        use crate::model::ExternalDependency;
+
+        let mut b = make_step("b", 30, vec![("a", DependencyType::FinishToStart)]);
+        b.external_dependencies.push(ExternalDependency {
+            external_project_id: "supplier".to_string(),
+            external_step_id: "deliver".to_string(),
+            expected_offset_mins: 90,
+        });
+        let template = make_template(vec![make_step("a", 20, vec![]), b]);
+        let result = solve(&template, None).unwrap();
+
+        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!(b.start_offset_mins, 90);
+        assert_eq!(result.summary.total_duration_mins, 120);
+    }
+
+    #[test]
+    fn test_inactive_conditional_steps_are_excluded() {
+        let mut allergens = make_step("allergens", 30, vec![]);
+        allergens.condition = Some("gluten_free".to_string());
+        let template = make_template(vec![
+            allergens,
+            make_step("cook", 20, vec![("allergens", DependencyType::FinishToStart)]),
+        ]);
+
+        let result = solve(&template, None).unwrap();
+        assert_eq!(result.solved_steps.len(), 1);
+        assert_eq!(result.summary.total_duration_mins, 20);
+
+        let options = SolveOptions {
+            active_conditions: vec!["gluten_free".to_string()],
+            ..Default::default()
+        };
+        let result = solve_with_options(&template, None, &options).unwrap();
+        assert_eq!(result.solved_steps.len(), 2);
+        assert_eq!(result.summary.total_duration_mins, 50);
+    }
+
+    #[test]
+    fn test_conditional_dependency_only_applies_when_active() {
+        let fs = DependencyType::FinishToStart;
+        let mut ship = make_step("ship", 10, vec![("build", fs.clone()), ("audit", fs)]);
+        ship.dependencies[1].condition = Some("compliance".to_string());
+        let template = make_template(vec![
+            make_step("build", 20, vec![]),
+            make_step("audit", 60, vec![]),
+            ship,
+        ]);
+
+        // Fast path: ship waits only for the build.
+        let result = solve(&template, None).unwrap();
+        assert_eq!(result.solved_steps.len(), 3);
+        assert_eq!(result.solved_steps[2].start_offset_mins, 20);
+
+        // Full compliance path: ship also waits for the audit.
+        let options = SolveOptions {
+            active_conditions: vec!["compliance".to_string()],
+            ..Default::default()
+        };
+        let result = solve_with_options(&template, None, &options).unwrap();
+        assert_eq!(result.solved_steps[2].start_offset_mins, 60);
+    }
+
+    #[test]
+    fn test_interruptible_step_pauses_outside_working_hours() {
+        use crate::model::{TimeConstraint, WorkingCalendar, WorkingWindow};
+
+        let mut cure = make_step("cure", 600, vec![]);
+        cure.interruptible = true;
+        let mut template = make_template(vec![cure, make_step("prep", 300, vec![])]);
+        // 08:00-17:00 daily, schedule anchored at midnight.
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow {
+                start_offset_mins: 480,
+                end_offset_mins: 1020,
+            }],
+        });
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-01-05T00:00:00".to_string()),
+            end_time: None,
+            timezone: None,
+        });
+        let result = solve(&template, None).unwrap();
+
+        let cure = result.solved_steps.iter().find(|s| s.step_id == "cure").unwrap();
+        assert_eq!(cure.start_offset_mins, 480);
+        assert_eq!(cure.end_offset_mins, 1440 + 540);
+        assert_eq!(cure.segments.len(), 2);
+        assert_eq!(cure.segments[0].wall_end.as_deref(), Some("2026-01-05T17:00:00"));
+        assert_eq!(cure.segments[1].wall_start.as_deref(), Some("2026-01-06T08:00:00"));
+
+        // Non-interruptible steps wait for a window that holds them whole.
+        let prep = result.solved_steps.iter().find(|s| s.step_id == "prep").unwrap();
+        assert_eq!((prep.start_offset_mins, prep.end_offset_mins), (480, 780));
+        assert!(prep.segments.is_empty());
+    }
+
+    #[test]
+    fn test_non_interruptible_successor_skips_blackout() {
+        use crate::model::{WorkingCalendar, WorkingWindow};
+
+        let mut template = make_template(vec![
+            make_step("b", 40, vec![]),
+            make_step("c", 40, vec![("b", DependencyType::FinishToStart)]),
+        ]);
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow { start_offset_mins: 0, end_offset_mins: 60 }],
+        });
+        let result = solve(&template, None).unwrap();
+
+        let c = result.solved_steps.iter().find(|s| s.step_id == "c").unwrap();
+        assert_eq!((c.start_offset_mins, c.end_offset_mins), (1440, 1480));
+        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!(b.total_float_mins, 20);
+    }
+
+    #[test]
+    fn test_successor_waits_for_interruptible_predecessor_to_finish() {
+        use crate::model::{WorkingCalendar, WorkingWindow};
+
+        let mut a = make_step("a", 120, vec![]);
+        a.interruptible = true;
+        let mut template = make_template(vec![
+            a,
+            make_step("b", 10, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow { start_offset_mins: 0, end_offset_mins: 60 }],
+        });
+        let result = solve(&template, None).unwrap();
+
+        let a = result.solved_steps.iter().find(|s| s.step_id == "a").unwrap();
+        assert_eq!(a.end_offset_mins, 1500);
+        assert_eq!(a.total_float_mins, 0);
+        // a finishes as day two's window closes, so b waits for day three.
+        let b = result.solved_steps.iter().find(|s| s.step_id == "b").unwrap();
+        assert_eq!((b.start_offset_mins, b.end_offset_mins), (2880, 2890));
+    }
+
+    #[test]
+    fn test_allocation_moves_interruptible_step_through_calendar() {
+        use crate::model::{
+            Resource, ResourceKind, ResourceNeed, TimeConstraint, WorkingCalendar, WorkingWindow,
+        };
+
+        let with_oven = |mut step: Step| {
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+            step
+        };
+        // The critical roast holds the oven until 17:40 on day two; the
+        // interruptible bake (10 working hours) has to wait for it.
+        let mut bake = make_step("bake", 600, vec![]);
+        bake.interruptible = true;
+        let mut template =
+            make_template(vec![with_oven(make_step("roast", 2500, vec![])), with_oven(bake)]);
+        template.resources = vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+        template.working_calendar = Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow {
+                start_offset_mins: 480,
+                end_offset_mins: 1020,
+            }],
+        });
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-01-05T00:00:00".to_string()),
+            end_time: None,
+            timezone: None,
+        });
+
+        let result = solve(&template, None).unwrap();
+        let bake = result.solved_steps.iter().find(|s| s.step_id == "bake").unwrap();
+        // 08:00 on day three: 9 hours, then the last hour on day four.
+        assert_eq!((bake.start_offset_mins, bake.end_offset_mins), (2880 + 480, 4320 + 540));
+        let spans: Vec<(u32, u32)> =
+            bake.segments.iter().map(|s| (s.start_offset_mins, s.end_offset_mins)).collect();
+        assert_eq!(spans, vec![(3360, 3900), (4800, 4860)]);
+        assert_eq!(bake.start_time.as_deref(), Some("2026-01-07T08:00:00"));
+        assert_eq!(bake.segments[1].wall_start.as_deref(), Some("2026-01-08T08:00:00"));
+        assert_eq!(result.summary.total_duration_mins, 4860);
+    }
+
+    #[test]
+    fn test_fixed_steps_keep_their_slot_during_allocation() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        let with_oven = |mut step: Step| {
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+            step
+        };
+        let fixed_at = |mut step: Step, offset_mins| {
+            step.timing_policy = Some(TimingPolicy::Fixed { offset_mins });
+            step
+        };
+        // The critical roast would normally take the oven first.
+        let mut template = make_template(vec![
+            with_oven(make_step("roast", 60, vec![])),
+            with_oven(fixed_at(make_step("briefing", 15, vec![]), 0)),
+        ]);
+        template.resources = vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+        let start_of = |solved: &SolvedSchedule, id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+
+        let solved = solve(&template, None).unwrap();
+        assert_eq!(start_of(&solved, "briefing"), 0);
+        assert_eq!(start_of(&solved, "roast"), 15);
+
+        // Two fixed steps on the one oven: both stay, and the clash is reported.
+        template.steps.push(with_oven(fixed_at(make_step("demo", 10, vec![]), 5)));
+        let solved = solve(&template, None).unwrap();
+        assert_eq!(start_of(&solved, "demo"), 5);
+        let clash = "Step 'demo' is fixed to start at 5 min but 'Oven' is already in use then";
+        assert!(solved.warnings.iter().any(|w| w == clash));
+    }
+
+    #[test]
+    fn test_hammock_spans_its_members() {
+        use crate::model::HammockGroup;
+
+        let mut template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 20, vec![("a", DependencyType::FinishToStart)]),
+            make_step("c", 90, vec![]),
+        ]);
+        template.hammock_groups.push(HammockGroup {
+            id: "prep".to_string(),
+            name: "Prep".to_string(),
+            member_step_ids: vec!["a".to_string(), "b".to_string()],
+        });
+        let result = solve(&template, None).unwrap();
+
+        assert_eq!(result.hammocks.len(), 1);
+        let hammock = &result.hammocks[0];
+        assert_eq!(hammock.group_id, "prep");
+        assert_eq!((hammock.start_offset_mins, hammock.end_offset_mins), (0, 50));
+        assert_eq!(hammock.duration_mins, 50);
+    }
+
+    #[test]
+    fn test_incremental_matches_full_solve() {
+        // a -> b -> c alongside a long, unconnected d -> e (e is ALAP).
+        let mut template = make_template(vec![
+            make_step("a", 10, vec![]),
+            make_step("b", 10, vec![("a", DependencyType::FinishToStart)]),
+            make_step("c", 10, vec![("b", DependencyType::FinishToStart)]),
+            make_step("d", 100, vec![]),
+            make_step("e", 20, vec![("d", DependencyType::StartToStart)]),
+        ]);
+        template.steps[4].timing_policy = Some(TimingPolicy::Alap);
+        let baseline = solve(&template, None).unwrap();
+        let changed = ["b".to_string()];
+
+        for duration in [40, 120, 5] {
+            template.steps[1].duration_mins = duration;
+            let full = solve(&template, None).unwrap();
+            let incremental = solve_incremental(&baseline, &changed, &template, None).unwrap();
+            assert_eq!(incremental, full, "b = {} min", duration);
+        }
+
+        let all: Vec<String> = template.steps.iter().map(|s| s.id.clone()).collect();
+        let full = solve(&template, None).unwrap();
+        assert_eq!(solve_incremental(&baseline, &all, &template, None).unwrap(), full);
+    }
+
+    #[test]
+    fn test_incremental_keeps_unconnected_steps() {
+        let mut template = make_template(vec![
+            make_step("a", 10, vec![]),
+            make_step("b", 10, vec![("a", DependencyType::FinishToStart)]),
+            make_step("x", 50, vec![]),
+        ]);
+        let mut baseline = solve(&template, None).unwrap();
+        // Tag the untouched step so we can tell it was reused, not recomputed.
+        baseline.solved_steps[2].start_time = Some("reused".to_string());
+
+        template.steps[0].duration_mins = 30;
+        let incremental =
+            solve_incremental(&baseline, &["a".to_string()], &template, None).unwrap();
+        let x = &incremental.solved_steps[2];
+        assert_eq!(x.start_time.as_deref(), Some("reused"));
+        assert_eq!(x.total_float_mins, 0);
+        assert_eq!(incremental.solved_steps[1].start_offset_mins, 30);
+        assert_eq!(incremental.summary.total_duration_mins, 50);
+    }
+
+    #[test]
+    fn test_steps_past_the_deadline_get_a_note() {
+        use crate::model::{NoteCode, TimeConstraint};
+
+        let mut template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 60, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: Some("2026-03-01T10:00:00".to_string()),
+            timezone: None,
+        });
+        let result = solve(&template, None).unwrap();
+
+        assert!(result.solved_steps[0].notes.is_empty());
+        let notes = &result.solved_steps[1].notes;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].code, NoteCode::DeadlineWarning);
+        assert!(notes[0].message.contains("30 min"));
+    }
+
+    #[test]
+    fn test_only_exact_duplicate_dependencies_are_dropped() {
+        // b depends on a: FS, FS again, FS at 50%, and SS.
+        let mut b = make_step(
+            "b",
+            20,
+            vec![
+                ("a", DependencyType::FinishToStart),
+                ("a", DependencyType::FinishToStart),
+                ("a", DependencyType::FinishToStart),
+                ("a", DependencyType::StartToStart),
+            ],
+        );
+        b.dependencies[2].trigger_at_percent = Some(50);
+        let unique = unique_dependencies(&b);
+        assert_eq!(unique.len(), 3);
+        assert_eq!(unique[0], &b.dependencies[0]);
+        assert_eq!(unique[1].trigger_at_percent, Some(50));
+        assert_eq!(unique[2].dependency_type, DependencyType::StartToStart);
+
+        // The plain FS still holds b until a finishes.
+        let template = make_template(vec![make_step("a", 60, vec![]), b]);
+        let result = cpm(&template).unwrap();
+        assert_eq!(result.early_starts["b"], 60);
+    }
+
+    #[test]
+    fn test_solve_backward_from_deadline_overrides_time_constraint() {
+        let mut template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 60, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+        template.time_constraint = Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: None,
+            timezone: None,
+        });
+        let deadline = parse_datetime("2026-03-01T12:00:00").unwrap();
+
+        let solved = solve_backward_from_deadline(&template, deadline, None).unwrap();
+        assert_eq!(solved.solved_steps[0].start_time.as_deref(), Some("2026-03-01T10:30:00"));
+        assert_eq!(solved.solved_steps[1].end_time.as_deref(), Some("2026-03-01T12:00:00"));
+        let tc = template.time_constraint.as_ref().unwrap();
+        assert_eq!(tc.start_time.as_deref(), Some("2026-03-01T09:00:00"));
+    }
+
+    #[test]
+    fn test_solve_with_max_duration_rejects_longer_schedules() {
+        let template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 20, vec![("a", DependencyType::FinishToStart)]),
+        ]);
+
+        assert!(solve_with_max_duration(&template, 50, None).is_ok());
+        assert!(matches!(
+            solve_with_max_duration(&template, 45, None),
+            Err(ConstraintViolation::MaxDurationExceeded { computed: 50, limit: 45 })
+        ));
+        let broken = make_template(vec![make_step("a", 0, vec![])]);
+        assert!(matches!(
+            solve_with_max_duration(&broken, 45, None),
+            Err(ConstraintViolation::Solve(SolveError::MissingDuration(_)))
+        ));
+    }
+
+    #[test]
+    fn test_solve_all_scenarios_keeps_going_after_a_failure() {
+        let optimistic = make_template(vec![make_step("a", 30, vec![])]);
+        let broken = make_template(vec![make_step("a", 0, vec![])]);
+        let pessimistic = make_template(vec![make_step("a", 90, vec![])]);
+
+        let results = solve_all_scenarios(&[optimistic, broken, pessimistic], None);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().summary.total_duration_mins, 30);
+        assert!(matches!(results[1], Err(SolveError::MissingDuration(_))));
+        assert_eq!(results[2].as_ref().unwrap().summary.total_duration_mins, 90);
+    }
+
+    #[test]
+    fn test_solve_batched_with_rayon_matches_sequential() {
+        let templates = [
+            make_template(vec![make_step("a", 30, vec![])]),
+            make_template(vec![make_step("a", 0, vec![])]),
+            make_template(vec![make_step("a", 90, vec![])]),
+        ];
+        let parallel = solve_batched_with_rayon(&templates, None);
+        let sequential = solve_all_scenarios(&templates, None);
+        assert_eq!(format!("{:?}", parallel), format!("{:?}", sequential));
+    }
+
+    #[test]
+    fn test_solve_with_timeout_returns_partial_result() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        // 200 steps in chains of 10, all contending for one oven: allocation
+        // is the slow part.
+        let mut steps: Vec<Step> = (0..200u32)
+            .map(|i| {
+                let prev = format!("s{}", i.saturating_sub(1));
+                let deps = if i % 10 == 0 {
+                    vec![]
+                } else {
+                    vec![(prev.as_str(), DependencyType::FinishToStart)]
+                };
+                make_step(&format!("s{}", i), 10 + i % 7, deps)
+            })
+            .collect();
+        for step in &mut steps {
+            step.resource_needs = vec![ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            }];
+        }
+        let mut template = make_template(steps);
+        template.resources = vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+
+        // A full solve that can't finish within the timeout, so the outcome
+        // doesn't depend on how fast this machine allocates.
+        let slow_solve = |template: &ScheduleTemplate, inventory: Option<&ResourceInventory>| {
+            thread::sleep(Duration::from_secs(2));
+            solve(template, inventory)
+        };
+        let started = std::time::Instant::now();
+        let partial =
+            solve_with_timeout_using(&template, None, Duration::from_millis(50), slow_solve)
+                .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1), "solve_with_timeout hung");
+
+        // CPM-only fallback: every step timed, nothing allocated.
+        assert_eq!(partial.solved_steps.len(), 200);
+        assert!(partial.solved_steps.iter().all(|s| s.assigned_resources.is_empty()));
+        assert_eq!(
+            partial.warnings,
+            vec![
+                "Solve timed out after 50 ms -- showing CPM timings without resource allocation"
+                    .to_string()
+            ]
+        );
+
+        // Plenty of time: the full solve comes back, with allocations.
+        let full = solve_with_timeout(&template, None, Duration::from_secs(60)).unwrap();
+        assert!(full.warnings.iter().all(|w| !w.contains("timed out")));
+        assert!(full.solved_steps.iter().all(|s| s.assigned_resources.len() == 1));
+    }
+
+    #[test]
+    fn test_resource_substitution_cascades_and_rejects_cycles() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        let oven = |id: &str| Resource {
+            id: id.to_string(),
+            name: id.to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        };
+        let mut bake = make_step("bake", 30, vec![]);
+        bake.resource_needs.push(ResourceNeed {
+            resource_id: "oven".to_string(),
+            quantity: 1,
+            min_people: None,
+            max_people: None,
+            role: None,
+            cost_per_minute: None,
+            allow_partial: false,
+        });
+        let mut template = make_template(vec![bake]);
+        template.resources = vec![oven("oven"), oven("portable"), oven("grill")];
+
+        let subs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+        };
+        let result = solve_with_resource_substitution(
+            &template,
+            None,
+            &subs(&[("oven", "portable"), ("portable", "grill")]),
+        )
+        .unwrap();
+        assert_eq!(result.solved_steps[0].assigned_resources[0].resource_id, "grill");
+
+        let err = solve_with_resource_substitution(
+            &template,
+            None,
+            &subs(&[("oven", "portable"), ("portable", "oven")]),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Circular resource substitution: oven -> portable -> oven");
+
+        let undefined = subs(&[("oven", "kiln")]);
+        let err = solve_with_resource_substitution(&template, None, &undefined).unwrap_err();
+        assert!(matches!(err, SolveError::ValidationFailed(_)));
+    }
+
+    #[test]
+    fn test_custom_priority_replaces_default_order() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        let oven_step = |id: &str, dur: u32| {
+            let mut step = make_step(id, dur, vec![]);
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+            step
+        };
+        let mut template = make_template(vec![oven_step("roast", 30), oven_step("toast", 10)]);
+        template.resources = vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+        let start_of = |solved: &SolvedSchedule, id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+
+        // By default the critical roast goes first.
+        let default = solve(&template, None).unwrap();
+        assert_eq!(start_of(&default, "roast"), 0);
+        assert_eq!(start_of(&default, "toast"), 30);
+
+        // Shortest first puts the toast in the oven before the roast.
+        let shortest_first = Box::new(|a: &SolvedStep, b: &SolvedStep| {
+            let dur = |s: &SolvedStep| s.end_offset_mins - s.start_offset_mins;
+            dur(a).cmp(&dur(b))
+        });
+        let custom = solve_with_custom_priority(&template, None, shortest_first).unwrap();
+        assert_eq!(start_of(&custom, "toast"), 0);
+        assert_eq!(start_of(&custom, "roast"), 10);
+    }
+
+    #[test]
+    fn test_deadline_proximity_places_tight_steps_first() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        let with_oven = |mut step: Step| {
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "oven".to_string(),
+                quantity: 1,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial: false,
+            });
+            step
+        };
+        // "simmer" (100) sets the deadline. "bake" starts first but has 70 of
+        // its 100 minutes as float; "roast" waits for "prep" but only has
+        // 10 of 60.
+        let mut template = make_template(vec![
+            make_step("simmer", 100, vec![]),
+            with_oven(make_step("bake", 30, vec![])),
+            make_step("prep", 20, vec![]),
+            with_oven(make_step("roast", 30, vec![("prep", DependencyType::FinishToStart)])),
+            make_step("serve", 40, vec![("roast", DependencyType::FinishToStart)]),
+        ]);
+        template.resources = vec![Resource {
+            id: "oven".to_string(),
+            name: "Oven".to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+        let start_of = |solved: &SolvedSchedule, id: &str| {
+            solved.solved_steps.iter().find(|s| s.step_id == id).unwrap().start_offset_mins
+        };
+
+        // By default the earlier bake gets the oven and roast uses its float.
+        let default = solve(&template, None).unwrap();
+        assert_eq!(start_of(&default, "bake"), 0);
+        assert_eq!(start_of(&default, "roast"), 30);
+
+        // By deadline proximity the tighter roast goes first.
+        let proximity =
+            solve_with_step_priorities_from_deadline_proximity(&template, None).unwrap();
+        assert_eq!(start_of(&proximity, "roast"), 20);
+        assert_eq!(start_of(&proximity, "bake"), 50);
+        assert_eq!(proximity.summary.total_duration_mins, 100);
+        assert_eq!(default.summary.total_duration_mins, 100);
+    }
+
+    #[test]
+    fn test_resource_splitting_starts_with_fewer_workers() {
+        use crate::model::{Resource, ResourceKind, ResourceNeed};
+
+        let with_cooks = |mut step: Step, quantity: u32, allow_partial: bool| {
+            step.resource_needs.push(ResourceNeed {
+                resource_id: "cooks".to_string(),
+                quantity,
+                min_people: None,
+                max_people: None,
+                role: None,
+                cost_per_minute: None,
+                allow_partial,
+            });
+            step
+        };
+        // The critical stew holds 2 of the 4 cooks for 100 minutes; the
+        // salad wants all 4 for 20 minutes.
+        let mut template = make_template(vec![
+            with_cooks(make_step("stew", 100, vec![]), 2, false),
+            with_cooks(make_step("salad", 20, vec![]), 4, true),
+        ]);
+        template.resources = vec![Resource {
+            id: "cooks".to_string(),
+            name: "Cooks".to_string(),
+            kind: ResourceKind::People,
+            capacity: 4,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }];
+        let salad = |solved: &SolvedSchedule| {
+            solved.solved_steps.iter().find(|s| s.step_id == "salad").unwrap().clone()
+        };
+
+        // Without splitting the salad waits for the stew to free its cooks.
+        let default = solve(&template, None).unwrap();
+        assert_eq!(salad(&default).start_offset_mins, 100);
+        assert_eq!(default.summary.total_duration_mins, 120);
+
+        // With splitting it starts at once with 2 cooks, taking twice as long.
+        let split = solve_with_resource_splitting(&template, None).unwrap();
+        let step = salad(&split);
+        assert_eq!((step.start_offset_mins, step.end_offset_mins), (0, 40));
+        assert_eq!(step.assigned_resources[0].quantity_used, 2);
+        assert_eq!(step.notes[0].code, NoteCode::PartialResources);
+        assert_eq!(split.summary.total_duration_mins, 100);
+
+        // Needing at least 3 cooks, it can't start early with the 2 free.
+        template.steps[1].resource_needs[0].min_people = Some(3);
+        let split = solve_with_resource_splitting(&template, None).unwrap();
+        assert_eq!(salad(&split).start_offset_mins, 100);
+        assert_eq!(salad(&split).assigned_resources[0].quantity_used, 4);
+    }
+
+    #[test]
+    fn test_all_critical_paths_enumerates_ties() {
+        // a(30) fans out to b(20) and c(20), both feeding d(10): two tied
+        // paths. e(5) -> f(5) is short and off the critical path.
+        let template = make_template(vec![
+            make_step("a", 30, vec![]),
+            make_step("b", 20, vec![("a", DependencyType::FinishToStart)]),
+            make_step("c", 20, vec![("a", DependencyType::FinishToStart)]),
+            make_step(
+                "d",
+                10,
+                vec![("b", DependencyType::FinishToStart), ("c", DependencyType::FinishToStart)],
+            ),
+            make_step("e", 5, vec![]),
+            make_step("f", 5, vec![("e", DependencyType::FinishToStart)]),
+        ]);
+
+        let paths = all_critical_paths(&template, 10).unwrap();
+        assert_eq!(paths, vec![vec!["a", "b", "d"], vec!["a", "c", "d"]]);
+        assert_eq!(all_critical_paths(&template, 1).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_missing_duration_error() {
         let template = make_template(vec![make_step("a", 0, vec![])]);
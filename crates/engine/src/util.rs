@@ -0,0 +1,1024 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::algo::toposort;
+use petgraph::graph::DiGraph;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    DependencyMode, DependencyType, Resource, ResourceInventory, ScheduleSummary,
+    ScheduleTemplate, SolvedSchedule, SolvedStep,
+};
+use crate::solver::SolveError;
+
+/// Key used by `split_schedule_by_track` for steps without a `track_id`.
+pub const DEFAULT_TRACK_KEY: &str = "default";
+
+// ---------------------------------------------------------------------------
+// Splitting
+// ---------------------------------------------------------------------------
+
+/// Partition a solved schedule into one sub-schedule per track, keyed by
+/// track ID (`"default"` for untracked steps).
+///
+/// Each sub-schedule keeps the full schedule's `template_id`, warnings, total
+/// duration and cost, so every team sees the same overall timeline; only the
+/// steps and the critical-path step IDs are scoped to the track.
+///
+/// `template` should be the template as solved (see
+/// `solver::prepare_template`), so recurring copies keep their step's track.
+pub fn split_schedule_by_track(
+    solved: &SolvedSchedule,
+    template: &ScheduleTemplate,
+) -> HashMap<String, SolvedSchedule> {
+    let step_tracks: HashMap<&str, &str> = template
+        .steps
+        .iter()
+        .filter_map(|s| s.track_id.as_deref().map(|t| (s.id.as_str(), t)))
+        .collect();
+    let track_of = |step_id: &str| {
+        step_tracks.get(step_id).copied().unwrap_or(DEFAULT_TRACK_KEY).to_string()
+    };
+
+    let mut split: HashMap<String, SolvedSchedule> = HashMap::new();
+    for step in &solved.solved_steps {
+        split
+            .entry(track_of(&step.step_id))
+            .or_insert_with(|| SolvedSchedule {
+                template_id: solved.template_id.clone(),
+                solved_steps: Vec::new(),
+                summary: ScheduleSummary {
+                    critical_path_step_ids: Vec::new(),
+                    ..solved.summary.clone()
+                },
+                warnings: solved.warnings.clone(),
+                gantt_rows: None,
+                near_critical_paths: None,
+                float_consumption: None,
+                hammocks: solved.hammocks.clone(),
+            })
+            .solved_steps
+            .push(step.clone());
+    }
+
+    for id in &solved.summary.critical_path_step_ids {
+        if let Some(sub) = split.get_mut(&track_of(id)) {
+            sub.summary.critical_path_step_ids.push(id.clone());
+        }
+    }
+
+    split
+}
+
+// ---------------------------------------------------------------------------
+// Merging
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("Step '{0}' is defined in both templates")]
+    DuplicateStepId(String),
+    #[error("Resource '{0}' is defined in both templates")]
+    DuplicateResourceId(String),
+    #[error("The templates have different time constraints")]
+    ConflictingTimeConstraint,
+}
+
+/// Combine two schedule modules into one template.
+///
+/// Steps, tracks, resources, recurring patterns, hammock groups and resource
+/// groups are concatenated, base first. Step and resource IDs must be unique
+/// across both templates; a track defined in both is kept once (the base's),
+/// and so is a tag both templates carry.
+/// The result is named "<base name> (<overlay name>)" and keeps the base's
+/// ID, description and version. Other settings come from the base when set
+/// there and from the overlay otherwise, except that two different time
+/// constraints can't be reconciled and are an error.
+pub fn merge_templates(
+    base: &ScheduleTemplate,
+    overlay: &ScheduleTemplate,
+) -> Result<ScheduleTemplate, MergeError> {
+    let step_ids: HashSet<&str> = base.steps.iter().map(|s| s.id.as_str()).collect();
+    if let Some(dup) = overlay.steps.iter().find(|s| step_ids.contains(s.id.as_str())) {
+        return Err(MergeError::DuplicateStepId(dup.id.clone()));
+    }
+    let resource_ids: HashSet<&str> = base.resources.iter().map(|r| r.id.as_str()).collect();
+    if let Some(dup) = overlay.resources.iter().find(|r| resource_ids.contains(r.id.as_str())) {
+        return Err(MergeError::DuplicateResourceId(dup.id.clone()));
+    }
+    let time_constraint = match (&base.time_constraint, &overlay.time_constraint) {
+        (Some(a), Some(b)) if a != b => return Err(MergeError::ConflictingTimeConstraint),
+        (a, b) => a.as_ref().or(b.as_ref()).cloned(),
+    };
+
+    let mut tags = base.tags.clone();
+    for tag in &overlay.tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    let mut tracks = base.tracks.clone();
+    for track in &overlay.tracks {
+        if !tracks.iter().any(|t| t.id == track.id) {
+            tracks.push(track.clone());
+        }
+    }
+
+    Ok(ScheduleTemplate {
+        id: base.id.clone(),
+        name: format!("{} ({})", base.name, overlay.name),
+        description: base.description.clone(),
+        version: base.version.clone(),
+        steps: concat(&base.steps, &overlay.steps),
+        tracks,
+        resources: concat(&base.resources, &overlay.resources),
+        time_constraint,
+        default_num_people: base.default_num_people.or(overlay.default_num_people),
+        max_concurrent_steps: base.max_concurrent_steps.or(overlay.max_concurrent_steps),
+        recurring_patterns: concat(&base.recurring_patterns, &overlay.recurring_patterns),
+        embedded_baseline: base.embedded_baseline.clone(),
+        working_calendar: base.working_calendar.clone().or(overlay.working_calendar.clone()),
+        hammock_groups: concat(&base.hammock_groups, &overlay.hammock_groups),
+        resource_groups: concat(&base.resource_groups, &overlay.resource_groups),
+        resource_calendars: concat(&base.resource_calendars, &overlay.resource_calendars),
+        tags,
+    })
+}
+
+fn concat<T: Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter().chain(b).cloned().collect()
+}
+
+/// Copy a template with every ID it defines prefixed by `{prefix}-`, so
+/// several copies can be merged into one schedule (see `merge_templates`).
+///
+/// The template, step, track, resource, resource group, hammock group and
+/// recurring pattern IDs are prefixed, and so is every reference to them
+/// (dependencies, resource needs, track assignments and orderings, group
+/// members, resource calendars and the embedded baseline). External dependencies point at other
+/// projects and are left alone, as are WBS codes, which must stay numeric:
+/// renumber them before merging copies that use them.
+pub fn clone_with_prefix(template: &ScheduleTemplate, prefix: &str) -> ScheduleTemplate {
+    let p = |id: &str| format!("{}-{}", prefix, id);
+    let mut result = template.clone();
+    result.id = p(&template.id);
+
+    for step in &mut result.steps {
+        step.id = p(&step.id);
+        for dep in &mut step.dependencies {
+            dep.step_id = p(&dep.step_id);
+        }
+        for need in &mut step.resource_needs {
+            need.resource_id = p(&need.resource_id);
+        }
+        if let Some(track_id) = &mut step.track_id {
+            *track_id = p(track_id);
+        }
+    }
+    for track in &mut result.tracks {
+        track.id = p(&track.id);
+        for step_id in &mut track.steps {
+            *step_id = p(step_id);
+        }
+    }
+    for resource in &mut result.resources {
+        resource.id = p(&resource.id);
+    }
+    for group in &mut result.resource_groups {
+        group.id = p(&group.id);
+        for resource_id in &mut group.resource_ids {
+            *resource_id = p(resource_id);
+        }
+    }
+    for calendar in &mut result.resource_calendars {
+        calendar.resource_id = p(&calendar.resource_id);
+    }
+    for group in &mut result.hammock_groups {
+        group.id = p(&group.id);
+        for step_id in &mut group.member_step_ids {
+            *step_id = p(step_id);
+        }
+    }
+    for pattern in &mut result.recurring_patterns {
+        pattern.template_step_id = p(&pattern.template_step_id);
+        pattern.prefix = p(&pattern.prefix);
+    }
+    if let Some(baseline) = &mut result.embedded_baseline {
+        baseline.template_id = p(&baseline.template_id);
+        for step in &mut baseline.steps {
+            step.step_id = p(&step.step_id);
+        }
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Tag filtering
+// ---------------------------------------------------------------------------
+
+/// How `filter_steps_by_tags` matches a step's tags against the required ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagFilterMode {
+    /// The step carries every required tag.
+    AllOf,
+    /// The step carries at least one required tag.
+    AnyOf,
+}
+
+/// Copy of `template` keeping only the steps whose tags match
+/// `required_tags`. Every reference to a dropped step goes with it:
+/// dependencies on it, track orderings, hammock group members and
+/// recurring patterns repeating it. With no required tags, every step
+/// matches under `AllOf` and none under `AnyOf`.
+pub fn filter_steps_by_tags(
+    template: &ScheduleTemplate,
+    required_tags: &[&str],
+    mode: TagFilterMode,
+) -> ScheduleTemplate {
+    let matches = |tags: &[String]| {
+        let has = |tag: &&str| tags.iter().any(|t| t == tag);
+        match mode {
+            TagFilterMode::AllOf => required_tags.iter().all(has),
+            TagFilterMode::AnyOf => required_tags.iter().any(has),
+        }
+    };
+    let kept: HashSet<&str> = template
+        .steps
+        .iter()
+        .filter(|s| matches(&s.tags))
+        .map(|s| s.id.as_str())
+        .collect();
+
+    let mut result = template.clone();
+    result.steps.retain(|s| kept.contains(s.id.as_str()));
+    for step in &mut result.steps {
+        step.dependencies.retain(|d| kept.contains(d.step_id.as_str()));
+    }
+    for track in &mut result.tracks {
+        track.steps.retain(|id| kept.contains(id.as_str()));
+    }
+    for group in &mut result.hammock_groups {
+        group.member_step_ids.retain(|id| kept.contains(id.as_str()));
+    }
+    result.recurring_patterns.retain(|p| kept.contains(p.template_step_id.as_str()));
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Adjacency matrix
+// ---------------------------------------------------------------------------
+
+/// The dependency graph of a template as a matrix, rows and columns in
+/// `step_ids` order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjacencyMatrix {
+    pub step_ids: Vec<String>,
+    /// `matrix[i][j]` is the type of step `j`'s dependency on step `i`, or
+    /// `None` when `j` doesn't depend on `i`.
+    pub matrix: Vec<Vec<Option<DependencyType>>>,
+}
+
+/// Build the adjacency matrix of a template's dependencies. Steps keep their
+/// template order. When a step lists several dependency types on the same
+/// predecessor, the first one is used.
+///
+/// Fails on a dependency on an unknown step, and on a step depending on
+/// itself or two steps depending on each other (an anti-parallel pair).
+/// Longer cycles are not detected here.
+pub fn to_adjacency_matrix(template: &ScheduleTemplate) -> Result<AdjacencyMatrix, SolveError> {
+    let index: HashMap<&str, usize> =
+        template.steps.iter().enumerate().map(|(i, s)| (s.id.as_str(), i)).collect();
+    let n = template.steps.len();
+    let mut matrix = vec![vec![None; n]; n];
+
+    for (j, step) in template.steps.iter().enumerate() {
+        for dep in &step.dependencies {
+            let Some(&i) = index.get(dep.step_id.as_str()) else {
+                return Err(SolveError::UnknownDependency(step.id.clone(), dep.step_id.clone()));
+            };
+            if i == j {
+                return Err(SolveError::CyclicDependency(step.id.clone()));
+            }
+            if matrix[j][i].is_some() {
+                let (a, b) =
+                    if i < j { (&dep.step_id, &step.id) } else { (&step.id, &dep.step_id) };
+                return Err(SolveError::CyclicDependency(format!("{}, {}", a, b)));
+            }
+            if matrix[i][j].is_none() {
+                matrix[i][j] = Some(dep.dependency_type.clone());
+            }
+        }
+    }
+
+    Ok(AdjacencyMatrix {
+        step_ids: template.steps.iter().map(|s| s.id.clone()).collect(),
+        matrix,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Topological levels
+// ---------------------------------------------------------------------------
+
+/// Number each step by the longest chain of steps that must come before it,
+/// keyed by step ID. Steps on the same level can run in parallel, and levels
+/// double as Gantt columns.
+///
+/// Levels follow the CPM forward pass with every step taking one unit: a
+/// step with no dependencies is on level 0, a FinishToStart dependency puts
+/// it one level after its predecessor, StartToStart and FinishToFinish on
+/// the same level, and StartToFinish one level before (never below 0). An
+/// `AnyOf` step takes the lowest level its dependencies allow. Fails on
+/// unknown dependencies and cycles, as CPM does.
+pub fn topological_levels(
+    template: &ScheduleTemplate,
+) -> Result<HashMap<String, u32>, SolveError> {
+    let index: HashMap<&str, usize> =
+        template.steps.iter().enumerate().map(|(i, s)| (s.id.as_str(), i)).collect();
+    let mut graph: DiGraph<usize, ()> = DiGraph::new();
+    let nodes: Vec<_> = (0..template.steps.len()).map(|i| graph.add_node(i)).collect();
+    for (j, step) in template.steps.iter().enumerate() {
+        for dep in &step.dependencies {
+            let Some(&i) = index.get(dep.step_id.as_str()) else {
+                return Err(SolveError::UnknownDependency(step.id.clone(), dep.step_id.clone()));
+            };
+            graph.add_edge(nodes[i], nodes[j], ());
+        }
+    }
+    let order = toposort(&graph, None).map_err(|cycle| {
+        SolveError::CyclicDependency(template.steps[graph[cycle.node_id()]].id.clone())
+    })?;
+
+    let mut levels: Vec<u32> = vec![0; template.steps.len()];
+    for node in order {
+        let step = &template.steps[graph[node]];
+        let releases = step.dependencies.iter().map(|dep| {
+            let pred = levels[index[dep.step_id.as_str()]];
+            match dep.dependency_type {
+                DependencyType::FinishToStart => pred + 1,
+                DependencyType::StartToStart | DependencyType::FinishToFinish => pred,
+                DependencyType::StartToFinish => pred.saturating_sub(1),
+            }
+        });
+        levels[graph[node]] = match step.dependency_mode {
+            DependencyMode::AllOf => releases.max(),
+            DependencyMode::AnyOf => releases.min(),
+        }
+        .unwrap_or(0);
+    }
+
+    Ok(template.steps.iter().map(|s| s.id.clone()).zip(levels).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Step IDs
+// ---------------------------------------------------------------------------
+
+/// Derive a step ID from a title: lowercased, runs of whitespace, `-` and `_`
+/// turned into single dashes, and other punctuation dropped. Non-ASCII letters
+/// and digits are kept. An empty result becomes `"step"`.
+///
+/// If the slug is already in `existing_ids`, `-2`, `-3`, ... is appended until
+/// it is unique.
+pub fn generate_step_id(title: &str, existing_ids: &HashSet<&str>) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    let slug = if slug.is_empty() { "step" } else { slug };
+
+    if !existing_ids.contains(slug) {
+        return slug.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|id| !existing_ids.contains(id.as_str()))
+        .expect("some suffix is always free")
+}
+
+/// How `normalize_step_ids` rewrites step IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdNormalizationStrategy {
+    /// Lowercase words joined by dashes: "StepA" becomes "step-a".
+    KebabCase,
+    /// Lowercase words joined by underscores: "StepA" becomes "step_a".
+    SnakeCase,
+    /// "step-1", "step-2", ... in template order.
+    Sequential,
+}
+
+/// Rewrite every step ID in the template with `strategy`, along with every
+/// reference to it: dependencies, track orderings, hammock group members,
+/// recurring patterns and the embedded baseline. External dependencies point
+/// at other projects and are left alone, as are dependencies on steps the
+/// template doesn't define.
+///
+/// Words are split at punctuation, whitespace and changes of case, so
+/// "step-A", "StepA" and "step_a" all become "step-a" under `KebabCase`.
+/// IDs that would collide get `-2`, `-3`, ... (`_2`, ... for `SnakeCase`)
+/// appended in template order.
+pub fn normalize_step_ids(template: &mut ScheduleTemplate, strategy: IdNormalizationStrategy) {
+    let separator = match strategy {
+        IdNormalizationStrategy::SnakeCase => "_",
+        _ => "-",
+    };
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut new_ids: Vec<String> = Vec::new();
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    for (i, step) in template.steps.iter().enumerate() {
+        let base = match strategy {
+            IdNormalizationStrategy::Sequential => format!("step-{}", i + 1),
+            _ => {
+                let words = id_words(&step.id).join(separator);
+                if words.is_empty() { "step".to_string() } else { words }
+            }
+        };
+        let id = if taken.contains(&base) {
+            (2..)
+                .map(|n| format!("{}{}{}", base, separator, n))
+                .find(|id| !taken.contains(id))
+                .expect("some suffix is always free")
+        } else {
+            base
+        };
+        taken.insert(id.clone());
+        renamed.entry(step.id.clone()).or_insert_with(|| id.clone());
+        new_ids.push(id);
+    }
+
+    let rename = |id: &mut String| {
+        if let Some(new_id) = renamed.get(id.as_str()) {
+            *id = new_id.clone();
+        }
+    };
+    for (step, new_id) in template.steps.iter_mut().zip(new_ids) {
+        step.id = new_id;
+        for dep in &mut step.dependencies {
+            rename(&mut dep.step_id);
+        }
+    }
+    for track in &mut template.tracks {
+        track.steps.iter_mut().for_each(rename);
+    }
+    for group in &mut template.hammock_groups {
+        group.member_step_ids.iter_mut().for_each(rename);
+    }
+    for pattern in &mut template.recurring_patterns {
+        rename(&mut pattern.template_step_id);
+    }
+    if let Some(baseline) = &mut template.embedded_baseline {
+        for step in &mut baseline.steps {
+            rename(&mut step.step_id);
+        }
+    }
+}
+
+/// Split an ID into lowercase words at anything that isn't a letter or digit
+/// and where the case changes: "stepA" and "HTTPServer" split before "A" and
+/// "Server".
+fn id_words(id: &str) -> Vec<String> {
+    let chars: Vec<char> = id.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let starts_word = c.is_uppercase()
+            && match prev {
+                Some(p) if p.is_lowercase() || p.is_numeric() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+// ---------------------------------------------------------------------------
+// Versions
+// ---------------------------------------------------------------------------
+
+/// Parse a semantic version like "1.2.3" into comparable numeric parts.
+/// Missing minor/patch parts count as 0 and any pre-release or build suffix
+/// ("-beta", "+42") is ignored. Returns `None` for anything else.
+pub fn parse_version(version: &str) -> Option<[u64; 3]> {
+    let core = version.trim().split(['-', '+']).next()?;
+    let mut parts = [0u64; 3];
+    for (i, part) in core.split('.').enumerate() {
+        *parts.get_mut(i)? = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+// ---------------------------------------------------------------------------
+// Resource usage
+// ---------------------------------------------------------------------------
+
+/// Capacity of `resource` once `inventory` overrides are applied.
+pub fn effective_capacity(resource: &Resource, inventory: Option<&ResourceInventory>) -> u32 {
+    inventory
+        .and_then(|inv| inv.items.iter().find(|i| i.resource_id == resource.id))
+        .map_or(resource.capacity, |i| i.available_quantity)
+}
+
+/// `(start, end, quantity)` for every assignment of `resource_id` in
+/// `solved_steps`.
+pub fn resource_usages(solved_steps: &[SolvedStep], resource_id: &str) -> Vec<(u32, u32, u32)> {
+    solved_steps
+        .iter()
+        .flat_map(|s| {
+            s.assigned_resources
+                .iter()
+                .filter(|a| a.resource_id == resource_id)
+                .map(move |a| (s.start_offset_mins, s.end_offset_mins, a.quantity_used))
+        })
+        .collect()
+}
+
+/// Total quantity of the `(start, end, quantity)` usages held at minute `t`.
+pub fn usage_at(usages: &[(u32, u32, u32)], t: u32) -> u32 {
+    usages.iter().filter(|&&(start, end, _)| start <= t && t < end).map(|u| u.2).sum()
+}
+
+/// The highest total of `usages` held at any minute in `[from, to)`, and the
+/// first minute it is reached. Usage only rises when a use starts, so only
+/// `from` and the starts after it need checking. `(0, from)` when nothing
+/// is held.
+pub fn peak_usage(usages: &[(u32, u32, u32)], from: u32, to: u32) -> (u32, u32) {
+    let mut candidates: Vec<u32> = std::iter::once(from)
+        .chain(usages.iter().map(|u| u.0).filter(|&t| t > from && t < to))
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates.into_iter().fold((0, from), |best, t| {
+        let used = usage_at(usages, t);
+        if used > best.0 { (used, t) } else { best }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Step, TimeConstraint, Track};
+
+    #[test]
+    fn test_split_by_track_with_default_key() {
+        let step = |id: &str, dur: u32, track: Option<&str>| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: dur,
+            track_id: track.map(str::to_string),
+            ..Default::default()
+        };
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("cook", 60, Some("kitchen")),
+                step("plate", 10, Some("kitchen")),
+                step("greet", 20, Some("service")),
+                step("misc", 5, None),
+            ],
+            tracks: vec![
+                Track {
+                    id: "kitchen".to_string(),
+                    name: "Kitchen".to_string(),
+                    steps: vec![],
+                    max_concurrent: None,
+                },
+                Track {
+                    id: "service".to_string(),
+                    name: "Service".to_string(),
+                    steps: vec![],
+                    max_concurrent: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let solved = crate::solver::solve(&template, None).unwrap();
+        let split = split_schedule_by_track(&solved, &template);
+
+        assert_eq!(split.len(), 3);
+        assert_eq!(split["kitchen"].solved_steps.len(), 2);
+        assert_eq!(split["kitchen"].summary.critical_path_step_ids, vec!["cook"]);
+        assert_eq!(split["kitchen"].summary.total_duration_mins, 60);
+        assert!(split["service"].summary.critical_path_step_ids.is_empty());
+        assert_eq!(split[DEFAULT_TRACK_KEY].solved_steps[0].step_id, "misc");
+    }
+
+    #[test]
+    fn test_merge_templates() {
+        let step = |id: &str| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            ..Default::default()
+        };
+        let kitchen = Track {
+            id: "kitchen".to_string(),
+            name: "Kitchen".to_string(),
+            steps: vec![],
+            max_concurrent: None,
+        };
+        let base = ScheduleTemplate {
+            id: "dinner".to_string(),
+            name: "Dinner".to_string(),
+            steps: vec![step("cook")],
+            tracks: vec![kitchen.clone()],
+            ..Default::default()
+        };
+        let overlay = ScheduleTemplate {
+            id: "dessert".to_string(),
+            name: "Dessert".to_string(),
+            steps: vec![step("bake")],
+            tracks: vec![kitchen],
+            max_concurrent_steps: Some(2),
+            ..Default::default()
+        };
+
+        let merged = merge_templates(&base, &overlay).unwrap();
+        assert_eq!(merged.id, "dinner");
+        assert_eq!(merged.name, "Dinner (Dessert)");
+        let ids: Vec<&str> = merged.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["cook", "bake"]);
+        assert_eq!(merged.tracks.len(), 1);
+        assert_eq!(merged.max_concurrent_steps, Some(2));
+
+        let clash = ScheduleTemplate { steps: vec![step("cook")], ..overlay.clone() };
+        assert!(matches!(
+            merge_templates(&base, &clash),
+            Err(MergeError::DuplicateStepId(id)) if id == "cook"
+        ));
+
+        let constraint = |start: &str| {
+            Some(TimeConstraint {
+                start_time: Some(start.to_string()),
+                end_time: None,
+                timezone: None,
+            })
+        };
+        let morning =
+            ScheduleTemplate { time_constraint: constraint("2026-01-01T08:00:00"), ..base };
+        let evening =
+            ScheduleTemplate { time_constraint: constraint("2026-01-01T18:00:00"), ..overlay };
+        assert!(matches!(
+            merge_templates(&morning, &evening),
+            Err(MergeError::ConflictingTimeConstraint)
+        ));
+    }
+
+    #[test]
+    fn test_prefixed_clones_merge_and_solve() {
+        use crate::model::{DependencyType, Resource, ResourceKind, ResourceNeed, StepDependency};
+
+        let template = ScheduleTemplate {
+            id: "run".to_string(),
+            name: "Run".to_string(),
+            steps: vec![
+                Step {
+                    id: "mix".to_string(),
+                    title: "Mix".to_string(),
+                    duration_mins: 20,
+                    track_id: Some("line".to_string()),
+                    resource_needs: vec![ResourceNeed {
+                        resource_id: "mixer".to_string(),
+                        quantity: 1,
+                        min_people: None,
+                        max_people: None,
+                        role: None,
+                        cost_per_minute: None,
+                        allow_partial: false,
+                    }],
+                    ..Default::default()
+                },
+                Step {
+                    id: "pack".to_string(),
+                    title: "Pack".to_string(),
+                    duration_mins: 10,
+                    track_id: Some("line".to_string()),
+                    dependencies: vec![StepDependency {
+                        step_id: "mix".to_string(),
+                        dependency_type: DependencyType::FinishToStart,
+                        trigger_at_percent: None,
+                        condition: None,
+                    }],
+                    ..Default::default()
+                },
+            ],
+            tracks: vec![Track {
+                id: "line".to_string(),
+                name: "Line".to_string(),
+                steps: vec!["mix".to_string(), "pack".to_string()],
+                max_concurrent: None,
+            }],
+            resources: vec![Resource {
+                id: "mixer".to_string(),
+                name: "Mixer".to_string(),
+                kind: ResourceKind::Equipment,
+                capacity: 1,
+                roles: vec![],
+                role_headcounts: vec![],
+                unit_cost: 0.0,
+                cost_per_unit_per_min: None,
+                utilization_target: None,
+            }],
+            ..Default::default()
+        };
+
+        let first = clone_with_prefix(&template, "a");
+        assert_eq!(first.id, "a-run");
+        assert_eq!(first.steps[1].dependencies[0].step_id, "a-mix");
+        assert_eq!(first.steps[0].resource_needs[0].resource_id, "a-mixer");
+        assert_eq!(first.tracks[0].steps, vec!["a-mix", "a-pack"]);
+        assert!(crate::validator::validate(&first).is_ok());
+
+        let merged = merge_templates(&first, &clone_with_prefix(&template, "b")).unwrap();
+        assert!(crate::validator::validate(&merged).is_ok());
+        let solved = crate::solver::solve(&merged, None).unwrap();
+        assert_eq!(solved.solved_steps.len(), 4);
+        assert_eq!(solved.summary.total_duration_mins, 30);
+    }
+
+    #[test]
+    fn test_adjacency_matrix() {
+        use crate::model::StepDependency;
+
+        let dep = |on: &str, dependency_type: DependencyType| StepDependency {
+            step_id: on.to_string(),
+            dependency_type,
+            trigger_at_percent: None,
+            condition: None,
+        };
+        let step = |id: &str, deps: Vec<StepDependency>| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            dependencies: deps,
+            ..Default::default()
+        };
+        let mut template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("a", vec![]),
+                step("b", vec![dep("a", DependencyType::FinishToStart)]),
+                step("c", vec![dep("a", DependencyType::StartToStart)]),
+            ],
+            ..Default::default()
+        };
+
+        let adjacency = to_adjacency_matrix(&template).unwrap();
+        assert_eq!(adjacency.step_ids, vec!["a", "b", "c"]);
+        assert_eq!(
+            adjacency.matrix[0],
+            vec![None, Some(DependencyType::FinishToStart), Some(DependencyType::StartToStart)]
+        );
+        assert!(adjacency.matrix[1].iter().chain(&adjacency.matrix[2]).all(Option::is_none));
+
+        template.steps[0].dependencies.push(dep("b", DependencyType::FinishToStart));
+        assert!(matches!(
+            to_adjacency_matrix(&template),
+            Err(SolveError::CyclicDependency(ids)) if ids == "a, b"
+        ));
+    }
+
+    #[test]
+    fn test_topological_levels_follow_dependency_types() {
+        use crate::model::StepDependency;
+
+        let dep = |on: &str, dependency_type: DependencyType| StepDependency {
+            step_id: on.to_string(),
+            dependency_type,
+            trigger_at_percent: None,
+            condition: None,
+        };
+        let step = |id: &str, deps: Vec<StepDependency>| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            dependencies: deps,
+            ..Default::default()
+        };
+        let mut either = step(
+            "either",
+            vec![dep("b", DependencyType::FinishToStart), dep("a", DependencyType::FinishToStart)],
+        );
+        either.dependency_mode = DependencyMode::AnyOf;
+        let mut template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("a", vec![]),
+                step("b", vec![dep("a", DependencyType::FinishToStart)]),
+                step("c", vec![dep("b", DependencyType::StartToStart)]),
+                step("d", vec![dep("c", DependencyType::FinishToFinish)]),
+                step("e", vec![dep("a", DependencyType::StartToFinish)]),
+                step(
+                    "f",
+                    vec![
+                        dep("d", DependencyType::FinishToStart),
+                        dep("a", DependencyType::FinishToStart),
+                    ],
+                ),
+                either,
+            ],
+            ..Default::default()
+        };
+
+        let levels = topological_levels(&template).unwrap();
+        let expected = [("a", 0), ("b", 1), ("c", 1), ("d", 1), ("e", 0), ("f", 2), ("either", 1)];
+        for (id, level) in expected {
+            assert_eq!(levels[id], level, "level of {}", id);
+        }
+
+        template.steps[0].dependencies.push(dep("f", DependencyType::FinishToStart));
+        assert!(matches!(
+            topological_levels(&template),
+            Err(SolveError::CyclicDependency(_))
+        ));
+    }
+
+    #[test]
+    fn test_generate_step_id_slugifies() {
+        let none = HashSet::new();
+        assert_eq!(generate_step_id("Preheat the Oven!", &none), "preheat-the-oven");
+        assert_eq!(generate_step_id("  mix -- dry_ingredients ", &none), "mix-dry-ingredients");
+        assert_eq!(generate_step_id("Crème Brûlée", &none), "crème-brûlée");
+        assert_eq!(generate_step_id("ÉCLAIR", &none), "éclair");
+        assert_eq!(generate_step_id("", &none), "step");
+        assert_eq!(generate_step_id("?!", &none), "step");
+    }
+
+    #[test]
+    fn test_generate_step_id_avoids_collisions() {
+        let mut existing: HashSet<&str> = HashSet::new();
+        existing.insert("bake");
+        assert_eq!(generate_step_id("Bake", &existing), "bake-2");
+        existing.insert("bake-2");
+        existing.insert("bake-3");
+        assert_eq!(generate_step_id("Bake", &existing), "bake-4");
+        existing.insert("step");
+        assert_eq!(generate_step_id("", &existing), "step-2");
+    }
+
+    #[test]
+    fn test_filter_steps_by_tags_drops_unmatched_steps_and_references() {
+        use crate::model::StepDependency;
+
+        let step = |id: &str, tags: &[&str], deps: &[&str]| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            dependencies: deps
+                .iter()
+                .map(|d| StepDependency {
+                    step_id: d.to_string(),
+                    dependency_type: DependencyType::FinishToStart,
+                    trigger_at_percent: None,
+                    condition: None,
+                })
+                .collect(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("prep", &["vegan", "kosher"], &[]),
+                step("cook", &["vegan"], &["prep"]),
+                step("serve", &["kosher"], &["cook", "prep"]),
+            ],
+            tracks: vec![Track {
+                id: "line".to_string(),
+                name: "Line".to_string(),
+                steps: vec!["prep".to_string(), "cook".to_string(), "serve".to_string()],
+                max_concurrent: None,
+            }],
+            tags: vec!["dinner".to_string()],
+            ..Default::default()
+        };
+
+        let kosher = filter_steps_by_tags(&template, &["kosher"], TagFilterMode::AllOf);
+        let ids: Vec<&str> = kosher.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["prep", "serve"]);
+        assert_eq!(kosher.steps[1].dependencies.len(), 1);
+        assert_eq!(kosher.steps[1].dependencies[0].step_id, "prep");
+        assert_eq!(kosher.tracks[0].steps, vec!["prep", "serve"]);
+        assert_eq!(kosher.tags, vec!["dinner"]);
+
+        let both = filter_steps_by_tags(&template, &["vegan", "kosher"], TagFilterMode::AllOf);
+        assert_eq!(both.steps.len(), 1);
+        let either = filter_steps_by_tags(&template, &["vegan", "kosher"], TagFilterMode::AnyOf);
+        assert_eq!(either.steps.len(), 3);
+        assert!(crate::solver::solve(&kosher, None).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_step_ids_rewrites_references() {
+        use crate::model::StepDependency;
+
+        let step = |id: &str, deps: &[&str]| Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            dependencies: deps
+                .iter()
+                .map(|d| StepDependency {
+                    step_id: d.to_string(),
+                    dependency_type: DependencyType::FinishToStart,
+                    trigger_at_percent: None,
+                    condition: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![
+                step("Step-A", &[]),
+                step("StepB", &["Step-A"]),
+                step("step_c", &["StepB"]),
+                step("step-a", &["step_c"]),
+                step("HTTPServer", &[]),
+            ],
+            tracks: vec![Track {
+                id: "main".to_string(),
+                name: "Main".to_string(),
+                steps: vec!["StepB".to_string(), "step_c".to_string()],
+                max_concurrent: None,
+            }],
+            ..Default::default()
+        };
+
+        let mut kebab = template.clone();
+        normalize_step_ids(&mut kebab, IdNormalizationStrategy::KebabCase);
+        let ids: Vec<&str> = kebab.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["step-a", "step-b", "step-c", "step-a-2", "http-server"]);
+        assert_eq!(kebab.steps[1].dependencies[0].step_id, "step-a");
+        assert_eq!(kebab.steps[3].dependencies[0].step_id, "step-c");
+        assert_eq!(kebab.tracks[0].steps, vec!["step-b", "step-c"]);
+
+        let solved = crate::solver::solve(&kebab, None).unwrap();
+        let mut solved_ids: Vec<&str> =
+            solved.solved_steps.iter().map(|s| s.step_id.as_str()).collect();
+        solved_ids.sort_unstable();
+        assert_eq!(solved_ids, vec!["http-server", "step-a", "step-a-2", "step-b", "step-c"]);
+        assert_eq!(solved.summary.total_duration_mins, 40);
+
+        let mut snake = template.clone();
+        normalize_step_ids(&mut snake, IdNormalizationStrategy::SnakeCase);
+        assert_eq!(snake.steps[3].id, "step_a_2");
+        assert_eq!(snake.steps[4].id, "http_server");
+
+        let mut sequential = template;
+        normalize_step_ids(&mut sequential, IdNormalizationStrategy::Sequential);
+        assert_eq!(sequential.steps[4].id, "step-5");
+        assert_eq!(sequential.steps[2].dependencies[0].step_id, "step-2");
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.9.9"), Some([1, 9, 9]));
+        assert_eq!(parse_version("2"), Some([2, 0, 0]));
+        assert_eq!(parse_version("2.1.0-beta.1"), Some([2, 1, 0]));
+        assert!(parse_version("2.0.0") > parse_version("1.9.9"));
+        assert_eq!(parse_version("one"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_peak_usage() {
+        let usages = [(0, 30, 1), (10, 40, 2), (30, 60, 2), (50, 60, 1)];
+        assert_eq!(usage_at(&usages, 35), 4);
+        // 3 at minute 10, then 4 from minute 30.
+        assert_eq!(peak_usage(&usages, 0, 60), (4, 30));
+        assert_eq!(peak_usage(&usages, 0, 30), (3, 10));
+        assert_eq!(peak_usage(&usages, 40, 60), (3, 50));
+        assert_eq!(peak_usage(&[], 20, 60), (0, 20));
+    }
+}
@@ -1,25 +1,152 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use petgraph::algo::is_cyclic_directed;
 use petgraph::graph::DiGraph;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
-use crate::model::ScheduleTemplate;
+use crate::model::{
+    DependencyMode, DependencyType, Resource, ResourceInventory, ResourceKind, ScheduleTemplate,
+    Step, TimeConstraint, WorkingCalendar,
+};
+use crate::solver::CpmResult;
+use crate::util::effective_capacity;
 
 // ---------------------------------------------------------------------------
 // Validation result types
 // ---------------------------------------------------------------------------
 
+/// What a validation error or warning is about, for callers that react to
+/// particular problems rather than just showing the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ValidationErrorCode {
+    DuplicateStepId,
+    InvalidWbsCode,
+    DuplicateWbsCode,
+    MissingDuration,
+    UnknownDependency,
+    SelfDependency,
+    DuplicateDependency,
+    /// Several dependency types on the same step (warning).
+    MixedDependencyTypes,
+    InvalidTrigger,
+    CyclicDependency,
+    /// A dependency on a step that only runs under a condition (warning).
+    ConditionalDependency,
+    /// An assumption about a step in another project (warning).
+    ExternalDependency,
+    /// Every step runs in parallel (warning).
+    NoDependencies,
+    UnknownResource,
+    UnknownRole,
+    InvalidRole,
+    /// Roles no step asks for (warning).
+    UnusedRoles,
+    InvalidResourceGroup,
+    /// No resources at all, or a step needing none of them (warning).
+    NoResourceNeeds,
+    /// A need beyond what a resource can ever provide.
+    CapacityExceeded,
+    /// More people than the template's `default_num_people` (warning).
+    DefaultHeadcountExceeded,
+    /// Steps running together need more people than there are (warning).
+    MinimumHeadcountConflict,
+    /// A consumable used up by the steps that must start first.
+    ConsumableExhausted,
+    /// A Cost resource with a capacity (warning).
+    IgnoredCapacity,
+    InvalidUtilizationTarget,
+    UnknownTrack,
+    /// A track without steps (warning).
+    EmptyTrack,
+    /// A track listing a step that doesn't exist (warning).
+    UnknownTrackStep,
+    UnknownRecurringStep,
+    /// Recurring steps depending on each other (warning).
+    RecurringDependency,
+    InvalidHammockGroup,
+    UnknownTimezone,
+    InvalidTimeConstraint,
+    /// The time window is shorter than the critical path (warning).
+    DeadlineTooShort,
+    InvalidCalendar,
+    /// A resource calendar for a resource that isn't scheduled over time
+    /// (warning).
+    IgnoredCalendar,
+    /// A step longer than (error) or exactly as long as (warning) the
+    /// longest working window.
+    StepExceedsWorkingWindow,
+    /// An interruptible step without a working calendar (warning).
+    InterruptibleWithoutCalendar,
+    InvalidConcurrencyLimit,
+    /// A concurrency limit below the possible parallelism (warning).
+    ConcurrencyLimitTooLow,
+    /// `AnyOf` mixed with start/finish alignments (warning).
+    AnyOfAlignment,
+    /// An ALAP step with no dependencies either way (warning).
+    UnanchoredAlap,
+    MissingVersion,
+    InvalidVersion,
+    /// More steps, resources or dependencies than `ComplexityLimits` allow.
+    ComplexityLimitExceeded,
+}
+
+/// One validation error or warning: its code, the message shown to users,
+/// and the IDs of the steps, resources, tracks or groups it is about.
+/// Displays as its message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+    pub code: ValidationErrorCode,
+    pub message: String,
+    pub affected_ids: Vec<String>,
+}
+
+impl ValidationError {
+    pub fn new(
+        code: ValidationErrorCode,
+        affected_ids: &[&str],
+        message: impl Into<String>,
+    ) -> Self {
+        ValidationError {
+            code,
+            message: message.into(),
+            affected_ids: affected_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Errors and warnings from validation. Both serialize as lists of messages,
+/// as they did before they were structured; serialize the
+/// `ValidationError`s themselves for codes and affected IDs.
 #[derive(Debug, Clone, Serialize)]
 pub struct ValidationResult {
-    pub errors: Vec<String>,
-    pub warnings: Vec<String>,
+    #[serde(serialize_with = "messages")]
+    pub errors: Vec<ValidationError>,
+    #[serde(serialize_with = "messages")]
+    pub warnings: Vec<ValidationError>,
 }
 
 impl ValidationResult {
     pub fn is_ok(&self) -> bool {
         self.errors.is_empty()
     }
+
+    /// Every error message, joined by "; ".
+    pub fn error_summary(&self) -> String {
+        let messages: Vec<&str> = self.errors.iter().map(|e| e.message.as_str()).collect();
+        messages.join("; ")
+    }
+}
+
+fn messages<S: Serializer>(findings: &[ValidationError], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(findings.iter().map(|f| &f.message))
 }
 
 // ---------------------------------------------------------------------------
@@ -29,8 +156,8 @@ impl ValidationResult {
 /// Validate a schedule template, returning errors (block solving) and
 /// warnings (advisory). Errors are listed before warnings.
 pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
-    let mut errors: Vec<String> = Vec::new();
-    let mut warnings: Vec<String> = Vec::new();
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
 
     // Build step ID set for quick lookup
     let step_ids: HashSet<&str> = template.steps.iter().map(|s| s.id.as_str()).collect();
@@ -38,6 +165,10 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
     // Build resource ID set for quick lookup
     let resource_ids: HashSet<&str> =
         template.resources.iter().map(|r| r.id.as_str()).collect();
+    let group_ids: HashSet<&str> =
+        template.resource_groups.iter().map(|g| g.id.as_str()).collect();
+    let resources_by_id: HashMap<&str, &Resource> =
+        template.resources.iter().map(|r| (r.id.as_str(), r)).collect();
 
     // -----------------------------------------------------------------------
     // Error: duplicate step IDs
@@ -46,9 +177,45 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
         let mut seen: HashMap<&str, bool> = HashMap::new();
         for step in &template.steps {
             if seen.insert(step.id.as_str(), true).is_some() {
-                errors.push(format!(
-                    "Duplicate step ID '{}' -- each step must have a unique ID",
-                    step.id
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::DuplicateStepId,
+                    &[&step.id],
+                    format!("Duplicate step ID '{}' -- each step must have a unique ID", step.id),
+                ));
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Error: malformed or duplicate WBS codes
+    // -----------------------------------------------------------------------
+    {
+        let mut seen: HashMap<&str, &Step> = HashMap::new();
+        for step in &template.steps {
+            let Some(code) = step.wbs_code.as_deref() else {
+                continue;
+            };
+            let well_formed = code
+                .split('.')
+                .all(|seg| !seg.is_empty() && seg.chars().all(|c| c.is_ascii_digit()));
+            if !well_formed {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidWbsCode,
+                    &[&step.id],
+                    format!(
+                        "Step '{}' has WBS code '{}' -- codes must be dot-separated numbers like '1.2.3'",
+                        step.title, code
+                    ),
+                ));
+            }
+            if let Some(other) = seen.insert(code, step) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::DuplicateWbsCode,
+                    &[&other.id, &step.id],
+                    format!(
+                        "Steps '{}' and '{}' share WBS code '{}' -- each code must be unique",
+                        other.title, step.title, code
+                    ),
                 ));
             }
         }
@@ -60,28 +227,371 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
     for step in &template.steps {
         // Error: missing duration
         if step.duration_mins == 0 {
-            errors.push(format!(
-                "Step '{}' has no duration -- every step needs a duration in minutes",
-                step.title
+            errors.push(ValidationError::new(
+                ValidationErrorCode::MissingDuration,
+                &[&step.id],
+                format!(
+                    "Step '{}' has no duration -- every step needs a duration in minutes",
+                    step.title
+                ),
             ));
         }
 
         // Error: dependency references non-existent step
         for dep in &step.dependencies {
             if !step_ids.contains(dep.step_id.as_str()) {
-                errors.push(format!(
-                    "Step '{}' depends on '{}' which doesn't exist",
-                    step.title, dep.step_id
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownDependency,
+                    &[&step.id, &dep.step_id],
+                    format!("Step '{}' depends on '{}' which doesn't exist", step.title, dep.step_id),
+                ));
+            }
+        }
+
+        // Error: self-reference; error/warning: repeated dependency entries
+        // predecessor -> (type, trigger point) of each dependency on it so far
+        let mut seen_deps: HashMap<&str, Vec<(&DependencyType, Option<u8>)>> = HashMap::new();
+        for dep in &step.dependencies {
+            if dep.step_id == step.id {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::SelfDependency,
+                    &[&step.id],
+                    format!(
+                        "Step '{}' depends on itself -- remove the dependency on '{}'",
+                        step.title, dep.step_id
+                    ),
+                ));
+                continue;
+            }
+            let seen = seen_deps.entry(dep.step_id.as_str()).or_default();
+            let key = (&dep.dependency_type, dep.trigger_at_percent);
+            if seen.contains(&key) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::DuplicateDependency,
+                    &[&step.id, &dep.step_id],
+                    format!(
+                        "Step '{}' lists its {:?} dependency on '{}' more than once",
+                        step.title, dep.dependency_type, dep.step_id
+                    ),
+                ));
+            } else if seen.iter().any(|(t, _)| *t != &dep.dependency_type) {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::MixedDependencyTypes,
+                    &[&step.id, &dep.step_id],
+                    format!(
+                        "Step '{}' has several dependency types on '{}' -- usually only one is intended",
+                        step.title, dep.step_id
+                    ),
+                ));
+            }
+            seen.push(key);
+        }
+
+        // Error: partial-completion trigger on the wrong dependency type or out of range
+        for dep in &step.dependencies {
+            let Some(pct) = dep.trigger_at_percent else {
+                continue;
+            };
+            if !matches!(dep.dependency_type, DependencyType::FinishToStart) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidTrigger,
+                    &[&step.id, &dep.step_id],
+                    format!(
+                        "Step '{}' sets trigger_at_percent on its dependency on '{}' -- only FinishToStart dependencies support it",
+                        step.title, dep.step_id
+                    ),
+                ));
+            } else if !(1..=100).contains(&pct) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidTrigger,
+                    &[&step.id, &dep.step_id],
+                    format!(
+                        "Step '{}' has trigger_at_percent {} on its dependency on '{}' -- it must be between 1 and 100",
+                        step.title, pct, dep.step_id
+                    ),
                 ));
             }
         }
 
         // Error: resource need references non-existent resource
         for need in &step.resource_needs {
-            if !resource_ids.contains(need.resource_id.as_str()) {
-                errors.push(format!(
-                    "Step '{}' requires resource '{}' which isn't defined",
-                    step.title, need.resource_id
+            let id = need.resource_id.as_str();
+            if !resource_ids.contains(id) && !group_ids.contains(id) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownResource,
+                    &[&step.id, id],
+                    format!(
+                        "Step '{}' requires resource '{}' which isn't defined",
+                        step.title, need.resource_id
+                    ),
+                ));
+            }
+        }
+
+        // Error: requested role isn't declared on the resource
+        for need in &step.resource_needs {
+            let Some(role) = &need.role else {
+                continue;
+            };
+            let Some(resource) = resources_by_id.get(need.resource_id.as_str()) else {
+                continue;
+            };
+            if !resource.roles.contains(role) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownRole,
+                    &[&step.id, &resource.id],
+                    format!(
+                        "Step '{}' requests role '{}' from '{}' which has no such role",
+                        step.title, role, resource.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Recurring step patterns
+    // -----------------------------------------------------------------------
+    {
+        let pattern_step_ids: HashSet<&str> = template
+            .recurring_patterns
+            .iter()
+            .map(|p| p.template_step_id.as_str())
+            .collect();
+
+        for pattern in &template.recurring_patterns {
+            let Some(step) = template.steps.iter().find(|s| s.id == pattern.template_step_id)
+            else {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownRecurringStep,
+                    &[&pattern.template_step_id],
+                    format!(
+                        "Recurring pattern '{}' repeats step '{}' which doesn't exist",
+                        pattern.prefix, pattern.template_step_id
+                    ),
+                ));
+                continue;
+            };
+
+            // Warning: the repeated step is linked to another repeated step
+            let depends_on_pattern = step
+                .dependencies
+                .iter()
+                .any(|d| d.step_id != step.id && pattern_step_ids.contains(d.step_id.as_str()));
+            let depended_on_by_pattern = template.steps.iter().any(|other| {
+                other.id != step.id
+                    && pattern_step_ids.contains(other.id.as_str())
+                    && other.dependencies.iter().any(|d| d.step_id == step.id)
+            });
+            if depends_on_pattern || depended_on_by_pattern {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::RecurringDependency,
+                    &[&step.id],
+                    format!(
+                        "Recurring step '{}' has dependencies on another recurring step -- only the first and last copies will be linked",
+                        step.title
+                    ),
+                ));
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Time constraint
+    // -----------------------------------------------------------------------
+    if let Some(tc) = &template.time_constraint {
+        // Error: unknown timezone
+        let tz = tc.timezone.as_deref().map(|name| (name, name.parse::<chrono_tz::Tz>()));
+        if let Some((name, Err(_))) = tz {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::UnknownTimezone,
+                &[],
+                format!(
+                    "Timezone '{}' is not a known IANA timezone name (e.g. 'Europe/London')",
+                    name
+                ),
+            ));
+        }
+
+        // Error: unparseable or out-of-order times
+        let times = validate_time_constraint(tc);
+        let times_ok = times.is_ok();
+        errors.extend(times.errors);
+        warnings.extend(times.warnings);
+
+        // Warning: the window is shorter than the dependency chain alone
+        let tz = match tz {
+            Some((_, Ok(tz))) => Some(tz),
+            _ => None,
+        };
+        if times_ok {
+            let window = crate::solver::deadline_mins(template, tz);
+            // The template solve actually schedules, recurring copies included.
+            let prepared = crate::solver::prepare_template(template, &Default::default());
+            let cpm = crate::solver::cpm(&prepared);
+            if let (Some(window), Ok(cpm)) = (window, cpm) {
+                if window < i64::from(cpm.project_end) {
+                    warnings.push(ValidationError::new(
+                        ValidationErrorCode::DeadlineTooShort,
+                        &[],
+                        format!(
+                            "Time window is {} minutes but the critical path alone takes {} -- the end time can't be met",
+                            window, cpm.project_end
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Resource groups
+    // -----------------------------------------------------------------------
+    for group in &template.resource_groups {
+        if resource_ids.contains(group.id.as_str()) {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidResourceGroup,
+                &[&group.id],
+                format!(
+                    "Resource group '{}' has the same ID as a resource ('{}') -- needs naming it would be ambiguous",
+                    group.name, group.id
+                ),
+            ));
+        }
+        if group.resource_ids.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidResourceGroup,
+                &[&group.id],
+                format!("Resource group '{}' has no member resources", group.name),
+            ));
+        }
+        for member in &group.resource_ids {
+            if !resource_ids.contains(member.as_str()) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownResource,
+                    &[&group.id, member],
+                    format!(
+                        "Resource group '{}' includes resource '{}' which isn't defined",
+                        group.name, member
+                    ),
+                ));
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Hammock groups
+    // -----------------------------------------------------------------------
+    {
+        let mut group_of: HashMap<&str, &str> = HashMap::new();
+        for group in &template.hammock_groups {
+            for member in &group.member_step_ids {
+                if !step_ids.contains(member.as_str()) {
+                    errors.push(ValidationError::new(
+                        ValidationErrorCode::InvalidHammockGroup,
+                        &[&group.id, member],
+                        format!(
+                            "Hammock group '{}' includes step '{}' which doesn't exist",
+                            group.name, member
+                        ),
+                    ));
+                }
+                if let Some(other) = group_of.insert(member.as_str(), group.name.as_str()) {
+                    if other != group.name {
+                        errors.push(ValidationError::new(
+                            ValidationErrorCode::InvalidHammockGroup,
+                            &[&group.id, member],
+                            format!(
+                                "Step '{}' belongs to hammock groups '{}' and '{}' -- a step can only be in one",
+                                member, other, group.name
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Working calendar
+    // -----------------------------------------------------------------------
+    if let Some(calendar) = &template.working_calendar {
+        if calendar.cycle_mins == 0 {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidCalendar,
+                &[],
+                "Working calendar has a cycle of 0 minutes -- use e.g. 1440 for a daily calendar",
+            ));
+        }
+        if calendar.working_windows.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidCalendar,
+                &[],
+                "Working calendar has no working windows",
+            ));
+        }
+        for window in &calendar.working_windows {
+            if window.start_offset_mins >= window.end_offset_mins
+                || window.end_offset_mins > calendar.cycle_mins
+            {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidCalendar,
+                    &[],
+                    format!(
+                        "Working window {}-{} must start before it ends and fit within the {}-minute cycle",
+                        window.start_offset_mins, window.end_offset_mins, calendar.cycle_mins
+                    ),
+                ));
+            }
+        }
+        let durations = validate_step_durations_against_calendar(template, calendar);
+        errors.extend(durations.errors);
+        warnings.extend(durations.warnings);
+    } else {
+        for step in template.steps.iter().filter(|s| s.interruptible) {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::InterruptibleWithoutCalendar,
+                &[&step.id],
+                format!(
+                    "Step '{}' is interruptible but no working calendar is defined -- it will run without pausing",
+                    step.title
+                ),
+            ));
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Resource calendars
+    // -----------------------------------------------------------------------
+    for calendar in &template.resource_calendars {
+        let id = calendar.resource_id.as_str();
+        match resources_by_id.get(id).map(|r| &r.kind) {
+            None => errors.push(ValidationError::new(
+                ValidationErrorCode::UnknownResource,
+                &[id],
+                format!("Resource calendar refers to resource '{}' which isn't defined", id),
+            )),
+            Some(ResourceKind::Consumable | ResourceKind::Cost) => {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::IgnoredCalendar,
+                    &[id],
+                    format!(
+                        "Resource '{}' has a calendar but only Equipment and People are scheduled over time -- it will be ignored",
+                        id
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+        for window in &calendar.available_windows {
+            if window.start_offset_mins >= window.end_offset_mins {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidCalendar,
+                    &[id],
+                    format!(
+                        "Availability window {}-{} of resource '{}' must start before it ends",
+                        window.start_offset_mins, window.end_offset_mins, id
+                    ),
                 ));
             }
         }
@@ -112,15 +622,19 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
 
         if is_cyclic_directed(&graph) {
             // Collect all edges that form cycles for the error message
-            let cyclic_steps: Vec<String> = template
+            let cyclic_steps: Vec<&str> = template
                 .steps
                 .iter()
                 .filter(|s| !s.dependencies.is_empty())
-                .map(|s| s.id.clone())
+                .map(|s| s.id.as_str())
                 .collect();
-            errors.push(format!(
-                "Circular dependency: {} -- steps have a dependency cycle",
-                cyclic_steps.join(" -> ")
+            errors.push(ValidationError::new(
+                ValidationErrorCode::CyclicDependency,
+                &cyclic_steps,
+                format!(
+                    "Circular dependency: {} -- steps have a dependency cycle",
+                    cyclic_steps.join(" -> ")
+                ),
             ));
         }
     }
@@ -130,27 +644,194 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
     // -----------------------------------------------------------------------
     let has_dependencies = template.steps.iter().any(|s| !s.dependencies.is_empty());
     if !has_dependencies {
-        warnings.push(
-            "No dependencies found -- all steps will run in parallel. Add dependencies if steps need ordering.".to_string(),
-        );
+        warnings.push(ValidationError::new(
+            ValidationErrorCode::NoDependencies,
+            &[],
+            "No dependencies found -- all steps will run in parallel. Add dependencies if steps need ordering.",
+        ));
     }
 
     if template.resources.is_empty() {
-        warnings.push(
-            "No resources defined -- solving without resource constraints".to_string(),
-        );
+        warnings.push(ValidationError::new(
+            ValidationErrorCode::NoResourceNeeds,
+            &[],
+            "No resources defined -- solving without resource constraints",
+        ));
     } else {
         // Warning: step with no resource needs when resources ARE defined
         for step in &template.steps {
             if step.resource_needs.is_empty() {
-                warnings.push(format!(
-                    "Step '{}' has no resource requirements -- it won't be resource-constrained",
-                    step.title
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::NoResourceNeeds,
+                    &[&step.id],
+                    format!(
+                        "Step '{}' has no resource requirements -- it won't be resource-constrained",
+                        step.title
+                    ),
                 ));
             }
         }
     }
 
+    // Global concurrency limit
+    if let Some(limit) = template.max_concurrent_steps {
+        if limit == 0 {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidConcurrencyLimit,
+                &[],
+                "max_concurrent_steps is 0 -- at least one step must be able to run",
+            ));
+        } else {
+            // Widest fan-out: the independent starting steps, or the most
+            // successors released by a single step.
+            let roots = template.steps.iter().filter(|s| s.dependencies.is_empty()).count();
+            let mut successors: HashMap<&str, usize> = HashMap::new();
+            for step in &template.steps {
+                for dep in &step.dependencies {
+                    *successors.entry(dep.step_id.as_str()).or_default() += 1;
+                }
+            }
+            let widest = successors.values().copied().max().unwrap_or(0).max(roots);
+            if (limit as usize) < widest {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::ConcurrencyLimitTooLow,
+                    &[],
+                    format!(
+                        "max_concurrent_steps is {} but up to {} steps could run in parallel -- some steps will be delayed",
+                        limit, widest
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Per-track concurrency limits
+    for track in &template.tracks {
+        if track.max_concurrent == Some(0) {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::InvalidConcurrencyLimit,
+                &[&track.id],
+                format!(
+                    "Track '{}' has max_concurrent 0 -- its steps can't be scheduled within it",
+                    track.name
+                ),
+            ));
+        }
+    }
+
+    // Warning: dependencies on conditional steps vanish when the condition is off
+    {
+        let conditions: HashMap<&str, &str> = template
+            .steps
+            .iter()
+            .filter_map(|s| s.condition.as_deref().map(|c| (s.id.as_str(), c)))
+            .collect();
+        for step in &template.steps {
+            for dep in &step.dependencies {
+                let Some(condition) = conditions.get(dep.step_id.as_str()) else {
+                    continue;
+                };
+                if step.condition.as_deref() != Some(*condition) {
+                    warnings.push(ValidationError::new(
+                        ValidationErrorCode::ConditionalDependency,
+                        &[&step.id, &dep.step_id],
+                        format!(
+                            "Step '{}' depends on '{}', which only runs when '{}' is active -- otherwise the dependency is dropped",
+                            step.title, dep.step_id, condition
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Warning: external dependencies are assumptions about other projects
+    for step in &template.steps {
+        for ext in &step.external_dependencies {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::ExternalDependency,
+                &[&step.id],
+                format!(
+                    "Step '{}' depends on '{}' in project '{}' -- assuming it finishes {} minutes after this schedule starts",
+                    step.title,
+                    ext.external_step_id,
+                    ext.external_project_id,
+                    ext.expected_offset_mins
+                ),
+            ));
+        }
+    }
+
+    // Warning: unversioned template; error: unparseable version
+    match template.version.as_deref() {
+        None => warnings.push(ValidationError::new(
+            ValidationErrorCode::MissingVersion,
+            &[],
+            "Template has no version -- set one (e.g. \"1.0.0\") so consumers can check compatibility",
+        )),
+        Some(version) if crate::util::parse_version(version).is_none() => {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidVersion,
+                &[],
+                format!("Template version '{}' isn't a semantic version like '1.2.0'", version),
+            ))
+        }
+        Some(_) => {}
+    }
+
+    // Warning: Cost resources ignore capacity
+    for resource in &template.resources {
+        if matches!(resource.kind, ResourceKind::Cost) && resource.capacity != 0 {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::IgnoredCapacity,
+                &[&resource.id],
+                format!(
+                    "Resource '{}' is a Cost resource -- its capacity of {} will be ignored",
+                    resource.name, resource.capacity
+                ),
+            ));
+        }
+    }
+
+    // Error: utilization targets outside 0.0-1.0
+    for resource in &template.resources {
+        if let Some(target) = resource.utilization_target {
+            if !(0.0..=1.0).contains(&target) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidUtilizationTarget,
+                    &[&resource.id],
+                    format!(
+                        "Resource '{}' has utilization target {} -- it must be between 0.0 and 1.0",
+                        resource.name, target
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Warning: AnyOf with start/finish alignments
+    for step in &template.steps {
+        if step.dependency_mode != DependencyMode::AnyOf {
+            continue;
+        }
+        let aligned = step.dependencies.iter().any(|d| {
+            matches!(
+                d.dependency_type,
+                DependencyType::StartToStart | DependencyType::FinishToFinish
+            )
+        });
+        if aligned {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::AnyOfAlignment,
+                &[&step.id],
+                format!(
+                    "Step '{}' waits for any one of its dependencies, some of which are StartToStart or FinishToFinish -- whichever releases it earliest wins, even if that only aligns its start or finish",
+                    step.title
+                ),
+            ));
+        }
+    }
+
     // Warning: ALAP step with no deps and no successors
     {
         use crate::model::TimingPolicy;
@@ -166,9 +847,389 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
                 && step.dependencies.is_empty()
                 && !steps_with_successors.contains(step.id.as_str())
             {
-                warnings.push(format!(
-                    "Step '{}' is set to ALAP but has no dependencies -- it will be pushed to the very end",
-                    step.title
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::UnanchoredAlap,
+                    &[&step.id],
+                    format!(
+                        "Step '{}' is set to ALAP but has no dependencies -- it will be pushed to the very end",
+                        step.title
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Resource needs that can never fit
+    let capacity = validate_resource_needs_against_capacity(template);
+    errors.extend(capacity.errors);
+    warnings.extend(capacity.warnings);
+
+    // Role declarations and role requests
+    let roles = validate_resource_roles(template);
+    errors.extend(roles.errors);
+    warnings.extend(roles.warnings);
+
+    // Track references
+    let tracks = validate_tracks_referenced_by_steps(template);
+    errors.extend(tracks.errors);
+    warnings.extend(tracks.warnings);
+
+    // Consumables used up before the steps that need them can start
+    let consumables = validate_circular_resource_needs(template);
+    errors.extend(consumables.errors);
+    warnings.extend(consumables.warnings);
+
+    ValidationResult { errors, warnings }
+}
+
+/// Check that a time constraint's `start_time` and `end_time` parse, and that
+/// the start comes before the end when both are set. The solver ignores a
+/// time it can't parse, so catching it here stops wall-clock scheduling
+/// from silently switching off.
+pub fn validate_time_constraint(tc: &TimeConstraint) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+
+    let mut parse = |field: &str, value: Option<&str>| {
+        let value = value?;
+        let parsed = crate::solver::parse_datetime(value);
+        if parsed.is_none() {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidTimeConstraint,
+                &[],
+                format!(
+                    "Time constraint {} '{}' isn't a date or ISO 8601 datetime (e.g. '2026-03-01T09:00:00')",
+                    field, value
+                ),
+            ));
+        }
+        parsed
+    };
+    let start = parse("startTime", tc.start_time.as_deref());
+    let end = parse("endTime", tc.end_time.as_deref());
+
+    if let (Some(start), Some(end)) = (start, end) {
+        if start >= end {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::InvalidTimeConstraint,
+                &[],
+                format!(
+                    "Time constraint startTime '{}' must be before endTime '{}'",
+                    tc.start_time.as_deref().unwrap_or_default(),
+                    tc.end_time.as_deref().unwrap_or_default()
+                ),
+            ));
+        }
+    }
+
+    ValidationResult { errors, warnings: Vec::new() }
+}
+
+/// Check that every non-interruptible step fits within the longest
+/// uninterrupted stretch of working time in `calendar` (see
+/// `calendar::longest_working_stretch`). A longer step can't be done without
+/// a break, which only interruptible steps may take: that is an error. A step
+/// exactly as long as the stretch is warned about, as it must start the
+/// moment the stretch does.
+///
+/// Unusable calendars are reported by `validate` and skipped here.
+pub fn validate_step_durations_against_calendar(
+    template: &ScheduleTemplate,
+    calendar: &WorkingCalendar,
+) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
+    if !crate::calendar::is_usable(calendar) {
+        return ValidationResult { errors, warnings };
+    }
+    let Some(longest) = crate::calendar::longest_working_stretch(calendar) else {
+        return ValidationResult { errors, warnings };
+    };
+
+    for step in template.steps.iter().filter(|s| !s.interruptible) {
+        if step.duration_mins > longest {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::StepExceedsWorkingWindow,
+                &[&step.id],
+                format!(
+                    "Step '{}' takes {} min but the longest working window is {} min -- make it interruptible or split it",
+                    step.title, step.duration_mins, longest
+                ),
+            ));
+        } else if step.duration_mins == longest {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::StepExceedsWorkingWindow,
+                &[&step.id],
+                format!(
+                    "Step '{}' takes exactly as long as the longest working window ({} min) -- it has no slack within it",
+                    step.title, longest
+                ),
+            ));
+        }
+    }
+
+    ValidationResult { errors, warnings }
+}
+
+/// Check the roles declared on resources and how steps request them.
+///
+/// Errors: blank role names, a role declared twice on one resource, and a
+/// step whose role-specific needs on one resource add up to more people
+/// than it has (counting each need's `min_people`, or its quantity when
+/// unset). Warning: a resource declaring roles that no step asks for.
+pub fn validate_resource_roles(template: &ScheduleTemplate) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
+
+    let resources_by_id: HashMap<&str, &Resource> =
+        template.resources.iter().map(|r| (r.id.as_str(), r)).collect();
+
+    for resource in &template.resources {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for role in &resource.roles {
+            if role.trim().is_empty() {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidRole,
+                    &[&resource.id],
+                    format!("Resource '{}' declares a blank role", resource.name),
+                ));
+            } else if !seen.insert(role.as_str()) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidRole,
+                    &[&resource.id],
+                    format!("Resource '{}' declares role '{}' more than once", resource.name, role),
+                ));
+            }
+        }
+        for headcount in &resource.role_headcounts {
+            if !resource.roles.contains(&headcount.role) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::InvalidRole,
+                    &[&resource.id],
+                    format!(
+                        "Resource '{}' gives a headcount for role '{}' it doesn't declare",
+                        resource.name, headcount.role
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut requested: HashSet<&str> = HashSet::new();
+    for step in &template.steps {
+        // resource_id -> headcount requested by role-specific needs
+        let mut by_role: HashMap<&str, u32> = HashMap::new();
+        for need in step.resource_needs.iter().filter(|n| n.role.is_some()) {
+            requested.insert(need.resource_id.as_str());
+            *by_role.entry(need.resource_id.as_str()).or_default() +=
+                need.min_people.unwrap_or(need.quantity);
+        }
+        let mut by_role: Vec<(&str, u32)> = by_role.into_iter().collect();
+        by_role.sort_unstable();
+        for (resource_id, headcount) in by_role {
+            let Some(resource) = resources_by_id.get(resource_id) else {
+                continue;
+            };
+            if headcount > resource.capacity {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::CapacityExceeded,
+                    &[&step.id, resource_id],
+                    format!(
+                        "Step '{}' asks for {} people by role from '{}' but only {} are defined",
+                        step.title, headcount, resource.name, resource.capacity
+                    ),
+                ));
+            }
+        }
+    }
+
+    for resource in &template.resources {
+        if !resource.roles.is_empty() && !requested.contains(resource.id.as_str()) {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::UnusedRoles,
+                &[&resource.id],
+                format!(
+                    "Resource '{}' declares roles but no step requests one -- they have no effect",
+                    resource.name
+                ),
+            ));
+        }
+    }
+
+    ValidationResult { errors, warnings }
+}
+
+/// Check that every step's `track_id` names a track the template defines,
+/// and warn about tracks no step belongs to (neither through its `track_id`
+/// nor by being listed in the track's `steps`) and about listed steps that
+/// don't exist.
+pub fn validate_tracks_referenced_by_steps(template: &ScheduleTemplate) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
+
+    let track_ids: HashSet<&str> = template.tracks.iter().map(|t| t.id.as_str()).collect();
+    for step in &template.steps {
+        if let Some(track_id) = step.track_id.as_deref() {
+            if !track_ids.contains(track_id) {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::UnknownTrack,
+                    &[&step.id, track_id],
+                    format!(
+                        "Step '{}' references track '{}' which is not defined in the template",
+                        step.title, track_id
+                    ),
+                ));
+            }
+        }
+    }
+
+    let used: HashSet<&str> = template.steps.iter().filter_map(|s| s.track_id.as_deref()).collect();
+    for track in &template.tracks {
+        if track.steps.is_empty() && !used.contains(track.id.as_str()) {
+            warnings.push(ValidationError::new(
+                ValidationErrorCode::EmptyTrack,
+                &[&track.id],
+                format!("Track '{}' has no steps assigned", track.name),
+            ));
+        }
+        for step_id in &track.steps {
+            if !template.steps.iter().any(|s| &s.id == step_id) {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::UnknownTrackStep,
+                    &[&track.id, step_id],
+                    format!("Track '{}' lists step '{}' which doesn't exist", track.name, step_id),
+                ));
+            }
+        }
+    }
+
+    ValidationResult { errors, warnings }
+}
+
+/// Check that no step's consumable need is used up by the steps that must
+/// start before it.
+///
+/// Consumables are drawn when a step starts and are never restocked, so
+/// everything a step waits on (through finish-to-start and start-to-start
+/// dependencies, all of which it needs under `AllOf`) draws from the same
+/// stock first. If those steps and the step itself need more of a
+/// consumable than there is, the step can never get its share whatever
+/// order the solver picks -- an error. Conditional steps and dependencies
+/// are left out, since they may never apply. A single need larger than the
+/// whole stock is reported by `validate_resource_needs_against_capacity`
+/// instead.
+pub fn validate_circular_resource_needs(template: &ScheduleTemplate) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+
+    let steps_by_id: HashMap<&str, &Step> =
+        template.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+    let draws = |step: &Step, resource_id: &str| -> u32 {
+        step.resource_needs
+            .iter()
+            .filter(|n| n.resource_id == resource_id)
+            .map(|n| n.quantity)
+            .sum()
+    };
+
+    for resource in &template.resources {
+        if !matches!(resource.kind, ResourceKind::Consumable) {
+            continue;
+        }
+        for step in &template.steps {
+            let own = draws(step, &resource.id);
+            if step.condition.is_some() || own == 0 || own > resource.capacity {
+                continue;
+            }
+
+            // Steps that always start before this one.
+            let mut before: HashSet<&str> = HashSet::new();
+            let mut stack: Vec<&Step> = vec![step];
+            while let Some(current) = stack.pop() {
+                if !matches!(current.dependency_mode, DependencyMode::AllOf) {
+                    continue;
+                }
+                for dep in &current.dependencies {
+                    let starts_first = matches!(
+                        dep.dependency_type,
+                        DependencyType::FinishToStart | DependencyType::StartToStart
+                    );
+                    let Some(&pred) = steps_by_id.get(dep.step_id.as_str()) else {
+                        continue;
+                    };
+                    if starts_first
+                        && dep.condition.is_none()
+                        && pred.condition.is_none()
+                        && pred.id != step.id
+                        && before.insert(pred.id.as_str())
+                    {
+                        stack.push(pred);
+                    }
+                }
+            }
+
+            let used: u32 = before.iter().map(|id| draws(steps_by_id[id], &resource.id)).sum();
+            if used + own > resource.capacity {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::ConsumableExhausted,
+                    &[&step.id, &resource.id],
+                    format!(
+                        "Step '{}' needs {} of '{}' but the steps that must start before it use {} of the {} available",
+                        step.title, own, resource.name, used, resource.capacity
+                    ),
+                ));
+            }
+        }
+    }
+
+    ValidationResult {
+        errors,
+        warnings: Vec::new(),
+    }
+}
+
+/// Check each step's resource needs against the capacities defined in the
+/// template. A need larger than its resource's capacity can never be
+/// allocated, so it is reported as an error before solving is attempted.
+pub fn validate_resource_needs_against_capacity(template: &ScheduleTemplate) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
+
+    let resources_by_id: HashMap<&str, &Resource> =
+        template.resources.iter().map(|r| (r.id.as_str(), r)).collect();
+
+    for step in &template.steps {
+        let mut people_needed: u32 = 0;
+        for need in &step.resource_needs {
+            let Some(resource) = resources_by_id.get(need.resource_id.as_str()) else {
+                continue;
+            };
+            match resource.kind {
+                // Cost resources have no capacity to exceed
+                ResourceKind::Cost => continue,
+                ResourceKind::People => people_needed += need.quantity,
+                _ => {}
+            }
+            if need.quantity > resource.capacity {
+                errors.push(ValidationError::new(
+                    ValidationErrorCode::CapacityExceeded,
+                    &[&step.id, &resource.id],
+                    format!(
+                        "Step '{}' needs {} units of '{}' but only {} are defined",
+                        step.title, need.quantity, resource.name, resource.capacity
+                    ),
+                ));
+            }
+        }
+
+        // Warning: more people than the template's default headcount
+        if let Some(headcount) = template.default_num_people {
+            if people_needed > headcount {
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::DefaultHeadcountExceeded,
+                    &[&step.id],
+                    format!(
+                        "Step '{}' needs {} people but the default headcount is {}",
+                        step.title, people_needed, headcount
+                    ),
                 ));
             }
         }
@@ -176,3 +1237,386 @@ pub fn validate(template: &ScheduleTemplate) -> ValidationResult {
 
     ValidationResult { errors, warnings }
 }
+
+// ---------------------------------------------------------------------------
+// Complexity limits
+// ---------------------------------------------------------------------------
+
+/// Upper bounds on template size, so a huge template is rejected up front
+/// instead of exhausting memory while it is solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplexityLimits {
+    pub max_steps: usize,
+    pub max_resources: usize,
+    pub max_dependencies_per_step: usize,
+    pub max_total_dependencies: usize,
+}
+
+impl Default for ComplexityLimits {
+    /// Far beyond any hand-written template, but finite.
+    fn default() -> Self {
+        ComplexityLimits {
+            max_steps: 10_000,
+            max_resources: 1_000,
+            max_dependencies_per_step: 1_000,
+            max_total_dependencies: 100_000,
+        }
+    }
+}
+
+/// Check a template's size against `limits`. Each limit exceeded is an
+/// error; a step with too many dependencies is reported by itself.
+///
+/// Recurring steps count as expanded, the way they are solved. A template
+/// over the step limit isn't expanded (that is what the limit guards
+/// against), so its dependencies are counted as written.
+pub fn validate_complexity(
+    template: &ScheduleTemplate,
+    limits: &ComplexityLimits,
+) -> ValidationResult {
+    let step_count = crate::expander::expanded_step_count(template);
+    let expanded;
+    let template = if step_count <= limits.max_steps && !template.recurring_patterns.is_empty() {
+        expanded = crate::expander::expand_recurring(template);
+        &expanded
+    } else {
+        template
+    };
+
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut exceeded = |affected_ids: &[&str], message: String| {
+        errors.push(ValidationError::new(
+            ValidationErrorCode::ComplexityLimitExceeded,
+            affected_ids,
+            message,
+        ));
+    };
+
+    if step_count > limits.max_steps {
+        exceeded(
+            &[],
+            format!(
+                "Template has {} steps, more than the limit of {}",
+                step_count, limits.max_steps
+            ),
+        );
+    }
+    if template.resources.len() > limits.max_resources {
+        exceeded(
+            &[],
+            format!(
+                "Template has {} resources, more than the limit of {}",
+                template.resources.len(),
+                limits.max_resources
+            ),
+        );
+    }
+    for step in &template.steps {
+        if step.dependencies.len() > limits.max_dependencies_per_step {
+            exceeded(
+                &[&step.id],
+                format!(
+                    "Step '{}' has {} dependencies, more than the limit of {}",
+                    step.title,
+                    step.dependencies.len(),
+                    limits.max_dependencies_per_step
+                ),
+            );
+        }
+    }
+    let total: usize = template.steps.iter().map(|s| s.dependencies.len()).sum();
+    if total > limits.max_total_dependencies {
+        exceeded(
+            &[],
+            format!(
+                "Template has {} dependencies, more than the limit of {}",
+                total, limits.max_total_dependencies
+            ),
+        );
+    }
+
+    ValidationResult {
+        errors,
+        warnings: Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Checks needing CPM timings
+// ---------------------------------------------------------------------------
+
+/// Check the minimum headcounts (`ResourceNeed::min_people`) of steps that
+/// CPM runs at the same time against their People resources' capacities.
+/// Runs CPM first; a template CPM can't handle yields no findings, as
+/// `validate` already reports why.
+pub fn validate_resource_capacity_for_minimum_headcount(
+    template: &ScheduleTemplate,
+) -> ValidationResult {
+    match crate::solver::cpm_only(template) {
+        Ok(cpm) => validate_after_cpm(template, &cpm, None),
+        Err(_) => ValidationResult {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        },
+    }
+}
+
+/// Checks that depend on CPM timings, run by the solver between CPM and
+/// resource allocation. Capacities honour `inventory` overrides.
+///
+/// A step whose `min_people` on a People resource add up to more than its
+/// capacity can never be staffed -- an error. Otherwise each People
+/// resource is checked at every CPM early start: steps running then and
+/// needing more people between them than it has get a warning, since
+/// allocation will delay some of them. Each resource gets at most one such
+/// warning, for its first overload.
+pub fn validate_after_cpm(
+    template: &ScheduleTemplate,
+    cpm: &CpmResult,
+    inventory: Option<&ResourceInventory>,
+) -> ValidationResult {
+    let mut errors: Vec<ValidationError> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
+
+    let titles: HashMap<&str, &str> =
+        template.steps.iter().map(|s| (s.id.as_str(), s.title.as_str())).collect();
+    let mut slices: Vec<u32> = cpm.solved_steps.iter().map(|s| s.start_offset_mins).collect();
+    slices.sort_unstable();
+    slices.dedup();
+
+    for resource in &template.resources {
+        if !matches!(resource.kind, ResourceKind::People) {
+            continue;
+        }
+        let capacity = effective_capacity(resource, inventory);
+        // (step ID, start, end, minimum headcount) of steps with a minimum.
+        let demands: Vec<(&str, u32, u32, u32)> = cpm
+            .solved_steps
+            .iter()
+            .filter_map(|solved| {
+                let step = template.steps.iter().find(|s| s.id == solved.step_id)?;
+                let headcount: u32 = step
+                    .resource_needs
+                    .iter()
+                    .filter(|n| n.resource_id == resource.id)
+                    .filter_map(|n| n.min_people)
+                    .sum();
+                (headcount > 0).then_some((
+                    solved.step_id.as_str(),
+                    solved.start_offset_mins,
+                    solved.end_offset_mins,
+                    headcount,
+                ))
+            })
+            .collect();
+
+        let title = |id: &str| titles.get(id).copied().unwrap_or(id).to_string();
+        let (unstaffable, demands): (Vec<_>, Vec<_>) =
+            demands.into_iter().partition(|d| d.3 > capacity);
+        for (step_id, _, _, needed) in unstaffable {
+            errors.push(ValidationError::new(
+                ValidationErrorCode::CapacityExceeded,
+                &[step_id, &resource.id],
+                format!(
+                    "Step '{}' needs at least {} people from '{}' but only {} are defined",
+                    title(step_id),
+                    needed,
+                    resource.name,
+                    capacity
+                ),
+            ));
+        }
+
+        for &t in &slices {
+            let active: Vec<_> = demands.iter().filter(|d| d.1 <= t && t < d.2).collect();
+            let needed: u32 = active.iter().map(|d| d.3).sum();
+            if needed > capacity {
+                let names: Vec<String> =
+                    active.iter().map(|d| format!("'{}'", title(d.0))).collect();
+                let mut affected: Vec<&str> = active.iter().map(|d| d.0).collect();
+                affected.push(&resource.id);
+                warnings.push(ValidationError::new(
+                    ValidationErrorCode::MinimumHeadcountConflict,
+                    &affected,
+                    format!(
+                        "Steps {} need at least {} people from '{}' at minute {} but only {} are defined -- some will be delayed",
+                        names.join(", "),
+                        needed,
+                        resource.name,
+                        t,
+                        capacity
+                    ),
+                ));
+                break;
+            }
+        }
+    }
+
+    ValidationResult { errors, warnings }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        DependencyType, RecurringStepPattern, Resource, ResourceKind, StepDependency,
+    };
+
+    fn step(id: &str, deps: &[&str]) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration_mins: 10,
+            dependencies: deps
+                .iter()
+                .map(|d| StepDependency {
+                    step_id: d.to_string(),
+                    dependency_type: DependencyType::FinishToStart,
+                    trigger_at_percent: None,
+                    condition: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn resource(id: &str) -> Resource {
+        Resource {
+            id: id.to_string(),
+            name: id.to_string(),
+            kind: ResourceKind::Equipment,
+            capacity: 1,
+            roles: vec![],
+            role_headcounts: vec![],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: None,
+            utilization_target: None,
+        }
+    }
+
+    fn messages(result: &ValidationResult) -> Vec<&str> {
+        result.errors.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complexity_limits_resources_and_dependencies() {
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("a", &[]), step("b", &["a"]), step("c", &["a", "b"])],
+            resources: vec![resource("oven"), resource("mixer")],
+            ..Default::default()
+        };
+        let limits = ComplexityLimits {
+            max_steps: 3,
+            max_resources: 2,
+            max_dependencies_per_step: 2,
+            max_total_dependencies: 3,
+        };
+        assert!(validate_complexity(&template, &limits).is_ok());
+
+        let tight = ComplexityLimits {
+            max_steps: 3,
+            max_resources: 1,
+            max_dependencies_per_step: 1,
+            max_total_dependencies: 2,
+        };
+        let result = validate_complexity(&template, &tight);
+        assert_eq!(
+            messages(&result),
+            vec![
+                "Template has 2 resources, more than the limit of 1",
+                "Step 'c' has 2 dependencies, more than the limit of 1",
+                "Template has 3 dependencies, more than the limit of 2",
+            ]
+        );
+        assert_eq!(result.errors[1].affected_ids, vec!["c".to_string()]);
+        assert!(result
+            .errors
+            .iter()
+            .all(|e| e.code == ValidationErrorCode::ComplexityLimitExceeded));
+    }
+
+    #[test]
+    fn test_complexity_limits_count_recurring_steps_expanded() {
+        // Five copies of a 10-minute step an hour apart, with a wait between
+        // each: 9 steps. Later copies depend on the copy and the wait before
+        // them, and each wait on the copy before it: 12 dependencies.
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("check", &[])],
+            recurring_patterns: vec![RecurringStepPattern {
+                template_step_id: "check".to_string(),
+                interval_mins: 60,
+                count: 5,
+                prefix: "check".to_string(),
+            }],
+            ..Default::default()
+        };
+        let limits = |max_steps: usize| ComplexityLimits {
+            max_steps,
+            max_resources: 0,
+            max_dependencies_per_step: 2,
+            max_total_dependencies: 11,
+        };
+        assert_eq!(
+            messages(&validate_complexity(&template, &limits(9))),
+            vec!["Template has 12 dependencies, more than the limit of 11"]
+        );
+        // Past the step limit, dependencies are counted unexpanded.
+        assert_eq!(
+            messages(&validate_complexity(&template, &limits(8))),
+            vec!["Template has 9 steps, more than the limit of 8"]
+        );
+    }
+
+    #[test]
+    fn test_dependencies_differing_only_in_trigger_are_not_duplicates() {
+        let mut b = step("b", &["a", "a"]);
+        b.dependencies[1].trigger_at_percent = Some(50);
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("a", &[]), b],
+            ..Default::default()
+        };
+        let result = validate(&template);
+        assert!(messages(&result).is_empty());
+        assert!(result
+            .warnings
+            .iter()
+            .all(|w| w.code != ValidationErrorCode::MixedDependencyTypes));
+    }
+
+    #[test]
+    fn test_deadline_too_short_counts_recurring_steps() {
+        // Three 10-minute checks an hour apart take 130 minutes.
+        let template = ScheduleTemplate {
+            id: "t".to_string(),
+            name: "T".to_string(),
+            steps: vec![step("check", &[])],
+            recurring_patterns: vec![RecurringStepPattern {
+                template_step_id: "check".to_string(),
+                interval_mins: 60,
+                count: 3,
+                prefix: "check".to_string(),
+            }],
+            time_constraint: Some(TimeConstraint {
+                start_time: Some("2026-01-05T08:00:00".to_string()),
+                end_time: Some("2026-01-05T09:00:00".to_string()),
+                timezone: None,
+            }),
+            ..Default::default()
+        };
+        let result = validate(&template);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.code == ValidationErrorCode::DeadlineTooShort));
+    }
+}
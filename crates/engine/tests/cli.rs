@@ -12,7 +12,7 @@ use predicates::str::contains;
 // ---------------------------------------------------------------------------
 
 fn cmd() -> Command {
-    Command::cargo_bin("skejj-engine").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("skejj-engine")
 }
 
 // ---------------------------------------------------------------------------
@@ -496,3 +496,2492 @@ fn solve_with_consumable_inventory_override() {
         warnings_with_inv
     );
 }
+
+// ---------------------------------------------------------------------------
+// Test 10: compress_report
+// Two oven steps contend for a single oven; the first one is reported as
+// compressible because it delays the second past its slack.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn compress_report() {
+    let input = r#"{
+        "command": "compressReport",
+        "template": {
+            "id": "t10",
+            "name": "Compress",
+            "steps": [
+                {
+                    "id": "step-a",
+                    "title": "Roast A",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                },
+                {
+                    "id": "step-b",
+                    "title": "Roast B",
+                    "durationMins": 20,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                },
+                {
+                    "id": "step-c",
+                    "title": "Set Table",
+                    "durationMins": 40,
+                    "dependencies": [],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains(r#""ok":true"#))
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let candidates = parsed["data"].as_array().unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0]["stepId"], "step-a");
+    assert_eq!(candidates[0]["compressionFreesDownstream"], true);
+}
+
+// ---------------------------------------------------------------------------
+// Test 11: solve_with_gantt_flag
+// `--gantt` adds pre-computed Gantt rows; without it the field is null.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_with_gantt_flag() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t11",
+            "name": "Gantt",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd()
+        .arg("--gantt")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let rows = parsed["data"]["ganttRows"].as_array().unwrap();
+    // Both steps start at 0 with no track: two default lanes.
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["trackName"], "Default");
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(parsed["data"]["ganttRows"].is_null());
+}
+
+// ---------------------------------------------------------------------------
+// Test 12: validate_unknown_role
+// A resource need requesting a role the resource doesn't declare is an error.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_unknown_role() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t12",
+            "name": "Roles",
+            "steps": [
+                {
+                    "id": "drive",
+                    "title": "Drive",
+                    "durationMins": 60,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "crew", "quantity": 1, "role": "pilot" }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "crew", "name": "Crew", "kind": "People", "capacity": 2, "roles": ["driver"] }
+            ]
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("requests role 'pilot' from 'Crew'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 13: score_two_schedules
+// Solving twice and scoring the pair returns a comparison of both scores.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn score_two_schedules() {
+    let template = r#"{
+        "id": "t13",
+        "name": "Score",
+        "steps": [
+            { "id": "a", "title": "A", "durationMins": 30, "dependencies": [], "resourceNeeds": [] }
+        ],
+        "tracks": [],
+        "resources": []
+    }"#;
+
+    let solve_input = format!(r#"{{"command":"solve","template":{}}}"#, template);
+    let output = cmd().write_stdin(solve_input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let solved = parsed["data"].to_string();
+
+    let score_input = format!(
+        r#"{{"command":"score","template":{},"baseline":{},"candidate":{}}}"#,
+        template, solved, solved
+    );
+    let output = cmd().write_stdin(score_input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["totalDelta"], 0.0);
+    assert_eq!(parsed["data"]["baseline"]["durationScore"], 1.0);
+}
+
+// ---------------------------------------------------------------------------
+// Test 14: validate_duplicate_wbs_code
+// Two steps sharing a WBS code, and a malformed code, are both errors.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_duplicate_wbs_code() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t14",
+            "name": "WBS",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [], "wbsCode": "1.1" },
+                { "id": "b", "title": "B", "durationMins": 10, "dependencies": [], "resourceNeeds": [], "wbsCode": "1.1" },
+                { "id": "c", "title": "C", "durationMins": 10, "dependencies": [], "resourceNeeds": [], "wbsCode": "1.x" }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("share WBS code '1.1'"))
+        .stdout(contains("has WBS code '1.x'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 15: validate_need_exceeds_capacity
+// A step needing more units than a resource defines can never be scheduled.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_need_exceeds_capacity() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t15",
+            "name": "Capacity",
+            "steps": [
+                {
+                    "id": "bake",
+                    "title": "Bake",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 5 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 3, "roles": [] }
+            ]
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("Step 'Bake' needs 5 units of 'Oven' but only 3 are defined"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 16: validate_trigger_at_percent
+// A partial-completion trigger is only allowed on FinishToStart and in 1-100.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_trigger_at_percent() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t16",
+            "name": "Triggers",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "b",
+                    "title": "B",
+                    "durationMins": 10,
+                    "dependencies": [
+                        { "stepId": "a", "dependencyType": "StartToStart", "triggerAtPercent": 50 }
+                    ],
+                    "resourceNeeds": []
+                },
+                {
+                    "id": "c",
+                    "title": "C",
+                    "durationMins": 10,
+                    "dependencies": [
+                        { "stepId": "a", "dependencyType": "FinishToStart", "triggerAtPercent": 0 }
+                    ],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("only FinishToStart dependencies support it"))
+        .stdout(contains("trigger_at_percent 0 on its dependency on 'a'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 17: solve_dry_run
+// `--dry-run` (or `"dryRun": true`) skips allocation, so two steps competing
+// for one oven both start at 0.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_dry_run() {
+    let template = r#"{
+            "id": "t17",
+            "name": "Dry run",
+            "steps": [
+                {
+                    "id": "a",
+                    "title": "A",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                },
+                {
+                    "id": "b",
+                    "title": "B",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }
+            ]
+        }"#;
+    let total_duration = |output: Vec<u8>| {
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        parsed["data"]["summary"]["totalDurationMins"].as_u64().unwrap()
+    };
+
+    let plain = format!(r#"{{ "command": "solve", "template": {} }}"#, template);
+    let output = cmd().write_stdin(plain.clone()).assert().success().get_output().stdout.clone();
+    assert_eq!(total_duration(output), 60);
+
+    let output = cmd()
+        .arg("--dry-run")
+        .write_stdin(plain)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(total_duration(output), 30);
+
+    let in_request =
+        format!(r#"{{ "command": "solve", "dryRun": true, "template": {} }}"#, template);
+    let output = cmd().write_stdin(in_request).assert().success().get_output().stdout.clone();
+    assert_eq!(total_duration(output), 30);
+}
+
+// ---------------------------------------------------------------------------
+// Test 18: cpm_command
+// The `cpm` command returns the raw early/late start windows.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn cpm_command() {
+    let input = r#"{
+        "command": "cpm",
+        "template": {
+            "id": "t18",
+            "name": "CPM",
+            "steps": [
+                { "id": "long", "title": "Long", "durationMins": 60, "dependencies": [], "resourceNeeds": [] },
+                { "id": "short", "title": "Short", "durationMins": 20, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["projectEnd"], 60);
+    assert_eq!(parsed["data"]["earlyStarts"]["short"], 0);
+    assert_eq!(parsed["data"]["lateStarts"]["short"], 40);
+}
+
+// ---------------------------------------------------------------------------
+// Test 19: solve_near_critical_threshold
+// `nearCriticalThreshold` adds chains of low-float steps to the response.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_near_critical_threshold() {
+    let input = r#"{
+        "command": "solve",
+        "nearCriticalThreshold": 15,
+        "template": {
+            "id": "t19",
+            "name": "Near critical",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 60, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 50, "dependencies": [], "resourceNeeds": [] },
+                { "id": "c", "title": "C", "durationMins": 20, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let paths = parsed["data"]["nearCriticalPaths"].as_array().unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths[1]["stepIds"][0], "b");
+    assert_eq!(paths[1]["maxFloatMins"], 10);
+}
+
+// ---------------------------------------------------------------------------
+// Test 20: solve_format_html
+// `--format html` writes a standalone HTML report instead of JSON.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_format_html() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t20",
+            "name": "Report",
+            "steps": [
+                { "id": "a", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .args(["--format", "html"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("<!DOCTYPE html>"))
+        .stdout(contains("<h2>Critical path</h2>"))
+        .stdout(contains("Prep"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 21: external_dependency_solve_and_warn
+// An external dependency delays its step and is flagged as an assumption.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn external_dependency_solve_and_warn() {
+    let template = r#"{
+            "id": "t21",
+            "name": "External",
+            "steps": [
+                {
+                    "id": "install",
+                    "title": "Install",
+                    "durationMins": 60,
+                    "dependencies": [],
+                    "resourceNeeds": [],
+                    "externalDependencies": [
+                        { "externalProjectId": "supplier", "externalStepId": "ship", "expectedOffsetMins": 240 }
+                    ]
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }"#;
+
+    let solve = format!(r#"{{ "command": "solve", "template": {} }}"#, template);
+    let output = cmd().write_stdin(solve).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["solvedSteps"][0]["startOffsetMins"], 240);
+
+    let validate = format!(r#"{{ "command": "validate", "template": {} }}"#, template);
+    cmd()
+        .write_stdin(validate)
+        .assert()
+        .success()
+        .stdout(contains("depends on 'ship' in project 'supplier'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 22: solve_msgpack_round_trip
+// `--format msgpack` reads and writes raw MessagePack with named fields.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_msgpack_round_trip() {
+    let request = serde_json::json!({
+        "command": "solve",
+        "template": {
+            "id": "t22",
+            "name": "Msgpack",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "b",
+                    "title": "B",
+                    "durationMins": 15,
+                    "dependencies": [{ "stepId": "a", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    });
+    let input = rmp_serde::to_vec_named(&request).unwrap();
+
+    let output = cmd()
+        .args(["--format", "msgpack"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = rmp_serde::from_slice(&output).unwrap();
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["data"]["templateId"], "t22");
+    assert_eq!(parsed["data"]["summary"]["totalDurationMins"], 45);
+
+    // Errors come back as MessagePack too.
+    let output = cmd()
+        .args(["--format", "msgpack"])
+        .write_stdin(vec![0xc1])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = rmp_serde::from_slice(&output).unwrap();
+    assert_eq!(parsed["ok"], false);
+}
+
+// ---------------------------------------------------------------------------
+// Test 23: validate_self_and_duplicate_dependencies
+// Self-references and repeated dependency entries are errors; mixing types on
+// the same predecessor is a warning.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_self_and_duplicate_dependencies() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t23",
+            "name": "Dependencies",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "b",
+                    "title": "B",
+                    "durationMins": 10,
+                    "dependencies": [
+                        { "stepId": "b", "dependencyType": "FinishToStart" },
+                        { "stepId": "a", "dependencyType": "FinishToStart" },
+                        { "stepId": "a", "dependencyType": "FinishToStart" },
+                        { "stepId": "a", "dependencyType": "StartToStart" }
+                    ],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("Step 'B' depends on itself"))
+        .stdout(contains("lists its FinishToStart dependency on 'a' more than once"))
+        .stdout(contains("has several dependency types on 'a'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 24: split_by_track
+// The `split` command returns one sub-schedule per track plus "default".
+// ---------------------------------------------------------------------------
+
+#[test]
+fn split_by_track() {
+    let input = r#"{
+        "command": "split",
+        "template": {
+            "id": "t24",
+            "name": "Split",
+            "steps": [
+                { "id": "cook", "title": "Cook", "durationMins": 60, "dependencies": [], "trackId": "kitchen", "resourceNeeds": [] },
+                { "id": "greet", "title": "Greet", "durationMins": 20, "dependencies": [], "trackId": "service", "resourceNeeds": [] },
+                { "id": "misc", "title": "Misc", "durationMins": 5, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [
+                { "id": "kitchen", "name": "Kitchen" },
+                { "id": "service", "name": "Service" }
+            ],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let data = parsed["data"].as_object().unwrap();
+    assert_eq!(data.len(), 3);
+    assert_eq!(data["kitchen"]["solvedSteps"][0]["stepId"], "cook");
+    assert_eq!(data["service"]["solvedSteps"][0]["stepId"], "greet");
+    assert_eq!(data["default"]["solvedSteps"][0]["stepId"], "misc");
+}
+
+// ---------------------------------------------------------------------------
+// Test 25: solve_active_conditions
+// Conditional steps only appear when their condition is listed in
+// `activeConditions`.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_active_conditions() {
+    let template = r#"{
+            "id": "t25",
+            "name": "Conditions",
+            "steps": [
+                { "id": "cook", "title": "Cook", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "allergens",
+                    "title": "Allergen check",
+                    "durationMins": 10,
+                    "dependencies": [{ "stepId": "cook", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": [],
+                    "condition": "gluten_free"
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }"#;
+    let step_count = |request: String| {
+        let output = cmd().write_stdin(request).assert().success().get_output().stdout.clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        parsed["data"]["solvedSteps"].as_array().unwrap().len()
+    };
+
+    assert_eq!(step_count(format!(r#"{{ "command": "solve", "template": {} }}"#, template)), 1);
+    assert_eq!(
+        step_count(format!(
+            r#"{{ "command": "solve", "activeConditions": ["gluten_free"], "template": {} }}"#,
+            template
+        )),
+        2
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 26: max_version_rejects_newer_template
+// A template newer than `--max-version` is refused before solving.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn max_version_rejects_newer_template() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t26",
+            "name": "Versioned",
+            "version": "2.0.0",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .args(["--max-version", "1.9.9"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stdout(contains(r#""ok":false"#))
+        .stdout(contains("newer than the maximum 1.9.9"));
+
+    cmd()
+        .args(["--min-version", "1.0.0", "--max-version", "2.0.0"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains(r#""ok":true"#));
+}
+
+// ---------------------------------------------------------------------------
+// Test 27: validate_hammock_groups
+// Hammock members must exist and a step can only be in one hammock group.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_hammock_groups() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t27",
+            "name": "Hammocks",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": [],
+            "hammockGroups": [
+                { "id": "g1", "name": "Prep", "memberStepIds": ["a", "b"] },
+                { "id": "g2", "name": "Cook", "memberStepIds": ["b", "ghost"] }
+            ]
+        }
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains("includes step 'ghost' which doesn't exist"))
+        .stdout(contains("Step 'b' belongs to hammock groups 'Prep' and 'Cook'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 28: toml_input_and_output
+// `--input-format toml` reads the request as TOML and `--output-format toml`
+// writes the response envelope as TOML, without changing the input format.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn toml_input_and_output() {
+    let input = r#"
+command = "solve"
+
+[template]
+id = "t28"
+name = "Toml"
+tracks = []
+resources = []
+
+[[template.steps]]
+id = "a"
+title = "A"
+durationMins = 15
+dependencies = []
+resourceNeeds = []
+"#;
+
+    let output = cmd()
+        .args(["--input-format", "toml", "--output-format", "toml"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: toml::Value = toml::from_str(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(parsed["ok"].as_bool(), Some(true));
+    assert_eq!(parsed["data"]["summary"]["totalDurationMins"].as_integer(), Some(15));
+
+    let json_input = r#"{
+        "command": "solve",
+        "template": { "id": "t28", "name": "Json", "steps": [], "tracks": [], "resources": [] }
+    }"#;
+    let output = cmd()
+        .args(["--output-format", "toml"])
+        .write_stdin(json_input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: toml::Value = toml::from_str(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(parsed["ok"].as_bool(), Some(true));
+
+    cmd()
+        .args(["--input-format", "toml"])
+        .write_stdin("command = ")
+        .assert()
+        .failure()
+        .stdout(contains("Invalid TOML input"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 29: risk_command
+// `risk` solves the template and lists structural risks.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn risk_command() {
+    let input = r#"{
+        "command": "risk",
+        "template": {
+            "id": "t29",
+            "name": "Risky",
+            "steps": [
+                {
+                    "id": "bake",
+                    "title": "Bake",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let ids: Vec<&str> = parsed["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["riskId"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["critical-high-demand:bake", "resource-peak:oven"]);
+    assert_eq!(parsed["data"][0]["severity"], "High");
+}
+
+// ---------------------------------------------------------------------------
+// Test 30: generate_step_id
+// `generateStepId` slugifies the title and avoids the given IDs.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn generate_step_id() {
+    let input = r#"{
+        "command": "generateStepId",
+        "title": "Preheat Oven",
+        "existingIds": ["preheat-oven"]
+    }"#;
+
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(contains(r#""data":"preheat-oven-2""#));
+}
+
+// ---------------------------------------------------------------------------
+// Test 31: solve_batch_reports_each_template
+// `solveBatch` solves every template even when one of them fails, returning
+// one entry per template in order.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_batch_reports_each_template() {
+    let input = r#"{
+        "command": "solveBatch",
+        "templates": [
+            {
+                "id": "cyclic",
+                "name": "Cyclic",
+                "steps": [
+                    {
+                        "id": "a",
+                        "title": "A",
+                        "durationMins": 10,
+                        "dependencies": [{ "stepId": "b", "dependencyType": "FinishToStart" }],
+                        "resourceNeeds": []
+                    },
+                    {
+                        "id": "b",
+                        "title": "B",
+                        "durationMins": 10,
+                        "dependencies": [{ "stepId": "a", "dependencyType": "FinishToStart" }],
+                        "resourceNeeds": []
+                    }
+                ],
+                "tracks": [],
+                "resources": []
+            },
+            {
+                "id": "fine",
+                "name": "Fine",
+                "steps": [
+                    { "id": "a", "title": "A", "durationMins": 25, "dependencies": [], "resourceNeeds": [] }
+                ],
+                "tracks": [],
+                "resources": []
+            }
+        ]
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["ok"], true);
+    let entries = parsed["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["ok"], false);
+    assert!(entries[0]["error"].as_str().unwrap().contains("Circular dependency"));
+    assert_eq!(entries[1]["ok"], true);
+    assert_eq!(entries[1]["data"]["templateId"], "fine");
+    assert_eq!(entries[1]["data"]["summary"]["totalDurationMins"], 25);
+}
+
+// ---------------------------------------------------------------------------
+// Test 32: validate_resource_groups
+// Steps may need a resource group; group members must be defined resources.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_resource_groups() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t32",
+            "name": "Groups",
+            "steps": [
+                {
+                    "id": "bake",
+                    "title": "Bake",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "any-oven", "quantity": 1 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }
+            ],
+            "resourceGroups": [
+                { "id": "any-oven", "name": "Any oven", "resourceIds": ["oven", "oven-9"] }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let errors: Vec<&str> = parsed["data"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        errors,
+        vec!["Resource group 'Any oven' includes resource 'oven-9' which isn't defined"]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 33: solve_template_file
+// `--file` solves a .json or .toml template file instead of reading stdin.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_template_file() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let json_path = dir.join("t33.json");
+    let toml_path = dir.join("t33.toml");
+    std::fs::write(
+        &json_path,
+        r#"{
+            "id": "t33-json",
+            "name": "From JSON",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 20, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        &toml_path,
+        r#"
+id = "t33-toml"
+name = "From TOML"
+tracks = []
+resources = []
+
+[[steps]]
+id = "a"
+title = "A"
+durationMins = 35
+dependencies = []
+resourceNeeds = []
+"#,
+    )
+    .unwrap();
+
+    for (path, id, total) in [(&json_path, "t33-json", 20), (&toml_path, "t33-toml", 35)] {
+        let output = cmd()
+            .arg("--file")
+            .arg(path)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(parsed["data"]["templateId"], id);
+        assert_eq!(parsed["data"]["summary"]["totalDurationMins"], total);
+    }
+
+    cmd()
+        .arg("--file")
+        .arg(dir.join("t33-missing.json"))
+        .assert()
+        .failure()
+        .stdout(contains("Failed to read template file"));
+
+    cmd()
+        .args(["--file", "template.yaml"])
+        .assert()
+        .failure()
+        .stdout(contains("expected a .json or .toml file"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 34: s_curve_command
+// `sCurve` solves the template and reports cumulative resource usage.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn s_curve_command() {
+    let input = r#"{
+        "command": "sCurve",
+        "resourceId": "crew",
+        "bucketMins": 30,
+        "template": {
+            "id": "t34",
+            "name": "Curve",
+            "steps": [
+                {
+                    "id": "dig",
+                    "title": "Dig",
+                    "durationMins": 60,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "crew", "quantity": 2 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "crew", "name": "Crew", "kind": "People", "capacity": 2, "roles": [] }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let buckets = parsed["data"]["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0]["timeMins"], 30);
+    assert_eq!(buckets[0]["cumulativeUsed"], 60.0);
+    assert_eq!(buckets[1]["cumulativeUsed"], 120.0);
+    assert_eq!(buckets[1]["plannedUsed"], 120.0);
+}
+
+// ---------------------------------------------------------------------------
+// Test 35: merge_templates
+// `merge` concatenates two templates and rejects duplicate step IDs.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn merge_templates() {
+    let template = |id: &str, step: &str| {
+        format!(
+            r#"{{
+                "id": "{id}",
+                "name": "{id}",
+                "steps": [
+                    {{ "id": "{step}", "title": "{step}", "durationMins": 10,
+                       "dependencies": [], "resourceNeeds": [] }}
+                ],
+                "tracks": [],
+                "resources": []
+            }}"#
+        )
+    };
+
+    let input = format!(
+        r#"{{ "command": "merge", "base": {}, "overlay": {} }}"#,
+        template("main", "cook"),
+        template("extra", "bake")
+    );
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["id"], "main");
+    assert_eq!(parsed["data"]["name"], "main (extra)");
+    assert_eq!(parsed["data"]["steps"].as_array().unwrap().len(), 2);
+
+    let input = format!(
+        r#"{{ "command": "merge", "base": {}, "overlay": {} }}"#,
+        template("main", "cook"),
+        template("extra", "cook")
+    );
+    cmd()
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stdout(contains("Step 'cook' is defined in both templates"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 36: solve_with_substitutions
+// `substitutions` moves resource needs onto stand-in resources.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_with_substitutions() {
+    let input = r#"{
+        "command": "solve",
+        "substitutions": [["oven", "portable-oven"]],
+        "template": {
+            "id": "t36",
+            "name": "Stand-in",
+            "steps": [
+                {
+                    "id": "bake",
+                    "title": "Bake",
+                    "durationMins": 30,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] },
+                {
+                    "id": "portable-oven",
+                    "name": "Portable oven",
+                    "kind": "Equipment",
+                    "capacity": 1,
+                    "roles": []
+                }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["solvedSteps"][0]["assignedResources"][0]["resourceId"],
+        "portable-oven"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 37: parallelize_report
+// `parallelizeReport` lists independent steps held apart by the concurrency
+// limit.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn parallelize_report() {
+    let input = r#"{
+        "command": "parallelizeReport",
+        "template": {
+            "id": "t37",
+            "name": "One at a time",
+            "maxConcurrentSteps": 2,
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 60, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                { "id": "c", "title": "C", "durationMins": 30, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let first = &parsed["data"][0];
+    assert_eq!(first["stepAId"], "b");
+    assert_eq!(first["stepBId"], "c");
+    assert_eq!(first["potentialSavingsMins"], 30);
+}
+
+// ---------------------------------------------------------------------------
+// Test 38: solve_multi_shares_resources
+// `solveMulti` solves projects against one shared pool, so they queue for
+// the same oven.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_multi_shares_resources() {
+    let project = |id: &str, mins: u32| {
+        format!(
+            r#"{{
+                "id": "{id}",
+                "name": "{id}",
+                "steps": [
+                    {{
+                        "id": "bake",
+                        "title": "Bake",
+                        "durationMins": {mins},
+                        "dependencies": [],
+                        "resourceNeeds": [{{ "resourceId": "oven", "quantity": 1 }}]
+                    }}
+                ],
+                "tracks": [],
+                "resources": [
+                    {{ "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }}
+                ]
+            }}"#
+        )
+    };
+    let input = format!(
+        r#"{{ "command": "solveMulti", "templates": [{}, {}] }}"#,
+        project("bread", 60),
+        project("pie", 30)
+    );
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let entries = parsed["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["data"]["templateId"], "bread");
+    assert_eq!(entries[1]["data"]["solvedSteps"][0]["startOffsetMins"], 60);
+    assert_eq!(entries[1]["data"]["summary"]["totalDurationMins"], 90);
+}
+
+// ---------------------------------------------------------------------------
+// Test 39: schema_command
+// `schema` returns the template JSON Schema.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn schema_command() {
+    let output = cmd()
+        .write_stdin(r#"{ "command": "schema" }"#)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["title"], "ScheduleTemplate");
+    let required = parsed["data"]["required"].as_array().unwrap();
+    assert!(required.contains(&serde_json::json!("steps")));
+}
+
+// ---------------------------------------------------------------------------
+// Test 40: validate_time_constraint
+// Unparseable or out-of-order times are errors; a window shorter than the
+// critical path is a warning.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_time_constraint() {
+    let input = |start: &str, end: &str| {
+        format!(
+            r#"{{
+                "command": "validate",
+                "template": {{
+                    "id": "t40",
+                    "name": "Window",
+                    "steps": [
+                        {{ "id": "a", "title": "A", "durationMins": 90, "dependencies": [], "resourceNeeds": [] }}
+                    ],
+                    "tracks": [],
+                    "resources": [],
+                    "timeConstraint": {{ "startTime": "{start}", "endTime": "{end}", "timezone": null }}
+                }}
+            }}"#
+        )
+    };
+
+    cmd()
+        .write_stdin(input("not-a-date", "2026-03-01T10:00:00"))
+        .assert()
+        .success()
+        .stdout(contains("startTime 'not-a-date' isn't a date"));
+    cmd()
+        .write_stdin(input("2026-03-01T10:00:00", "2026-03-01T09:00:00"))
+        .assert()
+        .success()
+        .stdout(contains("must be before endTime"));
+    cmd()
+        .write_stdin(input("2026-03-01T09:00:00", "2026-03-01T10:00:00"))
+        .assert()
+        .success()
+        .stdout(contains("Time window is 60 minutes but the critical path alone takes 90"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 41: adjacency_matrix_command
+// `adjacencyMatrix` returns the dependency graph as a matrix.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn adjacency_matrix_command() {
+    let input = r#"{
+        "command": "adjacencyMatrix",
+        "template": {
+            "id": "t41",
+            "name": "Matrix",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "b",
+                    "title": "B",
+                    "durationMins": 10,
+                    "dependencies": [{ "stepId": "a", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["stepIds"], serde_json::json!(["a", "b"]));
+    assert_eq!(parsed["data"]["matrix"], serde_json::json!([[null, "FinishToStart"], [null, null]]));
+}
+
+// ---------------------------------------------------------------------------
+// Test 42: loading_report_command
+// `loadingReport` solves the template and tabulates each resource's load.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn loading_report_command() {
+    let input = r#"{
+        "command": "loadingReport",
+        "periodMins": 60,
+        "template": {
+            "id": "t42",
+            "name": "Loading",
+            "steps": [
+                {
+                    "id": "dig",
+                    "title": "Dig",
+                    "durationMins": 90,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "crew", "quantity": 2 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "crew", "name": "Crew", "kind": "People", "capacity": 2, "roles": [] }
+            ]
+        }
+    }"#;
+
+    let output = cmd()
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["periods"], serde_json::json!([0, 60]));
+    assert_eq!(parsed["data"]["resources"][0]["name"], "Crew");
+    assert_eq!(parsed["data"]["resources"][0]["loads"], serde_json::json!([2, 2]));
+}
+
+// ---------------------------------------------------------------------------
+// Test 43: solve_with_detailed_float
+// `--detailed-float` fills in each step's path float.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_with_detailed_float() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t43",
+            "name": "Path float",
+            "steps": [
+                { "id": "long", "title": "Long", "durationMins": 60, "dependencies": [], "resourceNeeds": [] },
+                { "id": "short", "title": "Short", "durationMins": 20, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd()
+        .arg("--detailed-float")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let steps = parsed["data"]["solvedSteps"].as_array().unwrap();
+    assert_eq!(steps[0]["pathFloatMins"], 0);
+    assert_eq!(steps[1]["pathFloatMins"], 40);
+
+    // Without the flag the field is left null.
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(parsed["data"]["solvedSteps"][0]["pathFloatMins"].is_null());
+}
+
+// ---------------------------------------------------------------------------
+// Test 44: record_actuals_command
+// `record` writes actual times onto a solved schedule and warns on big
+// deviations from plan.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn record_actuals_command() {
+    let solve_input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t44",
+            "name": "Record",
+            "steps": [
+                { "id": "bake", "title": "Bake", "durationMins": 60, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": [],
+            "timeConstraint": { "startTime": "2026-03-01T09:00:00", "endTime": null, "timezone": null }
+        }
+    }"#;
+    let output = cmd().write_stdin(solve_input).assert().success().get_output().stdout.clone();
+    let solved: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let record_input = serde_json::json!({
+        "command": "record",
+        "schedule": solved["data"],
+        "updates": [
+            { "stepId": "bake", "actualStartTime": "2026-03-01T09:45:00", "actualEndTime": null }
+        ]
+    });
+    let output = cmd()
+        .write_stdin(record_input.to_string())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let step = &parsed["data"]["solvedSteps"][0];
+    assert_eq!(step["actualStartTime"], "2026-03-01T09:45:00");
+    assert!(step["actualEndTime"].is_null());
+    assert_eq!(parsed["data"]["warnings"], serde_json::json!(["Step 'bake' started 45 min behind plan"]));
+}
+
+// ---------------------------------------------------------------------------
+// Test 45: solve_format_plantuml
+// `--format plantuml` writes a dependency diagram with the critical path in red.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_format_plantuml() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t45",
+            "name": "Diagram",
+            "steps": [
+                { "id": "a", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "b",
+                    "title": "Cook",
+                    "durationMins": 20,
+                    "dependencies": [{ "stepId": "a", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": []
+                }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    cmd()
+        .args(["--format", "plantuml"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("@startuml"))
+        .stdout(contains("rectangle \"Prep\\n30 min\" as s0 #red"))
+        .stdout(contains("s0 --> s1 : FinishToStart"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 46: suggest_deps_command
+// `suggestDeps` proposes dependencies between unrelated steps.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn suggest_deps_command() {
+    let input = r#"{
+        "command": "suggestDeps",
+        "template": {
+            "id": "t46",
+            "name": "Suggest",
+            "steps": [
+                { "id": "a", "title": "Use the sauce", "durationMins": 10, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "Prepare the sauce", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let suggestion = &parsed["data"][0];
+    assert_eq!(suggestion["fromStepId"], "b");
+    assert_eq!(suggestion["toStepId"], "a");
+    assert_eq!(suggestion["reason"], "'prepare' usually comes before 'use'");
+    assert_eq!(suggestion["confidence"], 0.6);
+}
+
+// ---------------------------------------------------------------------------
+// Test 47: validate_step_durations_against_calendar
+// A non-interruptible step longer than any working window is an error; one
+// exactly as long is a warning.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_step_durations_against_calendar() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t47",
+            "name": "Shifts",
+            "steps": [
+                { "id": "a", "title": "Pour", "durationMins": 600, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "Paint", "durationMins": 540, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "c",
+                    "title": "Cure",
+                    "durationMins": 900,
+                    "dependencies": [],
+                    "resourceNeeds": [],
+                    "interruptible": true
+                }
+            ],
+            "tracks": [],
+            "resources": [],
+            "workingCalendar": {
+                "cycleMins": 1440,
+                "workingWindows": [{ "startOffsetMins": 480, "endOffsetMins": 1020 }]
+            }
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let errors = parsed["data"]["errors"].as_array().unwrap();
+    let warnings = parsed["data"]["warnings"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].as_str().unwrap().starts_with("Step 'Pour' takes 600 min"));
+    assert!(warnings.iter().any(|w| w.as_str().unwrap().starts_with("Step 'Paint' takes exactly")));
+    assert!(!warnings.iter().any(|w| w.as_str().unwrap().contains("Cure")));
+}
+
+// ---------------------------------------------------------------------------
+// Test 48: float_histogram_command
+// `floatHistogram` solves the template and counts steps by total float.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn float_histogram_command() {
+    let input = r#"{
+        "command": "floatHistogram",
+        "bucketSizeMins": 60,
+        "template": {
+            "id": "t48",
+            "name": "Float",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 90, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 60, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["bucketSizeMins"], 60);
+    assert_eq!(
+        parsed["data"]["buckets"],
+        serde_json::json!([
+            { "minFloat": 0, "maxFloat": 0, "stepCount": 1, "stepIds": ["a"] },
+            { "minFloat": 1, "maxFloat": 60, "stepCount": 1, "stepIds": ["b"] }
+        ])
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 49: validate_resource_roles
+// Duplicate roles, headcounts for undeclared roles and role requests beyond
+// capacity are errors; roles nobody requests are a warning.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_resource_roles() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t49",
+            "name": "Roles",
+            "steps": [
+                {
+                    "id": "drive",
+                    "title": "Drive",
+                    "durationMins": 60,
+                    "dependencies": [],
+                    "resourceNeeds": [
+                        { "resourceId": "crew", "quantity": 2, "role": "driver" },
+                        { "resourceId": "crew", "quantity": 1, "minPeople": 2, "role": "navigator" }
+                    ]
+                }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "crew", "name": "Crew", "kind": "People", "capacity": 3, "roles": ["driver", "navigator", "driver"], "roleHeadcounts": [{ "role": "chef", "headcount": 1 }] },
+                { "id": "cooks", "name": "Cooks", "kind": "People", "capacity": 2, "roles": ["chef"] }
+            ]
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let errors = parsed["data"]["errors"].as_array().unwrap();
+    assert!(errors.contains(&serde_json::json!("Resource 'Crew' declares role 'driver' more than once")));
+    assert!(errors.contains(&serde_json::json!(
+        "Resource 'Crew' gives a headcount for role 'chef' it doesn't declare"
+    )));
+    assert!(errors.contains(&serde_json::json!(
+        "Step 'Drive' asks for 4 people by role from 'Crew' but only 3 are defined"
+    )));
+    assert!(parsed["data"]["warnings"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("Resource 'Cooks' declares roles but no step requests one -- they have no effect")));
+}
+
+// ---------------------------------------------------------------------------
+// Test 50: what_if_add_command
+// `whatIfAdd` solves with and without extra capacity and compares the two.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn what_if_add_command() {
+    let input = r#"{
+        "command": "whatIfAdd",
+        "extraQuantity": 1,
+        "resource": { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] },
+        "template": {
+            "id": "t50",
+            "name": "Ovens",
+            "steps": [
+                { "id": "a", "title": "Bread", "durationMins": 40, "dependencies": [], "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+                { "id": "b", "title": "Cake", "durationMins": 40, "dependencies": [], "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] }
+            ],
+            "tracks": [],
+            "resources": [{ "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }]
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["baselineDurationMins"], 80);
+    assert_eq!(parsed["data"]["newDurationMins"], 40);
+    assert_eq!(parsed["data"]["savingsMins"], 40);
+    assert_eq!(parsed["data"]["savingsPercent"], 50.0);
+}
+
+// ---------------------------------------------------------------------------
+// Test 51: what_if_remove_command
+// `whatIfRemove` solves with and without a step and compares the two.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn what_if_remove_command() {
+    let input = r#"{
+        "command": "whatIfRemove",
+        "stepId": "glaze",
+        "template": {
+            "id": "t51",
+            "name": "Cake",
+            "steps": [
+                { "id": "bake", "title": "Bake", "durationMins": 40, "dependencies": [], "resourceNeeds": [] },
+                {
+                    "id": "glaze",
+                    "title": "Glaze",
+                    "durationMins": 20,
+                    "dependencies": [{ "stepId": "bake", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": [{ "resourceId": "brush", "quantity": 1 }]
+                }
+            ],
+            "tracks": [],
+            "resources": [{ "id": "brush", "name": "Brush", "kind": "Equipment", "capacity": 1, "roles": [] }]
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["baselineDurationMins"], 60);
+    assert_eq!(parsed["data"]["newDurationMins"], 40);
+    assert_eq!(parsed["data"]["freedResources"], serde_json::json!(["brush"]));
+    assert_eq!(parsed["data"]["relievedSteps"], serde_json::json!([]));
+}
+
+// ---------------------------------------------------------------------------
+// Test 52: solve_batch_in_parallel
+// `--parallel N` solves a batch on N threads without changing the result
+// order; a thread count of zero is rejected.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_batch_in_parallel() {
+    let template = |id: &str, mins: u32| {
+        format!(
+            r#"{{ "id": "{}", "name": "T", "steps": [{{ "id": "a", "title": "A", "durationMins": {}, "dependencies": [], "resourceNeeds": [] }}], "tracks": [], "resources": [] }}"#,
+            id, mins
+        )
+    };
+    let input = format!(
+        r#"{{ "command": "solveBatch", "templates": [{}, {}, {}] }}"#,
+        template("one", 10),
+        template("two", 20),
+        template("three", 30)
+    );
+
+    let output = cmd()
+        .args(["--parallel", "2"])
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let entries = parsed["data"].as_array().unwrap();
+    let ids: Vec<_> = entries.iter().map(|e| e["data"]["templateId"].clone()).collect();
+    assert_eq!(ids, vec!["one", "two", "three"]);
+    assert_eq!(entries[2]["data"]["summary"]["totalDurationMins"], 30);
+
+    cmd()
+        .args(["--parallel", "0"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stdout(contains("--parallel"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 53: validate_consumables_used_up_by_predecessors
+// A step whose consumable need is used up by the steps it waits on can never
+// start.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_consumables_used_up_by_predecessors() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t53",
+            "name": "Flour",
+            "steps": [
+                {
+                    "id": "dough",
+                    "title": "Dough",
+                    "durationMins": 20,
+                    "dependencies": [],
+                    "resourceNeeds": [{ "resourceId": "flour", "quantity": 3 }]
+                },
+                {
+                    "id": "dust",
+                    "title": "Dust",
+                    "durationMins": 5,
+                    "dependencies": [{ "stepId": "dough", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": [{ "resourceId": "flour", "quantity": 2 }]
+                },
+                {
+                    "id": "extra",
+                    "title": "Extra",
+                    "durationMins": 5,
+                    "dependencies": [{ "stepId": "dough", "dependencyType": "FinishToStart" }],
+                    "resourceNeeds": [{ "resourceId": "flour", "quantity": 2 }],
+                    "condition": "double_batch"
+                }
+            ],
+            "tracks": [],
+            "resources": [{ "id": "flour", "name": "Flour", "kind": "Consumable", "capacity": 4, "roles": [] }]
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["errors"],
+        serde_json::json!([
+            "Step 'Dust' needs 2 of 'Flour' but the steps that must start before it use 3 of the 4 available"
+        ])
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 54: solve_annotated
+// `--annotated` puts each step's template data next to its solved timings.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_annotated() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t54",
+            "name": "Bread",
+            "steps": [
+                {
+                    "id": "mix",
+                    "title": "Mix",
+                    "description": "By hand",
+                    "durationMins": 20,
+                    "dependencies": [],
+                    "trackId": "bench",
+                    "resourceNeeds": [{ "resourceId": "bowl", "quantity": 1 }]
+                }
+            ],
+            "tracks": [{ "id": "bench", "name": "Bench" }],
+            "resources": [{ "id": "bowl", "name": "Bowl", "kind": "Equipment", "capacity": 1, "roles": [] }]
+        }
+    }"#;
+
+    let output = cmd()
+        .arg("--annotated")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["templateName"], "Bread");
+    let step = &parsed["data"]["solvedSteps"][0];
+    assert_eq!(step["stepId"], "mix");
+    assert_eq!(step["endOffsetMins"], 20);
+    assert_eq!(step["title"], "Mix");
+    assert_eq!(step["description"], "By hand");
+    assert_eq!(step["trackName"], "Bench");
+    assert_eq!(step["resourceNeeds"][0]["resourceId"], "bowl");
+}
+
+// ---------------------------------------------------------------------------
+// Test 55: validate_track_references
+// A step naming an undefined track is an error; a track without steps, or
+// listing a step that doesn't exist, is a warning.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_track_references() {
+    let input = r#"{
+        "command": "validate",
+        "template": {
+            "id": "t55",
+            "name": "Tracks",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "trackId": "oven", "resourceNeeds": [] },
+                { "id": "b", "title": "B", "durationMins": 10, "dependencies": [], "trackId": "prep", "resourceNeeds": [] },
+                { "id": "c", "title": "C", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [
+                { "id": "prep", "name": "Prep" },
+                { "id": "wash", "name": "Washing up" },
+                { "id": "plate", "name": "Plating", "steps": ["c", "glaze"] }
+            ],
+            "resources": []
+        }
+    }"#;
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["errors"],
+        serde_json::json!(["Step 'A' references track 'oven' which is not defined in the template"])
+    );
+    let warnings = parsed["data"]["warnings"].as_array().unwrap();
+    assert!(warnings.contains(&serde_json::json!("Track 'Washing up' has no steps assigned")));
+    assert!(warnings
+        .contains(&serde_json::json!("Track 'Plating' lists step 'glaze' which doesn't exist")));
+    assert!(!warnings.iter().any(|w| w.as_str().unwrap().contains("'Prep'")));
+    assert!(!warnings.iter().any(|w| w.as_str().unwrap().contains("'Plating' has no steps")));
+}
+
+// ---------------------------------------------------------------------------
+// Test 56: solve_max_duration
+// `maxDurationMins` turns a schedule longer than the limit into an error.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_max_duration() {
+    let input = |limit: u32| {
+        format!(
+            r#"{{
+                "command": "solve",
+                "maxDurationMins": {},
+                "template": {{
+                    "id": "t56",
+                    "name": "Limit",
+                    "steps": [
+                        {{ "id": "a", "title": "A", "durationMins": 40, "dependencies": [], "resourceNeeds": [] }}
+                    ],
+                    "tracks": [],
+                    "resources": []
+                }}
+            }}"#,
+            limit
+        )
+    };
+
+    cmd().write_stdin(input(40)).assert().success().stdout(contains("\"ok\":true"));
+    cmd()
+        .write_stdin(input(30))
+        .assert()
+        .failure()
+        .stdout(contains("Schedule takes 40 min, more than the maximum of 30 min"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 57: solve_float_consumption
+// `--float-consumption` reports the float steps lost waiting for resources.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_float_consumption() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t57",
+            "name": "Oven",
+            "steps": [
+                { "id": "a", "title": "A", "durationMins": 30, "dependencies": [], "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+                { "id": "b", "title": "B", "durationMins": 20, "dependencies": [], "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+                { "id": "c", "title": "C", "durationMins": 60, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": [{ "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }]
+        }
+    }"#;
+
+    let output = cmd()
+        .arg("--float-consumption")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["floatConsumption"],
+        serde_json::json!([{
+            "stepId": "b",
+            "originalFloatMins": 40,
+            "floatConsumedByAllocation": 30,
+            "consumingResourceId": "oven"
+        }])
+    );
+
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(parsed["data"]["floatConsumption"].is_null());
+}
+
+// ---------------------------------------------------------------------------
+// Test 58: step_progress_command
+// `stepProgress` estimates a step's progress from the time elapsed.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn step_progress_command() {
+    let solve_input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t58",
+            "name": "Progress",
+            "steps": [
+                { "id": "bake", "title": "Bake", "durationMins": 60, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+    let output = cmd().write_stdin(solve_input).assert().success().get_output().stdout.clone();
+    let solved: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let progress_input = |step_id: &str, mins: u32| {
+        serde_json::json!({
+            "command": "stepProgress",
+            "schedule": solved["data"],
+            "stepId": step_id,
+            "currentTimeMins": mins
+        })
+        .to_string()
+    };
+    let output = cmd()
+        .write_stdin(progress_input("bake", 90))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"],
+        serde_json::json!({ "stepId": "bake", "percentComplete": 150.0, "status": "Overdue" })
+    );
+
+    cmd()
+        .write_stdin(progress_input("ice", 90))
+        .assert()
+        .failure()
+        .stdout(contains("Step 'ice' isn't in the schedule"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 59: solve_backward_command
+// `solveBackward` schedules the template so that it finishes at the deadline.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_backward_command() {
+    let input = |deadline: &str| {
+        format!(
+            r#"{{
+                "command": "solveBackward",
+                "deadline": "{}",
+                "template": {{
+                    "id": "t59",
+                    "name": "Backward",
+                    "steps": [
+                        {{ "id": "prep", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] }},
+                        {{ "id": "bake", "title": "Bake", "durationMins": 60,
+                          "dependencies": [{{ "stepId": "prep", "dependencyType": "FinishToStart" }}],
+                          "resourceNeeds": [] }}
+                    ],
+                    "tracks": [],
+                    "resources": []
+                }}
+            }}"#,
+            deadline
+        )
+    };
+    let output = cmd()
+        .write_stdin(input("2026-03-01T18:00:00"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let steps = parsed["data"]["solvedSteps"].as_array().unwrap();
+    assert_eq!(steps[0]["startTime"], "2026-03-01T16:30:00");
+    assert_eq!(steps[1]["endTime"], "2026-03-01T18:00:00");
+
+    cmd()
+        .write_stdin(input("teatime"))
+        .assert()
+        .failure()
+        .stdout(contains("Invalid deadline 'teatime'"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 60: leveling_metrics_command
+// `levelingMetrics` compares the CPM schedule with the leveled one.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn leveling_metrics_command() {
+    let input = r#"{
+        "command": "levelingMetrics",
+        "template": {
+            "id": "t60",
+            "name": "Leveling",
+            "steps": [
+                { "id": "roast", "title": "Roast", "durationMins": 60, "dependencies": [],
+                  "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+                { "id": "bake", "title": "Bake", "durationMins": 30, "dependencies": [],
+                  "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] }
+            ],
+            "tracks": [],
+            "resources": [
+                { "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }
+            ]
+        }
+    }"#;
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let metrics = &parsed["data"];
+    assert_eq!(metrics["durationExtensionMins"], 30);
+    assert_eq!(metrics["stepsDelayed"], 1);
+    assert_eq!(metrics["maxResourcePeakBefore"], 2);
+    assert_eq!(metrics["maxResourcePeakAfter"], 1);
+    assert_eq!(metrics["newlyCriticalSteps"], serde_json::json!(["bake"]));
+}
+
+// ---------------------------------------------------------------------------
+// Test 61: format_dot
+// `--format dot` writes a Graphviz graph for `solve` (critical path in red)
+// and for `validate`.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn format_dot() {
+    let input = |command: &str| {
+        format!(
+            r#"{{
+                "command": "{}",
+                "template": {{
+                    "id": "t61",
+                    "name": "Graph",
+                    "steps": [
+                        {{ "id": "a", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] }},
+                        {{ "id": "b", "title": "Cook", "durationMins": 20,
+                          "dependencies": [{{ "stepId": "a", "dependencyType": "FinishToStart" }}],
+                          "resourceNeeds": [] }}
+                    ],
+                    "tracks": [],
+                    "resources": []
+                }}
+            }}"#,
+            command
+        )
+    };
+
+    cmd()
+        .args(["--format", "dot"])
+        .write_stdin(input("solve"))
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("digraph \"Graph\" {"))
+        .stdout(contains("\"a\" [label=\"Prep\\n30 min\", color=red, fontcolor=red];"))
+        .stdout(contains("\"a\" -> \"b\" [label=\"FinishToStart\", color=red, style=dashed];"));
+
+    cmd()
+        .args(["--format", "dot"])
+        .write_stdin(input("validate"))
+        .assert()
+        .success()
+        .stdout(contains("\"a\" [label=\"Prep\\n30 min\"];"))
+        .stdout(contains("\"a\" -> \"b\" [label=\"FinishToStart\"];"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 62: validate_structured_errors
+// `validate` with `structured: true` reports codes and affected IDs; without
+// it, errors stay plain messages.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_structured_errors() {
+    let input = |structured: bool| {
+        serde_json::json!({
+            "command": "validate",
+            "structured": structured,
+            "template": {
+                "id": "t62",
+                "name": "Structured",
+                "version": "1.0.0",
+                "steps": [
+                    { "id": "a", "title": "Prep", "durationMins": 30,
+                      "dependencies": [{ "stepId": "ghost", "dependencyType": "FinishToStart" }],
+                      "resourceNeeds": [] }
+                ],
+                "tracks": [],
+                "resources": []
+            }
+        })
+        .to_string()
+    };
+
+    let output = cmd().write_stdin(input(true)).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["errors"],
+        serde_json::json!([{
+            "code": "UnknownDependency",
+            "message": "Step 'Prep' depends on 'ghost' which doesn't exist",
+            "affectedIds": ["a", "ghost"]
+        }])
+    );
+    assert_eq!(parsed["data"]["warnings"][0]["code"], "NoResourceNeeds");
+
+    let output = cmd().write_stdin(input(false)).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        parsed["data"]["errors"],
+        serde_json::json!(["Step 'Prep' depends on 'ghost' which doesn't exist"])
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Test 63: topological_levels_command
+// `topologicalLevels` numbers steps by the chain of steps before them.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn topological_levels_command() {
+    let input = r#"{
+        "command": "topologicalLevels",
+        "template": {
+            "id": "t63",
+            "name": "Levels",
+            "steps": [
+                { "id": "a", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "Cook", "durationMins": 20,
+                  "dependencies": [{ "stepId": "a", "dependencyType": "FinishToStart" }],
+                  "resourceNeeds": [] },
+                { "id": "c", "title": "Lay table", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"], serde_json::json!({ "a": 0, "b": 1, "c": 0 }));
+}
+
+// ---------------------------------------------------------------------------
+// Test 64: solve_batch_jsonl
+// `--format jsonl` writes one schedule, or `{ templateId, error }`, per line,
+// with or without `--parallel`.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_batch_jsonl() {
+    let template = |id: &str, mins: u32| {
+        format!(
+            r#"{{ "id": "{}", "name": "T", "steps": [{{ "id": "a", "title": "A", "durationMins": {}, "dependencies": [], "resourceNeeds": [] }}], "tracks": [], "resources": [] }}"#,
+            id, mins
+        )
+    };
+    let input = format!(
+        r#"{{ "command": "solveBatch", "templates": [{}, {}, {}] }}"#,
+        template("one", 10),
+        template("broken", 0),
+        template("three", 30)
+    );
+
+    for args in [vec!["--format", "jsonl"], vec!["--format", "jsonl", "--parallel", "2"]] {
+        let output = cmd()
+            .args(&args)
+            .write_stdin(input.clone())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["templateId"], "one");
+        assert_eq!(lines[1]["templateId"], "broken");
+        assert!(lines[1]["error"].as_str().unwrap().contains("no duration"));
+        assert_eq!(lines[2]["summary"]["totalDurationMins"], 30);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Test 65: max_steps_rejects_large_template
+// `--max-steps` rejects a template with more steps than allowed.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn max_steps_rejects_large_template() {
+    let input = r#"{
+        "command": "solve",
+        "template": {
+            "id": "t65",
+            "name": "Limits",
+            "steps": [
+                { "id": "a", "title": "Prep", "durationMins": 30, "dependencies": [], "resourceNeeds": [] },
+                { "id": "b", "title": "Cook", "durationMins": 20, "dependencies": [], "resourceNeeds": [] }
+            ],
+            "tracks": [],
+            "resources": []
+        }
+    }"#;
+    cmd().args(["--max-steps", "2"]).write_stdin(input).assert().success();
+
+    let output = cmd()
+        .args(["--max-steps", "1"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["error"], "Template has 2 steps, more than the limit of 1");
+}
+
+// ---------------------------------------------------------------------------
+// Test 66: markdown_input_is_solved
+// `--input-format markdown` solves a markdown table of steps.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn markdown_input_is_solved() {
+    let input = "\
+| Step | Duration (mins) | Depends On |
+|------|-----------------|------------|
+| Prep | 20              |            |
+| Cook | 40              | Prep       |
+";
+    let output = cmd()
+        .args(["--input-format", "markdown"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(parsed["data"]["summary"]["totalDurationMins"], 60);
+    assert_eq!(parsed["data"]["solvedSteps"][1]["stepId"], "cook");
+
+    cmd()
+        .args(["--input-format", "markdown"])
+        .write_stdin("| Step |\n|---|\n| Prep |\n")
+        .assert()
+        .failure()
+        .stdout(contains("Markdown table has no 'Duration (mins)' column"));
+}
+
+// ---------------------------------------------------------------------------
+// Test 67: compare_policies_command
+// `comparePolicies` reports an ASAP and an ALAP outcome side by side.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn compare_policies_command() {
+    let input = r#"{
+        "command": "comparePolicies",
+        "template": {
+            "id": "t67",
+            "name": "Policies",
+            "steps": [
+                { "id": "a", "title": "Roast", "durationMins": 60, "dependencies": [],
+                  "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+                { "id": "b", "title": "Bake", "durationMins": 20, "dependencies": [],
+                  "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] }
+            ],
+            "tracks": [],
+            "resources": [{ "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 2, "roles": [] }]
+        }
+    }"#;
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    for policy in ["asap", "alap"] {
+        let outcome = &parsed["data"][policy];
+        assert_eq!(outcome["totalDurationMins"], 60);
+        assert_eq!(outcome["stepsDelayedBeyondFloat"], 0);
+        assert_eq!(outcome["resources"][0]["resourceId"], "oven");
+        assert_eq!(outcome["resources"][0]["peakUsage"], 2);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Test 68: batch_rejects_templates_individually
+// A template failing the version or size checks in `solveBatch` or
+// `solveMulti` gets its own error entry; the others are still solved.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn batch_rejects_templates_individually() {
+    let template = |id: &str, version: &str| {
+        format!(
+            r#"{{ "id": "{}", "name": "{}", "version": "{}", "steps": [{{ "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }}], "tracks": [], "resources": [] }}"#,
+            id, id, version
+        )
+    };
+    for command in ["solveBatch", "solveMulti"] {
+        let input = format!(
+            r#"{{ "command": "{}", "templates": [{}, {}] }}"#,
+            command,
+            template("new", "3.0.0"),
+            template("old", "1.0.0")
+        );
+
+        let output = cmd()
+            .args(["--max-version", "2.0.0"])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let entries = parsed["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 2, "{}", command);
+        assert_eq!(entries[0]["ok"], false);
+        assert!(entries[0]["error"].as_str().unwrap().contains("newer than the maximum 2.0.0"));
+        assert_eq!(entries[1]["ok"], true);
+        assert_eq!(entries[1]["data"]["templateId"], "old");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Test 69: solve_multi_times_steps_through_working_calendar
+// `solveMulti` re-times a step moved by allocation through the working
+// calendar exactly as `solve` does.
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solve_multi_times_steps_through_working_calendar() {
+    let template = r#"{
+        "id": "kitchen",
+        "name": "Kitchen",
+        "steps": [
+            { "id": "b", "title": "Bake", "durationMins": 40, "dependencies": [],
+              "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] },
+            { "id": "a", "title": "Proof", "durationMins": 30, "dependencies": [],
+              "interruptible": true,
+              "resourceNeeds": [{ "resourceId": "oven", "quantity": 1 }] }
+        ],
+        "tracks": [],
+        "resources": [{ "id": "oven", "name": "Oven", "kind": "Equipment", "capacity": 1, "roles": [] }],
+        "workingCalendar": {
+            "cycleMins": 1440,
+            "workingWindows": [{ "startOffsetMins": 0, "endOffsetMins": 60 }]
+        }
+    }"#;
+    let step_a = |solved: &serde_json::Value| {
+        let steps = solved["solvedSteps"].as_array().unwrap();
+        steps.iter().find(|s| s["stepId"] == "a").unwrap().clone()
+    };
+
+    let input = format!(r#"{{ "command": "solve", "template": {} }}"#, template);
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let solo = step_a(&parsed["data"]);
+    assert_eq!(solo["startOffsetMins"], 40);
+    assert_eq!(solo["endOffsetMins"], 1450);
+    assert_eq!(solo["segments"].as_array().unwrap().len(), 2);
+
+    let input = format!(r#"{{ "command": "solveMulti", "templates": [{}] }}"#, template);
+    let output = cmd().write_stdin(input).assert().success().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(step_a(&parsed["data"][0]["data"]), solo);
+}
@@ -0,0 +1,517 @@
+/// Serde round-trip tests for the public model types.
+///
+/// Each test builds a fully-populated value, checks its JSON wire format
+/// against a literal (field names, enum variant spelling, `null` for unset
+/// options), then deserializes it back and asserts deep equality.
+///
+/// Run with: cargo test --manifest-path crates/engine/Cargo.toml
+use assert_json_diff::assert_json_eq;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use skejj_engine::allocator::FloatConsumption;
+use skejj_engine::baseline::{BaselineStep, ScheduleBaseline};
+use skejj_engine::gantt::{GanttBar, GanttRow};
+use skejj_engine::model::*;
+use skejj_engine::solver::NearCriticalPath;
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Serialize `value`, compare it to `expected`, and check it deserializes
+/// back to an equal value.
+fn assert_round_trip<T>(value: &T, expected: Value)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_value(value).unwrap();
+    assert_json_eq!(json, expected);
+
+    let back: T = serde_json::from_value(json).unwrap();
+    assert_eq!(&back, value);
+}
+
+/// Check `value` survives a trip through TOML. A TOML document must be a
+/// table, so this is only used on struct types; the enums are covered as
+/// their fields.
+fn assert_toml_round_trip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let text = toml::to_string(value).unwrap();
+    let back: T = toml::from_str(&text).unwrap();
+    assert_eq!(&back, value);
+}
+
+/// Check `value` also deserializes from its JSON with every field name in
+/// snake_case instead of camelCase.
+fn assert_snake_case_round_trip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = snake_case_keys(serde_json::to_value(value).unwrap());
+    let back: T = serde_json::from_value(json).unwrap();
+    assert_eq!(&back, value);
+}
+
+/// Rewrite object keys from camelCase to snake_case. Enum variants
+/// (`"Fixed"`) start with a capital and are left alone.
+fn snake_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, v)| {
+                    let mut snake = String::new();
+                    for (i, c) in key.chars().enumerate() {
+                        if i > 0 && c.is_ascii_uppercase() {
+                            snake.push('_');
+                            snake.push(c.to_ascii_lowercase());
+                        } else {
+                            snake.push(c);
+                        }
+                    }
+                    (snake, snake_case_keys(v))
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(snake_case_keys).collect()),
+        other => other,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Enums
+// ---------------------------------------------------------------------------
+
+#[test]
+fn enum_variants_use_source_names() {
+    assert_round_trip(&TimingPolicy::Asap, json!("Asap"));
+    assert_round_trip(&TimingPolicy::Alap, json!("Alap"));
+    assert_round_trip(
+        &TimingPolicy::Fixed { offset_mins: 30 },
+        json!({ "Fixed": { "offsetMins": 30 } }),
+    );
+
+    assert_round_trip(&DependencyType::FinishToStart, json!("FinishToStart"));
+    assert_round_trip(&DependencyType::StartToStart, json!("StartToStart"));
+    assert_round_trip(&DependencyType::FinishToFinish, json!("FinishToFinish"));
+    assert_round_trip(&DependencyType::StartToFinish, json!("StartToFinish"));
+
+    assert_round_trip(&DependencyMode::AllOf, json!("AllOf"));
+    assert_round_trip(&DependencyMode::AnyOf, json!("AnyOf"));
+
+    assert_round_trip(&ResourceKind::Equipment, json!("Equipment"));
+    assert_round_trip(&ResourceKind::People, json!("People"));
+    assert_round_trip(&ResourceKind::Consumable, json!("Consumable"));
+    assert_round_trip(&ResourceKind::Cost, json!("Cost"));
+
+    assert_round_trip(&NoteCode::ResourceConflict, json!("ResourceConflict"));
+    assert_round_trip(&NoteCode::AlternativeSlotAvailable, json!("AlternativeSlotAvailable"));
+    assert_round_trip(&NoteCode::FloatConsumedByAllocation, json!("FloatConsumedByAllocation"));
+    assert_round_trip(&NoteCode::DeadlineWarning, json!("DeadlineWarning"));
+}
+
+// ---------------------------------------------------------------------------
+// Template types
+// ---------------------------------------------------------------------------
+
+#[test]
+fn step_round_trip() {
+    let step = Step {
+        id: "bake".to_string(),
+        title: "Bake".to_string(),
+        description: Some("Into the oven".to_string()),
+        duration_mins: 45,
+        dependencies: vec![StepDependency {
+            step_id: "mix".to_string(),
+            dependency_type: DependencyType::FinishToStart,
+            trigger_at_percent: Some(80),
+            condition: Some("slow_oven".to_string()),
+        }],
+        track_id: Some("kitchen".to_string()),
+        timing_policy: Some(TimingPolicy::Alap),
+        resource_needs: vec![ResourceNeed {
+            resource_id: "oven".to_string(),
+            quantity: 1,
+            min_people: Some(1),
+            max_people: Some(2),
+            role: Some("baker".to_string()),
+            cost_per_minute: Some(0.5),
+            allow_partial: true,
+        }],
+        wbs_code: Some("1.2".to_string()),
+        external_dependencies: vec![ExternalDependency {
+            external_project_id: "supplier".to_string(),
+            external_step_id: "deliver".to_string(),
+            expected_offset_mins: 120,
+        }],
+        condition: Some("gluten_free".to_string()),
+        interruptible: true,
+        dependency_mode: DependencyMode::AnyOf,
+        tags: vec!["vegan".to_string()],
+    };
+
+    assert_round_trip(
+        &step,
+        json!({
+            "id": "bake",
+            "title": "Bake",
+            "description": "Into the oven",
+            "durationMins": 45,
+            "dependencies": [
+                {
+                    "stepId": "mix",
+                    "dependencyType": "FinishToStart",
+                    "triggerAtPercent": 80,
+                    "condition": "slow_oven"
+                }
+            ],
+            "trackId": "kitchen",
+            "timingPolicy": "Alap",
+            "resourceNeeds": [{
+                "resourceId": "oven",
+                "quantity": 1,
+                "minPeople": 1,
+                "maxPeople": 2,
+                "role": "baker",
+                "costPerMinute": 0.5,
+                "allowPartial": true
+            }],
+            "wbsCode": "1.2",
+            "externalDependencies": [{
+                "externalProjectId": "supplier",
+                "externalStepId": "deliver",
+                "expectedOffsetMins": 120
+            }],
+            "condition": "gluten_free",
+            "interruptible": true,
+            "dependencyMode": "AnyOf",
+            "tags": ["vegan"]
+        }),
+    );
+    assert_toml_round_trip(&step);
+    assert_snake_case_round_trip(&step);
+
+    let fixed = Step {
+        timing_policy: Some(TimingPolicy::Fixed { offset_mins: 30 }),
+        ..step
+    };
+    assert_snake_case_round_trip(&fixed);
+}
+
+#[test]
+fn unset_options_serialize_as_null_and_may_be_omitted() {
+    let step = Step {
+        id: "a".to_string(),
+        title: "A".to_string(),
+        duration_mins: 10,
+        ..Default::default()
+    };
+
+    assert_round_trip(
+        &step,
+        json!({
+            "id": "a",
+            "title": "A",
+            "description": null,
+            "durationMins": 10,
+            "dependencies": [],
+            "trackId": null,
+            "timingPolicy": null,
+            "resourceNeeds": [],
+            "wbsCode": null,
+            "externalDependencies": [],
+            "condition": null,
+            "interruptible": false,
+            "dependencyMode": "AllOf",
+            "tags": []
+        }),
+    );
+    assert_toml_round_trip(&step);
+
+    // Omitted option fields deserialize to `None`, same as explicit nulls.
+    let sparse: Step = serde_json::from_value(json!({
+        "id": "a",
+        "title": "A",
+        "durationMins": 10,
+        "dependencies": [],
+        "resourceNeeds": []
+    }))
+    .unwrap();
+    assert_eq!(sparse, step);
+}
+
+#[test]
+fn template_round_trip() {
+    let template = ScheduleTemplate {
+        id: "t".to_string(),
+        name: "Template".to_string(),
+        description: None,
+        version: Some("1.2.0".to_string()),
+        steps: vec![],
+        tracks: vec![Track {
+            id: "kitchen".to_string(),
+            name: "Kitchen".to_string(),
+            steps: vec!["a".to_string()],
+            max_concurrent: Some(2),
+        }],
+        resources: vec![Resource {
+            id: "crew".to_string(),
+            name: "Crew".to_string(),
+            kind: ResourceKind::People,
+            capacity: 3,
+            roles: vec!["driver".to_string()],
+            role_headcounts: vec![RoleHeadcount {
+                role: "driver".to_string(),
+                headcount: 1,
+            }],
+            unit_cost: 0.0,
+            cost_per_unit_per_min: Some(0.25),
+            utilization_target: Some(0.75),
+        }],
+        time_constraint: Some(TimeConstraint {
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: None,
+            timezone: Some("Europe/London".to_string()),
+        }),
+        default_num_people: Some(2),
+        max_concurrent_steps: Some(4),
+        recurring_patterns: vec![RecurringStepPattern {
+            template_step_id: "check".to_string(),
+            interval_mins: 60,
+            count: 3,
+            prefix: "check".to_string(),
+        }],
+        embedded_baseline: Some(ScheduleBaseline {
+            template_id: "t".to_string(),
+            total_duration_mins: 30,
+            steps: vec![BaselineStep {
+                step_id: "a".to_string(),
+                start_offset_mins: 0,
+                end_offset_mins: 30,
+                total_float_mins: 0,
+            }],
+        }),
+        working_calendar: Some(WorkingCalendar {
+            cycle_mins: 1440,
+            working_windows: vec![WorkingWindow {
+                start_offset_mins: 480,
+                end_offset_mins: 1020,
+            }],
+        }),
+        hammock_groups: vec![HammockGroup {
+            id: "prep".to_string(),
+            name: "Prep".to_string(),
+            member_step_ids: vec!["a".to_string()],
+        }],
+        resource_groups: vec![ResourceGroup {
+            id: "any-oven".to_string(),
+            name: "Any oven".to_string(),
+            resource_ids: vec!["oven-1".to_string(), "oven-2".to_string()],
+        }],
+        resource_calendars: vec![ResourceCalendar {
+            resource_id: "crew".to_string(),
+            available_windows: vec![AvailabilityWindow {
+                start_offset_mins: 0,
+                end_offset_mins: 240,
+            }],
+        }],
+        tags: vec!["kosher".to_string()],
+    };
+
+    assert_round_trip(
+        &template,
+        json!({
+            "id": "t",
+            "name": "Template",
+            "description": null,
+            "version": "1.2.0",
+            "steps": [],
+            "tracks": [{ "id": "kitchen", "name": "Kitchen", "steps": ["a"], "maxConcurrent": 2 }],
+            "resources": [{
+                "id": "crew",
+                "name": "Crew",
+                "kind": "People",
+                "capacity": 3,
+                "roles": ["driver"],
+                "roleHeadcounts": [{ "role": "driver", "headcount": 1 }],
+                "unitCost": 0.0,
+                "costPerUnitPerMin": 0.25,
+                "utilizationTarget": 0.75
+            }],
+            "timeConstraint": {
+                "startTime": "2026-03-01T09:00:00",
+                "endTime": null,
+                "timezone": "Europe/London"
+            },
+            "defaultNumPeople": 2,
+            "maxConcurrentSteps": 4,
+            "recurringPatterns": [
+                { "templateStepId": "check", "intervalMins": 60, "count": 3, "prefix": "check" }
+            ],
+            "embeddedBaseline": {
+                "templateId": "t",
+                "totalDurationMins": 30,
+                "steps": [
+                    { "stepId": "a", "startOffsetMins": 0, "endOffsetMins": 30, "totalFloatMins": 0 }
+                ]
+            },
+            "workingCalendar": {
+                "cycleMins": 1440,
+                "workingWindows": [{ "startOffsetMins": 480, "endOffsetMins": 1020 }]
+            },
+            "hammockGroups": [{ "id": "prep", "name": "Prep", "memberStepIds": ["a"] }],
+            "resourceGroups": [
+                { "id": "any-oven", "name": "Any oven", "resourceIds": ["oven-1", "oven-2"] }
+            ],
+            "resourceCalendars": [{
+                "resourceId": "crew",
+                "availableWindows": [{ "startOffsetMins": 0, "endOffsetMins": 240 }]
+            }],
+            "tags": ["kosher"]
+        }),
+    );
+    assert_toml_round_trip(&template);
+    assert_snake_case_round_trip(&template);
+}
+
+#[test]
+fn inventory_round_trip() {
+    let inventory = ResourceInventory {
+        items: vec![ResourceInventoryItem {
+            resource_id: "oven".to_string(),
+            available_quantity: 2,
+        }],
+    };
+
+    assert_round_trip(
+        &inventory,
+        json!({ "items": [{ "resourceId": "oven", "availableQuantity": 2 }] }),
+    );
+    assert_toml_round_trip(&inventory);
+    assert_snake_case_round_trip(&inventory);
+}
+
+// ---------------------------------------------------------------------------
+// Solver output types
+// ---------------------------------------------------------------------------
+
+#[test]
+fn solved_schedule_round_trip() {
+    let schedule = SolvedSchedule {
+        template_id: "t".to_string(),
+        solved_steps: vec![SolvedStep {
+            step_id: "a".to_string(),
+            start_offset_mins: 0,
+            end_offset_mins: 30,
+            start_time: Some("2026-03-01T09:00:00".to_string()),
+            end_time: Some("2026-03-01T09:30:00".to_string()),
+            actual_start_time: Some("2026-03-01T09:05:00".to_string()),
+            actual_end_time: None,
+            assigned_resources: vec![AssignedResource {
+                resource_id: "crew".to_string(),
+                quantity_used: 1,
+                role_used: None,
+            }],
+            total_float_mins: 0,
+            path_float_mins: Some(0),
+            is_critical: true,
+            wbs_code: None,
+            segments: vec![StepSegment {
+                start_offset_mins: 0,
+                end_offset_mins: 30,
+                wall_start: None,
+                wall_end: None,
+            }],
+            notes: vec![StepNote {
+                code: NoteCode::FloatConsumedByAllocation,
+                message: "waited for the oven".to_string(),
+            }],
+            cost: Some(7.5),
+        }],
+        summary: ScheduleSummary {
+            total_duration_mins: 30,
+            critical_path_step_ids: vec!["a".to_string()],
+            project_total_cost: 12.5,
+            total_cost: Some(7.5),
+        },
+        warnings: vec!["heads up".to_string()],
+        gantt_rows: Some(vec![GanttRow {
+            track_id: None,
+            track_name: "Default".to_string(),
+            bars: vec![GanttBar {
+                step_id: "a".to_string(),
+                start: 0,
+                end: 30,
+                is_critical: true,
+                color_hint: Some("red".to_string()),
+            }],
+        }]),
+        near_critical_paths: Some(vec![NearCriticalPath {
+            step_ids: vec!["a".to_string()],
+            max_float_mins: 0,
+        }]),
+        float_consumption: Some(vec![FloatConsumption {
+            step_id: "a".to_string(),
+            original_float_mins: 10,
+            float_consumed_by_allocation: 5,
+            consuming_resource_id: "crew".to_string(),
+        }]),
+        hammocks: vec![SolvedHammock {
+            group_id: "prep".to_string(),
+            start_offset_mins: 0,
+            end_offset_mins: 30,
+            duration_mins: 30,
+        }],
+    };
+
+    assert_round_trip(
+        &schedule,
+        json!({
+            "templateId": "t",
+            "solvedSteps": [{
+                "stepId": "a",
+                "startOffsetMins": 0,
+                "endOffsetMins": 30,
+                "startTime": "2026-03-01T09:00:00",
+                "endTime": "2026-03-01T09:30:00",
+                "actualStartTime": "2026-03-01T09:05:00",
+                "actualEndTime": null,
+                "assignedResources": [{ "resourceId": "crew", "quantityUsed": 1, "roleUsed": null }],
+                "totalFloatMins": 0,
+                "pathFloatMins": 0,
+                "isCritical": true,
+                "wbsCode": null,
+                "segments": [
+                    { "startOffsetMins": 0, "endOffsetMins": 30, "wallStart": null, "wallEnd": null }
+                ],
+                "notes": [{ "code": "FloatConsumedByAllocation", "message": "waited for the oven" }],
+                "cost": 7.5
+            }],
+            "summary": {
+                "totalDurationMins": 30,
+                "criticalPathStepIds": ["a"],
+                "projectTotalCost": 12.5,
+                "totalCost": 7.5
+            },
+            "warnings": ["heads up"],
+            "ganttRows": [{
+                "trackId": null,
+                "trackName": "Default",
+                "bars": [{ "stepId": "a", "start": 0, "end": 30, "isCritical": true, "colorHint": "red" }]
+            }],
+            "nearCriticalPaths": [{ "stepIds": ["a"], "maxFloatMins": 0 }],
+            "floatConsumption": [{
+                "stepId": "a",
+                "originalFloatMins": 10,
+                "floatConsumedByAllocation": 5,
+                "consumingResourceId": "crew"
+            }],
+            "hammocks": [
+                { "groupId": "prep", "startOffsetMins": 0, "endOffsetMins": 30, "durationMins": 30 }
+            ]
+        }),
+    );
+    assert_toml_round_trip(&schedule);
+}
@@ -0,0 +1,71 @@
+/// Tests for the generated template JSON Schema.
+///
+/// The example templates under `examples/json` must validate against the
+/// schema, and the schema's extra constraints must reject bad templates.
+///
+/// Run with: cargo test --manifest-path crates/engine/Cargo.toml
+use serde_json::{json, Value};
+use skejj_engine::schema::export_json_schema;
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn validator() -> jsonschema::Validator {
+    let schema: Value = serde_json::from_str(export_json_schema()).unwrap();
+    jsonschema::validator_for(&schema).unwrap()
+}
+
+fn minimal_template() -> Value {
+    json!({
+        "id": "t",
+        "name": "T",
+        "steps": [
+            { "id": "a", "title": "A", "durationMins": 10, "dependencies": [], "resourceNeeds": [] }
+        ],
+        "tracks": [],
+        "resources": []
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn example_templates_validate() {
+    let validator = validator();
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/json");
+    let mut checked = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let template: Value = serde_json::from_str(&text).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&template).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn schema_rejects_bad_templates() {
+    let validator = validator();
+    assert!(validator.is_valid(&minimal_template()));
+
+    let mut zero_duration = minimal_template();
+    zero_duration["steps"][0]["durationMins"] = json!(0);
+    assert!(!validator.is_valid(&zero_duration));
+
+    let mut empty_id = minimal_template();
+    empty_id["steps"][0]["id"] = json!("");
+    assert!(!validator.is_valid(&empty_id));
+
+    let mut bad_enum = minimal_template();
+    bad_enum["steps"][0]["timingPolicy"] = json!("Sometime");
+    assert!(!validator.is_valid(&bad_enum));
+
+    let mut missing_steps = minimal_template();
+    missing_steps.as_object_mut().unwrap().remove("steps");
+    assert!(!validator.is_valid(&missing_steps));
+}